@@ -0,0 +1,171 @@
+use std::sync::{OnceLock, RwLock};
+
+use serde_json::{json, Map, Value};
+use GORBIE::prelude::*;
+
+use crate::chapters::{self, CHAPTERS};
+use crate::collapsed;
+use crate::difficulty;
+use crate::i18n::tr;
+use crate::mastery;
+use crate::patterns;
+use crate::progress;
+use crate::review;
+use crate::schedule;
+use crate::score;
+use crate::session;
+use crate::settings;
+use crate::stats;
+use crate::storage;
+
+/// Tells a backup of this course apart from any other JSON file.
+const FORMAT: &str = "gorbie_teaches_cs";
+const VERSION: u64 = 1;
+/// Every file the course saves. A backup holds each one as a list of lines.
+const FILES: [&str; 13] = [
+    "settings.txt",
+    "session.txt",
+    "progress.txt",
+    "practice_days.txt",
+    "collapsed.txt",
+    "score.txt",
+    "mistakes.txt",
+    "patterns.txt",
+    "schedule.txt",
+    "difficulty.txt",
+    "stats.txt",
+    "card_time.txt",
+    "mastery.txt",
+];
+
+/// The file name typed into the settings and how the last export or import
+/// went.
+struct Backup {
+    path: String,
+    message: Option<String>,
+}
+
+static BACKUP: OnceLock<RwLock<Backup>> = OnceLock::new();
+
+fn backup_lock() -> &'static RwLock<Backup> {
+    BACKUP.get_or_init(|| {
+        RwLock::new(Backup {
+            path: default_path(),
+            message: None,
+        })
+    })
+}
+
+/// `gorbie_teaches_cs_backup.json` in the home directory, where it is easy
+/// to find and copy to another machine.
+fn default_path() -> String {
+    storage::home_file("gorbie_teaches_cs_backup.json")
+        .display()
+        .to_string()
+}
+
+fn export(path: &str) -> Result<(), String> {
+    let mut files = Map::new();
+    for name in FILES {
+        let text = storage::load(name).unwrap_or_default();
+        let lines = text.lines().map(Value::from).collect();
+        files.insert(name.to_string(), Value::Array(lines));
+    }
+    let backup = json!({
+        "format": FORMAT,
+        "version": VERSION,
+        "files": files,
+    });
+    let text = serde_json::to_string_pretty(&backup).map_err(|error| error.to_string())?;
+    std::fs::write(path, text).map_err(|error| format!("{path}: {error}"))
+}
+
+/// Replaces everything saved with the backup at `path`, then loads it. The
+/// whole backup is checked first, so a broken file changes nothing. Files
+/// the backup does not mention start empty.
+fn import(ctx: &egui::Context, path: &str) -> Result<(), String> {
+    let text = std::fs::read_to_string(path).map_err(|error| format!("{path}: {error}"))?;
+    let backup: Value = serde_json::from_str(&text).map_err(|error| format!("{path}: {error}"))?;
+    if backup["format"] != FORMAT {
+        return Err(format!("{path} is not a backup of this course"));
+    }
+    if !matches!(backup["version"].as_u64(), Some(version) if version <= VERSION) {
+        return Err(format!("{path} comes from a newer version of the course"));
+    }
+    let files = backup["files"]
+        .as_object()
+        .ok_or_else(|| format!("{path} holds no saved files"))?;
+    let mut contents = Vec::with_capacity(FILES.len());
+    for name in FILES {
+        let text = match files.get(name) {
+            None => String::new(),
+            Some(lines) => lines
+                .as_array()
+                .and_then(|lines| {
+                    lines
+                        .iter()
+                        .map(|line| line.as_str().map(|line| format!("{line}\n")))
+                        .collect::<Option<String>>()
+                })
+                .ok_or_else(|| format!("{path}: {name} is not a list of lines"))?,
+        };
+        contents.push((name, text));
+    }
+    for (name, text) in contents {
+        storage::save(name, &text)?;
+    }
+
+    settings::reload(ctx);
+    progress::reload();
+    collapsed::reload();
+    score::reload();
+    review::reload();
+    patterns::reload();
+    schedule::reload();
+    difficulty::reload();
+    stats::reload();
+    mastery::reload();
+    // Cards still show what was answered before the import.
+    for def in CHAPTERS {
+        chapters::reset_chapter(def.chapter);
+    }
+    if let Some(chapter) = session::saved_chapter() {
+        chapters::set_chapter(chapter);
+    }
+    Ok(())
+}
+
+/// The backup section of the settings: a file name, export and import.
+pub fn controls(ui: &mut egui::Ui) {
+    let mut backup = backup_lock().write().expect("backup lock poisoned");
+    ui.label(egui::RichText::new(tr("backup.title")).strong());
+    ui.horizontal(|ui| {
+        ui.label(tr("backup.file"));
+        ui.add(widgets::TextField::singleline(&mut backup.path));
+    });
+    ui.horizontal(|ui| {
+        if ui
+            .add(widgets::Button::new(tr("backup.export")))
+            .on_hover_text(tr("backup.export_hover"))
+            .clicked()
+        {
+            backup.message = Some(match export(&backup.path) {
+                Ok(()) => tr("backup.exported").to_string(),
+                Err(error) => error,
+            });
+        }
+        if ui
+            .add(widgets::Button::new(tr("backup.import")))
+            .on_hover_text(tr("backup.import_hover"))
+            .clicked()
+        {
+            backup.message = Some(match import(ui.ctx(), &backup.path) {
+                Ok(()) => tr("backup.imported").to_string(),
+                Err(error) => error,
+            });
+        }
+    });
+    if let Some(message) = &backup.message {
+        ui.label(message);
+    }
+}
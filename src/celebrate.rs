@@ -0,0 +1,108 @@
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use egui::{Color32, Pos2, Rect};
+use web_time::Instant;
+
+use crate::i18n::tr;
+use crate::rng::SimpleRng;
+use crate::settings;
+
+const DURATION: Duration = Duration::from_millis(2500);
+const PIECES: u64 = 90;
+/// Downward pull on the confetti, in points per second squared.
+const GRAVITY: f32 = 260.0;
+/// The Okabe-Ito colors, which stay apart for colorblind eyes too.
+const COLORS: [Color32; 5] = [
+    Color32::from_rgb(230, 159, 0),
+    Color32::from_rgb(86, 180, 233),
+    Color32::from_rgb(0, 158, 115),
+    Color32::from_rgb(240, 228, 66),
+    Color32::from_rgb(204, 121, 167),
+];
+
+/// What set the celebration off, for the line shown with it.
+#[derive(Clone, Copy, Debug)]
+pub enum Reason {
+    Quiz,
+    Streak(u32),
+}
+
+static CELEBRATION: OnceLock<RwLock<Option<(Instant, Reason)>>> = OnceLock::new();
+
+fn celebration_lock() -> &'static RwLock<Option<(Instant, Reason)>> {
+    CELEBRATION.get_or_init(|| RwLock::new(None))
+}
+
+/// Starts a celebration; it is drawn from the next frame on.
+pub fn start(reason: Reason) {
+    *celebration_lock()
+        .write()
+        .expect("celebration lock poisoned") = Some((Instant::now(), reason));
+}
+
+/// One piece of confetti `seconds` after the start: it drops in from above
+/// the top edge at its own speed and sways a little on the way down.
+fn piece(index: u64, seconds: f32, area: Rect) -> (Pos2, Color32) {
+    let mut rng = SimpleRng::new(index + 1);
+    let x = area.left() + area.width() * rng.gen_range_i32(0, 1000) as f32 / 1000.0;
+    let delay = rng.gen_range_i32(0, 600) as f32 / 1000.0;
+    let speed = rng.gen_range_i32(60, 220) as f32;
+    let sway = rng.gen_range_i32(10, 40) as f32;
+    let t = (seconds - delay).max(0.0);
+    let position = Pos2::new(
+        x + sway * (t * 3.0 + index as f32).sin(),
+        area.top() - 10.0 + speed * t + GRAVITY * t * t / 2.0,
+    );
+    (position, COLORS[index as usize % COLORS.len()])
+}
+
+/// Draws the running celebration over everything else. Called once per
+/// frame. With reduced motion there is no confetti, only the line of text.
+pub fn paint(ctx: &egui::Context) {
+    let mut celebration = celebration_lock()
+        .write()
+        .expect("celebration lock poisoned");
+    let Some((started, reason)) = *celebration else {
+        return;
+    };
+    if started.elapsed() >= DURATION {
+        *celebration = None;
+        return;
+    }
+    let area = ctx.content_rect();
+    let painter = ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("celebration"),
+    ));
+    if !settings::get().reduced_motion {
+        let seconds = started.elapsed().as_secs_f32();
+        for index in 0..PIECES {
+            let (position, color) = piece(index, seconds, area);
+            if index % 2 == 0 {
+                painter.circle_filled(position, 3.5, color);
+            } else {
+                painter.rect_filled(
+                    Rect::from_center_size(position, egui::vec2(5.0, 9.0)),
+                    1.0,
+                    color,
+                );
+            }
+        }
+    }
+    let text = match reason {
+        Reason::Quiz => tr("celebrate.quiz").to_string(),
+        Reason::Streak(streak) => tr("celebrate.streak").replace("{streak}", &streak.to_string()),
+    };
+    let font = egui::FontId::proportional(28.0);
+    let galley = painter.layout_no_wrap(text, font, ctx.style().visuals.strong_text_color());
+    let center = Pos2::new(area.center().x, area.top() + area.height() / 4.0);
+    let frame = Rect::from_center_size(center, galley.size() + egui::vec2(32.0, 16.0));
+    painter.rect_filled(frame, 8.0, ctx.style().visuals.window_fill);
+    painter.galley(
+        frame.center() - galley.size() / 2.0,
+        galley,
+        Color32::PLACEHOLDER,
+    );
+    ctx.request_repaint();
+}
@@ -1,10 +1,23 @@
-use egui::text::LayoutJob;
-use egui::RichText;
-use egui::TextStyle;
-use std::ops::Range;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-use crate::chapters::Chapter;
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::{self, Difficulty};
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::expr::{
+    self, build_steps, build_steps_in, expr_at_path, expr_to_string, path_in_subtree, reduce_at,
+    render_expr_with_highlight, Language, PathStep, Scanner,
+};
+use crate::glossary;
+use crate::i18n::tr;
+use crate::keyboard::AnswerToggle;
+use crate::mastery;
+use crate::palette::Palette;
+use crate::progress;
+use crate::rng::{self, SimpleRng};
+use crate::speech;
+use crate::stepper::{stepper, Steps};
+use crate::tree_practice::{TreePractice, Wording};
+use crate::tree_view::{draw_tree, draw_tree_skipping};
+use crate::worksheet;
 use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
 use GORBIE::prelude::*;
 
@@ -14,10 +27,17 @@ fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
     (CHAPTER, key)
 }
 
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
 struct ExpressionState {
     input: String,
     step: usize,
     rng: SimpleRng,
+    advanced: bool,
+    /// The values switched on for the names in [`SWITCHES`].
+    values: [bool; 3],
 }
 
 impl Default for ExpressionState {
@@ -25,162 +45,369 @@ impl Default for ExpressionState {
         Self {
             input: "not (true and false) or true".to_string(),
             step: 0,
-            rng: SimpleRng::new(seed_from_time()),
+            rng: SimpleRng::new(rng::seed_from_time()),
+            advanced: false,
+            values: [true, false, false],
         }
     }
 }
 
-struct RandomExerciseState {
-    rng: SimpleRng,
-    exercise: Exercise,
-    selection: Option<bool>,
+struct Practice {
+    expr: Expr,
+    answer: bool,
+    /// Where the learner is in the worked solution.
+    step: usize,
 }
 
-impl Default for RandomExerciseState {
-    fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
-        let exercise = generate_exercise(&mut rng);
-        Self {
-            rng,
-            exercise,
-            selection: None,
-        }
+impl Exercise for Practice {
+    type Answer = bool;
+
+    fn generate(rng: &mut SimpleRng, difficulty: Difficulty) -> Self {
+        generate_exercise(rng, difficulty)
     }
-}
 
-impl RandomExerciseState {
-    fn regenerate(&mut self) {
-        self.exercise = generate_exercise(&mut self.rng);
-        self.selection = None;
+    fn prompt(&self) -> String {
+        "Is this expression true or false?".to_string()
     }
-}
 
-struct TreeExerciseState {
-    rng: SimpleRng,
-    expr: Expr,
-    feedback: Option<String>,
-}
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::line(&expr_to_string(&self.expr)));
+    }
 
-impl Default for TreeExerciseState {
-    fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
-        let expr = generate_tree_expr(&mut rng);
-        Self {
-            rng,
-            expr,
-            feedback: None,
-        }
+    fn text(&self) -> String {
+        format!("```text\n{}\n```", expr_to_string(&self.expr))
     }
-}
 
-impl TreeExerciseState {
-    fn regenerate(&mut self) {
-        self.expr = generate_tree_expr(&mut self.rng);
-        self.feedback = None;
+    fn answer(&self) -> bool {
+        self.answer
+    }
+
+    fn distractors(&self, _rng: &mut SimpleRng) -> Vec<bool> {
+        vec![!self.answer]
+    }
+
+    fn choices(&self, _rng: &mut SimpleRng) -> Vec<bool> {
+        vec![true, false]
+    }
+
+    fn choice_label(answer: &bool) -> String {
+        answer.to_string()
+    }
+
+    fn hint(&self, _picked: &bool) -> String {
+        "Try another answer.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, _correct: bool) {
+        ui.add_space(4.0);
+        expr::worked_solution(ui, &mut self.step, &self.expr);
     }
 }
 
-struct Exercise {
-    expr: Expr,
-    answer: bool,
+struct Logic;
+
+#[derive(Clone, Copy)]
+enum UnaryOp {
+    Not,
 }
 
-struct SimpleRng {
-    state: u64,
+#[derive(Clone, Copy)]
+enum BinaryOp {
+    And,
+    Or,
+    /// Exactly one side is true. Like the ones below, only read with the
+    /// advanced operators on.
+    Xor,
+    /// False only when the left side is true and the right side is not.
+    Implies,
+    /// `not (a and b)`.
+    Nand,
 }
 
-impl SimpleRng {
-    fn new(seed: u64) -> Self {
-        Self { state: seed.max(1) }
+impl Language for Logic {
+    type Value = bool;
+    type Unary = UnaryOp;
+    type Binary = BinaryOp;
+
+    fn value_text(value: &bool) -> String {
+        value.to_string()
+    }
+
+    fn unary_text(op: UnaryOp) -> (&'static str, &'static str) {
+        match op {
+            UnaryOp::Not => ("not ", ""),
+        }
     }
 
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
+    fn unary_label(op: UnaryOp) -> &'static str {
+        match op {
+            UnaryOp::Not => "not",
+        }
     }
 
-    fn gen_range_i32(&mut self, min: i32, max: i32) -> i32 {
-        let span = (max - min + 1) as u32;
-        let value = self.next_u32() % span;
-        min + value as i32
+    fn binary_label(op: BinaryOp) -> &'static str {
+        match op {
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+            BinaryOp::Xor => "xor",
+            BinaryOp::Implies => "implies",
+            BinaryOp::Nand => "nand",
+        }
+    }
+
+    fn describe_unary(op: UnaryOp, value: &bool) -> String {
+        match op {
+            UnaryOp::Not => format!("Flip {value} with `not`"),
+        }
+    }
+
+    fn describe_binary(op: BinaryOp, left: &bool, right: &bool) -> String {
+        match op {
+            BinaryOp::And => format!("Check whether {left} and {right} are both true"),
+            BinaryOp::Or => format!("Check whether {left} or {right} is true"),
+            BinaryOp::Xor => format!("Check whether exactly one of {left} and {right} is true"),
+            BinaryOp::Implies => format!("Check whether {left} implies {right}"),
+            BinaryOp::Nand => format!("Check whether {left} and {right} are not both true"),
+        }
+    }
+
+    fn binary_name(op: BinaryOp) -> &'static str {
+        match op {
+            BinaryOp::And => "`and`",
+            BinaryOp::Or => "`or`",
+            BinaryOp::Xor => "`xor`",
+            BinaryOp::Implies => "`implies`",
+            BinaryOp::Nand => "`nand`",
+        }
+    }
+
+    fn precedence(op: BinaryOp) -> u8 {
+        match op {
+            BinaryOp::Implies => 1,
+            BinaryOp::Or => 2,
+            BinaryOp::Xor => 3,
+            BinaryOp::And | BinaryOp::Nand => 4,
+        }
+    }
+
+    fn unary_instruction(op: UnaryOp) -> &'static str {
+        match op {
+            UnaryOp::Not => "not",
+        }
+    }
+
+    fn binary_instruction(op: BinaryOp) -> &'static str {
+        match op {
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+            BinaryOp::Xor => "xor",
+            BinaryOp::Implies => "implies",
+            BinaryOp::Nand => "nand",
+        }
+    }
+
+    fn apply_unary(op: UnaryOp, value: &bool) -> Result<bool, String> {
+        match op {
+            UnaryOp::Not => Ok(!value),
+        }
     }
-}
 
-fn seed_from_time() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_nanos() as u64)
-        .unwrap_or(1)
+    fn apply_binary(op: BinaryOp, left: &bool, right: &bool) -> Result<bool, String> {
+        match op {
+            BinaryOp::And => Ok(*left && *right),
+            BinaryOp::Or => Ok(*left || *right),
+            BinaryOp::Xor => Ok(*left != *right),
+            BinaryOp::Implies => Ok(!*left || *right),
+            BinaryOp::Nand => Ok(!(*left && *right)),
+        }
+    }
 }
 
-#[derive(Clone)]
-enum ExprKind {
-    Bool(bool),
-    Not(Box<Expr>),
-    And(Box<Expr>, Box<Expr>),
-    Or(Box<Expr>, Box<Expr>),
+type Expr = expr::Expr<Logic>;
+
+struct Parser<'a> {
+    scan: Scanner<'a>,
+    /// Whether names like `a` and comparisons like `x > 3` may stand in for
+    /// a value, as in the truth table.
+    names: bool,
+    /// The comparisons read so far, in order.
+    comparisons: Vec<Comparison>,
+    /// Whether `xor`, `implies` and `nand` are read.
+    advanced: bool,
 }
 
-#[derive(Clone)]
-struct Expr {
-    kind: ExprKind,
+/// A name or a number on one side of a comparison.
+enum Operand {
+    Name(String),
+    Number(i64),
 }
 
-impl Expr {
-    fn boolean(value: bool) -> Self {
-        Self {
-            kind: ExprKind::Bool(value),
+impl std::fmt::Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Name(name) => f.write_str(name),
+            Operand::Number(number) => write!(f, "{number}"),
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum PathStep {
-    Unary,
-    Left,
-    Right,
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CompareOp {
+    Less,
+    LessEq,
+    Greater,
+    GreaterEq,
+    Equal,
+    NotEqual,
 }
 
-struct Step {
-    expr: Expr,
-    highlight: Option<Vec<PathStep>>,
+/// The comparison symbols, the two-letter ones first so `<=` is not read
+/// as `<`.
+const COMPARE_OPS: [(&str, CompareOp); 6] = [
+    ("<=", CompareOp::LessEq),
+    (">=", CompareOp::GreaterEq),
+    ("==", CompareOp::Equal),
+    ("!=", CompareOp::NotEqual),
+    ("<", CompareOp::Less),
+    (">", CompareOp::Greater),
+];
+
+/// A question about two numbers, like `x > 3`: it turns numbers into a
+/// boolean.
+struct Comparison {
+    left: Operand,
+    op: CompareOp,
+    right: Operand,
 }
 
-struct Parser<'a> {
-    input: &'a [u8],
-    pos: usize,
+impl Comparison {
+    /// The numbers compared, with names looked up in `numbers`.
+    fn operands(&self, numbers: &[(&str, i64)]) -> Result<(i64, i64), String> {
+        let value = |operand: &Operand| match operand {
+            Operand::Number(number) => Ok(*number),
+            Operand::Name(name) => numbers
+                .iter()
+                .find(|(known, _)| known == name)
+                .map(|(_, value)| *value)
+                .ok_or_else(|| format!("`{name}` has no slider here.")),
+        };
+        Ok((value(&self.left)?, value(&self.right)?))
+    }
+
+    fn eval(&self, numbers: &[(&str, i64)]) -> Result<bool, String> {
+        let (left, right) = self.operands(numbers)?;
+        Ok(match self.op {
+            CompareOp::Less => left < right,
+            CompareOp::LessEq => left <= right,
+            CompareOp::Greater => left > right,
+            CompareOp::GreaterEq => left >= right,
+            CompareOp::Equal => left == right,
+            CompareOp::NotEqual => left != right,
+        })
+    }
+
+    fn symbol(&self) -> &'static str {
+        COMPARE_OPS
+            .iter()
+            .find(|(_, op)| *op == self.op)
+            .map_or("?", |(symbol, _)| symbol)
+    }
+}
+
+/// How the comparison is written, and the name it goes by in the
+/// expression.
+impl std::fmt::Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} {}", self.left, self.symbol(), self.right)
+    }
 }
 
+/// Words the parser reads as operators, never as names.
+const KEYWORDS: [&str; 6] = ["and", "or", "not", "xor", "implies", "nand"];
+
+/// The operators behind the "Advanced operators" toggle.
+const ADVANCED: [&str; 3] = ["xor", "implies", "nand"];
+
 impl<'a> Parser<'a> {
     fn new(input: &'a str) -> Self {
         Self {
-            input: input.as_bytes(),
-            pos: 0,
+            scan: Scanner::new(input),
+            names: false,
+            comparisons: Vec::new(),
+            advanced: false,
+        }
+    }
+
+    fn advanced(mut self, advanced: bool) -> Self {
+        self.advanced = advanced;
+        self
+    }
+
+    fn with_names(input: &'a str) -> Self {
+        Self {
+            names: true,
+            ..Self::new(input)
         }
     }
 
     fn parse_expression(&mut self) -> Result<Expr, String> {
-        let expr = self.parse_or()?;
-        self.skip_ws();
-        if self.pos < self.input.len() {
-            return Err(format!("Unexpected input at position {}", self.pos + 1));
+        let expr = self.parse_implies()?;
+        self.scan.skip_ws();
+        if !self.scan.at_end() {
+            return Err(self.stuck(format!(
+                "Unexpected input at position {}",
+                self.scan.pos() + 1
+            )));
         }
         Ok(expr)
     }
 
+    /// `error`, unless one of the advanced operators comes next while they
+    /// are off: then a pointer to the toggle.
+    fn stuck(&self, error: String) -> String {
+        let mut ahead = self.scan.clone();
+        if self.advanced || !ADVANCED.iter().any(|word| ahead.consume_word(word)) {
+            return error;
+        }
+        format!(
+            "`xor`, `implies` and `nand` at position {} need the advanced operators on",
+            self.scan.pos() + 1
+        )
+    }
+
+    /// `implies` groups from the right: `a implies b implies c` means
+    /// `a implies (b implies c)`.
+    fn parse_implies(&mut self) -> Result<Expr, String> {
+        let left = self.parse_or()?;
+        self.scan.skip_ws();
+        if self.advanced && (self.scan.consume_word("implies") || self.scan.consume_bytes(b"->")) {
+            let right = self.parse_implies()?;
+            return Ok(Expr::binary(BinaryOp::Implies, left, right));
+        }
+        Ok(left)
+    }
+
     fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut node = self.parse_xor()?;
+        loop {
+            self.scan.skip_ws();
+            if self.scan.consume_word("or") || self.scan.consume_bytes(b"||") {
+                let right = self.parse_xor()?;
+                node = Expr::binary(BinaryOp::Or, node, right);
+            } else {
+                break;
+            }
+        }
+        Ok(node)
+    }
+
+    fn parse_xor(&mut self) -> Result<Expr, String> {
         let mut node = self.parse_and()?;
         loop {
-            self.skip_ws();
-            if self.consume_word("or") || self.consume_bytes(b"||") {
+            self.scan.skip_ws();
+            if self.advanced && self.scan.consume_word("xor") {
                 let right = self.parse_and()?;
-                node = Expr {
-                    kind: ExprKind::Or(Box::new(node), Box::new(right)),
-                };
+                node = Expr::binary(BinaryOp::Xor, node, right);
             } else {
                 break;
             }
@@ -191,92 +418,123 @@ impl<'a> Parser<'a> {
     fn parse_and(&mut self) -> Result<Expr, String> {
         let mut node = self.parse_unary()?;
         loop {
-            self.skip_ws();
-            if self.consume_word("and") || self.consume_bytes(b"&&") {
-                let right = self.parse_unary()?;
-                node = Expr {
-                    kind: ExprKind::And(Box::new(node), Box::new(right)),
-                };
+            self.scan.skip_ws();
+            let op = if self.scan.consume_word("and") || self.scan.consume_bytes(b"&&") {
+                BinaryOp::And
+            } else if self.advanced && self.scan.consume_word("nand") {
+                BinaryOp::Nand
             } else {
                 break;
-            }
+            };
+            let right = self.parse_unary()?;
+            node = Expr::binary(op, node, right);
         }
         Ok(node)
     }
 
     fn parse_unary(&mut self) -> Result<Expr, String> {
-        self.skip_ws();
-        if self.consume_word("not") || self.consume_bytes(b"!") {
+        self.scan.skip_ws();
+        if self.scan.consume_word("not") || self.scan.consume_bytes(b"!") {
             let inner = self.parse_unary()?;
-            return Ok(Expr {
-                kind: ExprKind::Not(Box::new(inner)),
-            });
+            return Ok(Expr::unary(UnaryOp::Not, inner));
         }
         self.parse_primary()
     }
 
     fn parse_primary(&mut self) -> Result<Expr, String> {
-        self.skip_ws();
-        if self.consume_bytes(b"(") {
-            let expr = self.parse_or()?;
-            self.skip_ws();
-            if !self.consume_bytes(b")") {
-                return Err("Expected ')'".to_string());
+        self.scan.skip_ws();
+        if self.scan.consume_bytes(b"(") {
+            let expr = self.parse_implies()?;
+            self.scan.skip_ws();
+            if !self.scan.consume_bytes(b")") {
+                return Err(self.stuck("Expected ')'".to_string()));
             }
             return Ok(expr);
         }
         if let Some(value) = self.consume_bool() {
-            return Ok(Expr::boolean(value));
-        }
-        Err(format!("Expected true/false at position {}", self.pos + 1))
-    }
-
-    fn consume_bool(&mut self) -> Option<bool> {
-        if self.consume_word("true") || self.consume_word("yes") || self.consume_word("on") {
-            return Some(true);
+            return Ok(Expr::value(value));
         }
-        if self.consume_word("false") || self.consume_word("no") || self.consume_word("off") {
-            return Some(false);
+        if self.names {
+            if let Some(left) = self.parse_operand()? {
+                return self.parse_comparison(left);
+            }
         }
-        None
+        Err(format!(
+            "Expected true/false at position {}",
+            self.scan.pos() + 1
+        ))
     }
 
-    fn skip_ws(&mut self) {
-        while let Some(byte) = self.peek() {
-            if byte.is_ascii_whitespace() {
-                self.pos += 1;
-            } else {
+    /// A name or a whole number, or `None` when neither comes next.
+    fn parse_operand(&mut self) -> Result<Option<Operand>, String> {
+        let start = self.scan.pos();
+        let Some(first) = self.scan.peek() else {
+            return Ok(None);
+        };
+        if !first.is_ascii_alphanumeric() {
+            return Ok(None);
+        }
+        let mut text = String::new();
+        while let Some(byte) = self.scan.peek() {
+            if !byte.is_ascii_alphanumeric() && byte != b'_' {
                 break;
             }
+            self.scan.bump();
+            text.push(byte as char);
         }
+        if first.is_ascii_digit() {
+            return text
+                .parse()
+                .map(|number| Some(Operand::Number(number)))
+                .map_err(|_| format!("`{text}` at position {} is not a number", start + 1));
+        }
+        if KEYWORDS.contains(&text.as_str()) {
+            return Err(format!(
+                "`{text}` at position {} needs something on its left",
+                start + 1
+            ));
+        }
+        Ok(Some(Operand::Name(text)))
     }
 
-    fn consume_bytes(&mut self, bytes: &[u8]) -> bool {
-        if self.input.get(self.pos..self.pos + bytes.len()) == Some(bytes) {
-            self.pos += bytes.len();
-            true
-        } else {
-            false
-        }
+    /// A comparison like `x > 3` if one follows `left`, written down as a
+    /// name whose value is worked out before the boolean steps. A name on
+    /// its own stays a name.
+    fn parse_comparison(&mut self, left: Operand) -> Result<Expr, String> {
+        self.scan.skip_ws();
+        let op = COMPARE_OPS
+            .into_iter()
+            .find(|(symbol, _)| self.scan.consume_bytes(symbol.as_bytes()));
+        let Some((symbol, op)) = op else {
+            return match left {
+                Operand::Name(name) => Ok(Expr::Var(name)),
+                Operand::Number(number) => Err(format!(
+                    "{number} is a number, not true or false. Compare it, like `x > {number}`"
+                )),
+            };
+        };
+        self.scan.skip_ws();
+        let Some(right) = self.parse_operand()? else {
+            return Err(format!(
+                "Expected a name or number after `{symbol}` at position {}",
+                self.scan.pos() + 1
+            ));
+        };
+        let comparison = Comparison { left, op, right };
+        let name = comparison.to_string();
+        self.comparisons.push(comparison);
+        Ok(Expr::Var(name))
     }
 
-    fn consume_word(&mut self, word: &str) -> bool {
-        let bytes = word.as_bytes();
-        if self.input.get(self.pos..self.pos + bytes.len()) != Some(bytes) {
-            return false;
+    fn consume_bool(&mut self) -> Option<bool> {
+        let scan = &mut self.scan;
+        if scan.consume_word("true") || scan.consume_word("yes") || scan.consume_word("on") {
+            return Some(true);
         }
-        let next = self.input.get(self.pos + bytes.len()).copied();
-        if let Some(byte) = next {
-            if byte.is_ascii_alphanumeric() || byte == b'_' {
-                return false;
-            }
+        if scan.consume_word("false") || scan.consume_word("no") || scan.consume_word("off") {
+            return Some(false);
         }
-        self.pos += bytes.len();
-        true
-    }
-
-    fn peek(&self) -> Option<u8> {
-        self.input.get(self.pos).copied()
+        None
     }
 }
 
@@ -285,728 +543,1049 @@ fn parse_expression(input: &str) -> Result<Expr, String> {
     parser.parse_expression()
 }
 
-fn as_bool(expr: &Expr) -> Option<bool> {
-    match expr.kind {
-        ExprKind::Bool(value) => Some(value),
-        _ => None,
-    }
-}
-
-fn is_reducible(expr: &Expr) -> bool {
-    match &expr.kind {
-        ExprKind::Bool(_) => false,
-        ExprKind::Not(inner) => as_bool(inner).is_some(),
-        ExprKind::And(left, right) | ExprKind::Or(left, right) => {
-            as_bool(left).is_some() && as_bool(right).is_some()
+/// The names in `expr`, each once, in the order they first appear.
+fn names_in(expr: &Expr, names: &mut Vec<String>) {
+    if let Expr::Var(name) = expr {
+        if !names.contains(name) {
+            names.push(name.clone());
         }
     }
+    for (_, child) in expr.children() {
+        names_in(child, names);
+    }
 }
 
-fn eval_reducible(expr: &Expr) -> Result<bool, String> {
-    match &expr.kind {
-        ExprKind::Bool(value) => Ok(*value),
-        ExprKind::Not(inner) => {
-            let value = as_bool(inner).ok_or_else(|| "Expected a boolean".to_string())?;
-            Ok(!value)
-        }
-        ExprKind::And(left, right) => {
-            let left = as_bool(left).ok_or_else(|| "Expected a boolean".to_string())?;
-            let right = as_bool(right).ok_or_else(|| "Expected a boolean".to_string())?;
-            Ok(left && right)
-        }
-        ExprKind::Or(left, right) => {
-            let left = as_bool(left).ok_or_else(|| "Expected a boolean".to_string())?;
-            let right = as_bool(right).ok_or_else(|| "Expected a boolean".to_string())?;
-            Ok(left || right)
-        }
-    }
+/// The steps of an expression with names: first every name replaced by
+/// its value, then the usual reduction.
+struct Substituted {
+    expr: Expr,
+    env: Vec<(String, bool)>,
+    steps: Vec<expr::Step<Logic>>,
 }
 
-fn find_reducible(expr: &Expr) -> Option<Vec<PathStep>> {
-    match &expr.kind {
-        ExprKind::Bool(_) => None,
-        ExprKind::Not(inner) => find_reducible(inner)
-            .map(|mut path| {
-                path.insert(0, PathStep::Unary);
-                path
-            })
-            .or_else(|| {
-                if is_reducible(expr) {
-                    Some(Vec::new())
-                } else {
-                    None
-                }
-            }),
-        ExprKind::And(left, right) | ExprKind::Or(left, right) => find_reducible(left)
-            .map(|mut path| {
-                path.insert(0, PathStep::Left);
-                path
-            })
-            .or_else(|| {
-                find_reducible(right).map(|mut path| {
-                    path.insert(0, PathStep::Right);
-                    path
-                })
-            })
-            .or_else(|| {
-                if is_reducible(expr) {
-                    Some(Vec::new())
-                } else {
-                    None
-                }
-            }),
+impl Substituted {
+    fn new(expr: Expr, env: Vec<(String, bool)>) -> Self {
+        let steps = build_steps(expr::substitute(expr.clone(), &env));
+        Self { expr, env, steps }
     }
 }
 
-fn reduce_at(expr: Expr, path: &[PathStep]) -> Result<Expr, String> {
-    if path.is_empty() {
-        return Ok(Expr::boolean(eval_reducible(&expr)?));
+impl Steps for Substituted {
+    fn count(&self) -> usize {
+        self.steps.len() + 1
     }
 
-    let (head, tail) = path.split_first().ok_or("Invalid path")?;
-    match (head, expr.kind) {
-        (PathStep::Unary, ExprKind::Not(inner)) => Ok(Expr {
-            kind: ExprKind::Not(Box::new(reduce_at(*inner, tail)?)),
-        }),
-        (PathStep::Left, ExprKind::And(left, right)) => Ok(Expr {
-            kind: ExprKind::And(Box::new(reduce_at(*left, tail)?), right),
-        }),
-        (PathStep::Right, ExprKind::And(left, right)) => Ok(Expr {
-            kind: ExprKind::And(left, Box::new(reduce_at(*right, tail)?)),
-        }),
-        (PathStep::Left, ExprKind::Or(left, right)) => Ok(Expr {
-            kind: ExprKind::Or(Box::new(reduce_at(*left, tail)?), right),
-        }),
-        (PathStep::Right, ExprKind::Or(left, right)) => Ok(Expr {
-            kind: ExprKind::Or(left, Box::new(reduce_at(*right, tail)?)),
-        }),
-        _ => Err("Invalid path".to_string()),
+    fn show(&self, ui: &mut egui::Ui, step: usize) {
+        if step > 0 {
+            self.steps.show(ui, step - 1);
+            return;
+        }
+        ui.add(CodeBlock::line(&expr_to_string(&self.expr)));
+        ui.add_space(6.0);
+        ui.label(tr("steps.tree_view"));
+        ui.add_space(4.0);
+        draw_tree(ui, &self.expr.to_tree(), |path| {
+            matches!(expr_at_path(&self.expr, path), Some(Expr::Var(_)))
+        });
+        ui.add_space(6.0);
+        let mut names = Vec::new();
+        names_in(&self.expr, &mut names);
+        let values: Vec<String> = names
+            .iter()
+            .filter_map(|name| self.env.iter().rev().find(|(known, _)| known == name))
+            .map(|(name, value)| format!("{name} = {value}"))
+            .collect();
+        ui.label(tr("booleans.substitute"));
+        ui.label(egui::RichText::new(values.join(", ")).monospace());
     }
 }
 
-fn expr_at_path<'a>(expr: &'a Expr, path: &[PathStep]) -> Option<&'a Expr> {
-    if path.is_empty() {
-        return Some(expr);
-    }
-    let (head, tail) = path.split_first()?;
-    match (head, &expr.kind) {
-        (PathStep::Unary, ExprKind::Not(inner)) => expr_at_path(inner, tail),
-        (PathStep::Left, ExprKind::And(left, _)) | (PathStep::Left, ExprKind::Or(left, _)) => {
-            expr_at_path(left, tail)
-        }
-        (PathStep::Right, ExprKind::And(_, right)) | (PathStep::Right, ExprKind::Or(_, right)) => {
-            expr_at_path(right, tail)
-        }
-        _ => None,
-    }
+/// The tautology card: a formula to try on every row.
+struct TautologyState {
+    input: String,
+    /// How many rows the demonstration has gone through so far.
+    shown: usize,
+    advanced: bool,
 }
 
-fn build_steps(expr: Expr) -> Result<Vec<Step>, String> {
-    let mut steps = Vec::new();
-    let mut current = expr;
-    loop {
-        let highlight = find_reducible(&current);
-        steps.push(Step {
-            expr: current.clone(),
-            highlight: highlight.clone(),
-        });
-        let Some(path) = highlight else { break };
-        current = reduce_at(current, &path)?;
-    }
-    Ok(steps)
-}
-
-fn render_expr_with_highlight(
-    expr: &Expr,
-    highlight: Option<&[PathStep]>,
-) -> (String, Vec<Range<usize>>) {
-    let mut text = String::new();
-    let mut highlight_range = None;
-    let highlight_enabled = highlight.is_some();
-    render_expr(
-        expr,
-        highlight.unwrap_or(&[]),
-        highlight_enabled,
-        &mut text,
-        &mut highlight_range,
-    );
-    let ranges = highlight_range.into_iter().collect();
-    (text, ranges)
-}
-
-fn render_expr(
-    expr: &Expr,
-    highlight_path: &[PathStep],
-    highlight_enabled: bool,
-    out: &mut String,
-    highlight_range: &mut Option<Range<usize>>,
-) {
-    let start = out.len();
-    match &expr.kind {
-        ExprKind::Bool(value) => {
-            if *value {
-                out.push_str("true");
-            } else {
-                out.push_str("false");
-            }
-        }
-        ExprKind::Not(inner) => {
-            out.push_str("not ");
-            let (child_path, child_highlight): (&[PathStep], bool) =
-                match highlight_path.split_first() {
-                    Some((PathStep::Unary, rest)) => (rest, highlight_enabled),
-                    _ => (&[], false),
-                };
-            render_expr(inner, child_path, child_highlight, out, highlight_range);
-        }
-        ExprKind::And(left, right) => {
-            out.push('(');
-            let (left_path, left_highlight, right_path, right_highlight): (
-                &[PathStep],
-                bool,
-                &[PathStep],
-                bool,
-            ) = match highlight_path.split_first() {
-                Some((PathStep::Left, rest)) => (rest, highlight_enabled, &[], false),
-                Some((PathStep::Right, rest)) => (&[], false, rest, highlight_enabled),
-                _ => (&[], false, &[], false),
-            };
-            render_expr(left, left_path, left_highlight, out, highlight_range);
-            out.push_str(" and ");
-            render_expr(right, right_path, right_highlight, out, highlight_range);
-            out.push(')');
-        }
-        ExprKind::Or(left, right) => {
-            out.push('(');
-            let (left_path, left_highlight, right_path, right_highlight): (
-                &[PathStep],
-                bool,
-                &[PathStep],
-                bool,
-            ) = match highlight_path.split_first() {
-                Some((PathStep::Left, rest)) => (rest, highlight_enabled, &[], false),
-                Some((PathStep::Right, rest)) => (&[], false, rest, highlight_enabled),
-                _ => (&[], false, &[], false),
-            };
-            render_expr(left, left_path, left_highlight, out, highlight_range);
-            out.push_str(" or ");
-            render_expr(right, right_path, right_highlight, out, highlight_range);
-            out.push(')');
-        }
-    }
-    let end = out.len();
-    if highlight_enabled && highlight_path.is_empty() {
-        *highlight_range = Some(start..end);
-    }
-}
-
-struct NodeDraw {
-    label: String,
-    depth: usize,
-    x: i32,
-    highlight: bool,
-    children: Vec<usize>,
-    path: Vec<PathStep>,
-}
-
-struct NodeLayout {
-    rect: egui::Rect,
-    label: String,
-    highlight: bool,
-    children: Vec<usize>,
-    path: Vec<PathStep>,
-}
-
-fn build_nodes(
-    expr: &Expr,
-    depth: usize,
-    path: &mut Vec<PathStep>,
-    highlight_path: Option<&[PathStep]>,
-    nodes: &mut Vec<NodeDraw>,
-    next_leaf_x: &mut i32,
-) -> usize {
-    let highlight = highlight_path.map_or(false, |sub| path_in_subtree(path, sub));
-    let (label, children, x) = match &expr.kind {
-        ExprKind::Bool(value) => {
-            let x = *next_leaf_x;
-            *next_leaf_x += 1;
-            (
-                if *value {
-                    "true".to_string()
-                } else {
-                    "false".to_string()
-                },
-                Vec::new(),
-                x,
-            )
-        }
-        ExprKind::Not(inner) => {
-            path.push(PathStep::Unary);
-            let child = build_nodes(inner, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            let x = nodes[child].x;
-            ("not".to_string(), vec![child], x)
-        }
-        ExprKind::And(left, right) => {
-            path.push(PathStep::Left);
-            let left_idx = build_nodes(left, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            path.push(PathStep::Right);
-            let right_idx = build_nodes(right, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            let x = (nodes[left_idx].x + nodes[right_idx].x) / 2;
-            ("and".to_string(), vec![left_idx, right_idx], x)
-        }
-        ExprKind::Or(left, right) => {
-            path.push(PathStep::Left);
-            let left_idx = build_nodes(left, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            path.push(PathStep::Right);
-            let right_idx = build_nodes(right, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            let x = (nodes[left_idx].x + nodes[right_idx].x) / 2;
-            ("or".to_string(), vec![left_idx, right_idx], x)
+impl Default for TautologyState {
+    fn default() -> Self {
+        Self {
+            input: "a or not a".to_string(),
+            shown: 0,
+            advanced: false,
         }
-    };
-
-    let index = nodes.len();
-    nodes.push(NodeDraw {
-        label,
-        depth,
-        x,
-        highlight,
-        children,
-        path: path.clone(),
-    });
-    index
-}
-
-fn path_in_subtree(path: &[PathStep], subtree: &[PathStep]) -> bool {
-    path.len() >= subtree.len() && path[..subtree.len()] == *subtree
-}
-
-fn build_tree_layout(
-    ui: &egui::Ui,
-    expr: &Expr,
-    highlight_path: Option<&[PathStep]>,
-) -> (Vec<NodeLayout>, egui::Vec2, egui::FontId) {
-    let mut nodes = Vec::new();
-    let mut next_leaf_x = 0;
-    let mut path = Vec::new();
-    let _root = build_nodes(
-        expr,
-        0,
-        &mut path,
-        highlight_path,
-        &mut nodes,
-        &mut next_leaf_x,
-    );
-
-    let max_label_len = nodes
-        .iter()
-        .map(|node| node.label.chars().count())
-        .max()
-        .unwrap_or(1);
-    let min_x = nodes.iter().map(|node| node.x).min().unwrap_or(0);
-    let max_x = nodes.iter().map(|node| node.x).max().unwrap_or(0);
-    let max_depth = nodes.iter().map(|node| node.depth).max().unwrap_or(0);
-
-    let font_id = TextStyle::Monospace.resolve(ui.style());
-    let (char_width, row_height) = ui.fonts_mut(|fonts| {
-        let width = fonts.glyph_width(&font_id, '0');
-        let height = fonts.row_height(&font_id);
-        (width.max(1.0), height.max(1.0))
-    });
-    let node_padding = egui::vec2((char_width * 0.6).max(4.0), (row_height * 0.2).max(2.0));
-    let node_width = max_label_len as f32 * char_width + node_padding.x * 2.0;
-    let node_height = row_height + node_padding.y * 2.0;
-    let col_gap = (char_width * 2.0).max(8.0);
-    let row_gap = (row_height * 0.8).max(8.0);
-    let col_spacing = node_width + col_gap;
-    let row_spacing = node_height + row_gap;
-
-    let layout_width = node_width + (max_x - min_x) as f32 * col_spacing;
-    let layout_height = node_height + max_depth as f32 * row_spacing;
-
-    let mut layouts = Vec::with_capacity(nodes.len());
-    for node in &nodes {
-        let x_center = node_width / 2.0 + (node.x - min_x) as f32 * col_spacing;
-        let y_center = node_height / 2.0 + node.depth as f32 * row_spacing;
-        let rect = egui::Rect::from_center_size(
-            egui::pos2(x_center, y_center),
-            egui::vec2(node_width, node_height),
-        );
-        layouts.push(NodeLayout {
-            rect,
-            label: node.label.clone(),
-            highlight: node.highlight,
-            children: node.children.clone(),
-            path: node.path.clone(),
-        });
     }
-
-    (layouts, egui::vec2(layout_width, layout_height), font_id)
 }
 
-fn highlight_formats(ui: &egui::Ui) -> (egui::TextFormat, egui::TextFormat) {
-    let font = TextStyle::Monospace.resolve(ui.style());
-    let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
-    let highlight = egui::TextFormat::simple(font, GORBIE::themes::ral(2009));
-    (normal, highlight)
+/// The Karnaugh map card: a formula over two or three names.
+struct KarnaughState {
+    input: String,
+    advanced: bool,
 }
 
-fn append_highlighted_line(
-    job: &mut LayoutJob,
-    line: &str,
-    ranges: &[Range<usize>],
-    normal: &egui::TextFormat,
-    highlight: &egui::TextFormat,
-) {
-    let mut cursor = 0;
-    for range in ranges {
-        let start = range.start.min(line.len());
-        let end = range.end.min(line.len());
-        if start > cursor {
-            job.append(&line[cursor..start], 0.0, normal.clone());
-        }
-        if end > start {
-            job.append(&line[start..end], 0.0, highlight.clone());
+impl Default for KarnaughState {
+    fn default() -> Self {
+        Self {
+            input: "(a and b) or (a and not b and c) or (not a and not b and c)".to_string(),
+            advanced: false,
         }
-        cursor = end;
-    }
-    if cursor < line.len() {
-        job.append(&line[cursor..], 0.0, normal.clone());
     }
 }
 
-fn highlighted_job(ui: &egui::Ui, line: &str, ranges: &[Range<usize>]) -> LayoutJob {
-    let (normal, highlight) = highlight_formats(ui);
-    let mut job = LayoutJob::default();
-    append_highlighted_line(&mut job, line, ranges, &normal, &highlight);
-    job
+/// The values along one side of a Karnaugh map. Neighbors differ in one
+/// name only, which is why the three-name order goes `FT` before `TT`.
+fn gray_order(names: usize) -> Vec<Vec<bool>> {
+    match names {
+        1 => vec![vec![false], vec![true]],
+        _ => vec![
+            vec![false, false],
+            vec![false, true],
+            vec![true, true],
+            vec![true, false],
+        ],
+    }
 }
 
-fn code_frame(ui: &mut egui::Ui, job: LayoutJob) {
-    let bg = ui.visuals().code_bg_color;
-    let stroke = ui.visuals().widgets.inactive.bg_stroke;
-    egui::Frame::group(ui.style())
-        .fill(bg)
-        .stroke(stroke)
-        .inner_margin(egui::Margin::same(8))
-        .corner_radius(10.0)
-        .show(ui, |ui| {
-            ui.label(job);
-        });
+/// A group of cells: each name either fixed to a value or free (`None`).
+type Group = Vec<Option<bool>>;
+
+fn group_holds(group: &Group, row: &[bool]) -> bool {
+    group
+        .iter()
+        .zip(row)
+        .all(|(fixed, value)| fixed.is_none_or(|fixed| fixed == *value))
 }
 
-fn draw_tree(ui: &mut egui::Ui, expr: &Expr, highlight_path: Option<&[PathStep]>) {
-    let (mut layouts, desired, font_id) = build_tree_layout(ui, expr, highlight_path);
-    let (rect, _response) = ui.allocate_at_least(desired, egui::Sense::hover());
-    let mut origin = rect.min;
-    if rect.width() > desired.x {
-        origin.x += (rect.width() - desired.x) / 2.0;
-    }
-    if rect.height() > desired.y {
-        origin.y += (rect.height() - desired.y) / 2.0;
-    }
+/// Whether every cell of `small` lies in `large` too.
+fn group_within(small: &Group, large: &Group) -> bool {
+    small
+        .iter()
+        .zip(large)
+        .all(|(small, large)| large.is_none() || small == large)
+}
 
-    for layout in &mut layouts {
-        layout.rect = layout.rect.translate(origin.to_vec2());
+/// Groups of true cells that cover every true cell of the table: the
+/// largest groups there are, taken greedily by how many cells they add.
+fn karnaugh_groups(rows: &[(Vec<bool>, bool)], names: usize) -> Vec<Group> {
+    let mut groups: Vec<Group> = vec![Vec::new()];
+    for _ in 0..names {
+        groups = groups
+            .into_iter()
+            .flat_map(|group| {
+                [None, Some(false), Some(true)].map(|fixed| {
+                    let mut group = group.clone();
+                    group.push(fixed);
+                    group
+                })
+            })
+            .collect();
     }
-
-    let highlight_color = GORBIE::themes::ral(2009);
-    let line_color = ui.visuals().widgets.inactive.bg_stroke.color;
-    let line_width = ui.visuals().widgets.inactive.bg_stroke.width.max(1.0);
-    let line_stroke = |highlight| {
-        egui::Stroke::new(
-            line_width,
-            if highlight {
-                highlight_color
-            } else {
-                line_color
-            },
-        )
-    };
-    let text_color = ui.visuals().text_color();
-    let painter = ui.painter();
-
-    for layout in &layouts {
-        for child_idx in &layout.children {
-            let child = &layouts[*child_idx];
-            let highlight = layout.highlight && child.highlight;
-            let stroke = line_stroke(highlight);
-            let start = layout.rect.center_bottom() + egui::vec2(0.0, stroke.width / 2.0);
-            let end = child.rect.center_top() - egui::vec2(0.0, stroke.width / 2.0);
-            let mid_y = (start.y + end.y) / 2.0;
-            let points = vec![
-                start,
-                egui::pos2(start.x, mid_y),
-                egui::pos2(end.x, mid_y),
-                end,
-            ];
-            painter.add(egui::Shape::line(points, stroke));
-        }
-        let stroke = line_stroke(layout.highlight);
-        painter.rect(
-            layout.rect,
-            egui::CornerRadius::same(4),
-            ui.visuals().code_bg_color,
-            stroke,
-            egui::StrokeKind::Inside,
-        );
-        let color = if layout.highlight {
-            highlight_color
-        } else {
-            text_color
+    let whole: Vec<Group> = groups
+        .into_iter()
+        .filter(|group| {
+            rows.iter()
+                .all(|(row, value)| *value || !group_holds(group, row))
+        })
+        .collect();
+    let largest: Vec<&Group> = whole
+        .iter()
+        .filter(|group| {
+            !whole
+                .iter()
+                .any(|other| other != *group && group_within(group, other))
+        })
+        .collect();
+
+    let mut uncovered: Vec<&Vec<bool>> = rows
+        .iter()
+        .filter(|(_, value)| *value)
+        .map(|(row, _)| row)
+        .collect();
+    let mut picked = Vec::new();
+    while !uncovered.is_empty() {
+        let Some(best) = largest.iter().max_by_key(|group| {
+            uncovered
+                .iter()
+                .filter(|row| group_holds(group, row))
+                .count()
+        }) else {
+            break;
         };
-        let galley = ui
-            .fonts_mut(|fonts| fonts.layout_no_wrap(layout.label.clone(), font_id.clone(), color));
-        let text_pos = layout.rect.center() - galley.size() / 2.0;
-        painter.galley(text_pos, galley, text_color);
+        uncovered.retain(|row| !group_holds(best, row));
+        picked.push((*best).clone());
     }
+    picked
 }
 
-fn draw_tree_interactive(
-    ui: &mut egui::Ui,
-    expr: &Expr,
-    next_path: Option<&[PathStep]>,
-) -> Option<Vec<PathStep>> {
-    let (mut layouts, desired, font_id) = build_tree_layout(ui, expr, None);
-    for layout in &mut layouts {
-        layout.highlight = next_path.map_or(false, |path| path == layout.path);
-    }
-
-    let (rect, _response) = ui.allocate_at_least(desired, egui::Sense::hover());
-    let mut origin = rect.min;
-    if rect.width() > desired.x {
-        origin.x += (rect.width() - desired.x) / 2.0;
-    }
-    if rect.height() > desired.y {
-        origin.y += (rect.height() - desired.y) / 2.0;
+/// A group as a formula: the names it fixes, joined by `and`.
+fn group_text(group: &Group, names: &[String]) -> String {
+    let literals: Vec<String> = group
+        .iter()
+        .zip(names)
+        .filter_map(|(fixed, name)| match fixed {
+            Some(true) => Some(name.clone()),
+            Some(false) => Some(format!("not {name}")),
+            None => None,
+        })
+        .collect();
+    if literals.is_empty() {
+        "true".to_string()
+    } else {
+        literals.join(" and ")
     }
+}
 
-    for layout in &mut layouts {
-        layout.rect = layout.rect.translate(origin.to_vec2());
-    }
+/// The map: one cell per row of the truth table, the first name down the
+/// side and the others along the top, with each group outlined.
+fn karnaugh_map(ui: &mut egui::Ui, names: &[String], expr: &Expr, groups: &[Group]) {
+    let palette = Palette::from_ui(ui);
+    let (down, across) = (gray_order(1), gray_order(names.len() - 1));
+    let cell = egui::vec2(56.0, 40.0);
+    let header = egui::vec2(72.0, 28.0);
+    let size = header + egui::vec2(cell.x * across.len() as f32, cell.y * down.len() as f32);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let described: Vec<String> = groups
+        .iter()
+        .map(|group| group_text(group, names))
+        .collect();
+    response.widget_info(|| {
+        egui::WidgetInfo::labeled(
+            egui::WidgetType::Other,
+            true,
+            format!("Karnaugh map with groups: {}", described.join("; ")),
+        )
+    });
 
-    let highlight_color = GORBIE::themes::ral(2009);
-    let line_color = ui.visuals().widgets.inactive.bg_stroke.color;
-    let line_width = ui.visuals().widgets.inactive.bg_stroke.width.max(1.0);
-    let line_stroke = |highlight| {
-        egui::Stroke::new(
-            line_width,
-            if highlight {
-                highlight_color
-            } else {
-                line_color
-            },
+    let painter = ui.painter_at(rect);
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let origin = rect.min + header;
+    let letter = |value: &bool| if *value { "T" } else { "F" };
+    let cell_rect = |row: usize, column: usize| {
+        egui::Rect::from_min_size(
+            origin + egui::vec2(column as f32 * cell.x, row as f32 * cell.y),
+            cell,
         )
     };
-    let text_color = ui.visuals().text_color();
-    let painter = ui.painter();
-    let mut clicked = None;
-
-    for layout in &layouts {
-        for child_idx in &layout.children {
-            let child = &layouts[*child_idx];
-            let highlight = layout.highlight && child.highlight;
-            let stroke = line_stroke(highlight);
-            let start = layout.rect.center_bottom() + egui::vec2(0.0, stroke.width / 2.0);
-            let end = child.rect.center_top() - egui::vec2(0.0, stroke.width / 2.0);
-            let mid_y = (start.y + end.y) / 2.0;
-            let points = vec![
-                start,
-                egui::pos2(start.x, mid_y),
-                egui::pos2(end.x, mid_y),
-                end,
-            ];
-            painter.add(egui::Shape::line(points, stroke));
-        }
-
-        let id = ui.make_persistent_id(("bool-tree-node", &layout.path));
-        let response = ui.interact(layout.rect, id, egui::Sense::click());
-        if response.clicked() {
-            clicked = Some(layout.path.clone());
+    painter.text(
+        rect.min + header / 2.0,
+        egui::Align2::CENTER_CENTER,
+        format!("{} \\ {}", names[0], names[1..].join(" ")),
+        font_id.clone(),
+        palette.weak_text,
+    );
+    for (column, values) in across.iter().enumerate() {
+        let label: Vec<&str> = values.iter().map(letter).collect();
+        painter.text(
+            egui::pos2(cell_rect(0, column).center().x, rect.top() + header.y / 2.0),
+            egui::Align2::CENTER_CENTER,
+            label.join(" "),
+            font_id.clone(),
+            palette.weak_text,
+        );
+    }
+    for (row, first) in down.iter().enumerate() {
+        painter.text(
+            egui::pos2(rect.left() + header.x / 2.0, cell_rect(row, 0).center().y),
+            egui::Align2::CENTER_CENTER,
+            letter(&first[0]),
+            font_id.clone(),
+            palette.weak_text,
+        );
+        for (column, rest) in across.iter().enumerate() {
+            let values: Vec<bool> = first.iter().chain(rest).copied().collect();
+            let env: Vec<(String, bool)> = names.iter().cloned().zip(values).collect();
+            let value = expr.eval_in(&env).unwrap_or(false);
+            let area = cell_rect(row, column);
+            painter.rect(
+                area,
+                0.0,
+                palette.code_fill,
+                palette.line,
+                egui::StrokeKind::Inside,
+            );
+            painter.text(
+                area.center(),
+                egui::Align2::CENTER_CENTER,
+                letter(&value),
+                font_id.clone(),
+                if value {
+                    palette.text
+                } else {
+                    palette.weak_text
+                },
+            );
         }
+    }
 
-        let stroke = line_stroke(layout.highlight);
-        painter.rect(
-            layout.rect,
-            egui::CornerRadius::same(4),
-            ui.visuals().code_bg_color,
-            stroke,
-            egui::StrokeKind::Inside,
-        );
-        let color = if layout.highlight {
-            highlight_color
-        } else {
-            text_color
+    // Groups that wrap around the edge are drawn as one outline per side.
+    let stroke = egui::Stroke::new(palette.stroke_width(2.0), palette.highlight);
+    for (index, group) in groups.iter().enumerate() {
+        let inset = 4.0 + 3.0 * index as f32;
+        let rows: Vec<usize> = (0..down.len())
+            .filter(|row| group[0].is_none_or(|fixed| fixed == down[*row][0]))
+            .collect();
+        let columns: Vec<usize> = (0..across.len())
+            .filter(|column| {
+                group[1..]
+                    .iter()
+                    .zip(&across[*column])
+                    .all(|(fixed, value)| fixed.is_none_or(|fixed| fixed == *value))
+            })
+            .collect();
+        let (Some(top), Some(bottom)) = (rows.first(), rows.last()) else {
+            continue;
         };
-        let galley = ui
-            .fonts_mut(|fonts| fonts.layout_no_wrap(layout.label.clone(), font_id.clone(), color));
-        let text_pos = layout.rect.center() - galley.size() / 2.0;
-        painter.galley(text_pos, galley, text_color);
+        let mut runs: Vec<(usize, usize)> = Vec::new();
+        for column in columns {
+            match runs.last_mut() {
+                Some((_, end)) if *end + 1 == column => *end = column,
+                _ => runs.push((column, column)),
+            }
+        }
+        for (start, end) in runs {
+            let outline = cell_rect(*top, start).union(cell_rect(*bottom, end));
+            painter.rect_stroke(outline.shrink(inset), 8.0, stroke, egui::StrokeKind::Inside);
+        }
     }
+}
 
-    clicked
+/// The numbers the comparison card has sliders for.
+const SLIDERS: [&str; 2] = ["x", "y"];
+
+/// The comparison card: a boolean expression over comparisons of the
+/// slider numbers.
+struct ComparisonState {
+    input: String,
+    numbers: [i64; 2],
+    step: usize,
 }
 
-fn count_ops(expr: &Expr) -> usize {
-    match &expr.kind {
-        ExprKind::Bool(_) => 0,
-        ExprKind::Not(inner) => 1 + count_ops(inner),
-        ExprKind::And(left, right) | ExprKind::Or(left, right) => {
-            1 + count_ops(left) + count_ops(right)
+impl Default for ComparisonState {
+    fn default() -> Self {
+        Self {
+            input: "x > 3 and not y == 5".to_string(),
+            numbers: [7, 5],
+            step: 0,
         }
     }
 }
 
-fn eval_expr(expr: &Expr) -> Result<bool, String> {
-    match &expr.kind {
-        ExprKind::Bool(value) => Ok(*value),
-        ExprKind::Not(inner) => Ok(!eval_expr(inner)?),
-        ExprKind::And(left, right) => Ok(eval_expr(left)? && eval_expr(right)?),
-        ExprKind::Or(left, right) => Ok(eval_expr(left)? || eval_expr(right)?),
-    }
-}
+/// The most names a truth table takes: three give eight rows.
+const MAX_NAMES: usize = 3;
 
-fn expr_to_string(expr: &Expr) -> String {
-    render_expr_with_highlight(expr, None).0
+/// The expression and the values picked for its names in the truth table
+/// card; the row with those values is highlighted.
+struct TruthTableState {
+    input: String,
+    inputs: Vec<bool>,
+    advanced: bool,
+}
+
+impl Default for TruthTableState {
+    fn default() -> Self {
+        Self {
+            input: "(a or b) and not c".to_string(),
+            inputs: vec![false; MAX_NAMES],
+            advanced: false,
+        }
+    }
+}
+
+/// Every row of the truth table for `names`: the values of the names,
+/// all false first, with the last name changing fastest.
+fn truth_rows(names: usize) -> Vec<Vec<bool>> {
+    (0..1usize << names)
+        .map(|row| {
+            (0..names)
+                .map(|index| row >> (names - 1 - index) & 1 == 1)
+                .collect()
+        })
+        .collect()
+}
+
+/// The sets the Venn card draws, as the names an expression uses for them.
+const SETS: [&str; 2] = ["A", "B"];
+
+/// The four parts of the Venn diagram, by whether they lie in A and in B.
+const REGIONS: [((bool, bool), &str); 4] = [
+    ((true, false), "only A"),
+    ((true, true), "A and B"),
+    ((false, true), "only B"),
+    ((false, false), "outside both"),
+];
+
+/// The expression whose points the Venn card shades.
+struct VennState {
+    input: String,
+}
+
+impl Default for VennState {
+    fn default() -> Self {
+        Self {
+            input: "A and not B".to_string(),
+        }
+    }
+}
+
+/// Whether each region of the diagram belongs to `expr`, in the order of
+/// [`REGIONS`].
+fn venn_regions(expr: &Expr) -> Result<[bool; 4], String> {
+    let mut shaded = [false; 4];
+    for (((a, b), _), shade) in REGIONS.iter().zip(&mut shaded) {
+        let env = [(SETS[0].to_string(), *a), (SETS[1].to_string(), *b)];
+        *shade = expr.eval_in(&env)?;
+    }
+    Ok(shaded)
+}
+
+/// Two overlapping circles in a box, with every point shaded whose region
+/// is in `shaded`. The box stands for everything outside both sets.
+fn venn_diagram(ui: &mut egui::Ui, shaded: [bool; 4]) {
+    let palette = Palette::from_ui(ui);
+    let size = egui::vec2(ui.available_width().min(360.0), 200.0);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let names: Vec<&str> = REGIONS
+        .iter()
+        .zip(shaded)
+        .filter(|(_, shade)| *shade)
+        .map(|((_, name), _)| *name)
+        .collect();
+    response.widget_info(|| {
+        let shaded = if names.is_empty() {
+            "nothing".to_string()
+        } else {
+            names.join(", ")
+        };
+        egui::WidgetInfo::labeled(
+            egui::WidgetType::Other,
+            true,
+            format!("Venn diagram of A and B, shaded: {shaded}"),
+        )
+    });
+
+    let painter = ui.painter_at(rect);
+    let radius = rect.height() * 0.36;
+    let a = rect.center() - egui::vec2(radius * 0.55, 0.0);
+    let b = rect.center() + egui::vec2(radius * 0.55, 0.0);
+    let fill = palette.highlight.gamma_multiply(0.45);
+    // Shaded in small squares, so any mix of regions comes out right.
+    let cell = 4.0;
+    let mut y = rect.top();
+    while y < rect.bottom() {
+        let mut x = rect.left();
+        while x < rect.right() {
+            let point = egui::pos2(x + cell / 2.0, y + cell / 2.0);
+            let inside = (point.distance(a) <= radius, point.distance(b) <= radius);
+            let region = REGIONS.iter().position(|(region, _)| *region == inside);
+            if region.is_some_and(|region| shaded[region]) {
+                let square = egui::Rect::from_min_size(egui::pos2(x, y), egui::vec2(cell, cell));
+                painter.rect_filled(square.intersect(rect), 0.0, fill);
+            }
+            x += cell;
+        }
+        y += cell;
+    }
+
+    painter.rect_stroke(rect, 4.0, palette.line, egui::StrokeKind::Inside);
+    painter.circle_stroke(a, radius, palette.line);
+    painter.circle_stroke(b, radius, palette.line);
+    let font_id = egui::FontId::proportional(18.0);
+    for (center, name, side) in [(a, SETS[0], -1.0), (b, SETS[1], 1.0)] {
+        painter.text(
+            center + egui::vec2(side * radius * 0.45, -radius * 0.65),
+            egui::Align2::CENTER_CENTER,
+            name,
+            font_id.clone(),
+            palette.text,
+        );
+    }
+}
+
+/// Expressions with something to leave out, with `x` and `y` for the
+/// names: each shortens to at most one operator.
+const REDUNDANT: [&str; 8] = [
+    "x or (x and y)",
+    "x and (x or y)",
+    "not not x",
+    "x and true",
+    "x or false",
+    "x and x",
+    "(x and y) or (x and not y)",
+    "(x or y) and (x or not y)",
+];
+
+/// The simplification card: an expression with redundancy and the
+/// learner's shorter version of it.
+struct SimplifyState {
+    rng: SimpleRng,
+    seed: u64,
+    expr: Expr,
+    input: String,
+    /// The input as it was when "Check" was last pressed.
+    checked: Option<String>,
+}
+
+impl Default for SimplifyState {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
+        let expr = generate_redundant(&mut rng);
+        Self {
+            rng,
+            seed,
+            expr,
+            input: String::new(),
+            checked: None,
+        }
+    }
+}
+
+impl SimplifyState {
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
+    }
+
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        self.expr = generate_redundant(&mut self.rng);
+        self.input.clear();
+        self.checked = None;
+    }
+}
+
+/// One of the [`REDUNDANT`] shapes, with two different names from `a`, `b`
+/// and `c` put in.
+fn generate_redundant(rng: &mut SimpleRng) -> Expr {
+    let names = ["a", "b", "c"];
+    let shape = REDUNDANT[rng.gen_range_i32(0, REDUNDANT.len() as i32 - 1) as usize];
+    let x = rng.gen_range_i32(0, 2) as usize;
+    let y = (x + 1 + rng.gen_range_i32(0, 1) as usize) % names.len();
+    let text = shape
+        .replace('x', "\0")
+        .replace('y', names[y])
+        .replace('\0', names[x]);
+    Parser::with_names(&text)
+        .parse_expression()
+        .expect("the redundant shapes parse")
+}
+
+/// The first choice of values for `names` where `left` and `right` come out
+/// different, or `None` when they agree everywhere.
+fn differing_row(left: &Expr, right: &Expr, names: &[String]) -> Option<Vec<(String, bool)>> {
+    truth_rows(names.len()).into_iter().find_map(|row| {
+        let env: Vec<(String, bool)> = names.iter().cloned().zip(row).collect();
+        (left.eval_in(&env) != right.eval_in(&env)).then_some(env)
+    })
+}
+
+/// The names the step-through and assignment cards have switches for.
+const SWITCHES: [&str; 3] = ["a", "b", "c"];
+
+/// Formulas no choice of values makes true, for the assignment card to
+/// mix in.
+const IMPOSSIBLE: [&str; 4] = [
+    "(a or b) and not a and not b",
+    "(a and not b) and (b or not a)",
+    "a and (not a or b) and not b",
+    "(a or b) and (a or not b) and not a",
+];
+
+/// What the learner claims about the formula: these values make it true,
+/// or none do.
+#[derive(Clone, Copy, PartialEq)]
+enum Claim {
+    Values([bool; 3]),
+    Impossible,
+}
+
+/// The assignment card: a formula over the switches and the learner's
+/// last claim about it.
+struct AssignmentState {
+    rng: SimpleRng,
+    seed: u64,
+    expr: Expr,
+    values: [bool; 3],
+    checked: Option<Claim>,
+}
+
+impl Default for AssignmentState {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
+        let expr = generate_formula(&mut rng);
+        Self {
+            rng,
+            seed,
+            expr,
+            values: [false; 3],
+            checked: None,
+        }
+    }
+}
+
+impl AssignmentState {
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
+    }
+
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        self.expr = generate_formula(&mut self.rng);
+        self.values = [false; 3];
+        self.checked = None;
+    }
+
+    fn holds(&self, values: &[bool]) -> bool {
+        let env: Vec<(String, bool)> = SWITCHES
+            .iter()
+            .map(|name| name.to_string())
+            .zip(values.iter().copied())
+            .collect();
+        self.expr.eval_in(&env).unwrap_or(false)
+    }
+
+    /// Whether some choice of values makes the formula true, trying all
+    /// eight.
+    fn possible(&self) -> bool {
+        truth_rows(SWITCHES.len()).iter().any(|row| self.holds(row))
+    }
+}
+
+/// A formula over the switches: mostly two or three `or`s of two names
+/// joined by `and`, now and then one of the [`IMPOSSIBLE`] ones.
+fn generate_formula(rng: &mut SimpleRng) -> Expr {
+    if rng.gen_range_i32(0, 3) == 0 {
+        let text = IMPOSSIBLE[rng.gen_range_i32(0, IMPOSSIBLE.len() as i32 - 1) as usize];
+        return Parser::with_names(text)
+            .parse_expression()
+            .expect("the impossible formulas parse");
+    }
+    let literal = |rng: &mut SimpleRng| {
+        let name = Expr::Var(SWITCHES[rng.gen_range_i32(0, 2) as usize].to_string());
+        if rng.gen_range_i32(0, 1) == 0 {
+            Expr::unary(UnaryOp::Not, name)
+        } else {
+            name
+        }
+    };
+    let clauses = rng.gen_range_i32(2, 3);
+    let mut formula = None;
+    for _ in 0..clauses {
+        let clause = Expr::binary(BinaryOp::Or, literal(rng), literal(rng));
+        formula = Some(match formula {
+            None => clause,
+            Some(formula) => Expr::binary(BinaryOp::And, formula, clause),
+        });
+    }
+    formula.expect("at least two clauses")
+}
+
+/// Formulas over `a` and `b` with different truth tables, for the
+/// reconstruction card to pick the table and the candidates from.
+const TABLE_FORMULAS: [&str; 10] = [
+    "a and b",
+    "a or b",
+    "not a",
+    "b",
+    "a and not b",
+    "not a and b",
+    "a or not b",
+    "not a or b",
+    "not (a and b)",
+    "not (a or b)",
+];
+
+/// How many formulas the reconstruction card offers.
+const CANDIDATES: usize = 4;
+
+/// The reconstruction card: a truth table, the formulas offered for it and
+/// which of them the learner picked.
+struct ReconstructState {
+    rng: SimpleRng,
+    seed: u64,
+    candidates: Vec<Expr>,
+    /// The index of the formula the table belongs to.
+    answer: usize,
+    selection: Option<usize>,
+}
+
+impl Default for ReconstructState {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
+        let (candidates, answer) = generate_candidates(&mut rng);
+        Self {
+            rng,
+            seed,
+            candidates,
+            answer,
+            selection: None,
+        }
+    }
+}
+
+impl ReconstructState {
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
+    }
+
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        (self.candidates, self.answer) = generate_candidates(&mut self.rng);
+        self.selection = None;
+    }
+}
+
+/// The values of `expr` over `a` and `b`, one per truth table row.
+fn column(expr: &Expr) -> Vec<Result<bool, String>> {
+    truth_rows(2)
+        .into_iter()
+        .map(|row| expr.eval_in(&[("a".to_string(), row[0]), ("b".to_string(), row[1])]))
+        .collect()
 }
 
-fn random_expr(rng: &mut SimpleRng, depth: u8, max_depth: u8) -> Expr {
-    let use_literal = depth >= max_depth || rng.gen_range_i32(0, 3) == 0;
+/// [`CANDIDATES`] of the [`TABLE_FORMULAS`] in random order, and which one
+/// the table shows.
+fn generate_candidates(rng: &mut SimpleRng) -> (Vec<Expr>, usize) {
+    let mut formulas = TABLE_FORMULAS;
+    rng.shuffle(&mut formulas);
+    let candidates = formulas[..CANDIDATES]
+        .iter()
+        .map(|text| {
+            Parser::with_names(text)
+                .parse_expression()
+                .expect("the table formulas parse")
+        })
+        .collect();
+    let answer = rng.gen_range_i32(0, CANDIDATES as i32 - 1) as usize;
+    (candidates, answer)
+}
+
+fn random_expr(rng: &mut SimpleRng, depth: u8, difficulty: Difficulty) -> Expr {
+    let use_literal = depth >= difficulty.max_depth() || rng.gen_range_i32(0, 3) == 0;
     if use_literal {
         let value = rng.gen_range_i32(0, 1) == 1;
-        return Expr::boolean(value);
+        return Expr::value(value);
     }
 
+    // The first level leaves out `not`.
     let roll = rng.gen_range_i32(0, 2);
-    if roll == 0 {
-        let inner = random_expr(rng, depth + 1, max_depth);
-        return Expr {
-            kind: ExprKind::Not(Box::new(inner)),
-        };
+    if roll == 0 && difficulty.harder_ops() {
+        let inner = random_expr(rng, depth + 1, difficulty);
+        return Expr::unary(UnaryOp::Not, inner);
     }
 
-    let left = random_expr(rng, depth + 1, max_depth);
-    let right = random_expr(rng, depth + 1, max_depth);
-    let kind = if rng.gen_range_i32(0, 1) == 0 {
-        ExprKind::And(Box::new(left), Box::new(right))
+    let left = random_expr(rng, depth + 1, difficulty);
+    let right = random_expr(rng, depth + 1, difficulty);
+    let op = if rng.gen_range_i32(0, 1) == 0 {
+        BinaryOp::And
     } else {
-        ExprKind::Or(Box::new(left), Box::new(right))
+        BinaryOp::Or
     };
-    Expr { kind }
+    Expr::binary(op, left, right)
 }
 
-fn generate_exercise(rng: &mut SimpleRng) -> Exercise {
+fn generate_exercise(rng: &mut SimpleRng, difficulty: Difficulty) -> Practice {
     for _ in 0..200 {
-        let expr = random_expr(rng, 0, 3);
-        if matches!(expr.kind, ExprKind::Bool(_)) {
+        let expr = random_expr(rng, 0, difficulty);
+        if expr.is_value() {
             continue;
         }
-        if count_ops(&expr) < 2 {
+        if expr.count_ops() < 2 {
             continue;
         }
-        if let Ok(answer) = eval_expr(&expr) {
-            return Exercise { expr, answer };
+        if let Ok(answer) = expr.eval() {
+            return Practice {
+                expr,
+                answer,
+                step: 0,
+            };
         }
     }
-    Exercise {
-        expr: Expr {
-            kind: ExprKind::And(
-                Box::new(Expr::boolean(true)),
-                Box::new(Expr::boolean(false)),
-            ),
-        },
+    Practice {
+        expr: Expr::binary(BinaryOp::And, Expr::value(true), Expr::value(false)),
         answer: false,
+        step: 0,
+    }
+}
+
+/// A true-or-false expression for a worksheet, with its value for the key.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<Practice>(rng, difficulty)
+}
+
+/// Checks the choices of a practice exercise, a practice exercise and a
+/// tree exercise for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<Practice>(rng, difficulty)?;
+    let exercise = generate_exercise(rng, difficulty);
+    let value = expr::self_check(&exercise.expr, parse_expression)?;
+    if value != exercise.answer {
+        return Err(format!(
+            "`{}` is {value}, but the card expects {}",
+            expr_to_string(&exercise.expr),
+            exercise.answer
+        ));
     }
+    expr::self_check(&generate_tree_expr(rng, difficulty), parse_expression)?;
+    Ok(())
 }
 
-fn generate_tree_expr(rng: &mut SimpleRng) -> Expr {
+fn generate_tree_expr(rng: &mut SimpleRng, difficulty: Difficulty) -> Expr {
     for _ in 0..200 {
-        let expr = random_expr(rng, 0, 3);
-        if matches!(expr.kind, ExprKind::Bool(_)) {
+        let expr = random_expr(rng, 0, difficulty);
+        if expr.is_value() {
             continue;
         }
-        if count_ops(&expr) < 2 {
+        if expr.count_ops() < 2 {
             continue;
         }
         return expr;
     }
-    Expr::boolean(true)
+    Expr::value(true)
 }
 
-pub fn booleans(nb: &mut NotebookCtx) {
-    nb.view(|ui| {
-        md!(
-            ui,
-            "# To Bool or Not to Bool\n\\
-             A **boolean** is a value with two options.\n\\
-             It answers a yes/no question.\n\\
-             We will write booleans as `true` and `false`.\n\n\\
-             Common pairs that mean the same idea:\n\\
-             - yes / no\n\\
-             - on / off\n\\
-             - true / false\n\\
-             - bit (0/1)\n\\
-             - thumbs up / thumbs down\n\\
-             - open / closed\n\\
-             - pass / fail"
-        );
-    });
+/// The value `op` has once its left side is `left`, if the right side
+/// cannot change it any more: `false and …` is false, `true or …` true.
+fn decides(op: BinaryOp, left: bool) -> Option<bool> {
+    match (op, left) {
+        (BinaryOp::And, false) => Some(false),
+        (BinaryOp::Or, true) => Some(true),
+        (BinaryOp::Implies, false) | (BinaryOp::Nand, false) => Some(true),
+        _ => None,
+    }
+}
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## Why booleans\n\\
-             Booleans let us ask questions and make decisions.\n\\
-             They are the simplest way to describe a condition.\n\n\\
-             Examples:\n\\
-             - Is the light on?\n\\
-             - Is the number bigger than 10?\n\\
-             - Did the user press the button?"
-        );
-    });
+/// A box to work out next and whether its left side alone decides it.
+type Next = Option<(Vec<PathStep>, bool)>;
+
+/// The next box short-circuit evaluation works out, and whether it is
+/// decided by its left side alone. Left sides go first, all the way down;
+/// a right side is only looked at when the left one did not decide.
+fn next_short_circuit(expr: &Expr) -> Next {
+    let inside = |step: PathStep, child: &Expr| {
+        next_short_circuit(child).map(|(mut path, skip)| {
+            path.insert(0, step);
+            (path, skip)
+        })
+    };
+    match expr {
+        Expr::Value(_) => None,
+        Expr::Var(_) => Some((Vec::new(), false)),
+        Expr::Unary(_, inner) if !inner.is_value() => inside(PathStep::Unary, inner),
+        Expr::Unary(..) => Some((Vec::new(), false)),
+        Expr::Binary(op, left, right) => match left.as_value() {
+            None => inside(PathStep::Left, left),
+            Some(left) if decides(*op, *left).is_some() => Some((Vec::new(), true)),
+            Some(_) if !right.is_value() => inside(PathStep::Right, right),
+            Some(_) => Some((Vec::new(), false)),
+        },
+    }
+}
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## Boolean operations\n\\
-             We can combine booleans using three simple operations:\n\\
-             - **not** flips a value.\n\\
-             - **and** needs both sides to be true.\n\\
-             - **or** needs at least one side to be true.\n\n\\
-             ```text\n\\
-             not true  -> false\n\\
-             true and false -> false\n\\
-             true or false  -> true\n\\
-             ```"
-        );
-    });
+/// `expr` with the box at `path` replaced by the value its left side
+/// decided.
+fn short_circuit_at(expr: Expr, path: &[PathStep]) -> Expr {
+    match (expr, path.split_first()) {
+        (Expr::Binary(op, left, _), None) => {
+            let left = left.as_value().copied().unwrap_or_default();
+            Expr::value(decides(op, left).unwrap_or(left))
+        }
+        (Expr::Unary(op, inner), Some((PathStep::Unary, rest))) => {
+            Expr::unary(op, short_circuit_at(*inner, rest))
+        }
+        (Expr::Binary(op, left, right), Some((PathStep::Left, rest))) => {
+            Expr::binary(op, short_circuit_at(*left, rest), *right)
+        }
+        (Expr::Binary(op, left, right), Some((PathStep::Right, rest))) => {
+            Expr::binary(op, *left, short_circuit_at(*right, rest))
+        }
+        (expr, _) => expr,
+    }
+}
+
+/// Every step of short-circuit evaluation: the expression, and the box
+/// worked out next with whether it skips its right side.
+struct ShortCircuit(Vec<(Expr, Next)>);
+
+impl ShortCircuit {
+    fn new(expr: Expr) -> Self {
+        let mut steps = Vec::new();
+        let mut current = expr;
+        loop {
+            let next = next_short_circuit(&current);
+            steps.push((current.clone(), next.clone()));
+            current = match next {
+                None => break,
+                Some((path, true)) => short_circuit_at(current, &path),
+                Some((path, false)) => match reduce_at(current, &path) {
+                    Ok(next) => next,
+                    Err(_) => break,
+                },
+            };
+        }
+        Self(steps)
+    }
+}
+
+/// Like the usual steps, with the right side a step skips faded out.
+impl Steps for ShortCircuit {
+    fn count(&self) -> usize {
+        self.0.len()
+    }
 
-    nb.view(|ui| {
-        md!(
+    fn show(&self, ui: &mut egui::Ui, step: usize) {
+        let (expr, next) = &self.0[step];
+        let path = next.as_ref().map(|(path, _)| path.as_slice());
+        let skipped = next.as_ref().filter(|(_, skip)| *skip).map(|(path, _)| {
+            let mut right = path.clone();
+            right.push(PathStep::Right);
+            right
+        });
+        let (code, ranges) = render_expr_with_highlight(expr, path);
+        ui.add(CodeBlock::line(&code).highlight_ranges(&ranges));
+        ui.add_space(6.0);
+        draw_tree_skipping(
             ui,
-            "## Rules of evaluation\n\\
-             When a boolean expression has several operations, there are rules:\n\\
-             - Parentheses first: `(true or false) and true`.\n\\
-             - Deepest expression first: evaluate the innermost parentheses first.\n\\
-             - not before and before or.\n\\
-             - Left-to-right when the precedence is the same.\n\n\\
-             These rules are called **precedence** and **associativity**.\n\\
-             You do not need to memorize the names, just the rules."
+            &expr.to_tree(),
+            |node| path.is_some_and(|path| path_in_subtree(node, path)),
+            |node| {
+                skipped
+                    .as_deref()
+                    .is_some_and(|right| path_in_subtree(node, right))
+            },
         );
-    });
+        ui.add_space(6.0);
+        let node = path.and_then(|path| expr_at_path(expr, path));
+        match (node, next) {
+            (Some(Expr::Binary(op, left, _)), Some((_, true))) => {
+                let left = left.as_value().copied().unwrap_or_default();
+                let value = decides(*op, left).unwrap_or(left);
+                ui.label(format!(
+                    "`{left} {} \u{2026}` is {value} whatever the right side is.",
+                    Logic::binary_label(*op)
+                ));
+                ui.label("So the faded right side is skipped: it is never worked out.");
+            }
+            (Some(_), _) => {
+                ui.label("The highlighted part is what you can evaluate next.");
+            }
+            _ => {
+                ui.label("Fully evaluated.");
+            }
+        }
+    }
+}
+
+/// The short-circuit card: one expression, worked out box by box or with
+/// short-circuiting.
+struct ShortCircuitState {
+    input: String,
+    short: bool,
+    step: usize,
+}
+
+impl Default for ShortCircuitState {
+    fn default() -> Self {
+        Self {
+            input: "false and (true or not false)".to_string(),
+            short: true,
+            step: 0,
+        }
+    }
+}
+
+/// The "Advanced operators" switch, and what the operators do while it is
+/// on. The basic lesson gets by without them.
+fn advanced_toggle(ui: &mut egui::Ui, advanced: &mut bool) {
+    ui.add(widgets::ToggleButton::new(advanced, "Advanced operators"));
+    if !*advanced {
+        return;
+    }
+    ui.label("xor: exactly one side is true.");
+    ui.label("implies (or ->): false only when the left is true and the right is false.");
+    ui.label("nand: not both true, the same as not (a and b).");
+    ui.label(
+        egui::RichText::new(
+            "They rank: not, and/nand, xor, or, implies. implies groups from the right.",
+        )
+        .small()
+        .weak(),
+    );
+}
+
+pub fn booleans(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# To Bool or Not to Bool\n\\
+         A **boolean** is a value with two options.\n\\
+         It answers a yes/no question.\n\\
+         We will write booleans as `true` and `false`.\n\n\\
+         Common pairs that mean the same idea:\n\\
+         - yes / no\n\\
+         - on / off\n\\
+         - true / false\n\\
+         - bit (0/1)\n\\
+         - thumbs up / thumbs down\n\\
+         - open / closed\n\\
+         - pass / fail",
+    );
+
+    speech::lesson(
+        nb,
+        "## Why booleans\n\\
+         Booleans let us ask questions and make decisions.\n\\
+         They are the simplest way to describe a condition.\n\n\\
+         Examples:\n\\
+         - Is the light on?\n\\
+         - Is the number bigger than 10?\n\\
+         - Did the user press the button?",
+    );
+
+    speech::lesson(
+        nb,
+        "## Boolean operations\n\\
+         We can combine booleans using three simple operations:\n\\
+         - **not** flips a value.\n\\
+         - **and** needs both sides to be true.\n\\
+         - **or** needs at least one side to be true.\n\n\\
+         ```text\n\\
+         not true  -> false\n\\
+         true and false -> false\n\\
+         true or false  -> true\n\\
+         ```",
+    );
+
+    speech::lesson(
+        nb,
+        "## Rules of evaluation\n\\
+         When a boolean expression has several operations, there are rules:\n\\
+         - Parentheses first: `(true or false) and true`.\n\\
+         - Deepest expression first: evaluate the innermost parentheses first.\n\\
+         - not before and before or.\n\\
+         - Left-to-right when the precedence is the same.\n\n\\
+         These rules are called **precedence** and **associativity**.\n\\
+         You do not need to memorize the names, just the rules.",
+    );
 
     nb.state(
-        &chapter_key("expression_state"),
+        &state_key("expression_state"),
         ExpressionState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Step through a boolean expression").heading());
+                if !card_heading(ui, "Step through a boolean expression") {
+                    return;
+                }
                 ui.add_space(4.0);
                 ui.label("Use true/false, and/or/not, and parentheses.");
+                ui.label("The names a, b and c work too: set their values with the switches.");
                 ui.add_space(6.0);
 
                 ui.horizontal(|ui| {
@@ -1016,205 +1595,935 @@ pub fn booleans(nb: &mut NotebookCtx) {
                         state.step = 0;
                     }
                     if ui.add(widgets::Button::new("Random")).clicked() {
-                        let expr = generate_tree_expr(&mut state.rng);
+                        let expr = generate_tree_expr(&mut state.rng, difficulty::current());
                         state.input = expr_to_string(&expr);
                         state.step = 0;
                     }
                 });
+                advanced_toggle(ui, &mut state.advanced);
 
-                let expr = match parse_expression(&state.input) {
+                let mut parser = Parser::with_names(&state.input).advanced(state.advanced);
+                let expr = match parser.parse_expression() {
                     Ok(expr) => expr,
                     Err(error) => {
-                        ui.add_space(6.0);
-                        ui.label(
-                            RichText::new(format!("Parse error: {error}"))
-                                .color(ui.visuals().error_fg_color),
-                        );
-                        ui.add_space(2.0);
-                        ui.label(
-                            RichText::new("Tip: check parentheses or a missing true/false.")
-                                .color(ui.visuals().weak_text_color()),
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: check parentheses or a missing true/false.",
                         );
                         return;
                     }
                 };
+                let mut names = Vec::new();
+                names_in(&expr, &mut names);
+                if let Some(name) = names.iter().find(|name| !SWITCHES.contains(&name.as_str())) {
+                    ui.label(exercise::not_quite(&format!(
+                        "`{name}` has no switch here; use a, b or c."
+                    )));
+                    return;
+                }
+                if names.is_empty() {
+                    let steps = build_steps(expr);
+                    ui.add_space(6.0);
+                    stepper(ui, &mut state.step, &steps);
+                    return;
+                }
+
+                ui.add_space(6.0);
+                let values = state.values;
+                ui.horizontal(|ui| {
+                    for (name, value) in SWITCHES.iter().zip(&mut state.values) {
+                        if names.iter().any(|used| used == name) {
+                            let label = format!("{name} = {value}");
+                            ui.add(widgets::ToggleButton::new(value, label));
+                        }
+                    }
+                });
+                if state.values != values {
+                    state.step = 0;
+                }
+                let env = SWITCHES
+                    .iter()
+                    .map(|name| name.to_string())
+                    .zip(state.values)
+                    .collect();
+                let steps = Substituted::new(expr, env);
+
+                ui.add_space(6.0);
+                stepper(ui, &mut state.step, &steps);
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("truth_table_state"),
+        TruthTableState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Truth table") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Names like a, b or c stand for a boolean that is not decided yet.");
+                ui.label("A truth table tries every choice for them: one row per choice.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Expression:");
+                    ui.add(widgets::TextField::singleline(&mut state.input));
+                });
+                advanced_toggle(ui, &mut state.advanced);
 
-                let steps = match build_steps(expr) {
-                    Ok(steps) => steps,
+                let mut parser = Parser::with_names(&state.input).advanced(state.advanced);
+                let expr = match parser.parse_expression() {
+                    Ok(expr) => expr,
                     Err(error) => {
-                        ui.add_space(6.0);
-                        ui.label(
-                            RichText::new(format!("Evaluation error: {error}"))
-                                .color(ui.visuals().error_fg_color),
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: use names, true/false, and/or/not, and parentheses.",
                         );
                         return;
                     }
                 };
+                let mut names = Vec::new();
+                names_in(&expr, &mut names);
+                if names.len() > MAX_NAMES {
+                    ui.label(exercise::not_quite(&format!(
+                        "This table fits up to {MAX_NAMES} names, and this has {}.",
+                        names.len()
+                    )));
+                    return;
+                }
+                ui.add_space(6.0);
+
+                ui.horizontal(|ui| {
+                    for (name, value) in names.iter().zip(&mut state.inputs) {
+                        let label = format!("{name} = {value}");
+                        ui.add(widgets::ToggleButton::new(value, label));
+                    }
+                });
+                ui.add_space(6.0);
+
+                let current = &state.inputs[..names.len()];
+                let code = expr_to_string(&expr);
+                let palette = Palette::from_ui(ui);
+                egui::Grid::new("truth_table")
+                    .striped(true)
+                    .spacing(egui::vec2(16.0, 4.0))
+                    .show(ui, |ui| {
+                        for name in &names {
+                            ui.label(egui::RichText::new(name).monospace().strong());
+                        }
+                        ui.label(egui::RichText::new(&code).monospace().strong());
+                        ui.end_row();
+                        for row in truth_rows(names.len()) {
+                            let env: Vec<(String, bool)> =
+                                names.iter().cloned().zip(row.iter().copied()).collect();
+                            let value = expr.eval_in(&env);
+                            let picked = row == current;
+                            let cell = |text: String| {
+                                let text = egui::RichText::new(text).monospace();
+                                if picked {
+                                    text.strong().color(palette.highlight)
+                                } else {
+                                    text
+                                }
+                            };
+                            for value in &row {
+                                ui.label(cell(value.to_string()));
+                            }
+                            match value {
+                                Ok(value) => ui.label(cell(value.to_string())),
+                                Err(error) => ui.label(cell(error)),
+                            };
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(6.0);
+                ui.label("The highlighted row belongs to the values picked above.");
+            });
+        },
+    );
 
-                let max_step = steps.len().saturating_sub(1);
-                if state.step > max_step {
-                    state.step = max_step;
+    nb.state(
+        &state_key("tautology_state"),
+        TautologyState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Always, never, or sometimes?") {
+                    return;
                 }
+                ui.add_space(4.0);
+                ui.label("A formula true in every row is a tautology.");
+                ui.label("One false in every row is a contradiction.");
+                ui.label("Anything else is contingent: its value depends on the names.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Formula:");
+                    if ui
+                        .add(widgets::TextField::singleline(&mut state.input))
+                        .changed()
+                    {
+                        state.shown = 0;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    for input in ["a or not a", "a and not a", "a implies (b implies a)"] {
+                        if ui.add(widgets::Button::new(input).small()).clicked() {
+                            state.input = input.to_string();
+                            state.shown = 0;
+                            state.advanced |= input.contains("implies");
+                        }
+                    }
+                });
+                advanced_toggle(ui, &mut state.advanced);
 
+                let mut parser = Parser::with_names(&state.input).advanced(state.advanced);
+                let expr = match parser.parse_expression() {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: use names, true/false, and/or/not, and parentheses.",
+                        );
+                        return;
+                    }
+                };
+                let mut names = Vec::new();
+                names_in(&expr, &mut names);
+                if names.len() > MAX_NAMES {
+                    ui.label(exercise::not_quite(&format!(
+                        "Use up to {MAX_NAMES} names, and this has {}.",
+                        names.len()
+                    )));
+                    return;
+                }
+                let rows: Vec<(Vec<bool>, Result<bool, String>)> = truth_rows(names.len())
+                    .into_iter()
+                    .map(|row| {
+                        let env: Vec<(String, bool)> =
+                            names.iter().cloned().zip(row.iter().copied()).collect();
+                        let value = expr.eval_in(&env);
+                        (row, value)
+                    })
+                    .collect();
                 ui.add_space(6.0);
                 ui.horizontal(|ui| {
+                    let more = state.shown < rows.len();
                     if ui
-                        .add_enabled(state.step > 0, widgets::Button::new("Prev"))
+                        .add_enabled(more, widgets::Button::new("Try the next row"))
                         .clicked()
                     {
-                        state.step = state.step.saturating_sub(1);
+                        state.shown += 1;
                     }
                     if ui
-                        .add_enabled(state.step < max_step, widgets::Button::new("Next"))
+                        .add_enabled(more, widgets::Button::new("Try all rows"))
                         .clicked()
                     {
-                        state.step = (state.step + 1).min(max_step);
+                        state.shown = rows.len();
                     }
-                    if ui.add(widgets::Button::new("Reset")).clicked() {
-                        state.step = 0;
+                    if ui
+                        .add_enabled(state.shown > 0, widgets::Button::new("Start over"))
+                        .clicked()
+                    {
+                        state.shown = 0;
                     }
-                    ui.add_space(6.0);
-                    ui.label(format!("Step {}/{}", state.step, max_step));
                 });
+                ui.add_space(6.0);
 
-                ui.add_space(8.0);
-                let step = &steps[state.step];
-                let (expression, expression_ranges) =
-                    render_expr_with_highlight(&step.expr, step.highlight.as_deref());
-                code_frame(ui, highlighted_job(ui, &expression, &expression_ranges));
+                let code = expr_to_string(&expr);
+                egui::Grid::new("tautology_table")
+                    .striped(true)
+                    .spacing(egui::vec2(16.0, 4.0))
+                    .show(ui, |ui| {
+                        for name in &names {
+                            ui.label(egui::RichText::new(name).monospace().strong());
+                        }
+                        ui.label(egui::RichText::new(&code).monospace().strong());
+                        ui.end_row();
+                        for (row, value) in rows.iter().take(state.shown) {
+                            for value in row {
+                                ui.label(egui::RichText::new(value.to_string()).monospace());
+                            }
+                            let value = match value {
+                                Ok(value) => value.to_string(),
+                                Err(error) => error.clone(),
+                            };
+                            ui.label(egui::RichText::new(value).monospace());
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(6.0);
+
+                let tried = &rows[..state.shown];
+                let trues = tried.iter().filter(|(_, value)| *value == Ok(true)).count();
+                let verdict = if trues > 0 && trues < tried.len() {
+                    "Contingent: it is true in some rows and false in others."
+                } else if tried.len() < rows.len() {
+                    "Not decided yet: a row still to come could change it."
+                } else if trues == rows.len() {
+                    "A tautology: true in every row."
+                } else {
+                    "A contradiction: false in every row."
+                };
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{} of {} rows tried. {verdict}",
+                        tried.len(),
+                        rows.len()
+                    ))
+                    .strong(),
+                );
+            });
+        },
+    );
 
+    nb.state(
+        &state_key("karnaugh_state"),
+        KarnaughState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Karnaugh map (advanced)") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("A Karnaugh map is a truth table folded into a grid.");
+                ui.label("Cells side by side differ in one name, even across the edges.");
+                ui.label("Each outlined group of true cells needs only the names it keeps fixed.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Formula:");
+                    ui.add(widgets::TextField::singleline(&mut state.input));
+                });
+                advanced_toggle(ui, &mut state.advanced);
+
+                let mut parser = Parser::with_names(&state.input).advanced(state.advanced);
+                let expr = match parser.parse_expression() {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: use names, true/false, and/or/not, and parentheses.",
+                        );
+                        return;
+                    }
+                };
+                let mut names = Vec::new();
+                names_in(&expr, &mut names);
+                if !(2..=MAX_NAMES).contains(&names.len()) {
+                    ui.label(exercise::not_quite(&format!(
+                        "The map needs two or three names, and this has {}.",
+                        names.len()
+                    )));
+                    return;
+                }
+                let rows: Vec<(Vec<bool>, bool)> = truth_rows(names.len())
+                    .into_iter()
+                    .map(|row| {
+                        let env: Vec<(String, bool)> =
+                            names.iter().cloned().zip(row.iter().copied()).collect();
+                        let value = expr.eval_in(&env).unwrap_or(false);
+                        (row, value)
+                    })
+                    .collect();
+                let groups = karnaugh_groups(&rows, names.len());
                 ui.add_space(6.0);
-                ui.label("Tree view:");
+                karnaugh_map(ui, &names, &expr, &groups);
+                ui.add_space(6.0);
+
+                let terms: Vec<String> = groups
+                    .iter()
+                    .map(|group| {
+                        let text = group_text(group, &names);
+                        if groups.len() > 1 && text.contains(" and ") {
+                            format!("({text})")
+                        } else {
+                            text
+                        }
+                    })
+                    .collect();
+                let shorter = if terms.is_empty() {
+                    "false".to_string()
+                } else {
+                    terms.join(" or ")
+                };
+                ui.label("One group per outline, joined by or:");
+                ui.add(CodeBlock::line(&shorter));
+                let operators = Parser::with_names(&shorter)
+                    .parse_expression()
+                    .map(|shorter| shorter.count_ops())
+                    .unwrap_or_default();
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{operators} operators instead of {}, true in the same cells.",
+                        expr.count_ops()
+                    ))
+                    .weak(),
+                );
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("venn_state"),
+        VennState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Venn diagram") {
+                    return;
+                }
                 ui.add_space(4.0);
-                draw_tree(ui, &step.expr, step.highlight.as_deref());
+                ui.label("Think of A and B as two groups of things, drawn as circles.");
+                ui.label("A point in circle A makes A true there; outside it, A is false.");
+                ui.label("The shaded points are the ones where the whole expression is true.");
                 ui.add_space(6.0);
-                if step.highlight.is_some() {
-                    ui.label("The highlighted part is what you can evaluate next.");
+                ui.horizontal(|ui| {
+                    ui.label("Expression:");
+                    ui.add(widgets::TextField::singleline(&mut state.input));
+                });
+                ui.horizontal(|ui| {
+                    for input in ["A and B", "A or B", "not A", "A and not B"] {
+                        if ui.add(widgets::Button::new(input).small()).clicked() {
+                            state.input = input.to_string();
+                        }
+                    }
+                });
+
+                let mut parser = Parser::with_names(&state.input);
+                let expr = match parser.parse_expression() {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: use A, B, and/or/not, and parentheses.",
+                        );
+                        return;
+                    }
+                };
+                let mut names = Vec::new();
+                names_in(&expr, &mut names);
+                if let Some(name) = names.iter().find(|name| !SETS.contains(&name.as_str())) {
+                    ui.label(exercise::not_quite(&format!(
+                        "The diagram only has A and B, so `{name}` has no circle."
+                    )));
+                    return;
+                }
+                let shaded = match venn_regions(&expr) {
+                    Ok(shaded) => shaded,
+                    Err(error) => {
+                        ui.label(exercise::not_quite(&error));
+                        return;
+                    }
+                };
+                ui.add_space(6.0);
+                venn_diagram(ui, shaded);
+                ui.add_space(6.0);
+                ui.label("and keeps what both sides shade, or what either side shades.");
+                ui.label("not swaps shaded and unshaded.");
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("comparison_state"),
+        ComparisonState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Comparisons") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("A comparison asks a yes/no question about numbers, like x > 3.");
+                ui.label("Its answer is a boolean, so and, or and not can work with it.");
+                ui.label("Use <, <=, >, >=, == (equal) and != (not equal).");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Expression:");
+                    if ui
+                        .add(widgets::TextField::singleline(&mut state.input))
+                        .changed()
+                    {
+                        state.step = 0;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    for (name, number) in SLIDERS.iter().zip(&mut state.numbers) {
+                        ui.add(widgets::Slider::new(number, 0..=10).text(*name));
+                    }
+                });
+
+                let mut parser = Parser::with_names(&state.input);
+                let expr = match parser.parse_expression() {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: compare x or y with a number, like x > 3.",
+                        );
+                        return;
+                    }
+                };
+                let numbers: Vec<(&str, i64)> =
+                    SLIDERS.iter().copied().zip(state.numbers).collect();
+                ui.add_space(6.0);
+                ui.label("Comparisons first:");
+                let mut env = Vec::new();
+                for comparison in &parser.comparisons {
+                    let line = match comparison.operands(&numbers).and_then(|(left, right)| {
+                        let value = comparison.eval(&numbers)?;
+                        Ok((left, right, value))
+                    }) {
+                        Ok((left, right, value)) => {
+                            env.push((comparison.to_string(), value));
+                            format!(
+                                "{comparison}  \u{2192}  {left} {} {right}  \u{2192}  {value}",
+                                comparison.symbol()
+                            )
+                        }
+                        Err(error) => format!("{comparison}: {error}"),
+                    };
+                    ui.label(egui::RichText::new(line).monospace());
+                }
+                ui.add_space(6.0);
+                stepper(ui, &mut state.step, &build_steps_in(expr, &env));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("short_circuit_state"),
+        ShortCircuitState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Short-circuit") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Once the left side of `and` is false, the whole is false.");
+                ui.label("Once the left side of `or` is true, the whole is true.");
+                ui.label("Computers use this: they skip the right side. That is short-circuiting.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Expression:");
+                    if ui
+                        .add(widgets::TextField::singleline(&mut state.input))
+                        .changed()
+                    {
+                        state.step = 0;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    for input in ["false and (true or not false)", "true or (false and true)"] {
+                        if ui.add(widgets::Button::new(input).small()).clicked() {
+                            state.input = input.to_string();
+                            state.step = 0;
+                        }
+                    }
+                });
+                let short = state.short;
+                ui.add(
+                    widgets::ChoiceToggle::new(&mut state.short)
+                        .small()
+                        .choice(false, "Every box")
+                        .choice(true, "Short-circuit"),
+                );
+                if state.short != short {
+                    state.step = 0;
+                }
+
+                let mut parser = Parser::new(&state.input);
+                let expr = match parser.parse_expression() {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: check parentheses or a missing true/false.",
+                        );
+                        return;
+                    }
+                };
+                let full = build_steps(expr.clone());
+                let short = ShortCircuit::new(expr);
+                ui.add_space(4.0);
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Every box: {} steps. Short-circuit: {} steps.",
+                        full.count() - 1,
+                        short.count() - 1
+                    ))
+                    .weak(),
+                );
+                ui.add_space(6.0);
+                if state.short {
+                    stepper(ui, &mut state.step, &short);
                 } else {
-                    ui.label("Fully evaluated.");
+                    stepper(ui, &mut state.step, &full);
                 }
             });
         },
     );
 
-    nb.state(&chapter_key("tree_exercise_state"), TreeExerciseState::default(), |ui, state| {
+    let practice = TreePractice::new(
+        chapter_key("tree_exercise_state"),
+        generate_tree_expr,
+        Wording {
+            id: "bool-tree-node",
+            nudge: "tree_practice.nudge_booleans",
+            already_value: "tree_practice.boolean",
+            lesson: "Step through a boolean expression",
+        },
+    );
+    nb.state(&state_key("tree_exercise_state"), practice, |ui, state| {
         with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            ui.label(RichText::new("Tree practice").heading());
+            if !card_heading(ui, "Tree practice") {
+                return;
+            }
             ui.add_space(6.0);
             ui.label("Click a box to evaluate it in the right order (left to right).");
             ui.label("Keep going until the whole tree becomes one value.");
             ui.add_space(6.0);
+            state.show(ui);
+        });
+    });
 
-            let mut show_hint = false;
-            ui.horizontal(|ui| {
-                if ui.add(widgets::Button::new("New tree")).clicked() {
-                    state.regenerate();
+    nb.state(
+        &state_key("random_exercise_state"),
+        MultipleChoice::<Practice>::default(),
+        |ui, practice| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Random practice") {
+                    return;
                 }
-                let hint_response = ui.add(widgets::Button::new("Hold for hint"));
-                let hint_keyboard = hint_response.has_focus()
-                    && ui.input(|input| {
-                        input.key_down(egui::Key::Enter) || input.key_down(egui::Key::Space)
-                    });
-                show_hint = hint_response.is_pointer_button_down_on() || hint_keyboard;
-                if hint_response.clicked() {
-                    state.feedback = None;
+                ui.add_space(6.0);
+                glossary::text(
+                    ui,
+                    "Evaluate the [[expression]], then choose true or false.",
+                );
+                ui.add_space(6.0);
+                practice.coop_controls(ui);
+                ui.add_space(6.0);
+                let key = chapter_key("random_exercise_state");
+                if let Some(correct) = practice.show(ui, key) {
+                    if correct {
+                        let expr = &practice.exercise().expr;
+                        mastery::solved(CHAPTER, mastery::tree_tier(expr));
+                    }
                 }
             });
-            ui.add_space(6.0);
-
-            let next_path = find_reducible(&state.expr);
-            let highlight_path = if show_hint {
-                next_path.as_deref()
-            } else {
-                None
-            };
-            let done = next_path.is_none();
+        },
+    );
 
-            let (expression, expression_ranges) =
-                render_expr_with_highlight(&state.expr, highlight_path);
-            code_frame(ui, highlighted_job(ui, &expression, &expression_ranges));
-            ui.add_space(6.0);
+    nb.state(
+        &state_key("review_state"),
+        Review::<Practice>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("random_exercise_state"));
+            });
+        },
+    );
 
-            let clicked = draw_tree_interactive(ui, &state.expr, highlight_path);
-            if !done {
-                if let Some(path) = clicked {
-                    if next_path.as_ref().map_or(false, |next| next == &path) {
-                        match reduce_at(state.expr.clone(), &path) {
-                            Ok(expr) => {
-                                state.expr = expr;
-                                state.feedback = None;
-                            }
-                            Err(error) => {
-                                state.feedback = Some(format!("Oops: {error}"));
-                            }
-                        }
-                    } else {
-                        let feedback = expr_at_path(&state.expr, &path).and_then(|expr| {
-                            if matches!(expr.kind, ExprKind::Bool(_)) {
-                                Some("Booleans already have a value.".to_string())
-                            } else {
-                                None
-                            }
-                        });
-                        state.feedback = Some(feedback.unwrap_or_else(|| {
-                            "Not yet. Work left-to-right; if there is no deeper expression, move up to the next level.".to_string()
-                        }));
+    nb.state(
+        &state_key("simplify_state"),
+        SimplifyState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Simplify") {
+                    return;
+                }
+                ui.add_space(6.0);
+                ui.label("Some expressions say the same thing in more words than needed.");
+                ui.label("Write a shorter one that is true for exactly the same values.");
+                ui.label("Shorter means fewer operators: and, or and not each count once.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New expression")).clicked() {
+                        state.regenerate();
                     }
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("simplify_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                });
+                ui.add_space(6.0);
+                let operators = state.expr.count_ops();
+                ui.add(CodeBlock::line(&expr_to_string(&state.expr)));
+                ui.label(egui::RichText::new(format!("Operators: {operators}")).weak());
+                ui.add_space(6.0);
+
+                let mut check = false;
+                ui.horizontal(|ui| {
+                    ui.label("Shorter:");
+                    ui.add(widgets::TextField::singleline(&mut state.input));
+                    check = ui.add(widgets::Button::new("Check")).clicked();
+                });
+                let mut parser = Parser::with_names(&state.input);
+                let parsed = parser.parse_expression();
+                let mut names = Vec::new();
+                names_in(&state.expr, &mut names);
+                if let Ok(expr) = &parsed {
+                    names_in(expr, &mut names);
+                }
+                let same = |expr: &Expr| {
+                    names.len() <= MAX_NAMES && differing_row(&state.expr, expr, &names).is_none()
+                };
+                if check {
+                    let correct = parsed
+                        .as_ref()
+                        .is_ok_and(|expr| same(expr) && expr.count_ops() < operators);
+                    progress::answer(chapter_key("simplify_state"), correct);
+                    state.checked = Some(state.input.clone());
+                }
+                if state.checked.as_ref() != Some(&state.input) {
+                    return;
                 }
-            }
 
-            ui.add_space(6.0);
-            if let Some(value) = as_bool(&state.expr) {
-                ui.label(format!("All done! Value = {value}."));
-            }
-            if let Some(feedback) = &state.feedback {
-                ui.label(feedback);
-            }
-        });
-    });
+                ui.add_space(4.0);
+                let expr = match parsed {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: use the names above, and/or/not, and parentheses.",
+                        );
+                        return;
+                    }
+                };
+                if names.len() > MAX_NAMES {
+                    ui.label(exercise::not_quite(
+                        "That brings in new names; stick to the ones above.",
+                    ));
+                    return;
+                }
+                if let Some(row) = differing_row(&state.expr, &expr, &names) {
+                    let values: Vec<String> = row
+                        .iter()
+                        .map(|(name, value)| format!("{name} = {value}"))
+                        .collect();
+                    ui.label(exercise::not_quite(&format!(
+                        "Not the same: with {} the two come out different.",
+                        values.join(", ")
+                    )));
+                    return;
+                }
+                let shorter = expr.count_ops();
+                if shorter >= operators {
+                    ui.label(exercise::not_quite(&format!(
+                        "Same values, but {shorter} operators is not fewer than {operators}."
+                    )));
+                    return;
+                }
+                ui.label(format!(
+                    "Correct! Same value for every choice, with {shorter} operators instead of \
+                     {operators}."
+                ));
+            });
+        },
+    );
 
     nb.state(
-        &chapter_key("random_exercise_state"),
-        RandomExerciseState::default(),
+        &state_key("assignment_state"),
+        AssignmentState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Random practice").heading());
+                if !card_heading(ui, "Make it true") {
+                    return;
+                }
                 ui.add_space(6.0);
-                ui.label("Evaluate the expression, then choose true or false.");
+                ui.label("Set the switches so that the formula comes out true.");
+                ui.label("If no setting can do it, choose \"Impossible\" instead.");
                 ui.add_space(6.0);
-                if ui.add(widgets::Button::new("New exercise")).clicked() {
-                    state.regenerate();
-                }
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New formula")).clicked() {
+                        state.regenerate();
+                    }
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("assignment_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                });
                 ui.add_space(6.0);
+                ui.add(CodeBlock::line(&expr_to_string(&state.expr)));
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    for (name, value) in SWITCHES.iter().zip(&mut state.values) {
+                        let label = format!("{name} = {value}");
+                        ui.add(widgets::ToggleButton::new(value, label));
+                    }
+                });
+                ui.add_space(6.0);
+                let mut claim = None;
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("Check")).clicked() {
+                        claim = Some(Claim::Values(state.values));
+                    }
+                    if ui.add(widgets::Button::new("Impossible")).clicked() {
+                        claim = Some(Claim::Impossible);
+                    }
+                });
+                if let Some(claim) = claim {
+                    let correct = match claim {
+                        Claim::Values(values) => state.holds(&values),
+                        Claim::Impossible => !state.possible(),
+                    };
+                    progress::answer(chapter_key("assignment_state"), correct);
+                    state.checked = Some(claim);
+                }
 
-                let expression = expr_to_string(&state.exercise.expr);
-                code_frame(ui, highlighted_job(ui, &expression, &[]));
+                ui.add_space(4.0);
+                match state.checked {
+                    Some(Claim::Values(values)) if values == state.values => {
+                        if state.holds(&values) {
+                            ui.label("Correct! With these values the formula is true.");
+                        } else {
+                            ui.label(exercise::not_quite(
+                                "With these values the formula is false.",
+                            ));
+                        }
+                    }
+                    Some(Claim::Impossible) if state.possible() => {
+                        ui.label(exercise::not_quite(
+                            "Some setting does make it true. Keep looking.",
+                        ));
+                    }
+                    Some(Claim::Impossible) => {
+                        ui.label("Correct! All eight settings leave the formula false.");
+                    }
+                    _ => {
+                        ui.label("Flip the switches, then check.");
+                    }
+                }
+            });
+        },
+    );
 
+    nb.state(
+        &state_key("reconstruct_state"),
+        ReconstructState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Which formula made this table?") {
+                    return;
+                }
                 ui.add_space(6.0);
-                ui.add(
-                    widgets::ChoiceToggle::new(&mut state.selection)
-                        .choice(Some(true), "true")
-                        .choice(Some(false), "false")
-                        .small(),
-                );
+                ui.label("Here is a truth table without its formula.");
+                ui.label("Pick the formula that gives the same value in every row.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New table")).clicked() {
+                        state.regenerate();
+                    }
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("reconstruct_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                });
+                ui.add_space(6.0);
+
+                let wanted = column(&state.candidates[state.answer]);
+                let picked = state
+                    .selection
+                    .map(|index| column(&state.candidates[index]));
+                let palette = Palette::from_ui(ui);
+                let cell = |text: String| egui::RichText::new(text).monospace();
+                egui::Grid::new("reconstruct_table")
+                    .striped(true)
+                    .spacing(egui::vec2(16.0, 4.0))
+                    .show(ui, |ui| {
+                        for heading in ["a", "b", "?"] {
+                            ui.label(cell(heading.to_string()).strong());
+                        }
+                        if let Some(index) = state.selection {
+                            let code = expr_to_string(&state.candidates[index]);
+                            ui.label(cell(code).strong());
+                        }
+                        ui.end_row();
+                        for (row, (value, wanted)) in truth_rows(2).iter().zip(&wanted).enumerate()
+                        {
+                            for value in value {
+                                ui.label(cell(value.to_string()));
+                            }
+                            ui.label(cell(format!("{}", wanted.clone().unwrap_or_default())));
+                            if let Some(picked) = &picked {
+                                let value = picked[row].clone().unwrap_or_default();
+                                if picked[row] == *wanted {
+                                    ui.label(cell(format!("{value} \u{2714}")));
+                                } else {
+                                    ui.label(
+                                        cell(format!("{value} \u{2718}")).color(palette.highlight),
+                                    );
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(6.0);
+
+                let previous = state.selection;
+                let mut toggle = AnswerToggle::new(&mut state.selection);
+                for (index, candidate) in state.candidates.iter().enumerate() {
+                    toggle = toggle.choice(Some(index), expr_to_string(candidate));
+                }
+                ui.add(toggle);
+                if let Some(index) = state.selection {
+                    if previous != state.selection {
+                        progress::answer(chapter_key("reconstruct_state"), index == state.answer);
+                    }
+                }
                 ui.add_space(4.0);
-                match state.selection {
-                    Some(value) if value == state.exercise.answer => ui.label("Correct!"),
-                    Some(_) => ui.label("Not quite. Try another answer."),
-                    None => ui.label("Pick an answer."),
+                match (state.selection, &picked) {
+                    (Some(index), _) if index == state.answer => {
+                        ui.label("Correct! It matches every row.");
+                    }
+                    (Some(_), Some(picked)) => {
+                        let wrong = picked.iter().zip(&wanted).filter(|(a, b)| a != b).count();
+                        ui.label(exercise::not_quite(&format!(
+                            "It gives a different value in {wrong} of the 4 rows, marked \u{2718}."
+                        )));
+                    }
+                    _ => {
+                        ui.label("Pick a formula.");
+                    }
                 }
             });
         },
     );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## What just happened\n\\
-             Booleans capture yes/no answers.\n\\
-             We can combine them with not, and, and or.\n\\
-             Evaluation rules help us compute the final true/false.\n\n\\
-             Next up: **Hello, state** uses values that can change over time."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## What just happened\n\\
+         Booleans capture yes/no answers.\n\\
+         We can combine them with not, and, and or.\n\\
+         Evaluation rules help us compute the final true/false.\n\n\\
+         Next up: **Hello, state** uses values that can change over time.",
+    );
 }
@@ -0,0 +1,426 @@
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::memory_map::{self, exclusive, inline, shared, Borrow, Owner};
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::stepper::Controls;
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::Borrowing;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+struct BorrowStep {
+    line: usize,
+    owners: &'static [Owner],
+    borrows: &'static [Borrow],
+    note: &'static str,
+}
+
+struct Snippet {
+    title: &'static str,
+    lines: &'static [&'static str],
+    steps: &'static [BorrowStep],
+}
+
+const SNIPPETS: &[Snippet] = &[
+    Snippet {
+        title: "Many readers",
+        lines: &[
+            "let s = String::from(\"hi\");",
+            "let a = &s;",
+            "let b = &s;",
+            "println!(\"{a} {b} {s}\");",
+        ],
+        steps: &[
+            BorrowStep {
+                line: 0,
+                owners: &[inline("s", "\"hi\"")],
+                borrows: &[],
+                note: "s owns the String.",
+            },
+            BorrowStep {
+                line: 1,
+                owners: &[inline("s", "\"hi\"")],
+                borrows: &[shared("a", "s")],
+                note: "a borrows s. It can look, but s is still the owner.",
+            },
+            BorrowStep {
+                line: 2,
+                owners: &[inline("s", "\"hi\"")],
+                borrows: &[shared("a", "s"), shared("b", "s")],
+                note: "A second shared borrow is fine. Many readers can look at once.",
+            },
+            BorrowStep {
+                line: 3,
+                owners: &[inline("s", "\"hi\"")],
+                borrows: &[shared("a", "s"), shared("b", "s")],
+                note: "Everyone reads the same value. Nobody changes it.",
+            },
+        ],
+    },
+    Snippet {
+        title: "One writer",
+        lines: &[
+            "let mut n = 5;",
+            "let r = &mut n;",
+            "*r += 1;",
+            "println!(\"{n}\");",
+        ],
+        steps: &[
+            BorrowStep {
+                line: 0,
+                owners: &[inline("n", "5")],
+                borrows: &[],
+                note: "n is mutable, so it may be changed later.",
+            },
+            BorrowStep {
+                line: 1,
+                owners: &[inline("n", "5")],
+                borrows: &[exclusive("r", "n")],
+                note: "r takes an exclusive borrow. While r is in use, nobody else may touch n.",
+            },
+            BorrowStep {
+                line: 2,
+                owners: &[inline("n", "6")],
+                borrows: &[exclusive("r", "n")],
+                note: "Writing through r changes the value that n owns.",
+            },
+            BorrowStep {
+                line: 3,
+                owners: &[inline("n", "6")],
+                borrows: &[],
+                note: "r is no longer used, so the borrow has ended and n can be read again.",
+            },
+        ],
+    },
+    Snippet {
+        title: "Readers, then a writer",
+        lines: &[
+            "let mut v = vec![1];",
+            "let first = &v;",
+            "println!(\"{first:?}\");",
+            "v.push(2);",
+        ],
+        steps: &[
+            BorrowStep {
+                line: 0,
+                owners: &[inline("v", "[1]")],
+                borrows: &[],
+                note: "v owns a vector.",
+            },
+            BorrowStep {
+                line: 1,
+                owners: &[inline("v", "[1]")],
+                borrows: &[shared("first", "v")],
+                note: "first is a shared borrow of v.",
+            },
+            BorrowStep {
+                line: 2,
+                owners: &[inline("v", "[1]")],
+                borrows: &[shared("first", "v")],
+                note: "This is the last time first is used.",
+            },
+            BorrowStep {
+                line: 3,
+                owners: &[inline("v", "[1, 2]")],
+                borrows: &[],
+                note: "The reader is done, so push may borrow v exclusively and change it.",
+            },
+        ],
+    },
+];
+
+#[derive(Default)]
+struct BorrowStepperState {
+    snippet: usize,
+    step: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Existing {
+    None,
+    Shared(usize),
+    Exclusive,
+}
+
+struct BorrowQuestion {
+    mutable: bool,
+    existing: Existing,
+    exclusive: bool,
+}
+
+impl BorrowQuestion {
+    fn allowed(&self) -> bool {
+        if self.exclusive && !self.mutable {
+            return false;
+        }
+        match self.existing {
+            Existing::None => true,
+            Existing::Shared(_) => !self.exclusive,
+            Existing::Exclusive => false,
+        }
+    }
+
+    fn explanation(&self) -> &'static str {
+        if self.exclusive && !self.mutable {
+            return "x is not declared with mut, so it cannot be borrowed exclusively.";
+        }
+        match (self.existing, self.exclusive) {
+            (Existing::None, false) => "No other borrows are alive, so reading is fine.",
+            (Existing::None, true) => "No other borrows are alive, so one writer is fine.",
+            (Existing::Shared(_), false) => "Shared borrows can live side by side.",
+            (Existing::Shared(_), true) => {
+                "A reader is still alive, so an exclusive borrow would pull the value out from under it."
+            }
+            (Existing::Exclusive, _) => {
+                "An exclusive borrow is still alive. Nobody else may touch x until it is done."
+            }
+        }
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.mutable {
+            lines.push("let mut x = 5;".to_string());
+        } else {
+            lines.push("let x = 5;".to_string());
+        }
+        let mut alive = Vec::new();
+        match self.existing {
+            Existing::None => {}
+            Existing::Shared(count) => {
+                for index in 0..count {
+                    let name = ["a", "b"][index];
+                    lines.push(format!("let {name} = &x;"));
+                    alive.push(name);
+                }
+            }
+            Existing::Exclusive => {
+                lines.push("let a = &mut x;".to_string());
+                alive.push("a");
+            }
+        }
+        if self.exclusive {
+            lines.push("let r = &mut x;".to_string());
+        } else {
+            lines.push("let r = &x;".to_string());
+        }
+        lines.push("println!(\"{r}\");".to_string());
+        for name in alive {
+            lines.push(format!("println!(\"{{{name}}}\");"));
+        }
+        lines
+    }
+
+    fn new_borrow_line(&self) -> usize {
+        match self.existing {
+            Existing::None => 1,
+            Existing::Shared(count) => 1 + count,
+            Existing::Exclusive => 2,
+        }
+    }
+}
+
+impl Exercise for BorrowQuestion {
+    type Answer = bool;
+
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        let existing = match rng.gen_range_i32(0, 3) {
+            0 => Existing::None,
+            1 => Existing::Shared(1),
+            2 => Existing::Shared(2),
+            _ => Existing::Exclusive,
+        };
+        let exclusive = rng.gen_range_i32(0, 1) == 1;
+        let mutable = existing == Existing::Exclusive || rng.gen_range_i32(0, 3) != 0;
+        BorrowQuestion {
+            mutable,
+            existing,
+            exclusive,
+        }
+    }
+
+    fn prompt(&self) -> String {
+        "Look at the highlighted line. Would the compiler accept it?".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        let lines = self.lines();
+        ui.add(CodeBlock::new(&lines).highlight_line(Some(self.new_borrow_line())));
+    }
+
+    /// The listing with the new borrow marked by a comment.
+    fn text(&self) -> String {
+        let mut lines = self.lines();
+        lines[self.new_borrow_line()].push_str(" // <- this line");
+        format!("```rust\n{}\n```", lines.join("\n"))
+    }
+
+    fn answer(&self) -> bool {
+        self.allowed()
+    }
+
+    fn distractors(&self, _rng: &mut SimpleRng) -> Vec<bool> {
+        vec![!self.allowed()]
+    }
+
+    fn choices(&self, _rng: &mut SimpleRng) -> Vec<bool> {
+        vec![true, false]
+    }
+
+    fn choice_label(answer: &bool) -> String {
+        if *answer { "Allowed" } else { "Not allowed" }.to_string()
+    }
+
+    fn hint(&self, _picked: &bool) -> String {
+        "Count the readers and writers that are still alive.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, correct: bool) {
+        if correct {
+            ui.label(self.explanation());
+        }
+    }
+}
+
+/// A borrow to judge on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<BorrowQuestion>(rng, difficulty)
+}
+
+/// Checks a generated borrow for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<BorrowQuestion>(rng, difficulty)
+}
+
+pub fn borrowing(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Borrowing and references\n\
+         Moving a value every time we want to use it would be tiring.\n\
+         Instead, Rust lets us **borrow**: we get a *reference* to the value\n\
+         while the owner keeps it.\n\n\
+         A reference is written with `&`. It points at a value someone else owns.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         Your friend owns a notebook.\n\
+         Many classmates may **read** it at the same time, looking over each other's shoulders.\n\
+         But if someone wants to **write** in it, they need it all to themselves,\n\
+         otherwise the readers would see the page change under their eyes.",
+    );
+
+    speech::lesson(
+        nb,
+        "## Two kinds of borrows\n\
+         - `&x` is a **shared** borrow: read only, and many may exist at once.\n\
+         - `&mut x` is an **exclusive** borrow: it may change the value, and it must be alone.\n\n\
+         ```rust\n\
+         let mut score = 10;\n\
+         let look = &score;      // shared\n\
+         println!(\"{look}\");\n\
+         let edit = &mut score;  // exclusive, fine because look is done\n\
+         *edit += 1;\n\
+         ```\n\
+         A borrow lasts until its last use, not until the end of the block.",
+    );
+
+    nb.state(
+        &state_key("borrow_stepper_state"),
+        BorrowStepperState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Borrow map") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Thin arrows are shared borrows. Thick highlighted arrows are exclusive.");
+                ui.add_space(6.0);
+
+                let mut snippet = state.snippet;
+                let mut toggle = widgets::ChoiceToggle::new(&mut snippet).small();
+                for (index, entry) in SNIPPETS.iter().enumerate() {
+                    toggle = toggle.choice(index, entry.title);
+                }
+                ui.add(toggle);
+                if snippet != state.snippet {
+                    state.snippet = snippet;
+                    state.step = 0;
+                }
+
+                let snippet = &SNIPPETS[state.snippet];
+                let max_step = snippet.steps.len().saturating_sub(1);
+
+                ui.add_space(6.0);
+                Controls::new(&mut state.step, max_step).show(ui);
+
+                let step = &snippet.steps[state.step];
+                ui.add_space(8.0);
+                ui.add(CodeBlock::new(snippet.lines).highlight_line(Some(step.line)));
+                ui.add_space(8.0);
+                memory_map::paint(ui, step.owners, None, step.borrows);
+                ui.add_space(6.0);
+                ui.label(step.note);
+            });
+        },
+    );
+
+    nb.view(|ui| {
+        note!(
+            ui,
+            "The rule in one sentence: **many readers or one writer, never both at the same time.**\n\
+             The compiler checks this for you before the program ever runs."
+        );
+    });
+
+    nb.state(
+        &state_key("borrow_quiz_state"),
+        MultipleChoice::<BorrowQuestion>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Is this borrow allowed?") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("borrow_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<BorrowQuestion>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("borrow_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - A reference borrows a value without taking ownership.\n\
+         - `&` borrows are shared and read only; many can exist at once.\n\
+         - `&mut` borrows are exclusive; while one is alive nobody else may use the value.\n\
+         - A borrow ends after its last use.\n\n\
+         Next up: **Structs, enums, and pattern matching** builds our own types.",
+    );
+}
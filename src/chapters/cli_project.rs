@@ -0,0 +1,442 @@
+use egui::RichText;
+use std::collections::BTreeMap;
+
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::exercise;
+use crate::progress;
+use crate::speech;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::CliProject;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+struct Sample {
+    name: &'static str,
+    text: &'static str,
+}
+
+const SAMPLES: &[Sample] = &[
+    Sample {
+        name: "poem",
+        text: "the cat sat\non the mat\nThe end",
+    },
+    Sample {
+        name: "cafe",
+        text: "Café au lait,\ncafé noir.\nNoir, noir!\n",
+    },
+    Sample {
+        name: "spaces",
+        text: "  many   spaces  here \n\nblank line above",
+    },
+];
+
+struct Candidate {
+    code: &'static [&'static str],
+    run: fn(&str) -> String,
+}
+
+struct Stage {
+    title: &'static str,
+    goal: &'static str,
+    candidates: &'static [Candidate],
+    reference: usize,
+}
+
+fn count_lines(input: &str) -> String {
+    input.lines().count().to_string()
+}
+
+fn count_newline_splits(input: &str) -> String {
+    input.split('\n').count().to_string()
+}
+
+fn count_newlines(input: &str) -> String {
+    input.matches('\n').count().to_string()
+}
+
+fn count_words(input: &str) -> String {
+    input.split_whitespace().count().to_string()
+}
+
+fn count_space_splits(input: &str) -> String {
+    input.split(' ').count().to_string()
+}
+
+fn count_chars(input: &str) -> String {
+    input.chars().count().to_string()
+}
+
+fn count_bytes(input: &str) -> String {
+    input.len().to_string()
+}
+
+fn count_visible_chars(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .count()
+        .to_string()
+}
+
+fn top_word_with(input: &str, trim: bool, lowercase: bool) -> String {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for word in input.split_whitespace() {
+        let word = if trim {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+        } else {
+            word
+        };
+        let word = if lowercase {
+            word.to_lowercase()
+        } else {
+            word.to_string()
+        };
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    match counts.iter().rev().max_by_key(|(_, count)| **count) {
+        Some((word, count)) => format!("{word} ({count})"),
+        None => "none".to_string(),
+    }
+}
+
+fn top_word(input: &str) -> String {
+    top_word_with(input, true, true)
+}
+
+fn top_word_case_sensitive(input: &str) -> String {
+    top_word_with(input, true, false)
+}
+
+fn top_word_untrimmed(input: &str) -> String {
+    top_word_with(input, false, true)
+}
+
+const STAGES: &[Stage] = &[
+    Stage {
+        title: "Count lines",
+        goal: "Report how many lines the input has. A trailing newline does not start a new line.",
+        candidates: &[
+            Candidate {
+                code: &["let lines = input.split('\\n').count();"],
+                run: count_newline_splits,
+            },
+            Candidate {
+                code: &["let lines = input.lines().count();"],
+                run: count_lines,
+            },
+            Candidate {
+                code: &["let lines = input.matches('\\n').count();"],
+                run: count_newlines,
+            },
+        ],
+        reference: 1,
+    },
+    Stage {
+        title: "Count words",
+        goal: "Report how many words there are. Runs of spaces and newlines separate words.",
+        candidates: &[
+            Candidate {
+                code: &["let words = input.split_whitespace().count();"],
+                run: count_words,
+            },
+            Candidate {
+                code: &["let words = input.split(' ').count();"],
+                run: count_space_splits,
+            },
+        ],
+        reference: 0,
+    },
+    Stage {
+        title: "Count characters",
+        goal: "Report how many characters there are, like `wc -m`. An é counts once.",
+        candidates: &[
+            Candidate {
+                code: &["let chars = input.len();"],
+                run: count_bytes,
+            },
+            Candidate {
+                code: &["let chars = input.chars().filter(|c| !c.is_whitespace()).count();"],
+                run: count_visible_chars,
+            },
+            Candidate {
+                code: &["let chars = input.chars().count();"],
+                run: count_chars,
+            },
+        ],
+        reference: 2,
+    },
+    Stage {
+        title: "Most common word",
+        goal: "Find the most common word. Ignore punctuation and upper/lower case; ties go to the alphabetically first word.",
+        candidates: &[
+            Candidate {
+                code: &[
+                    "let mut counts: BTreeMap<String, usize> = BTreeMap::new();",
+                    "for word in input.split_whitespace() {",
+                    "    *counts.entry(word.to_lowercase()).or_insert(0) += 1;",
+                    "}",
+                    "let top = counts.iter().rev().max_by_key(|(_, n)| **n);",
+                ],
+                run: top_word_untrimmed,
+            },
+            Candidate {
+                code: &[
+                    "let mut counts: BTreeMap<String, usize> = BTreeMap::new();",
+                    "for word in input.split_whitespace() {",
+                    "    let word = word.trim_matches(|c: char| !c.is_alphanumeric());",
+                    "    *counts.entry(word.to_lowercase()).or_insert(0) += 1;",
+                    "}",
+                    "let top = counts.iter().rev().max_by_key(|(_, n)| **n);",
+                ],
+                run: top_word,
+            },
+            Candidate {
+                code: &[
+                    "let mut counts: BTreeMap<String, usize> = BTreeMap::new();",
+                    "for word in input.split_whitespace() {",
+                    "    let word = word.trim_matches(|c: char| !c.is_alphanumeric());",
+                    "    *counts.entry(word.to_string()).or_insert(0) += 1;",
+                    "}",
+                    "let top = counts.iter().rev().max_by_key(|(_, n)| **n);",
+                ],
+                run: top_word_case_sensitive,
+            },
+        ],
+        reference: 1,
+    },
+];
+
+struct ProjectState {
+    stage: usize,
+    picks: Vec<Option<usize>>,
+    checked: Vec<bool>,
+}
+
+impl Default for ProjectState {
+    fn default() -> Self {
+        Self {
+            stage: 0,
+            picks: vec![None; STAGES.len()],
+            checked: vec![false; STAGES.len()],
+        }
+    }
+}
+
+impl ProjectState {
+    fn passed(&self, stage: usize) -> bool {
+        self.checked[stage] && self.picks[stage].is_some_and(|pick| stage_matches(stage, pick))
+    }
+
+    fn passed_count(&self) -> usize {
+        (0..STAGES.len())
+            .filter(|stage| self.passed(*stage))
+            .count()
+    }
+}
+
+/// A stage passes when the picked code agrees with the reference on every sample.
+fn stage_matches(stage: usize, pick: usize) -> bool {
+    let stage = &STAGES[stage];
+    let candidate = &stage.candidates[pick];
+    let reference = &stage.candidates[stage.reference];
+    SAMPLES
+        .iter()
+        .all(|sample| (candidate.run)(sample.text) == (reference.run)(sample.text))
+}
+
+fn assembled_program(state: &ProjectState) -> Vec<&'static str> {
+    let mut lines = vec![
+        "use std::collections::BTreeMap;",
+        "use std::io::Read;",
+        "",
+        "fn main() {",
+        "    let mut input = String::new();",
+        "    std::io::stdin().read_to_string(&mut input).unwrap();",
+    ];
+    for (stage, pick) in STAGES.iter().zip(&state.picks) {
+        let candidate = &stage.candidates[pick.unwrap_or(stage.reference)];
+        lines.extend(candidate.code.iter().copied());
+    }
+    lines.push("    println!(\"{lines} {words} {chars} {top:?}\");");
+    lines.push("}");
+    lines
+}
+
+fn stage_view(ui: &mut egui::Ui, state: &mut ProjectState, index: usize) {
+    let stage = &STAGES[index];
+    ui.label(RichText::new(format!("Stage {}: {}", index + 1, stage.title)).strong());
+    ui.label(stage.goal);
+    ui.add_space(6.0);
+
+    let previous = state.picks[index];
+    let mut toggle = widgets::ChoiceToggle::new(&mut state.picks[index]).small();
+    for (choice, _) in stage.candidates.iter().enumerate() {
+        let letter = (b'A' + choice as u8) as char;
+        toggle = toggle.choice(Some(choice), format!("Option {letter}"));
+    }
+    ui.add(toggle);
+    if state.picks[index] != previous {
+        state.checked[index] = false;
+    }
+
+    let Some(pick) = state.picks[index] else {
+        ui.add_space(4.0);
+        ui.label("Pick an option to see its code.");
+        return;
+    };
+    let candidate = &stage.candidates[pick];
+    ui.add_space(6.0);
+    ui.add(CodeBlock::new(candidate.code));
+    ui.add_space(6.0);
+    if ui.add(widgets::Button::new("Run on the samples")).clicked() && !state.checked[index] {
+        state.checked[index] = true;
+        // A wrong run counts against the project; a right one only once it
+        // completes the project.
+        let done = state.passed_count() == STAGES.len();
+        if done || !state.passed(index) {
+            progress::answer(chapter_key("project_state"), done);
+        }
+    }
+    if !state.checked[index] {
+        return;
+    }
+
+    ui.add_space(6.0);
+    let reference = &stage.candidates[stage.reference];
+    for sample in SAMPLES {
+        let got = (candidate.run)(sample.text);
+        let expected = (reference.run)(sample.text);
+        let verdict = if got == expected { "ok" } else { "mismatch" };
+        ui.label(format!(
+            "{}: got {got}, expected {expected} ({verdict})",
+            sample.name
+        ));
+    }
+    ui.add_space(4.0);
+    if state.passed(index) {
+        ui.label("Correct! Every sample matches.");
+    } else {
+        ui.label(exercise::not_quite(
+            "Look at the sample that disagrees and think about what makes it special.",
+        ));
+    }
+}
+
+pub fn cli_project(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Mini project: a word counter\n\
+         Time to put the pieces together. We build a small command-line tool,\n\
+         like `wc`, that reads text and reports lines, words, characters,\n\
+         and the most common word.",
+    );
+
+    speech::lesson(
+        nb,
+        "## The plan\n\
+         1. Read all of standard input into a `String`.\n\
+         2. Count lines.\n\
+         3. Count words.\n\
+         4. Count characters.\n\
+         5. Find the most common word.\n\n\
+         Each stage offers a few ways to write the code. Pick one and run it:\n\
+         the notebook checks it against sample inputs, so you know it works before moving on.",
+    );
+
+    nb.view(|ui| {
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            if !card_heading(ui, "Sample inputs") {
+                return;
+            }
+            ui.add_space(4.0);
+            ui.label("Newlines are written as \\n so you can see them.");
+            ui.add_space(6.0);
+            for sample in SAMPLES {
+                let line = format!("{}: {:?}", sample.name, sample.text);
+                ui.add(CodeBlock::line(&line));
+                ui.add_space(4.0);
+            }
+        });
+    });
+
+    nb.state(
+        &state_key("project_state"),
+        ProjectState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Build the tool") {
+                    return;
+                }
+                ui.add_space(6.0);
+                let passed = state.passed_count();
+                ui.add(
+                    widgets::ProgressBar::new(passed as f32 / STAGES.len() as f32)
+                        .segments(STAGES.len())
+                        .text(format!("{passed}/{} stages", STAGES.len())),
+                );
+                ui.add_space(6.0);
+
+                let mut toggle = widgets::ChoiceToggle::new(&mut state.stage).small();
+                for (index, stage) in STAGES.iter().enumerate() {
+                    toggle = toggle.choice(index, stage.title);
+                }
+                toggle = toggle.choice(STAGES.len(), "Full tool");
+                ui.add(toggle);
+                ui.add_space(8.0);
+
+                if state.stage < STAGES.len() {
+                    let stage = state.stage;
+                    stage_view(ui, state, stage);
+                    if state.passed(stage) {
+                        ui.add_space(4.0);
+                        if ui.add(widgets::Button::new("Next stage")).clicked() {
+                            state.stage = stage + 1;
+                        }
+                    }
+                    return;
+                }
+
+                if passed < STAGES.len() {
+                    ui.label(
+                        "Finish every stage first. The full tool is assembled from your picks.",
+                    );
+                    return;
+                }
+                let program = assembled_program(state);
+                ui.add(CodeBlock::new(&program).line_numbers(true));
+                ui.add_space(6.0);
+                ui.label("Running the finished tool on every sample:");
+                for sample in SAMPLES {
+                    ui.label(format!(
+                        "{}: {} lines, {} words, {} chars, top word {}",
+                        sample.name,
+                        count_lines(sample.text),
+                        count_words(sample.text),
+                        count_chars(sample.text),
+                        top_word(sample.text),
+                    ));
+                }
+                ui.add_space(4.0);
+                ui.label("Save it as src/main.rs and try: echo \"hello hello world\" | cargo run");
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - A real tool is many small steps glued together.\n\
+         - Checking each step against sample inputs catches mistakes early.\n\
+         - The tricky inputs (trailing newlines, extra spaces, accents, capitals) are where bugs hide.\n\n\
+         You finished Track C. Go build something of your own!",
+    );
+}
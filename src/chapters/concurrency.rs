@@ -0,0 +1,613 @@
+use egui::TextStyle;
+use std::time::Duration;
+use web_time::Instant;
+
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::glossary;
+use crate::palette::Palette;
+use crate::rng::{self, SimpleRng};
+use crate::settings;
+use crate::speech;
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::Concurrency;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+const MESSAGES_PER_WORKER: usize = 3;
+const PLAY_INTERVAL: Duration = Duration::from_millis(700);
+
+const CHANNEL_LINES: &[&str] = &[
+    "let (tx, rx) = mpsc::channel();",
+    "let tx_b = tx.clone();",
+    "thread::spawn(move || for i in 1..=3 { tx.send(format!(\"A{i}\")).unwrap() });",
+    "thread::spawn(move || for i in 1..=3 { tx_b.send(format!(\"B{i}\")).unwrap() });",
+    "for msg in rx {",
+    "    println!(\"{msg}\");",
+    "}",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Actor {
+    WorkerA,
+    WorkerB,
+    Main,
+}
+
+impl Actor {
+    fn name(&self) -> &'static str {
+        match self {
+            Actor::WorkerA => "worker A",
+            Actor::WorkerB => "worker B",
+            Actor::Main => "main",
+        }
+    }
+}
+
+const ACTORS: &[Actor] = &[Actor::WorkerA, Actor::WorkerB, Actor::Main];
+
+/// Replays a schedule and reports what each event did: the message it sent
+/// or received, plus what is still waiting in the channel afterwards.
+struct Replay {
+    labels: Vec<String>,
+    queue: Vec<String>,
+    received: Vec<String>,
+    sent_a: usize,
+    sent_b: usize,
+}
+
+fn replay(events: &[Actor]) -> Replay {
+    let mut replay = Replay {
+        labels: Vec::new(),
+        queue: Vec::new(),
+        received: Vec::new(),
+        sent_a: 0,
+        sent_b: 0,
+    };
+    for event in events {
+        match event {
+            Actor::WorkerA => {
+                replay.sent_a += 1;
+                let message = format!("A{}", replay.sent_a);
+                replay.labels.push(format!("send {message}"));
+                replay.queue.push(message);
+            }
+            Actor::WorkerB => {
+                replay.sent_b += 1;
+                let message = format!("B{}", replay.sent_b);
+                replay.labels.push(format!("send {message}"));
+                replay.queue.push(message);
+            }
+            Actor::Main => {
+                let message = replay.queue.remove(0);
+                replay.labels.push(format!("recv {message}"));
+                replay.received.push(message);
+            }
+        }
+    }
+    replay
+}
+
+impl Replay {
+    fn can_run(&self, actor: Actor) -> bool {
+        match actor {
+            Actor::WorkerA => self.sent_a < MESSAGES_PER_WORKER,
+            Actor::WorkerB => self.sent_b < MESSAGES_PER_WORKER,
+            Actor::Main => !self.queue.is_empty(),
+        }
+    }
+
+    fn finished(&self) -> bool {
+        ACTORS.iter().all(|actor| !self.can_run(*actor))
+    }
+}
+
+struct ChannelState {
+    rng: SimpleRng,
+    events: Vec<Actor>,
+    playing: bool,
+    last_tick: Option<Instant>,
+}
+
+impl Default for ChannelState {
+    fn default() -> Self {
+        Self {
+            rng: SimpleRng::new(rng::seed_from_time()),
+            events: Vec::new(),
+            playing: false,
+            last_tick: None,
+        }
+    }
+}
+
+impl ChannelState {
+    fn random_step(&mut self) {
+        let replay = replay(&self.events);
+        let runnable: Vec<Actor> = ACTORS
+            .iter()
+            .copied()
+            .filter(|actor| replay.can_run(*actor))
+            .collect();
+        if runnable.is_empty() {
+            return;
+        }
+        let index = self.rng.gen_range_i32(0, runnable.len() as i32 - 1) as usize;
+        self.events.push(runnable[index]);
+    }
+}
+
+/// What the two workers send, for [`OrderQuestion`].
+const WORKERS_SEND: &str = "Worker A sends A1 then A2. Worker B sends B1 then B2.";
+
+/// Four printed orders, one of which a channel never allows.
+struct OrderQuestion {
+    orders: Vec<Vec<String>>,
+    impossible: usize,
+}
+
+impl Exercise for OrderQuestion {
+    type Answer = Vec<String>;
+
+    /// Three orders that a real run could print, plus one where a worker's
+    /// own messages arrive out of order, which a channel never allows.
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        let mut orders: Vec<Vec<String>> = Vec::new();
+        while orders.len() < 3 {
+            let order = random_interleaving(rng);
+            if !orders.contains(&order) {
+                orders.push(order);
+            }
+        }
+        let mut broken = random_interleaving(rng);
+        let worker = if rng.gen_range_i32(0, 1) == 0 {
+            "A"
+        } else {
+            "B"
+        };
+        let first = broken.iter().position(|m| *m == format!("{worker}1"));
+        let second = broken.iter().position(|m| *m == format!("{worker}2"));
+        if let (Some(first), Some(second)) = (first, second) {
+            broken.swap(first, second);
+        }
+        let impossible = rng.gen_range_i32(0, 3) as usize;
+        orders.insert(impossible, broken);
+        OrderQuestion { orders, impossible }
+    }
+
+    fn prompt(&self) -> String {
+        "Which printed order can never happen?".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.label(WORKERS_SEND);
+    }
+
+    fn text(&self) -> String {
+        WORKERS_SEND.to_string()
+    }
+
+    fn answer(&self) -> Vec<String> {
+        self.orders[self.impossible].clone()
+    }
+
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<Vec<String>> {
+        let answer = self.answer();
+        self.choices(rng)
+            .into_iter()
+            .filter(|choice| *choice != answer)
+            .collect()
+    }
+
+    /// The orders as drawn; the impossible one was already put at a random
+    /// place among them.
+    fn choices(&self, _rng: &mut SimpleRng) -> Vec<Vec<String>> {
+        self.orders.clone()
+    }
+
+    fn choice_label(answer: &Vec<String>) -> String {
+        answer.join(" ")
+    }
+
+    fn hint(&self, _picked: &Vec<String>) -> String {
+        "That order can happen. Look for a worker whose messages swapped.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, correct: bool) {
+        if correct {
+            glossary::text(
+                ui,
+                "A [[channel]] keeps each sender's messages in the order they were sent.",
+            );
+        }
+    }
+}
+
+/// Printed orders to judge on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<OrderQuestion>(rng, difficulty)
+}
+
+/// Checks generated orders for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<OrderQuestion>(rng, difficulty)
+}
+
+fn random_interleaving(rng: &mut SimpleRng) -> Vec<String> {
+    let mut a = 0;
+    let mut b = 0;
+    let mut order = Vec::new();
+    while a < 2 || b < 2 {
+        let pick_a = b == 2 || (a < 2 && rng.gen_range_i32(0, 1) == 0);
+        if pick_a {
+            a += 1;
+            order.push(format!("A{a}"));
+        } else {
+            b += 1;
+            order.push(format!("B{b}"));
+        }
+    }
+    order
+}
+
+/// Workers on the left, the channel as a queue in the middle, and the
+/// receiving main thread on the right.
+fn paint_channel(ui: &mut egui::Ui, replay: &Replay, last: Option<Actor>) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
+    let box_h = row_height + 12.0;
+    let width = ui.available_width().max(360.0);
+    let height = box_h * 2.0 + 16.0;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let palette = Palette::from_ui(ui);
+    let highlight_color = palette.highlight;
+    let text_color = palette.text;
+    let weak_color = palette.weak_text;
+    let line_stroke = palette.line;
+    let fill = palette.code_fill;
+
+    let worker_w = (width * 0.2).min(120.0);
+    let stroke_for = |actor: Actor| {
+        if last == Some(actor) {
+            egui::Stroke::new(2.0, highlight_color)
+        } else {
+            line_stroke
+        }
+    };
+
+    let worker_a = egui::Rect::from_min_size(rect.min, egui::vec2(worker_w, box_h));
+    let worker_b = egui::Rect::from_min_size(
+        egui::pos2(rect.left(), rect.bottom() - box_h),
+        egui::vec2(worker_w, box_h),
+    );
+    let main_rect = egui::Rect::from_min_size(
+        egui::pos2(rect.right() - worker_w, rect.center().y - box_h / 2.0),
+        egui::vec2(worker_w, box_h),
+    );
+    let pipe = egui::Rect::from_min_max(
+        egui::pos2(worker_a.right() + 30.0, rect.center().y - box_h / 2.0 - 4.0),
+        egui::pos2(main_rect.left() - 30.0, rect.center().y + box_h / 2.0 + 4.0),
+    );
+
+    for (actor, actor_rect, label) in [
+        (
+            Actor::WorkerA,
+            worker_a,
+            format!("A sent {}", replay.sent_a),
+        ),
+        (
+            Actor::WorkerB,
+            worker_b,
+            format!("B sent {}", replay.sent_b),
+        ),
+        (
+            Actor::Main,
+            main_rect,
+            format!("main got {}", replay.received.len()),
+        ),
+    ] {
+        painter.rect(
+            actor_rect,
+            egui::CornerRadius::same(4),
+            fill,
+            stroke_for(actor),
+            egui::StrokeKind::Inside,
+        );
+        painter.text(
+            actor_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            label,
+            font_id.clone(),
+            text_color,
+        );
+    }
+
+    painter.rect(
+        pipe,
+        egui::CornerRadius::same(8),
+        egui::Color32::TRANSPARENT,
+        line_stroke,
+        egui::StrokeKind::Inside,
+    );
+    let arrow_stroke = egui::Stroke::new(1.5, line_stroke.color);
+    for worker in [worker_a, worker_b] {
+        let start = worker.right_center();
+        let end = pipe.left_center();
+        painter.arrow(start, end - start, arrow_stroke);
+    }
+    painter.arrow(
+        pipe.right_center(),
+        main_rect.left_center() - pipe.right_center(),
+        arrow_stroke,
+    );
+
+    if replay.queue.is_empty() {
+        painter.text(
+            pipe.center(),
+            egui::Align2::CENTER_CENTER,
+            "channel is empty",
+            font_id.clone(),
+            weak_color,
+        );
+        return;
+    }
+    // The oldest message sits next to main, ready to be received first.
+    let slot_w = (pipe.width() - 8.0) / (MESSAGES_PER_WORKER * 2) as f32;
+    for (index, message) in replay.queue.iter().enumerate() {
+        let right = pipe.right() - 4.0 - index as f32 * slot_w;
+        let slot = egui::Rect::from_min_max(
+            egui::pos2(right - slot_w + 3.0, pipe.top() + 4.0),
+            egui::pos2(right, pipe.bottom() - 4.0),
+        );
+        painter.rect(
+            slot,
+            egui::CornerRadius::same(4),
+            fill,
+            line_stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.text(
+            slot.center(),
+            egui::Align2::CENTER_CENTER,
+            message,
+            font_id.clone(),
+            text_color,
+        );
+    }
+}
+
+/// One lane per thread; each event is a dot on the lane of the thread that ran it.
+fn paint_timelines(ui: &mut egui::Ui, events: &[Actor], labels: &[String]) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
+    let lane_h = row_height + 14.0;
+    let width = ui.available_width().max(360.0);
+    let height = lane_h * ACTORS.len() as f32;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let palette = Palette::from_ui(ui);
+    let highlight_color = palette.highlight;
+    let text_color = palette.text;
+    let weak_color = palette.weak_text;
+    let line_stroke = palette.line;
+
+    let label_w = 80.0;
+    let total = MESSAGES_PER_WORKER * 4;
+    let step_w = (width - label_w) / total as f32;
+    for (lane, actor) in ACTORS.iter().enumerate() {
+        let y = rect.top() + lane as f32 * lane_h + lane_h / 2.0;
+        painter.text(
+            egui::pos2(rect.left(), y),
+            egui::Align2::LEFT_CENTER,
+            actor.name(),
+            font_id.clone(),
+            weak_color,
+        );
+        painter.line_segment(
+            [
+                egui::pos2(rect.left() + label_w, y),
+                egui::pos2(rect.right(), y),
+            ],
+            egui::Stroke::new(1.0, line_stroke.color),
+        );
+    }
+    for (time, (actor, label)) in events.iter().zip(labels).enumerate() {
+        let lane = ACTORS.iter().position(|a| a == actor).unwrap_or(0);
+        let x = rect.left() + label_w + (time as f32 + 0.5) * step_w;
+        let y = rect.top() + lane as f32 * lane_h + lane_h / 2.0;
+        let latest = time + 1 == events.len();
+        painter.circle_filled(
+            egui::pos2(x, y),
+            4.0,
+            if latest { highlight_color } else { text_color },
+        );
+        let short = label.rsplit(' ').next().unwrap_or(label);
+        painter.text(
+            egui::pos2(x, y - 5.0),
+            egui::Align2::CENTER_BOTTOM,
+            short,
+            font_id.clone(),
+            if latest { highlight_color } else { weak_color },
+        );
+    }
+}
+
+pub fn concurrency(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Concurrency basics\n\
+         A **thread** is a worker that runs at the same time as the rest of the program.\n\
+         Several threads can make a program faster, but they must not trip over each other.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         Two cooks prepare dishes in a kitchen. Instead of both grabbing the same plate,\n\
+         each puts finished dishes on a counter, and a waiter takes them from the other end.\n\
+         The counter is a **channel**: one side sends, the other side receives.",
+    );
+
+    speech::lesson(
+        nb,
+        "## Minimal code\n\
+         ```rust\n\
+         use std::sync::mpsc;\n\
+         use std::thread;\n\n\
+         let (tx, rx) = mpsc::channel();\n\
+         thread::spawn(move || tx.send(\"hello\").unwrap());\n\
+         println!(\"{}\", rx.recv().unwrap());\n\
+         ```\n\
+         `tx` is the sending end, `rx` the receiving end.\n\
+         The value **moves** into the channel, so the two threads never share it.",
+    );
+
+    nb.state(
+        &state_key("channel_state"),
+        ChannelState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Two workers, one channel") {
+                    return;
+                }
+                ui.add_space(4.0);
+                glossary::text(
+                    ui,
+                    "You are the scheduler: decide which [[thread]] runs next, or press Play.",
+                );
+                ui.add_space(6.0);
+                ui.add(CodeBlock::new(CHANNEL_LINES));
+                ui.add_space(6.0);
+
+                if state.playing {
+                    let interval = settings::animation_interval(PLAY_INTERVAL);
+                    let due = state
+                        .last_tick
+                        .is_none_or(|tick| tick.elapsed() >= interval);
+                    if due {
+                        state.random_step();
+                        state.last_tick = Some(Instant::now());
+                    }
+                    if replay(&state.events).finished() {
+                        state.playing = false;
+                    } else {
+                        ui.ctx().request_repaint_after(interval);
+                    }
+                }
+
+                let current = replay(&state.events);
+                ui.horizontal(|ui| {
+                    for actor in ACTORS {
+                        let label = match actor {
+                            Actor::Main => "main receives".to_string(),
+                            _ => format!("{} sends", actor.name()),
+                        };
+                        if ui
+                            .add_enabled(current.can_run(*actor), widgets::Button::new(label))
+                            .clicked()
+                        {
+                            state.events.push(*actor);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("Random step")).clicked() {
+                        state.random_step();
+                    }
+                    let play_label = if state.playing { "Pause" } else { "Play" };
+                    if ui.add(widgets::Button::new(play_label)).clicked() {
+                        state.playing = !state.playing;
+                        state.last_tick = None;
+                    }
+                    if ui
+                        .add_enabled(!state.events.is_empty(), widgets::Button::new("Undo"))
+                        .clicked()
+                    {
+                        state.events.pop();
+                    }
+                    if ui.add(widgets::Button::new("Reset")).clicked() {
+                        state.events.clear();
+                        state.playing = false;
+                    }
+                });
+
+                let current = replay(&state.events);
+                ui.add_space(8.0);
+                paint_channel(ui, &current, state.events.last().copied());
+                ui.add_space(8.0);
+                paint_timelines(ui, &state.events, &current.labels);
+                ui.add_space(6.0);
+                if current.received.is_empty() {
+                    ui.label("main has not received anything yet.");
+                } else {
+                    ui.label(format!("main printed: {}", current.received.join(", ")));
+                }
+                if current.finished() {
+                    ui.label(
+                        "Done. Every message arrived exactly once, and each worker's messages kept their order.",
+                    );
+                }
+            });
+        },
+    );
+
+    nb.view(|ui| {
+        note!(
+            ui,
+            "Try different schedules. The printed order changes between runs,\n\
+             but A1 always comes before A2, and no message is ever lost or doubled.\n\
+             That is what makes message passing race-free."
+        );
+    });
+
+    nb.state(
+        &state_key("order_quiz_state"),
+        MultipleChoice::<OrderQuestion>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Practice: impossible orders") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("order_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<OrderQuestion>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("order_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - A thread is a worker running at the same time as others.\n\
+         - The order in which threads run is not fixed.\n\
+         - A channel moves values from senders to a receiver, one at a time.\n\
+         - Each sender's messages arrive in order, and none are lost.\n\n\
+         Next up: **Mini project: a small CLI tool** builds a word counter from the pieces so far.",
+    );
+}
@@ -0,0 +1,162 @@
+use egui::RichText;
+
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+use crate::i18n::tr;
+use crate::progress;
+use crate::score;
+use crate::stats;
+
+use super::{card_heading, overview, Track, CHAPTERS};
+
+/// Accuracy below this many percent marks a chapter as a sticking point.
+const STRUGGLING_BELOW: u32 = 60;
+/// Cards listed under "Time per card".
+const TOP_CARDS: usize = 10;
+
+/// `"25 min"`, or `"1 h 05 min"` from an hour on.
+fn duration_label(seconds: f64) -> String {
+    let minutes = seconds as u64 / 60;
+    if minutes >= 60 {
+        format!("{} h {:02} min", minutes / 60, minutes % 60)
+    } else {
+        format!("{minutes} min")
+    }
+}
+
+/// One row per lesson chapter: answers, accuracy, time, solved cards and
+/// hints taken.
+fn chapter_table(ui: &mut egui::Ui) {
+    egui::Grid::new("dashboard_chapters")
+        .striped(true)
+        .spacing([16.0, 4.0])
+        .show(ui, |ui| {
+            for heading in [
+                "dashboard.chapter",
+                "dashboard.attempts",
+                "dashboard.accuracy",
+                "dashboard.time",
+                "dashboard.solved",
+                "dashboard.hints",
+            ] {
+                ui.label(RichText::new(tr(heading)).strong());
+            }
+            ui.end_row();
+
+            for def in CHAPTERS
+                .iter()
+                .filter(|def| def.is_lesson() && def.track != Track::Start)
+            {
+                let stats = stats::chapter(def.chapter);
+                ui.label(format!("{}. {}", def.label(), def.title));
+                ui.label(stats.attempts().to_string());
+                match stats.accuracy() {
+                    Some(percent) if percent < STRUGGLING_BELOW => {
+                        ui.label(RichText::new(format!("\u{26A0} {percent}%")).strong())
+                            .on_hover_text(tr("dashboard.struggling"));
+                    }
+                    Some(percent) => {
+                        ui.label(format!("{percent}%"));
+                    }
+                    None => {
+                        ui.label("-");
+                    }
+                }
+                ui.label(duration_label(stats.seconds));
+                match progress::completion(def.chapter) {
+                    Some((solved, total)) => ui.label(format!("{solved}/{total}")),
+                    None => ui.label("-"),
+                };
+                ui.label(progress::hints(def.chapter).to_string());
+                ui.end_row();
+            }
+        });
+}
+
+/// Exercises not solved yet in the chapters the learner has started, each
+/// linking to its card.
+fn unfinished(ui: &mut egui::Ui) {
+    let mut any = false;
+    for def in CHAPTERS {
+        if !stats::chapter(def.chapter).started() {
+            continue;
+        }
+        for key in progress::unfinished(def.chapter) {
+            overview::exercise_link(ui, (def.chapter, key));
+            any = true;
+        }
+    }
+    if !any {
+        ui.label(tr("dashboard.nothing_unfinished"));
+    }
+}
+
+/// The cards the learner spent the most time on, read from the local time
+/// log.
+fn card_table(ui: &mut egui::Ui) {
+    let cards = stats::top_cards(TOP_CARDS);
+    if cards.is_empty() {
+        ui.label(tr("dashboard.no_card_time"));
+        return;
+    }
+    egui::Grid::new("dashboard_cards")
+        .striped(true)
+        .spacing([16.0, 4.0])
+        .show(ui, |ui| {
+            for heading in ["dashboard.chapter", "dashboard.card", "dashboard.time"] {
+                ui.label(RichText::new(tr(heading)).strong());
+            }
+            ui.end_row();
+
+            for (chapter, card, seconds) in cards {
+                match CHAPTERS.iter().find(|def| def.id == chapter) {
+                    Some(def) => ui.label(format!("{}. {}", def.label(), def.title)),
+                    None => ui.label(chapter),
+                };
+                ui.label(card);
+                ui.label(duration_label(seconds));
+                ui.end_row();
+            }
+        });
+}
+
+pub fn dashboard(nb: &mut NotebookCtx) {
+    nb.view(|ui| {
+        md!(ui, "{}", tr("dashboard.intro"));
+    });
+
+    nb.view(|ui| {
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            if !card_heading(ui, "Chapters") {
+                return;
+            }
+            ui.add_space(4.0);
+            if let Some(summary) = score::summary() {
+                ui.label(summary);
+                ui.add_space(4.0);
+            }
+            chapter_table(ui);
+        });
+    });
+
+    nb.view(|ui| {
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            if !card_heading(ui, "Unfinished exercises") {
+                return;
+            }
+            ui.add_space(4.0);
+            unfinished(ui);
+        });
+    });
+
+    nb.view(|ui| {
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            if !card_heading(ui, "Time per card") {
+                return;
+            }
+            ui.add_space(4.0);
+            card_table(ui);
+        });
+    });
+}
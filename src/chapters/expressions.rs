@@ -1,9 +1,27 @@
-use crate::chapters::Chapter;
-use egui::text::LayoutJob;
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::{self, Difficulty};
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::expr::{
+    self, build_steps_in, expr_at_path, expr_to_string, first_difference, Language, PathStep,
+    Scanner,
+};
+use crate::glossary;
+use crate::keyboard::AnswerToggle;
+use crate::mastery;
+use crate::palette::Palette;
+use crate::progress;
+use crate::rng::{self, SimpleRng};
+use crate::speech;
+use crate::stepper::{stepper, Autoplay, Steps};
+use crate::tree_practice::{TreePractice, Wording};
+use crate::tree_view::{draw_tree, draw_tree_interactive};
+use crate::worksheet;
 use egui::RichText;
-use egui::TextStyle;
-use std::ops::Range;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+use web_time::Instant;
 use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
 use GORBIE::prelude::*;
 
@@ -13,1058 +31,1538 @@ fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
     (CHAPTER, key)
 }
 
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+/// How hard random practice is: the adaptive level, or one the learner
+/// picked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Preset {
+    Adaptive,
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Preset {
+    const ALL: [(Preset, &'static str); 4] = [
+        (Preset::Adaptive, "Adaptive"),
+        (Preset::Easy, "Easy"),
+        (Preset::Medium, "Medium"),
+        (Preset::Hard, "Hard"),
+    ];
+
+    /// The fixed level, which sets how deep trees get, how large their
+    /// numbers are and whether `*` shows up.
+    fn difficulty(self) -> Option<Difficulty> {
+        match self {
+            Preset::Adaptive => None,
+            Preset::Easy => Some(Difficulty::new(1)),
+            Preset::Medium => Some(Difficulty::new(3)),
+            Preset::Hard => Some(Difficulty::new(5)),
+        }
+    }
+}
+
+/// The values a practice answer may have at `difficulty`. The hardest
+/// level can come out below zero.
+fn practice_answers(difficulty: Difficulty) -> RangeInclusive<i64> {
+    match difficulty.level() {
+        1 => 0..=20,
+        5 => -99..=199,
+        _ => 0..=99,
+    }
+}
+
+/// Random practice: the controls above the exercise, which new exercises
+/// follow.
 struct RandomExerciseState {
-    rng: SimpleRng,
-    exercise: Exercise,
-    choices: Vec<i64>,
-    selection: Option<i64>,
+    practice: MultipleChoice<Practice>,
+    preset: Preset,
+    /// Whether trees may hold a unary minus. Kept in the seed as its flag,
+    /// see [`Difficulty::seed_with_flag`], which is set when it is off.
+    negation: bool,
 }
 
 impl Default for RandomExerciseState {
     fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
-        let exercise = generate_exercise(&mut rng);
-        let choices = build_choices(&mut rng, exercise.answer);
+        let seed = difficulty::current().seed_with_flag(rng::seed_from_time(), false);
         Self {
-            rng,
-            exercise,
-            choices,
-            selection: None,
+            practice: MultipleChoice::with_seed(seed),
+            preset: Preset::Adaptive,
+            negation: true,
         }
     }
 }
 
 impl RandomExerciseState {
+    /// Everything the exercise depends on comes from its seed, so a shared
+    /// or broadcast seed rebuilds it whatever is picked on this card. The
+    /// controls follow the seed instead.
+    fn follow_seed(&mut self) {
+        let seed = self.practice.seed();
+        self.negation = !Difficulty::flag_of_seed(seed);
+        if self
+            .preset
+            .difficulty()
+            .is_some_and(|preset| preset != Difficulty::of_seed(seed))
+        {
+            self.preset = Preset::Adaptive;
+        }
+    }
+}
+
+/// Two expressions side by side; the learner says which one comes out
+/// larger, or that they are equal.
+struct CompareState {
+    rng: SimpleRng,
+    seed: u64,
+    left: Expr,
+    right: Expr,
+    /// How the left value compares to the right one, once picked.
+    selection: Option<Ordering>,
+}
+
+impl Default for CompareState {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut state = Self {
+            rng: SimpleRng::new(seed),
+            seed,
+            left: Expr::value(0),
+            right: Expr::value(0),
+            selection: None,
+        };
+        state.load_seed(seed);
+        state
+    }
+}
+
+impl CompareState {
     fn regenerate(&mut self) {
-        self.exercise = generate_exercise(&mut self.rng);
-        self.choices = build_choices(&mut self.rng, self.exercise.answer);
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
+    }
+
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        (self.left, self.right) = generate_comparison(&mut self.rng, Difficulty::of_seed(seed));
         self.selection = None;
     }
+
+    fn answer(&self) -> Option<Ordering> {
+        Some(self.left.eval().ok()?.cmp(&self.right.eval().ok()?))
+    }
 }
 
-struct Exercise {
+/// Two trees whose values lie close together, so that a glance is not
+/// enough. Every fourth pair comes out equal.
+fn generate_comparison(rng: &mut SimpleRng, difficulty: Difficulty) -> (Expr, Expr) {
+    let left = generate_tree_expr(rng, difficulty);
+    let Ok(target) = left.eval() else {
+        return (left, Expr::value(0));
+    };
+    let equal = rng.gen_range_i32(0, 3) == 0;
+    let mut right = generate_tree_expr(rng, difficulty);
+    for _ in 0..200 {
+        let value = right.eval().unwrap_or(i64::MIN);
+        let close = if equal {
+            value == target
+        } else {
+            value != target && (value - target).abs() <= 6
+        };
+        if close {
+            break;
+        }
+        right = generate_tree_expr(rng, difficulty);
+    }
+    (left, right)
+}
+
+struct Practice {
     expr: Expr,
     answer: i64,
+    /// Where the learner is in the worked solution.
+    step: usize,
 }
 
-struct TreeExerciseState {
+struct DictationState {
     rng: SimpleRng,
-    expr: Expr,
-    feedback: Option<String>,
+    seed: u64,
+    exercise: Practice,
+    seconds: u32,
+    shown_at: Option<Instant>,
+    guess: i64,
+    checked: Option<i64>,
 }
 
-impl Default for TreeExerciseState {
+impl Default for DictationState {
     fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
-        let expr = generate_tree_expr(&mut rng);
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
+        let exercise = generate_exercise(&mut rng, Difficulty::of_seed(seed));
         Self {
             rng,
-            expr,
-            feedback: None,
+            seed,
+            exercise,
+            seconds: 3,
+            shown_at: None,
+            guess: 0,
+            checked: None,
         }
     }
 }
 
-impl TreeExerciseState {
+impl DictationState {
     fn regenerate(&mut self) {
-        self.expr = generate_tree_expr(&mut self.rng);
-        self.feedback = None;
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
     }
+
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        self.exercise = generate_exercise(&mut self.rng, Difficulty::of_seed(seed));
+        self.shown_at = Some(Instant::now());
+        self.guess = 0;
+        self.checked = None;
+    }
+}
+
+/// A tile of the expression builder: one of its numbers, by index, or an
+/// operator.
+#[derive(Clone, Copy)]
+enum Tile {
+    Number(usize),
+    Op(BinaryOp),
 }
 
-struct SimpleRng {
-    state: u64,
+/// The operators the builder hands out. Unlike the numbers, each can be
+/// used as often as needed.
+const BUILDER_OPS: [BinaryOp; 3] = [BinaryOp::Add, BinaryOp::Sub, BinaryOp::Mul];
+
+/// The scaffold is the tree of `(a ? b) ? c`: slot 0 holds the top
+/// operator, slot 1 the operator on the left with the numbers of slots 2
+/// and 3 below it, and slot 4 the number on the right.
+const BUILDER_SLOTS: usize = 5;
+const OP_SLOTS: [usize; 2] = [0, 1];
+/// Which slot hangs below which, for the lines of the scaffold.
+const BUILDER_EDGES: [(usize, usize); 4] = [(0, 1), (0, 4), (1, 2), (1, 3)];
+
+struct BuilderState {
+    rng: SimpleRng,
+    seed: u64,
+    target: i64,
+    /// The three numbers that make the target and a spare one, shuffled.
+    numbers: Vec<i64>,
+    slots: [Option<Tile>; BUILDER_SLOTS],
+    feedback: Option<String>,
 }
 
-impl SimpleRng {
-    fn new(seed: u64) -> Self {
-        Self { state: seed.max(1) }
+impl Default for BuilderState {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut state = Self {
+            rng: SimpleRng::new(seed),
+            seed,
+            target: 0,
+            numbers: Vec::new(),
+            slots: [None; BUILDER_SLOTS],
+            feedback: None,
+        };
+        state.load_seed(seed);
+        state
     }
+}
 
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
+impl BuilderState {
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
     }
 
-    fn gen_range_i64(&mut self, min: i64, max: i64) -> i64 {
-        let span = (max - min + 1) as u64;
-        let value = self.next_u32() as u64 % span;
-        min + value as i64
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        let (target, numbers) = generate_builder(&mut self.rng, Difficulty::of_seed(seed));
+        self.target = target;
+        self.numbers = numbers;
+        self.slots = [None; BUILDER_SLOTS];
+        self.feedback = None;
     }
 
-    fn shuffle<T>(&mut self, values: &mut [T]) {
-        if values.len() <= 1 {
-            return;
+    fn used(&self, index: usize) -> bool {
+        self.slots
+            .iter()
+            .any(|slot| matches!(slot, Some(Tile::Number(used)) if *used == index))
+    }
+
+    fn number_at(&self, slot: usize) -> Option<Expr> {
+        match self.slots[slot]? {
+            Tile::Number(index) => Some(Expr::value(self.numbers[index])),
+            Tile::Op(_) => None,
         }
-        for i in (1..values.len()).rev() {
-            let j = self.gen_range_i64(0, i as i64) as usize;
-            values.swap(i, j);
+    }
+
+    fn op_at(&self, slot: usize) -> Option<BinaryOp> {
+        match self.slots[slot]? {
+            Tile::Op(op) => Some(op),
+            Tile::Number(_) => None,
         }
     }
-}
 
-fn seed_from_time() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_nanos() as u64)
-        .unwrap_or(1)
-}
+    /// The left branch, once its operator and both numbers are in.
+    fn left(&self) -> Option<Expr> {
+        Some(Expr::binary(
+            self.op_at(1)?,
+            self.number_at(2)?,
+            self.number_at(3)?,
+        ))
+    }
 
-fn build_choices(rng: &mut SimpleRng, answer: i64) -> Vec<i64> {
-    let mut choices = vec![answer];
-    while choices.len() < 4 {
-        let delta = rng.gen_range_i64(-5, 5);
-        if delta == 0 {
-            continue;
-        }
-        let candidate = answer + delta;
-        if candidate < 0 {
-            continue;
-        }
-        if !choices.contains(&candidate) {
-            choices.push(candidate);
-        }
+    /// The whole tree, once every slot is filled.
+    fn assembled(&self) -> Option<Expr> {
+        Some(Expr::binary(
+            self.op_at(0)?,
+            self.left()?,
+            self.number_at(4)?,
+        ))
     }
-    rng.shuffle(&mut choices);
-    choices
-}
 
-fn generate_exercise(rng: &mut SimpleRng) -> Exercise {
-    for _ in 0..200 {
-        let expr = random_expr(rng, 0, 3);
-        if matches!(expr.kind, ExprKind::Num(_)) {
-            continue;
-        }
-        if count_ops(&expr) < 2 {
-            continue;
-        }
-        if let Ok(answer) = eval_expr(&expr) {
-            if (0..=99).contains(&answer) {
-                return Exercise { expr, answer };
+    /// Puts `tile` into `slot` if it fits there, and says what the tree
+    /// makes now. A number tile already in the tree moves over.
+    fn drop_tile(&mut self, slot: usize, tile: Tile) {
+        let wants_op = OP_SLOTS.contains(&slot);
+        match tile {
+            Tile::Number(_) if wants_op => {
+                self.feedback = Some(
+                    "This box has branches, so it needs an operator. Numbers go at the bottom."
+                        .to_string(),
+                );
+                return;
+            }
+            Tile::Op(_) if !wants_op => {
+                self.feedback = Some(
+                    "This box is at the bottom of the tree, so it needs a number.".to_string(),
+                );
+                return;
+            }
+            Tile::Number(index) => {
+                for filled in &mut self.slots {
+                    if matches!(filled, Some(Tile::Number(used)) if *used == index) {
+                        *filled = None;
+                    }
+                }
             }
+            Tile::Op(_) => {}
         }
-    }
-    Exercise {
-        expr: Expr {
-            kind: ExprKind::Add(
-                Box::new(Expr {
-                    kind: ExprKind::Mul(Box::new(Expr::num(2)), Box::new(Expr::num(3))),
-                }),
-                Box::new(Expr::num(1)),
+        self.slots[slot] = Some(tile);
+
+        let Some(expr) = self.assembled() else {
+            let open = self.slots.iter().filter(|slot| slot.is_none()).count();
+            self.feedback = Some(
+                match self.left().map(|left| (expr_to_string(&left), left.eval())) {
+                    Some((text, Ok(value))) => {
+                        format!("The left branch {text} makes {value}. {open} more to fill.")
+                    }
+                    _ => format!("{open} more to fill."),
+                },
+            );
+            return;
+        };
+        let text = expr_to_string(&expr);
+        let correct = expr.eval().is_ok_and(|value| value == self.target);
+        self.feedback = Some(match expr.eval() {
+            Ok(value) if correct => format!("{text} = {value}. You hit the target!"),
+            Ok(value) => format!(
+                "{text} = {value}, not {}. Drag other tiles over the boxes to change it.",
+                self.target
             ),
-        },
-        answer: 7,
+            Err(error) => error,
+        });
+        progress::answer(chapter_key("builder_state"), correct);
     }
 }
 
-fn eval_expr(expr: &Expr) -> Result<i64, String> {
-    match &expr.kind {
-        ExprKind::Num(value) => Ok(*value),
-        ExprKind::Neg(inner) => eval_expr(inner)?
-            .checked_neg()
-            .ok_or_else(|| "Overflow".to_string()),
-        ExprKind::Add(left, right) => eval_expr(left)?
-            .checked_add(eval_expr(right)?)
-            .ok_or_else(|| "Overflow".to_string()),
-        ExprKind::Sub(left, right) => eval_expr(left)?
-            .checked_sub(eval_expr(right)?)
-            .ok_or_else(|| "Overflow".to_string()),
-        ExprKind::Mul(left, right) => eval_expr(left)?
-            .checked_mul(eval_expr(right)?)
-            .ok_or_else(|| "Overflow".to_string()),
+/// A target and four numbers, three of which make it with two of
+/// [`BUILDER_OPS`] in the shape of the scaffold.
+fn generate_builder(rng: &mut SimpleRng, difficulty: Difficulty) -> (i64, Vec<i64>) {
+    let max = difficulty.max_value() as i64;
+    for _ in 0..100 {
+        let mut numbers: Vec<i64> = (0..4).map(|_| rng.gen_range_i64(1, max)).collect();
+        let mut op = || BUILDER_OPS[rng.gen_range_i64(0, BUILDER_OPS.len() as i64 - 1) as usize];
+        let (inner, outer) = (op(), op());
+        let expr = Expr::binary(
+            outer,
+            Expr::binary(inner, Expr::value(numbers[0]), Expr::value(numbers[1])),
+            Expr::value(numbers[2]),
+        );
+        if let Ok(target) = expr.eval() {
+            if (0..=99).contains(&target) {
+                rng.shuffle(&mut numbers);
+                return (target, numbers);
+            }
+        }
     }
+    (7, vec![2, 3, 1, 4])
 }
 
-fn count_ops(expr: &Expr) -> usize {
-    match &expr.kind {
-        ExprKind::Num(_) => 0,
-        ExprKind::Neg(inner) => 1 + count_ops(inner),
-        ExprKind::Add(left, right) | ExprKind::Sub(left, right) | ExprKind::Mul(left, right) => {
-            1 + count_ops(left) + count_ops(right)
-        }
-    }
+/// How a tile looks, in the tray and in the tree alike.
+fn tile_frame(ui: &mut egui::Ui, text: &str) {
+    egui::Frame::group(ui.style())
+        .inner_margin(egui::Margin::symmetric(10, 4))
+        .show(ui, |ui| {
+            ui.label(RichText::new(text).monospace().strong());
+        });
 }
 
-fn expr_to_string(expr: &Expr) -> String {
-    render_expr_with_highlight(expr, None).0
+/// A round of the target game: reach `target` with an expression of one's
+/// own, using each of `numbers` at most once.
+struct TargetState {
+    rng: SimpleRng,
+    seed: u64,
+    target: i64,
+    numbers: Vec<i64>,
+    input: String,
+    /// The input as it was when "Check" was pressed.
+    checked: Option<String>,
 }
 
-fn random_expr(rng: &mut SimpleRng, depth: u8, max_depth: u8) -> Expr {
-    let use_number = depth >= max_depth || rng.gen_range_i64(0, 4) == 0;
-    if use_number {
-        let value = rng.gen_range_i64(1, 9);
-        return Expr::num(value);
+impl Default for TargetState {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
+        let (target, numbers) = generate_target(&mut rng, Difficulty::of_seed(seed));
+        Self {
+            rng,
+            seed,
+            target,
+            numbers,
+            input: String::new(),
+            checked: None,
+        }
     }
+}
 
-    let roll = rng.gen_range_i64(0, 4);
-    if roll == 3 {
-        let inner = random_expr(rng, depth + 1, max_depth);
-        return Expr {
-            kind: ExprKind::Neg(Box::new(inner)),
-        };
+impl TargetState {
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
     }
 
-    let left = random_expr(rng, depth + 1, max_depth);
-    let right = random_expr(rng, depth + 1, max_depth);
-    let kind = match roll {
-        0 => ExprKind::Add(Box::new(left), Box::new(right)),
-        1 => ExprKind::Sub(Box::new(left), Box::new(right)),
-        _ => ExprKind::Mul(Box::new(left), Box::new(right)),
-    };
-    Expr { kind }
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        let (target, numbers) = generate_target(&mut self.rng, Difficulty::of_seed(seed));
+        self.target = target;
+        self.numbers = numbers;
+        self.input.clear();
+        self.checked = None;
+    }
 }
 
-fn generate_tree_expr(rng: &mut SimpleRng) -> Expr {
-    for _ in 0..120 {
-        let expr = random_expr(rng, 0, 3);
-        if matches!(expr.kind, ExprKind::Num(_)) {
-            continue;
+/// A target and four numbers, three or all of which reach it when they
+/// are combined one after the other.
+fn generate_target(rng: &mut SimpleRng, difficulty: Difficulty) -> (i64, Vec<i64>) {
+    let max = difficulty.max_value() as i64;
+    for _ in 0..200 {
+        let mut numbers: Vec<i64> = (0..4).map(|_| rng.gen_range_i64(1, max)).collect();
+        let used = rng.gen_range_i64(3, 4) as usize;
+        let mut expr = Expr::value(numbers[0]);
+        for number in &numbers[1..used] {
+            let op = BUILDER_OPS[rng.gen_range_i64(0, BUILDER_OPS.len() as i64 - 1) as usize];
+            expr = Expr::binary(op, expr, Expr::value(*number));
         }
-        if let Ok(value) = eval_expr(&expr) {
-            if (-50..=50).contains(&value) {
-                return expr;
-            }
+        let Ok(target) = expr.eval() else {
+            continue;
+        };
+        if (10..=99).contains(&target) && !numbers.contains(&target) {
+            rng.shuffle(&mut numbers);
+            return (target, numbers);
         }
     }
-    Expr::num(1)
+    (24, vec![4, 6, 1, 3])
 }
 
-struct ExpressionState {
-    input: String,
-    step: usize,
-    rng: SimpleRng,
-}
-
-impl Default for ExpressionState {
-    fn default() -> Self {
-        Self {
-            input: "(3 * 2) + 2".to_string(),
-            step: 0,
-            rng: SimpleRng::new(seed_from_time()),
-        }
+/// The numbers written in `expr`, left to right.
+fn numbers_in(expr: &Expr, out: &mut Vec<i64>) {
+    if let Some(value) = expr.as_value() {
+        out.push(*value);
+    }
+    for (_, child) in expr.children() {
+        numbers_in(child, out);
     }
 }
 
-#[derive(Clone)]
-enum ExprKind {
-    Num(i64),
-    Neg(Box<Expr>),
-    Add(Box<Expr>, Box<Expr>),
-    Sub(Box<Expr>, Box<Expr>),
-    Mul(Box<Expr>, Box<Expr>),
+/// Whether `expr` sticks to the given numbers, each used at most once.
+fn check_numbers(numbers: &[i64], expr: &Expr) -> Result<(), String> {
+    let mut used = Vec::new();
+    numbers_in(expr, &mut used);
+    let mut left = numbers.to_vec();
+    for value in used {
+        let Some(index) = left.iter().position(|number| *number == value) else {
+            return Err(if numbers.contains(&value) {
+                format!("{value} is used more often than it is given.")
+            } else {
+                format!("{value} is not one of the numbers.")
+            });
+        };
+        left.swap_remove(index);
+    }
+    Ok(())
 }
 
-#[derive(Clone)]
-struct Expr {
-    kind: ExprKind,
+/// The tree shown without its code, for the learner to write the code.
+struct TreeToCodeState {
+    rng: SimpleRng,
+    seed: u64,
+    expr: Expr,
+    input: String,
+    /// The input as it was when "Check" was pressed.
+    checked: Option<String>,
 }
 
-impl Expr {
-    fn num(value: i64) -> Self {
+impl Default for TreeToCodeState {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
+        let expr = generate_tree_expr(&mut rng, Difficulty::of_seed(seed));
         Self {
-            kind: ExprKind::Num(value),
+            rng,
+            seed,
+            expr,
+            input: String::new(),
+            checked: None,
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-enum PathStep {
-    Unary,
-    Left,
-    Right,
+impl TreeToCodeState {
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
+    }
+
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        self.expr = generate_tree_expr(&mut self.rng, Difficulty::of_seed(seed));
+        self.input.clear();
+        self.checked = None;
+    }
 }
 
-struct Step {
-    expr: Expr,
-    highlight: Option<Vec<PathStep>>,
+/// Numbers with operators between them and no parentheses yet; the learner
+/// adds parentheses until the line comes out at `target`.
+struct ParenState {
+    rng: SimpleRng,
+    seed: u64,
+    numbers: Vec<i64>,
+    /// `ops[i]` stands between `numbers[i]` and `numbers[i + 1]`.
+    ops: Vec<BinaryOp>,
+    target: i64,
+    /// Parentheses opened right before each number.
+    opens: Vec<u8>,
+    /// Parentheses closed right after each number.
+    closes: Vec<u8>,
+    /// The line as it was when "Check" was pressed.
+    checked: Option<String>,
 }
 
-struct Parser<'a> {
-    input: &'a [u8],
-    pos: usize,
+/// The most parentheses one gap takes; a click past it clears the gap.
+const MAX_PARENS: u8 = 2;
+
+impl Default for ParenState {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut state = Self {
+            rng: SimpleRng::new(seed),
+            seed,
+            numbers: Vec::new(),
+            ops: Vec::new(),
+            target: 0,
+            opens: Vec::new(),
+            closes: Vec::new(),
+            checked: None,
+        };
+        state.load_seed(seed);
+        state
+    }
 }
 
-impl<'a> Parser<'a> {
-    fn new(input: &'a str) -> Self {
-        Self {
-            input: input.as_bytes(),
-            pos: 0,
-        }
+impl ParenState {
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
     }
 
-    fn parse_expression(&mut self) -> Result<Expr, String> {
-        let expr = self.parse_sum()?;
-        self.skip_ws();
-        if self.pos < self.input.len() {
-            return Err(format!("Unexpected input at position {}", self.pos + 1));
-        }
-        Ok(expr)
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        let (numbers, ops, target) = generate_parens(&mut self.rng);
+        self.opens = vec![0; numbers.len()];
+        self.closes = vec![0; numbers.len()];
+        self.numbers = numbers;
+        self.ops = ops;
+        self.target = target;
+        self.checked = None;
     }
 
-    fn parse_sum(&mut self) -> Result<Expr, String> {
-        let mut node = self.parse_product()?;
-        loop {
-            self.skip_ws();
-            if self.consume(b'+') {
-                let right = self.parse_product()?;
-                node = Expr {
-                    kind: ExprKind::Add(Box::new(node), Box::new(right)),
-                };
-            } else if self.consume(b'-') {
-                let right = self.parse_product()?;
-                node = Expr {
-                    kind: ExprKind::Sub(Box::new(node), Box::new(right)),
-                };
-            } else {
-                break;
+    /// The line with the parentheses placed so far.
+    fn text(&self) -> String {
+        let mut text = String::new();
+        for (index, number) in self.numbers.iter().enumerate() {
+            if index > 0 {
+                text.push_str(&format!(
+                    " {} ",
+                    Arithmetic::binary_label(self.ops[index - 1])
+                ));
             }
+            text.push_str(&"(".repeat(self.opens[index] as usize));
+            text.push_str(&number.to_string());
+            text.push_str(&")".repeat(self.closes[index] as usize));
         }
-        Ok(node)
+        text
     }
+}
 
-    fn parse_product(&mut self) -> Result<Expr, String> {
-        let mut node = self.parse_factor()?;
-        loop {
-            self.skip_ws();
-            if self.consume(b'*') {
-                let right = self.parse_factor()?;
-                node = Expr {
-                    kind: ExprKind::Mul(Box::new(node), Box::new(right)),
-                };
-            } else {
-                break;
+/// Every value the numbers and operators can make, one per way to group
+/// them with parentheses.
+fn groupings(numbers: &[i64], ops: &[BinaryOp]) -> Vec<i64> {
+    if let [number] = numbers {
+        return vec![*number];
+    }
+    let mut values = Vec::new();
+    for split in 1..numbers.len() {
+        for left in groupings(&numbers[..split], &ops[..split - 1]) {
+            for right in groupings(&numbers[split..], &ops[split..]) {
+                if let Ok(value) = Arithmetic::apply_binary(ops[split - 1], &left, &right) {
+                    values.push(value);
+                }
             }
         }
-        Ok(node)
     }
+    values
+}
 
-    fn parse_factor(&mut self) -> Result<Expr, String> {
-        self.skip_ws();
-        if self.consume(b'-') {
-            let inner = self.parse_factor()?;
-            return Ok(Expr {
-                kind: ExprKind::Neg(Box::new(inner)),
-            });
+/// Four numbers, the three operators between them and a target that only
+/// parentheses reach: without any, the line makes something else.
+fn generate_parens(rng: &mut SimpleRng) -> (Vec<i64>, Vec<BinaryOp>, i64) {
+    for _ in 0..100 {
+        let numbers: Vec<i64> = (0..4).map(|_| rng.gen_range_i64(1, 9)).collect();
+        let ops: Vec<BinaryOp> = (0..3)
+            .map(|_| BUILDER_OPS[rng.gen_range_i64(0, BUILDER_OPS.len() as i64 - 1) as usize])
+            .collect();
+        let mut text = numbers[0].to_string();
+        for (op, number) in ops.iter().zip(&numbers[1..]) {
+            text.push_str(&format!(" {} {number}", Arithmetic::binary_label(*op)));
         }
-        if self.consume(b'(') {
-            let inner = self.parse_sum()?;
-            self.skip_ws();
-            if !self.consume(b')') {
-                return Err(format!("Expected ')' at position {}", self.pos + 1));
-            }
-            return Ok(inner);
+        let Ok(plain) = parse_expression(&text).and_then(|expr| expr.eval()) else {
+            continue;
+        };
+        let mut targets = groupings(&numbers, &ops);
+        targets.retain(|value| *value != plain && (0..=99).contains(value));
+        if targets.is_empty() {
+            continue;
         }
-        self.parse_number()
+        let target = targets[rng.gen_range_i64(0, targets.len() as i64 - 1) as usize];
+        return (numbers, ops, target);
     }
+    (
+        vec![2, 3, 4, 1],
+        vec![BinaryOp::Add, BinaryOp::Mul, BinaryOp::Sub],
+        19,
+    )
+}
 
-    fn parse_number(&mut self) -> Result<Expr, String> {
-        self.skip_ws();
-        let start = self.pos;
-        let mut value: i64 = 0;
-        while let Some(byte) = self.peek() {
-            if !byte.is_ascii_digit() {
-                break;
-            }
-            self.pos += 1;
-            let digit = (byte - b'0') as i64;
-            value = value
-                .checked_mul(10)
-                .and_then(|v| v.checked_add(digit))
-                .ok_or_else(|| "Number too large".to_string())?;
-        }
-        if self.pos == start {
-            return Err(format!("Expected a number at position {}", self.pos + 1));
-        }
-        Ok(Expr::num(value))
+/// Operators the "Swap and regroup" card explores: two that do not mind
+/// being swapped or regrouped, and one that does.
+const SWAP_OPS: [(BinaryOp, &str); 3] = [
+    (BinaryOp::Add, "+"),
+    (BinaryOp::Mul, "*"),
+    (BinaryOp::Sub, "-"),
+];
+
+/// `(a op b) op c`, rearranged by the learner one swap or regroup at a time.
+struct SwapState {
+    rng: SimpleRng,
+    op: BinaryOp,
+    expr: Expr,
+    /// The value before any rearranging.
+    original: i64,
+    /// The code before each move, oldest first.
+    history: Vec<String>,
+}
+
+impl Default for SwapState {
+    fn default() -> Self {
+        let mut state = Self {
+            rng: SimpleRng::new(rng::fresh_seed()),
+            op: BinaryOp::Add,
+            expr: Expr::value(0),
+            original: 0,
+            history: Vec::new(),
+        };
+        state.regenerate();
+        state
     }
+}
 
-    fn skip_ws(&mut self) {
-        while let Some(byte) = self.peek() {
-            if !byte.is_ascii_whitespace() {
-                break;
+impl SwapState {
+    /// Three different numbers, so that every swap of a subtraction shows.
+    fn regenerate(&mut self) {
+        let mut numbers: Vec<i64> = Vec::new();
+        while numbers.len() < 3 {
+            let number = self.rng.gen_range_i64(1, 9);
+            if !numbers.contains(&number) {
+                numbers.push(number);
             }
-            self.pos += 1;
         }
+        let number = |index: usize| Expr::value(numbers[index]);
+        self.expr = Expr::binary(
+            self.op,
+            Expr::binary(self.op, number(0), number(1)),
+            number(2),
+        );
+        self.original = self.expr.eval().unwrap_or_default();
+        self.history.clear();
     }
 
-    fn peek(&self) -> Option<u8> {
-        self.input.get(self.pos).copied()
+    fn apply(&mut self, expr: Expr) {
+        self.history.push(expr_to_string(&self.expr));
+        self.expr = expr;
     }
+}
 
-    fn consume(&mut self, byte: u8) -> bool {
-        if self.peek() == Some(byte) {
-            self.pos += 1;
-            true
-        } else {
-            false
+/// `expr` with the two sides of the operator at `path` swapped.
+fn swap_at(expr: Expr, path: &[PathStep]) -> Expr {
+    match (expr, path.split_first()) {
+        (Expr::Binary(op, left, right), None) => Expr::Binary(op, right, left),
+        (Expr::Binary(op, left, right), Some((PathStep::Left, rest))) => {
+            Expr::binary(op, swap_at(*left, rest), *right)
+        }
+        (Expr::Binary(op, left, right), Some((PathStep::Right, rest))) => {
+            Expr::binary(op, *left, swap_at(*right, rest))
         }
+        (Expr::Unary(op, inner), Some((PathStep::Unary, rest))) => {
+            Expr::unary(op, swap_at(*inner, rest))
+        }
+        (expr, _) => expr,
     }
 }
 
-fn parse_expression(input: &str) -> Result<Expr, String> {
-    let mut parser = Parser::new(input);
-    parser.parse_expression()
-}
-
-fn as_num(expr: &Expr) -> Option<i64> {
-    match expr.kind {
-        ExprKind::Num(value) => Some(value),
+/// Moves the parentheses of `(a op b) op c` over to `a op (b op c)`, or
+/// back. `None` when the tree has neither shape.
+fn regroup(expr: &Expr) -> Option<Expr> {
+    let Expr::Binary(op, left, right) = expr else {
+        return None;
+    };
+    match (&**left, &**right) {
+        (Expr::Binary(inner, a, b), c) if inner == op => Some(Expr::binary(
+            *op,
+            (**a).clone(),
+            Expr::binary(*op, (**b).clone(), c.clone()),
+        )),
+        (a, Expr::Binary(inner, b, c)) if inner == op => Some(Expr::binary(
+            *op,
+            Expr::binary(*op, a.clone(), (**b).clone()),
+            (**c).clone(),
+        )),
         _ => None,
     }
 }
 
-fn is_reducible(expr: &Expr) -> bool {
-    match &expr.kind {
-        ExprKind::Num(_) => false,
-        ExprKind::Neg(inner) => as_num(inner).is_some(),
-        ExprKind::Add(left, right) | ExprKind::Sub(left, right) | ExprKind::Mul(left, right) => {
-            as_num(left).is_some() && as_num(right).is_some()
-        }
+impl Exercise for Practice {
+    type Answer = i64;
+
+    fn generate(rng: &mut SimpleRng, difficulty: Difficulty) -> Self {
+        generate_exercise(rng, difficulty)
     }
-}
 
-fn eval_reducible(expr: &Expr) -> Result<i64, String> {
-    match &expr.kind {
-        ExprKind::Num(value) => Ok(*value),
-        ExprKind::Neg(inner) => {
-            let value = as_num(inner).ok_or_else(|| "Expected a number".to_string())?;
-            value.checked_neg().ok_or_else(|| "Overflow".to_string())
-        }
-        ExprKind::Add(left, right) => {
-            let left = as_num(left).ok_or_else(|| "Expected a number".to_string())?;
-            let right = as_num(right).ok_or_else(|| "Expected a number".to_string())?;
-            left.checked_add(right)
-                .ok_or_else(|| "Overflow".to_string())
-        }
-        ExprKind::Sub(left, right) => {
-            let left = as_num(left).ok_or_else(|| "Expected a number".to_string())?;
-            let right = as_num(right).ok_or_else(|| "Expected a number".to_string())?;
-            left.checked_sub(right)
-                .ok_or_else(|| "Overflow".to_string())
-        }
-        ExprKind::Mul(left, right) => {
-            let left = as_num(left).ok_or_else(|| "Expected a number".to_string())?;
-            let right = as_num(right).ok_or_else(|| "Expected a number".to_string())?;
-            left.checked_mul(right)
-                .ok_or_else(|| "Overflow".to_string())
-        }
+    /// Practice drawn for the card: the flag of `seed` switches the unary
+    /// minus off, and the hardest level allows answers below zero.
+    fn from_seed(rng: &mut SimpleRng, seed: u64) -> Self {
+        let difficulty = Difficulty::of_seed(seed);
+        let negation = !Difficulty::flag_of_seed(seed);
+        generate_practice(rng, difficulty, negation, practice_answers(difficulty))
     }
-}
 
-fn find_reducible(expr: &Expr) -> Option<Vec<PathStep>> {
-    match &expr.kind {
-        ExprKind::Num(_) => None,
-        ExprKind::Neg(inner) => find_reducible(inner)
-            .map(|mut path| {
-                path.insert(0, PathStep::Unary);
-                path
-            })
-            .or_else(|| {
-                if is_reducible(expr) {
-                    Some(Vec::new())
-                } else {
-                    None
-                }
-            }),
-        ExprKind::Add(left, right) | ExprKind::Sub(left, right) | ExprKind::Mul(left, right) => {
-            find_reducible(left)
-                .map(|mut path| {
-                    path.insert(0, PathStep::Left);
-                    path
-                })
-                .or_else(|| {
-                    find_reducible(right).map(|mut path| {
-                        path.insert(0, PathStep::Right);
-                        path
-                    })
-                })
-                .or_else(|| {
-                    if is_reducible(expr) {
-                        Some(Vec::new())
-                    } else {
-                        None
-                    }
-                })
-        }
+    fn prompt(&self) -> String {
+        "What is the value of this expression?".to_string()
     }
-}
 
-fn reduce_at(expr: Expr, path: &[PathStep]) -> Result<Expr, String> {
-    if path.is_empty() {
-        return Ok(Expr::num(eval_reducible(&expr)?));
-    }
-
-    let (head, tail) = path.split_first().ok_or("Invalid path")?;
-    match (head, expr.kind) {
-        (PathStep::Unary, ExprKind::Neg(inner)) => Ok(Expr {
-            kind: ExprKind::Neg(Box::new(reduce_at(*inner, tail)?)),
-        }),
-        (PathStep::Left, ExprKind::Add(left, right)) => Ok(Expr {
-            kind: ExprKind::Add(Box::new(reduce_at(*left, tail)?), right),
-        }),
-        (PathStep::Right, ExprKind::Add(left, right)) => Ok(Expr {
-            kind: ExprKind::Add(left, Box::new(reduce_at(*right, tail)?)),
-        }),
-        (PathStep::Left, ExprKind::Sub(left, right)) => Ok(Expr {
-            kind: ExprKind::Sub(Box::new(reduce_at(*left, tail)?), right),
-        }),
-        (PathStep::Right, ExprKind::Sub(left, right)) => Ok(Expr {
-            kind: ExprKind::Sub(left, Box::new(reduce_at(*right, tail)?)),
-        }),
-        (PathStep::Left, ExprKind::Mul(left, right)) => Ok(Expr {
-            kind: ExprKind::Mul(Box::new(reduce_at(*left, tail)?), right),
-        }),
-        (PathStep::Right, ExprKind::Mul(left, right)) => Ok(Expr {
-            kind: ExprKind::Mul(left, Box::new(reduce_at(*right, tail)?)),
-        }),
-        _ => Err("Invalid reduction path".to_string()),
-    }
-}
-
-fn expr_at_path<'a>(expr: &'a Expr, path: &[PathStep]) -> Option<&'a Expr> {
-    if path.is_empty() {
-        return Some(expr);
-    }
-    let (head, tail) = path.split_first()?;
-    match (head, &expr.kind) {
-        (PathStep::Unary, ExprKind::Neg(inner)) => expr_at_path(inner, tail),
-        (PathStep::Left, ExprKind::Add(left, _))
-        | (PathStep::Left, ExprKind::Sub(left, _))
-        | (PathStep::Left, ExprKind::Mul(left, _)) => expr_at_path(left, tail),
-        (PathStep::Right, ExprKind::Add(_, right))
-        | (PathStep::Right, ExprKind::Sub(_, right))
-        | (PathStep::Right, ExprKind::Mul(_, right)) => expr_at_path(right, tail),
-        _ => None,
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::line(&expr_to_string(&self.expr)));
     }
-}
 
-fn build_steps(expr: Expr) -> Result<Vec<Step>, String> {
-    let mut steps = Vec::new();
-    let mut current = expr;
-    loop {
-        if let Some(path) = find_reducible(&current) {
-            steps.push(Step {
-                expr: current.clone(),
-                highlight: Some(path.clone()),
-            });
-            current = reduce_at(current, &path)?;
-        } else {
-            steps.push(Step {
-                expr: current.clone(),
-                highlight: None,
-            });
-            break;
+    fn text(&self) -> String {
+        format!("```text\n{}\n```", expr_to_string(&self.expr))
+    }
+
+    fn answer(&self) -> i64 {
+        self.answer
+    }
+
+    /// Values close to the answer, below zero only if the answer is.
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<i64> {
+        let mut distractors = Vec::new();
+        while distractors.len() < 3 {
+            let delta = rng.gen_range_i64(-5, 5);
+            if delta == 0 {
+                continue;
+            }
+            let candidate = self.answer + delta;
+            if candidate < 0 && self.answer >= 0 {
+                continue;
+            }
+            if !distractors.contains(&candidate) {
+                distractors.push(candidate);
+            }
         }
+        distractors
     }
-    Ok(steps)
-}
-
-fn render_expr_with_highlight(
-    expr: &Expr,
-    highlight: Option<&[PathStep]>,
-) -> (String, Vec<Range<usize>>) {
-    let mut text = String::new();
-    let mut highlight_range = None;
-    let highlight_enabled = highlight.is_some();
-    render_expr(
-        expr,
-        highlight.unwrap_or(&[]),
-        highlight_enabled,
-        &mut text,
-        &mut highlight_range,
-    );
-    let ranges = highlight_range.into_iter().collect();
-    (text, ranges)
-}
-
-fn render_expr(
-    expr: &Expr,
-    highlight_path: &[PathStep],
-    highlight_enabled: bool,
-    out: &mut String,
-    highlight_range: &mut Option<Range<usize>>,
-) {
-    let start = out.len();
-    match &expr.kind {
-        ExprKind::Num(value) => {
-            out.push_str(&value.to_string());
-        }
-        ExprKind::Neg(inner) => {
-            out.push_str("(-");
-            let (child_path, child_highlight): (&[PathStep], bool) =
-                match highlight_path.split_first() {
-                    Some((PathStep::Unary, rest)) => (rest, highlight_enabled),
-                    _ => (&[], false),
-                };
-            render_expr(inner, child_path, child_highlight, out, highlight_range);
-            out.push(')');
-        }
-        ExprKind::Add(left, right) => {
-            out.push('(');
-            let (left_path, left_highlight, right_path, right_highlight): (
-                &[PathStep],
-                bool,
-                &[PathStep],
-                bool,
-            ) = match highlight_path.split_first() {
-                Some((PathStep::Left, rest)) => (rest, highlight_enabled, &[], false),
-                Some((PathStep::Right, rest)) => (&[], false, rest, highlight_enabled),
-                _ => (&[], false, &[], false),
-            };
-            render_expr(left, left_path, left_highlight, out, highlight_range);
-            out.push_str(" + ");
-            render_expr(right, right_path, right_highlight, out, highlight_range);
-            out.push(')');
+
+    fn choice_label(answer: &i64) -> String {
+        answer.to_string()
+    }
+
+    fn hint(&self, _picked: &i64) -> String {
+        "Try another answer or generate a new one.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, _correct: bool) {
+        ui.add_space(4.0);
+        expr::worked_solution(ui, &mut self.step, &self.expr);
+    }
+}
+
+fn generate_exercise(rng: &mut SimpleRng, difficulty: Difficulty) -> Practice {
+    generate_practice(rng, difficulty, true, 0..=99)
+}
+
+/// An exercise whose value lies in `answers`, with a unary minus only if
+/// `negation` allows it.
+fn generate_practice(
+    rng: &mut SimpleRng,
+    difficulty: Difficulty,
+    negation: bool,
+    answers: RangeInclusive<i64>,
+) -> Practice {
+    for _ in 0..200 {
+        let expr = random_expr(rng, 0, difficulty, negation);
+        if expr.is_value() {
+            continue;
         }
-        ExprKind::Sub(left, right) => {
-            out.push('(');
-            let (left_path, left_highlight, right_path, right_highlight): (
-                &[PathStep],
-                bool,
-                &[PathStep],
-                bool,
-            ) = match highlight_path.split_first() {
-                Some((PathStep::Left, rest)) => (rest, highlight_enabled, &[], false),
-                Some((PathStep::Right, rest)) => (&[], false, rest, highlight_enabled),
-                _ => (&[], false, &[], false),
-            };
-            render_expr(left, left_path, left_highlight, out, highlight_range);
-            out.push_str(" - ");
-            render_expr(right, right_path, right_highlight, out, highlight_range);
-            out.push(')');
+        if expr.count_ops() < 2 {
+            continue;
         }
-        ExprKind::Mul(left, right) => {
-            out.push('(');
-            let (left_path, left_highlight, right_path, right_highlight): (
-                &[PathStep],
-                bool,
-                &[PathStep],
-                bool,
-            ) = match highlight_path.split_first() {
-                Some((PathStep::Left, rest)) => (rest, highlight_enabled, &[], false),
-                Some((PathStep::Right, rest)) => (&[], false, rest, highlight_enabled),
-                _ => (&[], false, &[], false),
-            };
-            render_expr(left, left_path, left_highlight, out, highlight_range);
-            out.push_str(" * ");
-            render_expr(right, right_path, right_highlight, out, highlight_range);
-            out.push(')');
+        if let Ok(answer) = expr.eval() {
+            if answers.contains(&answer) {
+                return Practice {
+                    expr,
+                    answer,
+                    step: 0,
+                };
+            }
         }
     }
-    let end = out.len();
-    if highlight_enabled && highlight_path.is_empty() {
-        *highlight_range = Some(start..end);
+    Practice {
+        expr: Expr::binary(
+            BinaryOp::Add,
+            Expr::binary(BinaryOp::Mul, Expr::value(2), Expr::value(3)),
+            Expr::value(1),
+        ),
+        answer: 7,
+        step: 0,
     }
 }
 
-fn path_in_subtree(path: &[PathStep], subtree: &[PathStep]) -> bool {
-    path.len() >= subtree.len() && path[..subtree.len()] == *subtree
+/// An expression to work out on paper, with its value for the answer key.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<Practice>(rng, difficulty)
 }
 
-struct NodeDraw {
-    label: String,
-    depth: usize,
-    x: i32,
-    highlight: bool,
-    children: Vec<usize>,
-    path: Vec<PathStep>,
+/// One round of `--self-check`: the choices of a practice exercise, a
+/// practice exercise whose answer must be its value, and a tree exercise.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<Practice>(rng, difficulty)?;
+    let exercise = generate_exercise(rng, difficulty);
+    let value = expr::self_check(&exercise.expr, parse_expression)?;
+    if value != exercise.answer {
+        return Err(format!(
+            "`{}` is {value}, but the card expects {}",
+            expr_to_string(&exercise.expr),
+            exercise.answer
+        ));
+    }
+    expr::self_check(&generate_tree_expr(rng, difficulty), parse_expression)?;
+    Ok(())
 }
 
-struct NodeLayout {
-    rect: egui::Rect,
-    label: String,
-    highlight: bool,
-    children: Vec<usize>,
-    path: Vec<PathStep>,
+fn random_expr(rng: &mut SimpleRng, depth: u8, difficulty: Difficulty, negation: bool) -> Expr {
+    let use_number = depth >= difficulty.max_depth() || rng.gen_range_i64(0, 4) == 0;
+    if use_number {
+        let value = rng.gen_range_i64(1, difficulty.max_value() as i64);
+        return Expr::value(value);
+    }
+
+    // The first level only adds and subtracts.
+    let roll = if difficulty.harder_ops() {
+        rng.gen_range_i64(0, 5)
+    } else {
+        rng.gen_range_i64(0, 1)
+    };
+    if roll == 3 && negation {
+        let inner = random_expr(rng, depth + 1, difficulty, negation);
+        return Expr::unary(UnaryOp::Neg, inner);
+    }
+    // Small exponents only, or hardly any power stays in range.
+    if roll == 5 {
+        let base = random_expr(rng, depth + 1, difficulty, negation);
+        let exponent = Expr::value(rng.gen_range_i64(2, 3));
+        return Expr::binary(BinaryOp::Pow, base, exponent);
+    }
+
+    let left = random_expr(rng, depth + 1, difficulty, negation);
+    let right = random_expr(rng, depth + 1, difficulty, negation);
+    let op = match roll {
+        0 => BinaryOp::Add,
+        1 => BinaryOp::Sub,
+        _ => BinaryOp::Mul,
+    };
+    Expr::binary(op, left, right)
 }
 
-fn build_nodes(
-    expr: &Expr,
-    depth: usize,
-    path: &mut Vec<PathStep>,
-    highlight_path: Option<&[PathStep]>,
-    nodes: &mut Vec<NodeDraw>,
-    next_leaf_x: &mut i32,
-) -> usize {
-    let highlight = highlight_path.map_or(false, |sub| path_in_subtree(path, sub));
-    let (label, children, x) = match &expr.kind {
-        ExprKind::Num(value) => {
-            let x = *next_leaf_x;
-            *next_leaf_x += 1;
-            (value.to_string(), Vec::new(), x)
+fn generate_tree_expr(rng: &mut SimpleRng, difficulty: Difficulty) -> Expr {
+    for _ in 0..120 {
+        let expr = random_expr(rng, 0, difficulty, true);
+        if expr.is_value() {
+            continue;
+        }
+        if let Ok(value) = expr.eval() {
+            if (-50..=50).contains(&value) {
+                return expr;
+            }
         }
-        ExprKind::Neg(inner) => {
-            path.push(PathStep::Unary);
-            let child = build_nodes(inner, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            let x = nodes[child].x;
-            ("-".to_string(), vec![child], x)
+    }
+    Expr::value(1)
+}
+
+struct ExpressionState {
+    input: String,
+    step: usize,
+    rng: SimpleRng,
+    postfix: bool,
+    postfix_step: usize,
+    play: Autoplay,
+    postfix_play: Autoplay,
+    machine: bool,
+}
+
+impl Default for ExpressionState {
+    fn default() -> Self {
+        Self {
+            input: "(3 * 2) + 2".to_string(),
+            step: 0,
+            rng: SimpleRng::new(rng::seed_from_time()),
+            postfix: false,
+            postfix_step: 0,
+            play: Autoplay::default(),
+            postfix_play: Autoplay::default(),
+            machine: false,
         }
-        ExprKind::Add(left, right) => {
-            path.push(PathStep::Left);
-            let left_idx = build_nodes(left, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            path.push(PathStep::Right);
-            let right_idx = build_nodes(right, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            let x = (nodes[left_idx].x + nodes[right_idx].x) / 2;
-            ("+".to_string(), vec![left_idx, right_idx], x)
+    }
+}
+
+/// Ends the error for a result too big or too small for an `i64`.
+const OVERFLOW: &str = "does not fit in the 64 bits a number gets, so there is no answer. \
+    The card \"At the edge of the numbers\" shows what the processor does instead.";
+
+struct Arithmetic;
+
+#[derive(Clone, Copy)]
+enum UnaryOp {
+    Neg,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    /// Whole-number division, dropping the remainder: `7 / 2` is `3`.
+    Div,
+    /// What is left over after the division: `7 % 2` is `1`.
+    Rem,
+    /// Repeated multiplication: `2 ^ 3` is `2 * 2 * 2`.
+    Pow,
+}
+
+impl Language for Arithmetic {
+    type Value = i64;
+    type Unary = UnaryOp;
+    type Binary = BinaryOp;
+
+    fn value_text(value: &i64) -> String {
+        value.to_string()
+    }
+
+    fn unary_text(op: UnaryOp) -> (&'static str, &'static str) {
+        match op {
+            UnaryOp::Neg => ("(-", ")"),
         }
-        ExprKind::Sub(left, right) => {
-            path.push(PathStep::Left);
-            let left_idx = build_nodes(left, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            path.push(PathStep::Right);
-            let right_idx = build_nodes(right, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            let x = (nodes[left_idx].x + nodes[right_idx].x) / 2;
-            ("-".to_string(), vec![left_idx, right_idx], x)
+    }
+
+    fn unary_label(op: UnaryOp) -> &'static str {
+        match op {
+            UnaryOp::Neg => "-",
         }
-        ExprKind::Mul(left, right) => {
-            path.push(PathStep::Left);
-            let left_idx = build_nodes(left, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            path.push(PathStep::Right);
-            let right_idx = build_nodes(right, depth + 1, path, highlight_path, nodes, next_leaf_x);
-            path.pop();
-            let x = (nodes[left_idx].x + nodes[right_idx].x) / 2;
-            ("*".to_string(), vec![left_idx, right_idx], x)
+    }
+
+    fn binary_label(op: BinaryOp) -> &'static str {
+        match op {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Pow => "^",
         }
-    };
+    }
 
-    let index = nodes.len();
-    nodes.push(NodeDraw {
-        label,
-        depth,
-        x,
-        highlight,
-        children,
-        path: path.clone(),
-    });
-    index
-}
-
-fn build_tree_layout(
-    ui: &egui::Ui,
-    expr: &Expr,
-    highlight_path: Option<&[PathStep]>,
-) -> (Vec<NodeLayout>, egui::Vec2, egui::FontId) {
-    let mut nodes = Vec::new();
-    let mut next_leaf_x = 0;
-    let mut path = Vec::new();
-    let _root = build_nodes(
-        expr,
-        0,
-        &mut path,
-        highlight_path,
-        &mut nodes,
-        &mut next_leaf_x,
-    );
+    fn describe_unary(op: UnaryOp, value: &i64) -> String {
+        match op {
+            UnaryOp::Neg => format!("Flip the sign of {value}"),
+        }
+    }
 
-    let max_label_len = nodes
-        .iter()
-        .map(|node| node.label.chars().count())
-        .max()
-        .unwrap_or(1);
-    let min_x = nodes.iter().map(|node| node.x).min().unwrap_or(0);
-    let max_x = nodes.iter().map(|node| node.x).max().unwrap_or(0);
-    let max_depth = nodes.iter().map(|node| node.depth).max().unwrap_or(0);
-
-    let font_id = TextStyle::Monospace.resolve(ui.style());
-    let (char_width, row_height) = ui.fonts_mut(|fonts| {
-        let width = fonts.glyph_width(&font_id, '0');
-        let height = fonts.row_height(&font_id);
-        (width.max(1.0), height.max(1.0))
-    });
-    let node_padding = egui::vec2((char_width * 0.6).max(4.0), (row_height * 0.2).max(2.0));
-    let node_width = max_label_len as f32 * char_width + node_padding.x * 2.0;
-    let node_height = row_height + node_padding.y * 2.0;
-    let col_gap = (char_width * 2.0).max(8.0);
-    let row_gap = (row_height * 0.8).max(8.0);
-    let col_spacing = node_width + col_gap;
-    let row_spacing = node_height + row_gap;
-
-    let layout_width = node_width + (max_x - min_x) as f32 * col_spacing;
-    let layout_height = node_height + max_depth as f32 * row_spacing;
-
-    let mut layouts = Vec::with_capacity(nodes.len());
-    for node in &nodes {
-        let x_center = node_width / 2.0 + (node.x - min_x) as f32 * col_spacing;
-        let y_center = node_height / 2.0 + node.depth as f32 * row_spacing;
-        let rect = egui::Rect::from_center_size(
-            egui::pos2(x_center, y_center),
-            egui::vec2(node_width, node_height),
-        );
-        layouts.push(NodeLayout {
-            rect,
-            label: node.label.clone(),
-            highlight: node.highlight,
-            children: node.children.clone(),
-            path: node.path.clone(),
-        });
+    fn describe_binary(op: BinaryOp, left: &i64, right: &i64) -> String {
+        match op {
+            BinaryOp::Add => format!("Add {left} and {right}"),
+            BinaryOp::Sub => format!("Subtract {right} from {left}"),
+            BinaryOp::Mul => format!("Multiply {left} by {right}"),
+            BinaryOp::Div => format!("Divide {left} by {right}"),
+            BinaryOp::Rem => format!("Take the remainder of {left} divided by {right}"),
+            BinaryOp::Pow => format!("Raise {left} to the power of {right}"),
+        }
     }
 
-    (layouts, egui::vec2(layout_width, layout_height), font_id)
-}
+    fn binary_name(op: BinaryOp) -> &'static str {
+        match op {
+            BinaryOp::Add => "addition",
+            BinaryOp::Sub => "subtraction",
+            BinaryOp::Mul => "multiplication",
+            BinaryOp::Div => "division",
+            BinaryOp::Rem => "the remainder",
+            BinaryOp::Pow => "a power",
+        }
+    }
 
-fn code_frame(ui: &mut egui::Ui, job: LayoutJob) {
-    let bg = ui.visuals().code_bg_color;
-    let stroke = ui.visuals().widgets.inactive.bg_stroke;
-    egui::Frame::group(ui.style())
-        .fill(bg)
-        .stroke(stroke)
-        .inner_margin(egui::Margin::same(8))
-        .corner_radius(10.0)
-        .show(ui, |ui| {
-            ui.label(job);
-        });
-}
+    fn precedence(op: BinaryOp) -> u8 {
+        match op {
+            BinaryOp::Add | BinaryOp::Sub => 1,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 2,
+            BinaryOp::Pow => 3,
+        }
+    }
 
-fn highlight_formats(ui: &egui::Ui) -> (egui::TextFormat, egui::TextFormat) {
-    let font = TextStyle::Monospace.resolve(ui.style());
-    let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
-    let highlight = egui::TextFormat::simple(font, GORBIE::themes::ral(2009));
-    (normal, highlight)
-}
+    fn unary_instruction(op: UnaryOp) -> &'static str {
+        match op {
+            UnaryOp::Neg => "neg",
+        }
+    }
 
-fn append_highlighted_line(
-    job: &mut LayoutJob,
-    line: &str,
-    ranges: &[Range<usize>],
-    normal: &egui::TextFormat,
-    highlight: &egui::TextFormat,
-) {
-    let mut cursor = 0;
-    for range in ranges {
-        let start = range.start.min(line.len());
-        let end = range.end.min(line.len());
-        if start > cursor {
-            job.append(&line[cursor..start], 0.0, normal.clone());
+    fn binary_instruction(op: BinaryOp) -> &'static str {
+        match op {
+            BinaryOp::Add => "add",
+            BinaryOp::Sub => "sub",
+            BinaryOp::Mul => "mul",
+            BinaryOp::Div => "div",
+            BinaryOp::Rem => "rem",
+            BinaryOp::Pow => "pow",
         }
-        if end > start {
-            job.append(&line[start..end], 0.0, highlight.clone());
+    }
+
+    fn apply_unary(op: UnaryOp, value: &i64) -> Result<i64, String> {
+        match op {
+            UnaryOp::Neg => value.checked_neg(),
         }
-        cursor = end;
+        .ok_or_else(|| format!("-({value}) {OVERFLOW}"))
     }
-    if cursor < line.len() {
-        job.append(&line[cursor..], 0.0, normal.clone());
+
+    fn apply_binary(op: BinaryOp, left: &i64, right: &i64) -> Result<i64, String> {
+        if matches!(op, BinaryOp::Div | BinaryOp::Rem) && *right == 0 {
+            return Err(format!(
+                "There is no way to share {left} into 0 equal groups, so dividing by zero has no answer, and no remainder either."
+            ));
+        }
+        match op {
+            BinaryOp::Add => left.checked_add(*right),
+            BinaryOp::Sub => left.checked_sub(*right),
+            BinaryOp::Mul => left.checked_mul(*right),
+            BinaryOp::Div => left.checked_div(*right),
+            BinaryOp::Rem => left.checked_rem(*right),
+            BinaryOp::Pow => {
+                let Ok(exponent) = u32::try_from(*right) else {
+                    return Err(format!(
+                        "{left} ^ {right} would be a fraction, and this stepper only knows whole numbers."
+                    ));
+                };
+                left.checked_pow(exponent)
+            }
+        }
+        .ok_or_else(|| format!("{left} {} {right} {OVERFLOW}", Self::binary_label(op)))
     }
 }
 
-fn highlighted_job(ui: &egui::Ui, line: &str, ranges: &[Range<usize>]) -> LayoutJob {
-    let (normal, highlight) = highlight_formats(ui);
-    let mut job = LayoutJob::default();
-    append_highlighted_line(&mut job, line, ranges, &normal, &highlight);
-    job
+type Expr = expr::Expr<Arithmetic>;
+
+struct Parser<'a> {
+    scan: Scanner<'a>,
 }
 
-fn draw_tree(ui: &mut egui::Ui, expr: &Expr, highlight_path: Option<&[PathStep]>) {
-    let (mut layouts, desired, font_id) = build_tree_layout(ui, expr, highlight_path);
-    let (rect, _response) = ui.allocate_at_least(desired, egui::Sense::hover());
-    let mut origin = rect.min;
-    if rect.width() > desired.x {
-        origin.x += (rect.width() - desired.x) / 2.0;
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            scan: Scanner::new(input),
+        }
     }
-    if rect.height() > desired.y {
-        origin.y += (rect.height() - desired.y) / 2.0;
+
+    fn parse_expression(&mut self) -> Result<Expr, String> {
+        let expr = self.parse_sum()?;
+        self.scan.skip_ws();
+        if !self.scan.at_end() {
+            return Err(format!(
+                "Unexpected input at position {}",
+                self.scan.pos() + 1
+            ));
+        }
+        Ok(expr)
     }
 
-    for layout in &mut layouts {
-        layout.rect = layout.rect.translate(origin.to_vec2());
+    /// Names given values first, each like `x <- 4;` or `x ← 4;` as in
+    /// the state chapter, then the expression to step through.
+    fn parse_program(&mut self) -> Result<(Vec<(String, Expr)>, Expr), String> {
+        let mut bindings = Vec::new();
+        loop {
+            let expr = self.parse_sum()?;
+            self.scan.skip_ws();
+            let arrow_at = self.scan.pos();
+            if !self.scan.consume_bytes(b"<-") && !self.scan.consume_bytes("\u{2190}".as_bytes()) {
+                if !self.scan.at_end() {
+                    return Err(format!(
+                        "Unexpected input at position {}",
+                        self.scan.pos() + 1
+                    ));
+                }
+                return Ok((bindings, expr));
+            }
+            let Expr::Var(name) = expr else {
+                return Err(format!(
+                    "Only a name can get a value, not the part before position {}",
+                    arrow_at + 1
+                ));
+            };
+            let value = self.parse_sum()?;
+            self.scan.skip_ws();
+            if !self.scan.consume_bytes(b";") {
+                return Err(format!("Expected ';' at position {}", self.scan.pos() + 1));
+            }
+            bindings.push((name, value));
+        }
     }
 
-    let highlight_color = GORBIE::themes::ral(2009);
-    let line_color = ui.visuals().widgets.inactive.bg_stroke.color;
-    let line_width = ui.visuals().widgets.inactive.bg_stroke.width.max(1.0);
-    let line_stroke = |highlight| {
-        egui::Stroke::new(
-            line_width,
-            if highlight {
-                highlight_color
+    fn parse_sum(&mut self) -> Result<Expr, String> {
+        let mut node = self.parse_product()?;
+        loop {
+            self.scan.skip_ws();
+            if self.scan.consume_bytes(b"+") {
+                let right = self.parse_product()?;
+                node = Expr::binary(BinaryOp::Add, node, right);
+            } else if self.scan.consume_bytes(b"-") {
+                let right = self.parse_product()?;
+                node = Expr::binary(BinaryOp::Sub, node, right);
             } else {
-                line_color
-            },
-        )
-    };
-    let text_color = ui.visuals().text_color();
-    let painter = ui.painter();
-
-    for layout in &layouts {
-        for child_idx in &layout.children {
-            let child = &layouts[*child_idx];
-            let highlight = layout.highlight && child.highlight;
-            let stroke = line_stroke(highlight);
-            let start = layout.rect.center_bottom() + egui::vec2(0.0, stroke.width / 2.0);
-            let end = child.rect.center_top() - egui::vec2(0.0, stroke.width / 2.0);
-            let mid_y = (start.y + end.y) / 2.0;
-            let points = vec![
-                start,
-                egui::pos2(start.x, mid_y),
-                egui::pos2(end.x, mid_y),
-                end,
-            ];
-            painter.add(egui::Shape::line(points, stroke));
+                break;
+            }
         }
-        let stroke = line_stroke(layout.highlight);
-        painter.rect(
-            layout.rect,
-            egui::CornerRadius::same(4),
-            ui.visuals().code_bg_color,
-            stroke,
-            egui::StrokeKind::Inside,
-        );
-        let color = if layout.highlight {
-            highlight_color
-        } else {
-            text_color
-        };
-        let galley = ui
-            .fonts_mut(|fonts| fonts.layout_no_wrap(layout.label.clone(), font_id.clone(), color));
-        let text_pos = layout.rect.center() - galley.size() / 2.0;
-        painter.galley(text_pos, galley, text_color);
+        Ok(node)
     }
-}
 
-fn draw_tree_interactive(
-    ui: &mut egui::Ui,
-    expr: &Expr,
-    next_path: Option<&[PathStep]>,
-) -> Option<Vec<PathStep>> {
-    let (mut layouts, desired, font_id) = build_tree_layout(ui, expr, None);
-    for layout in &mut layouts {
-        layout.highlight = next_path.map_or(false, |path| path == layout.path);
+    fn parse_product(&mut self) -> Result<Expr, String> {
+        let mut node = self.parse_factor()?;
+        loop {
+            self.scan.skip_ws();
+            if self.scan.consume_bytes(b"*") {
+                let right = self.parse_factor()?;
+                node = Expr::binary(BinaryOp::Mul, node, right);
+            } else if self.scan.consume_bytes(b"/") {
+                let right = self.parse_factor()?;
+                node = Expr::binary(BinaryOp::Div, node, right);
+            } else if self.scan.consume_bytes(b"%") {
+                let right = self.parse_factor()?;
+                node = Expr::binary(BinaryOp::Rem, node, right);
+            } else {
+                break;
+            }
+        }
+        Ok(node)
     }
 
-    let (rect, _response) = ui.allocate_at_least(desired, egui::Sense::hover());
-    let mut origin = rect.min;
-    if rect.width() > desired.x {
-        origin.x += (rect.width() - desired.x) / 2.0;
+    /// A unary minus applies to the whole power after it, as in math:
+    /// `-2 ^ 2` is `-(2 ^ 2)`.
+    fn parse_factor(&mut self) -> Result<Expr, String> {
+        self.scan.skip_ws();
+        if self.scan.consume_bytes(b"-") {
+            let inner = self.parse_factor()?;
+            return Ok(Expr::unary(UnaryOp::Neg, inner));
+        }
+        self.parse_power()
     }
-    if rect.height() > desired.y {
-        origin.y += (rect.height() - desired.y) / 2.0;
+
+    /// Powers group from the right: `2 ^ 3 ^ 2` is `2 ^ (3 ^ 2)`.
+    fn parse_power(&mut self) -> Result<Expr, String> {
+        let base = self.parse_atom()?;
+        self.scan.skip_ws();
+        if self.scan.consume_bytes(b"^") {
+            let exponent = self.parse_factor()?;
+            return Ok(Expr::binary(BinaryOp::Pow, base, exponent));
+        }
+        Ok(base)
     }
 
-    for layout in &mut layouts {
-        layout.rect = layout.rect.translate(origin.to_vec2());
+    fn parse_atom(&mut self) -> Result<Expr, String> {
+        self.scan.skip_ws();
+        if self.scan.consume_bytes(b"(") {
+            let inner = self.parse_sum()?;
+            self.scan.skip_ws();
+            if !self.scan.consume_bytes(b")") {
+                return Err(format!("Expected ')' at position {}", self.scan.pos() + 1));
+            }
+            return Ok(inner);
+        }
+        if self
+            .scan
+            .peek()
+            .is_some_and(|byte| byte.is_ascii_alphabetic() || byte == b'_')
+        {
+            return Ok(self.parse_name());
+        }
+        self.parse_number()
     }
 
-    let highlight_color = GORBIE::themes::ral(2009);
-    let line_color = ui.visuals().widgets.inactive.bg_stroke.color;
-    let line_width = ui.visuals().widgets.inactive.bg_stroke.width.max(1.0);
-    let line_stroke = |highlight| {
-        egui::Stroke::new(
-            line_width,
-            if highlight {
-                highlight_color
-            } else {
-                line_color
-            },
-        )
-    };
-    let text_color = ui.visuals().text_color();
-    let painter = ui.painter();
-    let mut clicked = None;
-
-    for layout in &layouts {
-        for child_idx in &layout.children {
-            let child = &layouts[*child_idx];
-            let highlight = layout.highlight && child.highlight;
-            let stroke = line_stroke(highlight);
-            let start = layout.rect.center_bottom() + egui::vec2(0.0, stroke.width / 2.0);
-            let end = child.rect.center_top() - egui::vec2(0.0, stroke.width / 2.0);
-            let mid_y = (start.y + end.y) / 2.0;
-            let points = vec![
-                start,
-                egui::pos2(start.x, mid_y),
-                egui::pos2(end.x, mid_y),
-                end,
-            ];
-            painter.add(egui::Shape::line(points, stroke));
+    fn parse_name(&mut self) -> Expr {
+        let mut name = String::new();
+        while let Some(byte) = self.scan.peek() {
+            if !byte.is_ascii_alphanumeric() && byte != b'_' {
+                break;
+            }
+            self.scan.bump();
+            name.push(byte as char);
+        }
+        Expr::Var(name)
+    }
+
+    fn parse_number(&mut self) -> Result<Expr, String> {
+        self.scan.skip_ws();
+        let start = self.scan.pos();
+        let mut value: i64 = 0;
+        while let Some(byte) = self.scan.peek() {
+            if !byte.is_ascii_digit() {
+                break;
+            }
+            self.scan.bump();
+            let digit = (byte - b'0') as i64;
+            value = value
+                .checked_mul(10)
+                .and_then(|v| v.checked_add(digit))
+                .ok_or_else(|| "Number too large".to_string())?;
+        }
+        if self.scan.pos() == start {
+            return Err(format!(
+                "Expected a number at position {}",
+                self.scan.pos() + 1
+            ));
+        }
+        Ok(Expr::value(value))
+    }
+}
+
+fn parse_expression(input: &str) -> Result<Expr, String> {
+    let mut parser = Parser::new(input);
+    parser.parse_expression()
+}
+
+/// The variables of the stepper with their values, newest last. The one
+/// named `looked_up` is picked out while the current step replaces it.
+fn variables_panel(ui: &mut egui::Ui, env: &[(String, i64)], looked_up: Option<&str>) {
+    ui.label("Variables:");
+    let current = looked_up.and_then(|name| env.iter().rposition(|(bound, _)| bound == name));
+    for (index, (name, value)) in env.iter().enumerate() {
+        let line = RichText::new(format!("{name} \u{2190} {value}")).monospace();
+        if current == Some(index) {
+            ui.label(line.strong().color(ui.visuals().selection.stroke.color));
+        } else {
+            ui.label(line);
+        }
+    }
+}
+
+/// Shows the code frame for `seconds` after `shown_at`, then hides it behind
+/// a blank frame of the same shape. Returns whether the code is still visible.
+fn timed_code_frame(
+    ui: &mut egui::Ui,
+    line: &str,
+    shown_at: Option<Instant>,
+    seconds: u32,
+) -> bool {
+    let remaining = shown_at
+        .map(|start| Duration::from_secs(seconds as u64).saturating_sub(start.elapsed()))
+        .unwrap_or(Duration::ZERO);
+    if remaining.is_zero() {
+        let hidden: String = line
+            .chars()
+            .map(|ch| if ch == ' ' { ' ' } else { '?' })
+            .collect();
+        ui.add(CodeBlock::line(&hidden).copyable(false));
+        return false;
+    }
+    ui.add(CodeBlock::line(line).copyable(false));
+    ui.label(format!("Hiding in {:.1} s", remaining.as_secs_f32()));
+    ui.ctx().request_repaint_after(Duration::from_millis(100));
+    true
+}
+
+/// The "At the edge of the numbers" card: an expression near the ends of
+/// `i64`, evaluated with the usual stop at an overflow or wrapping around.
+struct OverflowState {
+    input: String,
+    wrap: bool,
+}
+
+impl Default for OverflowState {
+    fn default() -> Self {
+        Self {
+            input: "9223372036854775807 + 1".to_string(),
+            wrap: false,
         }
+    }
+}
+
+/// The first operation that ran off an end of the numbers, and which end.
+struct Wrap {
+    code: String,
+    above: bool,
+}
 
-        let id = ui.make_persistent_id(("tree-exercise-node", &layout.path));
-        let response = ui.interact(layout.rect, id, egui::Sense::click());
-        if response.clicked() {
-            clicked = Some(layout.path.clone());
+/// Evaluates the way the processor does: a result that does not fit wraps
+/// around to the other end instead of stopping. Division by zero and
+/// negative exponents still have no value.
+fn eval_wrapping(expr: &Expr, wrap: &mut Option<Wrap>) -> Result<i64, String> {
+    let (value, overflow) = match expr {
+        Expr::Value(value) => return Ok(*value),
+        Expr::Var(name) => return Err(format!("`{name}` has no value on this card.")),
+        Expr::Unary(UnaryOp::Neg, inner) => {
+            let value = eval_wrapping(inner, wrap)?;
+            let (result, overflow) = value.overflowing_neg();
+            (result, overflow.then(|| (format!("-({value})"), true)))
+        }
+        Expr::Binary(op, left, right) => {
+            let left = eval_wrapping(left, wrap)?;
+            let right = eval_wrapping(right, wrap)?;
+            let (result, overflow, above) = match op {
+                // Sharing by anything but -1 stays in range, so those and the
+                // errors go the usual way.
+                BinaryOp::Div | BinaryOp::Rem if right != -1 => {
+                    return Arithmetic::apply_binary(*op, &left, &right);
+                }
+                BinaryOp::Pow if right < 0 => {
+                    return Arithmetic::apply_binary(*op, &left, &right);
+                }
+                BinaryOp::Add => {
+                    let (result, overflow) = left.overflowing_add(right);
+                    (result, overflow, right > 0)
+                }
+                BinaryOp::Sub => {
+                    let (result, overflow) = left.overflowing_sub(right);
+                    (result, overflow, right < 0)
+                }
+                BinaryOp::Mul => {
+                    let (result, overflow) = left.overflowing_mul(right);
+                    (result, overflow, (left < 0) == (right < 0))
+                }
+                BinaryOp::Div => {
+                    let (result, overflow) = left.overflowing_div(right);
+                    (result, overflow, true)
+                }
+                BinaryOp::Rem => {
+                    let (result, overflow) = left.overflowing_rem(right);
+                    (result, overflow, true)
+                }
+                BinaryOp::Pow => {
+                    let Ok(exponent) = u32::try_from(right) else {
+                        return Err(format!(
+                            "The exponent {right} is too big to work out, even wrapping."
+                        ));
+                    };
+                    let (result, overflow) = left.overflowing_pow(exponent);
+                    (result, overflow, left >= 0 || exponent % 2 == 0)
+                }
+            };
+            let code = format!("{left} {} {right}", Arithmetic::binary_label(*op));
+            (result, overflow.then_some((code, above)))
         }
+    };
+    if let (None, Some((code, above))) = (&wrap, overflow) {
+        *wrap = Some(Wrap { code, above });
+    }
+    Ok(value)
+}
+
+/// Where `value` sits between the smallest and the largest `i64`, from 0 at
+/// the left end to 1 at the right.
+fn line_fraction(value: i64) -> f32 {
+    ((value as f64 - i64::MIN as f64) / 2f64.powi(64)) as f32
+}
 
-        let stroke = line_stroke(layout.highlight);
-        painter.rect(
-            layout.rect,
-            egui::CornerRadius::same(4),
-            ui.visuals().code_bg_color,
-            stroke,
-            egui::StrokeKind::Inside,
+/// All of `i64` as one line, with `value` marked on it. After a wrap, a
+/// dashed loop leaves the line at the end it ran off and comes back in at
+/// the other.
+fn number_line(ui: &mut egui::Ui, value: i64, wrap: Option<&Wrap>) {
+    let palette = Palette::from_ui(ui);
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), 84.0), egui::Sense::hover());
+    response.widget_info(|| {
+        egui::WidgetInfo::labeled(
+            egui::WidgetType::Other,
+            true,
+            format!(
+                "Number line from {} to {}, marked at {value}",
+                i64::MIN,
+                i64::MAX
+            ),
+        )
+    });
+    let painter = ui.painter_at(rect);
+    let font_id = egui::TextStyle::Small.resolve(ui.style());
+    let left = rect.left() + 24.0;
+    let right = rect.right() - 24.0;
+    let y = rect.top() + 22.0;
+    let stroke = egui::Stroke::new(2.0, palette.text);
+    painter.line_segment([egui::pos2(left, y), egui::pos2(right, y)], stroke);
+    for (number, align) in [
+        (i64::MIN, egui::Align2::LEFT_BOTTOM),
+        (0, egui::Align2::CENTER_BOTTOM),
+        (i64::MAX, egui::Align2::RIGHT_BOTTOM),
+    ] {
+        let x = left + (right - left) * line_fraction(number);
+        painter.line_segment([egui::pos2(x, y - 5.0), egui::pos2(x, y + 5.0)], stroke);
+        painter.text(
+            egui::pos2(x, y - 7.0),
+            align,
+            number.to_string(),
+            font_id.clone(),
+            palette.weak_text,
         );
-        let color = if layout.highlight {
-            highlight_color
+    }
+
+    let highlight = egui::Stroke::new(2.0, palette.highlight);
+    if let Some(wrap) = wrap {
+        let (out, back) = if wrap.above {
+            (right, left)
         } else {
-            text_color
+            (left, right)
         };
-        let galley = ui
-            .fonts_mut(|fonts| fonts.layout_no_wrap(layout.label.clone(), font_id.clone(), color));
-        let text_pos = layout.rect.center() - galley.size() / 2.0;
-        painter.galley(text_pos, galley, text_color);
+        let side = if wrap.above { 1.0 } else { -1.0 };
+        let lane = y + 28.0;
+        let points = [
+            egui::pos2(out, y),
+            egui::pos2(out + side * 14.0, y),
+            egui::pos2(out + side * 14.0, lane),
+            egui::pos2(back - side * 14.0, lane),
+            egui::pos2(back - side * 14.0, y),
+        ];
+        painter.extend(egui::Shape::dashed_line(&points, highlight, 6.0, 4.0));
+        painter.arrow(points[4], egui::vec2(side * 12.0, 0.0), highlight);
     }
-
-    clicked
+    let x = left + (right - left) * line_fraction(value);
+    painter.circle_filled(egui::pos2(x, y), 5.0, palette.highlight);
+    let align = if x < rect.center().x {
+        egui::Align2::LEFT_TOP
+    } else {
+        egui::Align2::RIGHT_TOP
+    };
+    painter.text(
+        egui::pos2(x, rect.bottom() - 16.0),
+        align,
+        value.to_string(),
+        egui::TextStyle::Monospace.resolve(ui.style()),
+        palette.highlight,
+    );
 }
 
 pub fn expressions(nb: &mut NotebookCtx) {
-    nb.view(|ui| {
-        md!(
-            ui,
-            "# Hello, expressions\n\
-             An **expression** is a little sentence that describes the world.\n\
-             Expressions let us draw conclusions or answer questions by applying simple rules\n\
-             either by hand or with a computer.\n\n\
-             An expression can be as simple as a **constant** value like `3`.\n\
-             Or you can build larger expressions from smaller ones using symbols like\n\
-             `+`, `-`, or `*`. We call those symbols **operations**.\n\n\
-             Examples:\n\
-             - `3`\n\
-             - `3 + 1`\n\
-             - `(10 - 4)`\n\
-             - `(3 * 2) + 2`\n\
-             - `-(4 + 1) * 3`\n\n\
-             Expressions can be *evaluated*, which means turning them into a single value.\n\
-             That final value is what the expression *means*.\n\n"
-        );
-    });
+    speech::lesson(
+        nb,
+        "# Hello, expressions\n\
+         An **expression** is a little sentence that describes the world.\n\
+         Expressions let us draw conclusions or answer questions by applying simple rules\n\
+         either by hand or with a computer.\n\n\
+         An expression can be as simple as a **constant** value like `3`.\n\
+         Or you can build larger expressions from smaller ones using symbols like\n\
+         `+`, `-`, `*`, `/`, `%`, or `^`. We call those symbols **operations**.\n\n\
+         Examples:\n\
+         - `3`\n\
+         - `3 + 1`\n\
+         - `(10 - 4)`\n\
+         - `(3 * 2) + 2`\n\
+         - `-(4 + 1) * 3`\n\
+         - `2 ^ 3`, which is `2 * 2 * 2`, *2 to the power of 3*\n\n\
+         Expressions can be *evaluated*, which means turning them into a single value.\n\
+         That final value is what the expression *means*.\n\n",
+    );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## A tiny story\n\
-             Imagine two baskets of apples.\n\
-             Each basket holds 3 apples, and we have 2 baskets.\n\
-             So we can write `3 * 2` and get **6**.\n\n\
-             Now imagine there are 2 extra apples on the table:\n\
-             - First, multiply the baskets: `3 * 2`.\n\
-             - Then add the extras: `(3 * 2) + 2`.\n\n\
-             By describing the situation with an expression, we can evaluate it to find out how many apples there are in total."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         Imagine two baskets of apples.\n\
+         Each basket holds 3 apples, and we have 2 baskets.\n\
+         So we can write `3 * 2` and get **6**.\n\n\
+         Now imagine there are 2 extra apples on the table:\n\
+         - First, multiply the baskets: `3 * 2`.\n\
+         - Then add the extras: `(3 * 2) + 2`.\n\n\
+         By describing the situation with an expression, we can evaluate it to find out how many apples there are in total.",
+    );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## The rules of evaluation\n\
-             When an expression has several operations, there are rules:\n\
-             - Parentheses first: `(3 + 2) * 4` evaluates the part inside `()` first.\n\
-             - Left-to-right when the precedence is the same: `8 - 3 - 2` means `(8 - 3) - 2`.\n\
-             - Inside-out: evaluate the deepest expression before outer ones.\n\
-             - Multiplication before addition or subtraction: `3 + 2 * 4` means `3 + (2 * 4)`.\n\
-             - Unary minus sticks to the number or parentheses: `-(3 + 2)`.\n\n\
-             These rules are called **precedence** (what happens first) and\n\
-             **associativity** (how ties are grouped).\n\
-             You do not need to memorize the names, just the rules."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## Sharing and leftovers\n\
+         Now share 7 apples between 2 friends.\n\
+         Each friend gets `7 / 2`, which is **3**: we only count whole apples.\n\
+         One apple is left over, and `7 % 2` gives exactly that: **1**.\n\
+         So `/` tells you how many fit, and `%` (say *remainder*) tells you what is left.\n\n\
+         Sharing into zero groups makes no sense, so `7 / 0` and `7 % 0` have no value.\n\
+         The stepper below stops at such a box and tells you why.",
+    );
+
+    speech::lesson(
+        nb,
+        "## The rules of evaluation\n\
+         When an expression has several operations, there are rules:\n\
+         - Parentheses first: `(3 + 2) * 4` evaluates the part inside `()` first.\n\
+         - Left-to-right when the precedence is the same: `8 - 3 - 2` means `(8 - 3) - 2`.\n\
+         - Inside-out: evaluate the deepest expression before outer ones.\n\
+         - Powers before everything else: `2 * 3 ^ 2` means `2 * (3 ^ 2)`, and `-2 ^ 2` means `-(2 ^ 2)`.\n\
+         - Powers group from the right: `2 ^ 3 ^ 2` means `2 ^ (3 ^ 2)`, which is `2 ^ 9`.\n\
+         - Multiplication before addition or subtraction: `3 + 2 * 4` means `3 + (2 * 4)`.\n\
+         - Division `/` and remainder `%` rank with multiplication: `9 - 7 % 4` means `9 - (7 % 4)`.\n\
+         - Unary minus sticks to the number or parentheses: `-(3 + 2)`.\n\n\
+         These rules are called **precedence** (what happens first) and\n\
+         **associativity** (how ties are grouped).\n\
+         You do not need to memorize the names, just the rules.",
+    );
 
     nb.view(|ui| {
         note!(
@@ -1075,14 +1573,28 @@ pub fn expressions(nb: &mut NotebookCtx) {
         );
     });
 
+    speech::lesson(
+        nb,
+        "## Names for values\n\
+         You can give a value a name and use the name instead: `x <- 4; x * 2 + 1`.\n\
+         Read `<-` as *gets*: `x` gets 4.\n\
+         When the computer meets `x`, it looks up its value and puts it in its place.\n\
+         That look-up is a step of its own, so the stepper below shows it too.\n\n\
+         Names that hold values are called **variables**. The state chapter shows\n\
+         what happens when a variable gets a new value.",
+    );
+
     nb.state(
-        &chapter_key("expression_state"),
+        &state_key("expression_state"),
         ExpressionState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Step through an expression").heading());
+                if !card_heading(ui, "Step through an expression") {
+                    return;
+                }
                 ui.add_space(4.0);
-                ui.label("Use numbers, +, -, *, parentheses, and unary minus.");
+                ui.label("Use numbers, +, -, *, /, %, ^, parentheses, and unary minus.");
+                ui.label("Give names values first if you like: x <- 4; x * 2 + 1");
                 ui.label("This tool shows the exact order the computer evaluates.");
                 ui.label("Step forward to see which part is solved next.");
                 ui.add_space(6.0);
@@ -1092,163 +1604,516 @@ pub fn expressions(nb: &mut NotebookCtx) {
                     let response = ui.add(widgets::TextField::singleline(&mut state.input));
                     if response.changed() {
                         state.step = 0;
+                        state.postfix_step = 0;
+                        state.play.stop();
+                        state.postfix_play.stop();
                     }
                     if ui.add(widgets::Button::new("Random")).clicked() {
-                        let expr = generate_tree_expr(&mut state.rng);
+                        let expr = generate_tree_expr(&mut state.rng, difficulty::current());
                         state.input = expr_to_string(&expr);
                         state.step = 0;
+                        state.postfix_step = 0;
+                        state.play.stop();
+                        state.postfix_play.stop();
                     }
                 });
 
-                let expr = match parse_expression(&state.input) {
-                    Ok(expr) => expr,
+                let mut parser = Parser::new(&state.input);
+                let (bindings, expr) = match parser.parse_program() {
+                    Ok(program) => program,
                     Err(error) => {
-                        ui.add_space(6.0);
-                        ui.label(
-                            RichText::new(format!("Parse error: {error}"))
-                                .color(ui.visuals().error_fg_color),
-                        );
-                        ui.add_space(2.0);
-                        ui.label(
-                            RichText::new("Tip: check parentheses or a missing number/operator.")
-                                .color(ui.visuals().weak_text_color()),
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: check parentheses, a missing number/operator, or a missing ';'.",
                         );
                         return;
                     }
                 };
 
-                let steps = match build_steps(expr) {
-                    Ok(steps) => steps,
-                    Err(error) => {
-                        ui.add_space(6.0);
-                        ui.label(
-                            RichText::new(format!("Evaluation error: {error}"))
+                let mut env = Vec::new();
+                for (name, value) in bindings {
+                    match value.eval_in(&env) {
+                        Ok(value) => env.push((name, value)),
+                        Err(error) => {
+                            ui.add_space(6.0);
+                            ui.label(
+                                RichText::new(format!(
+                                    "{name} <- {}: {error}",
+                                    expr_to_string(&value)
+                                ))
                                 .color(ui.visuals().error_fg_color),
-                        );
-                        return;
+                            );
+                            return;
+                        }
                     }
-                };
-
-                let max_step = steps.len().saturating_sub(1);
-                if state.step > max_step {
-                    state.step = max_step;
                 }
 
+                let postfix = state.postfix.then(|| expr::Postfix::new(&expr, &env));
+                let program = state.machine.then(|| expr::Program::new(&expr, &env));
+                let steps = build_steps_in(expr, &env);
+
+                ui.add_space(6.0);
+                if !env.is_empty() {
+                    let looked_up = steps.get(state.step).and_then(|step| {
+                        let node = expr_at_path(&step.expr, step.highlight.as_deref()?)?;
+                        match node {
+                            Expr::Var(name) => Some(name.as_str()),
+                            _ => None,
+                        }
+                    });
+                    variables_panel(ui, &env, looked_up);
+                    ui.add_space(6.0);
+                }
+                state.play.show(ui, &mut state.step, steps.count() - 1);
+                stepper(ui, &mut state.step, &steps);
+
                 ui.add_space(6.0);
                 ui.horizontal(|ui| {
-                    if ui
-                        .add_enabled(state.step > 0, widgets::Button::new("Prev"))
-                        .clicked()
+                    ui.add(widgets::ToggleButton::new(
+                        &mut state.machine,
+                        "Stack machine",
+                    ));
+                    ui.add(widgets::ToggleButton::new(
+                        &mut state.postfix,
+                        "Postfix view",
+                    ));
+                });
+                if let Some(program) = program {
+                    ui.add_space(4.0);
+                    ui.label("Compiled for a stack machine, the expression becomes instructions.");
+                    ui.label("`push` puts a value on the stack, `load` the value of a name.");
+                    ui.label("The others take their operands off the stack and push the result.");
+                    ui.label("The machine keeps pace with the stepper: one step, one operator.");
+                    ui.add_space(4.0);
+                    program.show(ui, state.step);
+                }
+                let Some(postfix) = postfix else {
+                    return;
+                };
+                ui.add_space(4.0);
+                ui.label("A machine reads the same expression in postfix order:");
+                ui.label("first the operands, then the operator. Values go on a stack.");
+                ui.label("An operator takes its operands off the top and puts its result back.");
+                ui.add_space(4.0);
+                state
+                    .postfix_play
+                    .show(ui, &mut state.postfix_step, postfix.count() - 1);
+                stepper(ui, &mut state.postfix_step, &postfix);
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("tree_exercise_state"),
+        TreePractice::new(
+            chapter_key("tree_exercise_state"),
+            generate_tree_expr,
+            Wording {
+                id: "tree-exercise-node",
+                nudge: "tree_practice.nudge_numbers",
+                already_value: "tree_practice.constant",
+                lesson: "Step through an expression",
+            },
+        ),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Tree practice") {
+                    return;
+                }
+                ui.add_space(6.0);
+                ui.label("Train your evaluation order: deepest first, then left to right.");
+                ui.label("This builds the same skill you use when you read code.");
+                ui.label("Click a box to evaluate it in the right order (left to right).");
+                ui.label("Keep going until the whole tree becomes one number.");
+                ui.add_space(6.0);
+                state.show(ui);
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("tree_to_code_state"),
+        TreeToCodeState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "From tree to code") {
+                    return;
+                }
+                ui.add_space(6.0);
+                ui.label("Here is only the tree. Write the expression it belongs to.");
+                ui.label("Use parentheses wherever the rules would group differently.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New tree")).clicked() {
+                        state.regenerate();
+                    }
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("tree_to_code_state"), state.seed)
                     {
-                        state.step = state.step.saturating_sub(1);
+                        state.load_seed(seed);
+                    }
+                });
+                ui.add_space(6.0);
+                draw_tree(ui, &state.expr.to_tree(), |_| false);
+                ui.add_space(6.0);
+
+                let mut check = false;
+                ui.horizontal(|ui| {
+                    ui.label("Expression:");
+                    ui.add(widgets::TextField::singleline(&mut state.input));
+                    check = ui.add(widgets::Button::new("Check")).clicked();
+                });
+                let mut parser = Parser::new(&state.input);
+                let parsed = parser.parse_expression();
+                if check {
+                    let correct = parsed
+                        .as_ref()
+                        .is_ok_and(|expr| first_difference(&state.expr, expr).is_none());
+                    progress::answer(chapter_key("tree_to_code_state"), correct);
+                    state.checked = Some(state.input.clone());
+                }
+                if state.checked.as_ref() != Some(&state.input) {
+                    return;
+                }
+
+                ui.add_space(4.0);
+                let expr = match parsed {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: check parentheses or a missing number/operator.",
+                        );
+                        return;
+                    }
+                };
+                let Some(difference) = first_difference(&state.expr, &expr) else {
+                    ui.label("Correct! Your code makes exactly this tree.");
+                    return;
+                };
+                ui.label(exercise::not_quite(
+                    "Your code makes the tree below. The highlighted box is the first one that differs.",
+                ));
+                ui.add_space(4.0);
+                draw_tree(ui, &expr.to_tree(), |path| *path == difference);
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("random_exercise_state"),
+        RandomExerciseState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Random practice") {
+                    return;
+                }
+                ui.add_space(6.0);
+                glossary::text(
+                    ui,
+                    "Practice turning a whole [[expression]] into one value.",
+                );
+                ui.label("Try to do the steps in your head or on paper, then check.");
+                ui.label("Generate a new expression and evaluate it.");
+                ui.add_space(6.0);
+                state.practice.coop_controls(ui);
+                ui.add_space(6.0);
+                state.follow_seed();
+                let options = (state.preset, state.negation);
+                ui.horizontal(|ui| {
+                    ui.label("Level:");
+                    let mut toggle = widgets::ChoiceToggle::new(&mut state.preset).small();
+                    for (preset, label) in Preset::ALL {
+                        toggle = toggle.choice(preset, label);
                     }
+                    ui.add(toggle);
+                    ui.add(widgets::ToggleButton::new(
+                        &mut state.negation,
+                        "Unary minus",
+                    ));
+                });
+                state
+                    .practice
+                    .draw(state.preset.difficulty(), !state.negation);
+                if (state.preset, state.negation) != options {
+                    state.practice.regenerate();
+                }
+                ui.add_space(6.0);
+                let key = chapter_key("random_exercise_state");
+                if let Some(correct) = state.practice.show(ui, key) {
+                    if correct {
+                        let expr = &state.practice.exercise().expr;
+                        mastery::solved(CHAPTER, mastery::tree_tier(expr));
+                    }
+                }
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<Practice>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("random_exercise_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("compare_state"),
+        CompareState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Which is larger?") {
+                    return;
+                }
+                ui.add_space(6.0);
+                ui.label("Work out both expressions and say which one has the larger value.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New pair")).clicked() {
+                        state.regenerate();
+                    }
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("compare_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                });
+                ui.add_space(6.0);
+                let (left, right) = (expr_to_string(&state.left), expr_to_string(&state.right));
+                ui.horizontal(|ui| {
+                    ui.label("Left:");
+                    ui.add(CodeBlock::line(&left));
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Right:");
+                    ui.add(CodeBlock::line(&right));
+                });
+                ui.add_space(6.0);
+                let Some(answer) = state.answer() else {
+                    return;
+                };
+                let before = state.selection;
+                ui.add(
+                    AnswerToggle::new(&mut state.selection)
+                        .choice(Some(Ordering::Greater), "Left is larger")
+                        .choice(Some(Ordering::Equal), "Equal")
+                        .choice(Some(Ordering::Less), "Right is larger"),
+                );
+                let Some(selection) = state.selection else {
+                    return;
+                };
+                if state.selection != before {
+                    progress::answer(chapter_key("compare_state"), selection == answer);
+                }
+                ui.add_space(4.0);
+                if selection == answer {
+                    ui.label("Correct!");
+                } else {
+                    ui.label(exercise::not_quite("Work both sides out once more."));
+                }
+                if let (Ok(left_value), Ok(right_value)) = (state.left.eval(), state.right.eval()) {
+                    ui.label(format!(
+                        "{left} = {left_value}, and {right} = {right_value}."
+                    ));
+                }
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("dictation_state"),
+        DictationState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Flash practice") {
+                    return;
+                }
+                ui.add_space(6.0);
+                ui.label("The expression is only shown for a moment.");
+                ui.label("Read it, keep it in your head, then type its value.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Show for:");
+                    ui.add(widgets::Slider::new(&mut state.seconds, 1..=10).text("s"));
+                });
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
                     if ui
-                        .add_enabled(state.step < max_step, widgets::Button::new("Next"))
+                        .add(widgets::Button::new("Flash an expression"))
                         .clicked()
                     {
-                        state.step = (state.step + 1).min(max_step);
+                        state.regenerate();
                     }
-                    if ui.add(widgets::Button::new("Reset")).clicked() {
-                        state.step = 0;
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("dictation_state"), state.seed)
+                    {
+                        state.load_seed(seed);
                     }
-                    ui.add_space(6.0);
-                    ui.label(format!("Step {}/{}", state.step, max_step));
                 });
+                ui.add_space(6.0);
+                if state.shown_at.is_none() {
+                    ui.label("Press the button when you are ready.");
+                    return;
+                }
 
-                ui.add_space(8.0);
-                let step = &steps[state.step];
-                let (expression, expression_ranges) =
-                    render_expr_with_highlight(&step.expr, step.highlight.as_deref());
-                code_frame(ui, highlighted_job(ui, &expression, &expression_ranges));
+                let expression = expr_to_string(&state.exercise.expr);
+                let visible = timed_code_frame(ui, &expression, state.shown_at, state.seconds);
+                if visible {
+                    return;
+                }
 
                 ui.add_space(6.0);
-                ui.label("Tree view:");
+                ui.horizontal(|ui| {
+                    ui.label("Value:");
+                    ui.add_enabled(
+                        state.checked.is_none(),
+                        widgets::NumberField::new(&mut state.guess)
+                            .speed(1.0)
+                            .min_decimals(0)
+                            .max_decimals(0),
+                    );
+                    if ui
+                        .add_enabled(state.checked.is_none(), widgets::Button::new("Check"))
+                        .clicked()
+                    {
+                        state.checked = Some(state.guess);
+                        progress::answer(
+                            chapter_key("dictation_state"),
+                            state.guess == state.exercise.answer,
+                        );
+                    }
+                });
                 ui.add_space(4.0);
-                draw_tree(ui, &step.expr, step.highlight.as_deref());
-                ui.add_space(6.0);
-                if step.highlight.is_some() {
-                    ui.label("The highlighted part is what you can evaluate next.");
-                } else {
-                    ui.label("Fully evaluated.");
+                match state.checked {
+                    Some(value) if value == state.exercise.answer => {
+                        ui.label("Correct!");
+                        ui.label(format!("{expression} = {}", state.exercise.answer));
+                    }
+                    Some(_) => {
+                        ui.label("Not quite. Here is the expression again:");
+                        ui.label(format!("{expression} = {}", state.exercise.answer));
+                    }
+                    None => {
+                        ui.label("Type your answer, then check.");
+                    }
                 }
             });
         },
     );
 
     nb.state(
-        &chapter_key("tree_exercise_state"),
-        TreeExerciseState::default(),
+        &state_key("builder_state"),
+        BuilderState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Tree practice").heading());
+                if !card_heading(ui, "Build an expression") {
+                    return;
+                }
                 ui.add_space(6.0);
-                ui.label("Train your evaluation order: deepest first, then left to right.");
-                ui.label("This builds the same skill you use when you read code.");
-                ui.label("Click a box to evaluate it in the right order (left to right).");
-                ui.label("Keep going until the whole tree becomes one number.");
+                ui.label("Now the other way around: you know the value, find the expression.");
+                ui.label("Drag tiles into the tree until it works out to the target.");
+                ui.label("Each number can be used once. One of them is not needed.");
                 ui.add_space(6.0);
-                let mut show_hint = false;
                 ui.horizontal(|ui| {
-                    if ui.add(widgets::Button::new("New tree")).clicked() {
+                    if ui.add(widgets::Button::new("New target")).clicked() {
                         state.regenerate();
                     }
-                    let hint_response = ui.add(widgets::Button::new("Hold for hint"));
-                    let hint_keyboard = hint_response.has_focus()
-                        && ui.input(|input| {
-                            input.key_down(egui::Key::Enter) || input.key_down(egui::Key::Space)
-                        });
-                    show_hint = hint_response.is_pointer_button_down_on() || hint_keyboard;
-                    if hint_response.clicked() {
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("builder_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                    if ui.add(widgets::Button::new("Empty the tree")).clicked() {
+                        state.slots = [None; BUILDER_SLOTS];
                         state.feedback = None;
                     }
                 });
                 ui.add_space(6.0);
-
-                let next_path = find_reducible(&state.expr);
-                let highlight_path = if show_hint {
-                    next_path.as_deref()
-                } else {
-                    None
-                };
-                let done = next_path.is_none();
-
-                let (expression, expression_ranges) =
-                    render_expr_with_highlight(&state.expr, highlight_path);
-                code_frame(ui, highlighted_job(ui, &expression, &expression_ranges));
+                ui.label(RichText::new(format!("Target: {}", state.target)).strong());
                 ui.add_space(6.0);
 
-                let clicked = draw_tree_interactive(ui, &state.expr, highlight_path);
-                if !done {
-                    if let Some(path) = clicked {
-                        if next_path.as_ref().map_or(false, |next| next == &path) {
-                            match reduce_at(state.expr.clone(), &path) {
-                                Ok(expr) => {
-                                    state.expr = expr;
-                                    state.feedback = None;
-                                }
-                                Err(error) => {
-                                    state.feedback = Some(format!("Oops: {error}"));
-                                }
-                            }
+                ui.horizontal(|ui| {
+                    for (index, value) in state.numbers.iter().enumerate() {
+                        if state.used(index) {
+                            ui.add_enabled_ui(false, |ui| tile_frame(ui, &value.to_string()));
                         } else {
-                            let feedback = expr_at_path(&state.expr, &path).and_then(|expr| {
-                                if matches!(expr.kind, ExprKind::Num(_)) {
-                                    Some("Constants already have a value.".to_string())
-                                } else {
-                                    None
-                                }
+                            let id = egui::Id::new(("builder-number", index));
+                            ui.dnd_drag_source(id, Tile::Number(index), |ui| {
+                                tile_frame(ui, &value.to_string())
                             });
-                            state.feedback = Some(feedback.unwrap_or_else(|| {
-                                "Not yet. Work left-to-right; if there is no deeper expression, move up to the next level.".to_string()
-                            }));
                         }
                     }
+                    ui.add_space(12.0);
+                    for op in BUILDER_OPS {
+                        let label = Arithmetic::binary_label(op);
+                        let id = egui::Id::new(("builder-op", label));
+                        ui.dnd_drag_source(id, Tile::Op(op), |ui| tile_frame(ui, label));
+                    }
+                });
+                ui.add_space(10.0);
+
+                // Three rows of four columns put the slots where the boxes of
+                // the tree view would be.
+                const LAYOUT: [[Option<usize>; 4]; 3] = [
+                    [None, None, Some(0), None],
+                    [None, Some(1), None, Some(4)],
+                    [Some(2), None, Some(3), None],
+                ];
+                let mut rects = [egui::Rect::NOTHING; BUILDER_SLOTS];
+                let mut dropped = None;
+                egui::Grid::new("builder-scaffold")
+                    .spacing(egui::vec2(12.0, 20.0))
+                    .show(ui, |ui| {
+                        for row in LAYOUT {
+                            for cell in row {
+                                let Some(slot) = cell else {
+                                    ui.label("");
+                                    continue;
+                                };
+                                let text = match state.slots[slot] {
+                                    Some(Tile::Number(index)) => state.numbers[index].to_string(),
+                                    Some(Tile::Op(op)) => Arithmetic::binary_label(op).to_string(),
+                                    None => "?".to_string(),
+                                };
+                                let frame = egui::Frame::group(ui.style())
+                                    .inner_margin(egui::Margin::symmetric(10, 4));
+                                let (inner, payload) = ui.dnd_drop_zone::<Tile, _>(frame, |ui| {
+                                    ui.set_min_width(24.0);
+                                    ui.label(RichText::new(text).monospace().strong());
+                                });
+                                rects[slot] = inner.response.rect;
+                                if let Some(tile) = payload {
+                                    dropped = Some((slot, *tile));
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                let stroke = Palette::from_ui(ui).line;
+                for (parent, child) in BUILDER_EDGES {
+                    ui.painter().line_segment(
+                        [rects[parent].center_bottom(), rects[child].center_top()],
+                        stroke,
+                    );
+                }
+                if let Some((slot, tile)) = dropped {
+                    state.drop_tile(slot, tile);
                 }
 
                 ui.add_space(6.0);
-                if let Some(value) = as_num(&state.expr) {
-                    ui.label(format!("All done! Value = {value}."));
-                }
                 if let Some(feedback) = &state.feedback {
                     ui.label(feedback);
                 }
@@ -1257,45 +2122,369 @@ pub fn expressions(nb: &mut NotebookCtx) {
     );
 
     nb.state(
-        &chapter_key("random_exercise_state"),
-        RandomExerciseState::default(),
+        &state_key("target_state"),
+        TargetState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Random practice").heading());
+                if !card_heading(ui, "Reach the target") {
+                    return;
+                }
                 ui.add_space(6.0);
-                ui.label("Practice turning a whole expression into one value.");
-                ui.label("Try to do the steps in your head or on paper, then check.");
-                ui.label("Generate a new expression and evaluate it.");
+                ui.label("Write an expression that comes out at the target.");
+                ui.label("Any operators and parentheses are fine, but only the numbers below.");
+                ui.label("Each number can be used once, and you do not need all of them.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New numbers")).clicked() {
+                        state.regenerate();
+                    }
+                    if let Some(seed) = rng::seed_field(ui, chapter_key("target_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                });
+                ui.add_space(6.0);
+                ui.label(RichText::new(format!("Target: {}", state.target)).strong());
+                ui.horizontal(|ui| {
+                    ui.label("Numbers:");
+                    for number in &state.numbers {
+                        tile_frame(ui, &number.to_string());
+                    }
+                });
                 ui.add_space(6.0);
-                if ui.add(widgets::Button::new("New exercise")).clicked() {
-                    state.regenerate();
+
+                let mut check = false;
+                ui.horizontal(|ui| {
+                    ui.label("Expression:");
+                    ui.add(widgets::TextField::singleline(&mut state.input));
+                    check = ui.add(widgets::Button::new("Check")).clicked();
+                });
+                let mut parser = Parser::new(&state.input);
+                let parsed = parser.parse_expression();
+                if check {
+                    let correct = parsed.as_ref().is_ok_and(|expr| {
+                        check_numbers(&state.numbers, expr).is_ok()
+                            && expr.eval().is_ok_and(|value| value == state.target)
+                    });
+                    progress::answer(chapter_key("target_state"), correct);
+                    state.checked = Some(state.input.clone());
+                }
+                if state.checked.as_ref() != Some(&state.input) {
+                    return;
+                }
+
+                ui.add_space(4.0);
+                let expr = match parsed {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: check parentheses or a missing number/operator.",
+                        );
+                        return;
+                    }
+                };
+                if let Err(error) = check_numbers(&state.numbers, &expr) {
+                    ui.label(exercise::not_quite(&error));
+                    return;
+                }
+                match expr.eval() {
+                    Ok(value) if value == state.target => {
+                        ui.label(format!(
+                            "{} = {value}. Right on target!",
+                            state.input.trim()
+                        ));
+                    }
+                    Ok(value) => {
+                        ui.label(format!(
+                            "{} = {value}, which is {} away from {}.",
+                            state.input.trim(),
+                            (value - state.target).abs(),
+                            state.target
+                        ));
+                    }
+                    Err(error) => {
+                        ui.label(error);
+                    }
+                }
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("paren_state"),
+        ParenState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Place the parentheses") {
+                    return;
                 }
                 ui.add_space(6.0);
-                let expression = expr_to_string(&state.exercise.expr);
-                code_frame(ui, highlighted_job(ui, &expression, &[]));
+                ui.label("Without parentheses, this line does not come out at the target.");
+                ui.label("Click the gaps next to the numbers to add parentheses until it does.");
+                ui.label("Each click adds one more; a third click empties the gap again.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New line")).clicked() {
+                        state.regenerate();
+                    }
+                    if let Some(seed) = rng::seed_field(ui, chapter_key("paren_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                    if ui.add(widgets::Button::new("Remove all")).clicked() {
+                        state.opens.fill(0);
+                        state.closes.fill(0);
+                    }
+                });
                 ui.add_space(6.0);
-                let mut toggle = widgets::ChoiceToggle::new(&mut state.selection).small();
-                for choice in &state.choices {
-                    toggle = toggle.choice(Some(*choice), choice.to_string());
+                ui.label(RichText::new(format!("Target: {}", state.target)).strong());
+                ui.add_space(4.0);
+
+                let gap = |ui: &mut egui::Ui, count: &mut u8, paren: &str| {
+                    let label = if *count == 0 {
+                        "\u{00B7}".to_string()
+                    } else {
+                        paren.repeat(*count as usize)
+                    };
+                    if ui.add(widgets::Button::new(label).small()).clicked() {
+                        *count = (*count + 1) % (MAX_PARENS + 1);
+                    }
+                };
+                ui.horizontal(|ui| {
+                    for index in 0..state.numbers.len() {
+                        if index > 0 {
+                            let op = Arithmetic::binary_label(state.ops[index - 1]);
+                            ui.label(RichText::new(op).monospace().strong());
+                        }
+                        gap(ui, &mut state.opens[index], "(");
+                        ui.label(
+                            RichText::new(state.numbers[index].to_string())
+                                .monospace()
+                                .strong(),
+                        );
+                        gap(ui, &mut state.closes[index], ")");
+                    }
+                });
+                ui.add_space(6.0);
+
+                let text = state.text();
+                ui.add(CodeBlock::line(&text));
+                let value = parse_expression(&text).and_then(|expr| expr.eval());
+                if ui.add(widgets::Button::new("Check")).clicked() {
+                    let correct = value.as_ref().is_ok_and(|value| *value == state.target);
+                    progress::answer(chapter_key("paren_state"), correct);
+                    state.checked = Some(text.clone());
+                }
+                if state.checked.as_ref() != Some(&text) {
+                    return;
                 }
-                ui.add(toggle);
                 ui.add_space(4.0);
-                match state.selection {
-                    Some(value) if value == state.exercise.answer => ui.label("Correct!"),
-                    Some(_) => ui.label("Not quite. Try another answer or generate a new one."),
-                    None => ui.label("Pick an answer."),
+                match value {
+                    Ok(value) if value == state.target => {
+                        ui.label(format!("{text} = {value}. The parentheses did it!"));
+                    }
+                    Ok(value) => {
+                        ui.label(exercise::not_quite(&format!(
+                            "{text} = {value}, not {}.",
+                            state.target
+                        )));
+                    }
+                    Err(_) => {
+                        ui.label(exercise::not_quite(
+                            "The parentheses do not match up yet: every ( needs a ) after it.",
+                        ));
+                    }
                 }
             });
         },
     );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## What just happened\n\
-             Expressions are little machines that turn inputs into values.\n\
-             You can use their results anywhere a number is needed.\n\n\
-             Next up: **Hello, state** shows how to *store* a value in a named box."
-        );
-    });
+    nb.state(
+        &state_key("swap_state"),
+        SwapState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Swap and regroup") {
+                    return;
+                }
+                ui.add_space(6.0);
+                ui.label(
+                    "Does the order matter? Click an operator in the tree to swap its two sides.",
+                );
+                ui.label(
+                    "Regroup moves the parentheses, from (a + b) + c to a + (b + c) and back.",
+                );
+                ui.label("Watch whether the value changes.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    let before = state.op;
+                    let mut toggle = widgets::ChoiceToggle::new(&mut state.op).small();
+                    for (op, label) in SWAP_OPS {
+                        toggle = toggle.choice(op, label);
+                    }
+                    ui.add(toggle);
+                    if ui.add(widgets::Button::new("New numbers")).clicked() || state.op != before {
+                        state.regenerate();
+                    }
+                    if let Some(expr) = regroup(&state.expr) {
+                        if ui.add(widgets::Button::new("Regroup")).clicked() {
+                            state.apply(expr);
+                        }
+                    }
+                });
+                ui.add_space(6.0);
+
+                expr::history(ui, &state.history);
+                ui.add(CodeBlock::line(&expr_to_string(&state.expr)));
+                ui.add_space(6.0);
+                let tree = state.expr.to_tree();
+                if let Some(path) = draw_tree_interactive(ui, "swap-node", &tree, |_| false, None) {
+                    if matches!(expr_at_path(&state.expr, &path), Some(Expr::Binary(..))) {
+                        let expr = swap_at(state.expr.clone(), &path);
+                        state.apply(expr);
+                    }
+                }
+                ui.add_space(6.0);
+
+                let Ok(value) = state.expr.eval() else {
+                    return;
+                };
+                if state.history.is_empty() {
+                    ui.label(format!("The value is {value}. Now rearrange it."));
+                    return;
+                }
+                let name = Arithmetic::binary_name(state.op);
+                if value == state.original {
+                    ui.label(format!("Still {value}, the same as at the start."));
+                    if state.op != BinaryOp::Sub {
+                        ui.label(format!(
+                            "With {name}, swapping and regrouping never change the value."
+                        ));
+                        ui.label(
+                        "Mathematicians call that commutative (swap) and associative (regroup).",
+                    );
+                    }
+                } else {
+                    ui.label(
+                        RichText::new(format!(
+                            "Now {value}, but it started at {}!",
+                            state.original
+                        ))
+                        .color(ui.visuals().selection.stroke.color),
+                    );
+                    ui.label(format!("With {name}, the order and the grouping matter."));
+                    ui.label("So subtraction is neither commutative nor associative.");
+                }
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("overflow_state"),
+        OverflowState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "At the edge of the numbers") {
+                    return;
+                }
+                ui.add_space(6.0);
+                ui.label("The computer keeps every number in 64 bits, so there is a largest");
+                ui.label(format!("one, {}, and a smallest, {}.", i64::MAX, i64::MIN));
+                ui.label("Step past either end and the stepper stops with an overflow.");
+                ui.label("The processor itself does not stop: it wraps around to the other end.");
+                ui.add_space(6.0);
+
+                ui.horizontal(|ui| {
+                    ui.label("Expression:");
+                    ui.add(widgets::TextField::singleline(&mut state.input));
+                });
+                ui.horizontal(|ui| {
+                    for (label, input) in [
+                        ("Largest + 1", "9223372036854775807 + 1"),
+                        ("Smallest - 1", "-9223372036854775807 - 1 - 1"),
+                        ("Big times big", "3037000500 * 3037000500"),
+                        ("2 ^ 64", "2 ^ 64"),
+                    ] {
+                        if ui.add(widgets::Button::new(label).small()).clicked() {
+                            state.input = input.to_string();
+                        }
+                    }
+                });
+                ui.add(widgets::ToggleButton::new(
+                    &mut state.wrap,
+                    "Wrap around like the processor",
+                ));
+                ui.add_space(6.0);
+
+                let mut parser = Parser::new(&state.input);
+                let expr = match parser.parse_expression() {
+                    Ok(expr) => expr,
+                    Err(error) => {
+                        expr::parse_error(
+                            ui,
+                            &state.input,
+                            &error,
+                            parser.scan.pos(),
+                            "Tip: check parentheses or a missing number/operator.",
+                        );
+                        return;
+                    }
+                };
+                let mut wrap = None;
+                let value = if state.wrap {
+                    eval_wrapping(&expr, &mut wrap)
+                } else {
+                    expr.eval()
+                };
+                let value = match value {
+                    Ok(value) => value,
+                    Err(error) => {
+                        let overflow = error.contains(OVERFLOW);
+                        ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+                        if overflow && !state.wrap {
+                            ui.label("Switch on wrapping to see what the processor answers.");
+                        } else if state.wrap {
+                            ui.label("Wrapping only helps numbers that run off an end.");
+                            ui.label("This part has no answer on the processor either.");
+                        }
+                        return;
+                    }
+                };
+                number_line(ui, value, wrap.as_ref());
+                ui.add_space(4.0);
+                match wrap {
+                    Some(wrap) => {
+                        let (end, other) = if wrap.above {
+                            ("largest", "smallest")
+                        } else {
+                            ("smallest", "largest")
+                        };
+                        ui.label(format!(
+                            "{} ran past the {end} number and came back in at the {other}.",
+                            wrap.code
+                        ));
+                        ui.label(format!(
+                            "So the processor answers {value}, which is off by 2^64."
+                        ));
+                        ui.label("Programs that count money or time have to watch out for this!");
+                    }
+                    None => {
+                        ui.label(format!("{value} fits, nothing wrapped."));
+                    }
+                }
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## What just happened\n\
+         Expressions are little machines that turn inputs into values.\n\
+         You can use their results anywhere a number is needed.\n\n\
+         Next up: **Hello, state** shows how to *store* a value in a named box.",
+    );
 }
@@ -1,9 +1,10 @@
-use egui::text::LayoutJob;
-use egui::RichText;
-use egui::TextStyle;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-use crate::chapters::Chapter;
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Quiz, Review};
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::worksheet;
 use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
 use GORBIE::prelude::*;
 
@@ -13,6 +14,10 @@ fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
     (CHAPTER, key)
 }
 
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
 struct FunctionMachineState {
     input: i32,
 }
@@ -85,140 +90,83 @@ struct FunctionQuestion {
     output: i32,
 }
 
-struct PracticeState {
-    rng: SimpleRng,
-    question: FunctionQuestion,
-    choices: Vec<i32>,
-    selection: Option<i32>,
+impl FunctionQuestion {
+    fn lines(&self) -> [String; 4] {
+        [
+            format!("function {}(n) {{", self.kind.name()),
+            format!("    {}", self.kind.body()),
+            "}".to_string(),
+            format!("result <- {}({})", self.kind.name(), self.input),
+        ]
+    }
 }
 
-impl Default for PracticeState {
-    fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
-        let question = generate_question(&mut rng);
-        let choices = build_choices(&mut rng, question.output);
-        Self {
-            rng,
-            question,
-            choices,
-            selection: None,
+impl Exercise for FunctionQuestion {
+    type Answer = i32;
+
+    fn generate(rng: &mut SimpleRng, difficulty: Difficulty) -> Self {
+        let last_kind = if difficulty.harder_ops() { 2 } else { 1 };
+        let kind = match rng.gen_range_i32(0, last_kind) {
+            0 => FunctionKind::Double,
+            1 => FunctionKind::AddTwo,
+            _ => FunctionKind::Square,
+        };
+        let input = rng.gen_range_i32(0, difficulty.max_value());
+        let output = kind.apply(input);
+        FunctionQuestion {
+            kind,
+            input,
+            output,
         }
     }
-}
 
-impl PracticeState {
-    fn regenerate(&mut self) {
-        self.question = generate_question(&mut self.rng);
-        self.choices = build_choices(&mut self.rng, self.question.output);
-        self.selection = None;
+    fn prompt(&self) -> String {
+        "What is the result of this function call?".to_string()
     }
-}
-
-struct SimpleRng {
-    state: u64,
-}
 
-impl SimpleRng {
-    fn new(seed: u64) -> Self {
-        Self { state: seed.max(1) }
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::new(&self.lines()));
     }
 
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
+    fn text(&self) -> String {
+        format!("```text\n{}\n```", self.lines().join("\n"))
     }
 
-    fn gen_range_i32(&mut self, min: i32, max: i32) -> i32 {
-        let span = (max - min + 1) as u32;
-        let value = self.next_u32() % span;
-        min + value as i32
+    fn answer(&self) -> i32 {
+        self.output
     }
 
-    fn shuffle<T>(&mut self, values: &mut [T]) {
-        if values.len() <= 1 {
-            return;
-        }
-        for i in (1..values.len()).rev() {
-            let j = self.gen_range_i32(0, i as i32) as usize;
-            values.swap(i, j);
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<i32> {
+        let mut distractors = Vec::new();
+        while distractors.len() < 3 {
+            let delta = rng.gen_range_i32(-3, 3);
+            if delta == 0 {
+                continue;
+            }
+            let candidate = self.output + delta;
+            if candidate < 0 {
+                continue;
+            }
+            if !distractors.contains(&candidate) {
+                distractors.push(candidate);
+            }
         }
+        distractors
     }
-}
-
-fn seed_from_time() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_nanos() as u64)
-        .unwrap_or(1)
-}
-
-fn generate_question(rng: &mut SimpleRng) -> FunctionQuestion {
-    let kind = match rng.gen_range_i32(0, 2) {
-        0 => FunctionKind::Double,
-        1 => FunctionKind::AddTwo,
-        _ => FunctionKind::Square,
-    };
-    let input = rng.gen_range_i32(0, 9);
-    let output = kind.apply(input);
-    FunctionQuestion { kind, input, output }
-}
 
-fn build_choices(rng: &mut SimpleRng, answer: i32) -> Vec<i32> {
-    let mut choices = vec![answer];
-    while choices.len() < 4 {
-        let delta = rng.gen_range_i32(-3, 3);
-        if delta == 0 {
-            continue;
-        }
-        let candidate = answer + delta;
-        if candidate < 0 || candidate > 20 {
-            continue;
-        }
-        if !choices.contains(&candidate) {
-            choices.push(candidate);
-        }
+    fn choice_label(answer: &i32) -> String {
+        answer.to_string()
     }
-    rng.shuffle(&mut choices);
-    choices
 }
 
-fn code_frame(ui: &mut egui::Ui, job: LayoutJob) {
-    let bg = ui.visuals().code_bg_color;
-    let stroke = ui.visuals().widgets.inactive.bg_stroke;
-    egui::Frame::group(ui.style())
-        .fill(bg)
-        .stroke(stroke)
-        .inner_margin(egui::Margin::same(8))
-        .corner_radius(10.0)
-        .show(ui, |ui| {
-            ui.label(job);
-        });
+/// A function call to trace on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<FunctionQuestion>(rng, difficulty)
 }
 
-fn code_job(ui: &egui::Ui, lines: &[String]) -> LayoutJob {
-    let font = TextStyle::Monospace.resolve(ui.style());
-    let format = egui::TextFormat::simple(font, ui.visuals().text_color());
-    let mut job = LayoutJob::default();
-    for (index, line) in lines.iter().enumerate() {
-        job.append(line, 0.0, format.clone());
-        if index + 1 < lines.len() {
-            job.append("\n", 0.0, format.clone());
-        }
-    }
-    job
-}
-
-fn question_code(question: &FunctionQuestion) -> Vec<String> {
-    vec![
-        format!("function {}(n) {{", question.kind.name()),
-        format!("    {}", question.kind.body()),
-        "}".to_string(),
-        format!("result <- {}({})", question.kind.name(), question.input),
-    ]
+/// Checks a generated function call for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<FunctionQuestion>(rng, difficulty)
 }
 
 fn double_plus_one(input: i32) -> i32 {
@@ -232,48 +180,44 @@ fn step_output(input: i32) -> i32 {
 }
 
 pub fn functions(nb: &mut NotebookCtx) {
-    nb.view(|ui| {
-        md!(
-            ui,
-            "# Functions as reusable steps\n\
-             A **function** is a named recipe. It takes some input, follows steps,\n\
-             and gives back a result. You can call the same function many times\n\
-             instead of rewriting the same logic."
-        );
-    });
-
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## A tiny story\n\
-             You pack lunches for three friends. The steps are the same each time:\n\
-             slice bread, add filling, wrap it up. You could repeat the steps by hand,\n\
-             but it is easier to name the recipe once and reuse it."
-        );
-    });
-
-    nb.view(|ui| {
-        md!(
-            ui,
-             "## Define and call\n\
-             A function has a **name** and a **parameter**. The parameter is the input.\n\
-             The last line is the result it gives back.\n\
-             ```text\n\
-             function double(n) {{\n\
-                 n * 2\n\
-             }}\n\
-             result <- double(4)\n\
-             ```\n\
-             The call `double(4)` means: run the recipe with input `4`."
-        );
-    });
+    speech::lesson(
+        nb,
+        "# Functions as reusable steps\n\
+         A **function** is a named recipe. It takes some input, follows steps,\n\
+         and gives back a result. You can call the same function many times\n\
+         instead of rewriting the same logic.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         You pack lunches for three friends. The steps are the same each time:\n\
+         slice bread, add filling, wrap it up. You could repeat the steps by hand,\n\
+         but it is easier to name the recipe once and reuse it.",
+    );
+
+    speech::lesson(
+        nb,
+        "## Define and call\n\
+         A function has a **name** and a **parameter**. The parameter is the input.\n\
+         The last line is the result it gives back.\n\
+         ```text\n\
+         function double(n) {\n\
+             n * 2\n\
+         }\n\
+         result <- double(4)\n\
+         ```\n\
+         The call `double(4)` means: run the recipe with input `4`.",
+    );
 
     nb.state(
-        &chapter_key("function_machine_state"),
+        &state_key("function_machine_state"),
         FunctionMachineState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Function machine").heading());
+                if !card_heading(ui, "Function machine") {
+                    return;
+                }
                 ui.add_space(4.0);
                 ui.label("Slide the input and watch the output change.");
                 ui.add_space(6.0);
@@ -289,7 +233,7 @@ pub fn functions(nb: &mut NotebookCtx) {
                     "}".to_string(),
                 ];
                 ui.add_space(6.0);
-                code_frame(ui, code_job(ui, &lines));
+                ui.add(CodeBlock::new(&lines));
 
                 let output = double_plus_one(state.input);
                 ui.add_space(6.0);
@@ -300,11 +244,13 @@ pub fn functions(nb: &mut NotebookCtx) {
     );
 
     nb.state(
-        &chapter_key("call_counter_state"),
+        &state_key("call_counter_state"),
         CallCounterState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Call it many times").heading());
+                if !card_heading(ui, "Call it many times") {
+                    return;
+                }
                 ui.add_space(4.0);
                 ui.label("A function is reusable. Each call is a fresh run.");
                 ui.add_space(6.0);
@@ -335,46 +281,53 @@ pub fn functions(nb: &mut NotebookCtx) {
     );
 
     nb.state(
-        &chapter_key("function_practice_state"),
-        PracticeState::default(),
-        |ui, state| {
+        &state_key("function_practice_state"),
+        MultipleChoice::<FunctionQuestion>::default(),
+        |ui, practice| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Quick practice").heading());
-                ui.add_space(6.0);
-                ui.label("What is the result of this function call?");
-                ui.add_space(6.0);
-                if ui.add(widgets::Button::new("New exercise")).clicked() {
-                    state.regenerate();
+                if !card_heading(ui, "Quick practice") {
+                    return;
                 }
-
-                ui.add_space(6.0);
-                let lines = question_code(&state.question);
-                code_frame(ui, code_job(ui, &lines));
                 ui.add_space(6.0);
+                practice.show(ui, chapter_key("function_practice_state"));
+            });
+        },
+    );
 
-                let mut toggle = widgets::ChoiceToggle::new(&mut state.selection).small();
-                for choice in &state.choices {
-                    toggle = toggle.choice(Some(*choice), choice.to_string());
-                }
-                ui.add(toggle);
-                ui.add_space(4.0);
-                match state.selection {
-                    Some(value) if value == state.question.output => ui.label("Correct!"),
-                    Some(_) => ui.label("Not quite. Try again."),
-                    None => ui.label("Pick an answer."),
+    nb.state(
+        &state_key("review_state"),
+        Review::<FunctionQuestion>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
                 }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("function_practice_state"));
             });
         },
     );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## Recap\n\
-             - A function is a named recipe.\n\
-             - Inputs are called parameters.\n\
-             - Calling a function runs the steps and gives a result.\n\
-             - Reuse functions to avoid repeating the same work."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - A function is a named recipe.\n\
+         - Inputs are called parameters.\n\
+         - Calling a function runs the steps and gives a result.\n\
+         - Reuse functions to avoid repeating the same work.",
+    );
+
+    nb.state(
+        &state_key("quiz_state"),
+        Quiz::<FunctionQuestion>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Chapter quiz") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("quiz_state"));
+            });
+        },
+    );
 }
@@ -1,12 +1,20 @@
-use egui::text::LayoutJob;
-use egui::RichText;
 use egui::TextStyle;
-use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::chapters::Chapter;
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::exercise;
 use crate::flowchart::{
-    paint_flowchart, Flowchart, FlowchartEdge, FlowchartNode, FlowchartNodeKind, FlowchartStyle,
+    flowchart_svg, paint_flowchart, Flowchart, FlowchartEdge, FlowchartNode, FlowchartNodeKind,
+    FlowchartStyle,
 };
+use crate::glossary;
+use crate::keyboard::AnswerToggle;
+use crate::palette::Palette;
+use crate::progress;
+use crate::rng::{self, SimpleRng};
+use crate::speech;
+use crate::stepper::Controls;
+use crate::svg;
 use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
 use GORBIE::prelude::*;
 
@@ -16,36 +24,8 @@ fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
     (CHAPTER, key)
 }
 
-struct SimpleRng {
-    state: u64,
-}
-
-impl SimpleRng {
-    fn new(seed: u64) -> Self {
-        Self { state: seed.max(1) }
-    }
-
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
-    }
-
-    fn gen_range_i32(&mut self, min: i32, max: i32) -> i32 {
-        let span = (max - min + 1) as u32;
-        let value = self.next_u32() % span;
-        min + value as i32
-    }
-}
-
-fn seed_from_time() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_nanos() as u64)
-        .unwrap_or(1)
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
 }
 
 struct PlannerState {
@@ -142,16 +122,19 @@ struct Scenario {
 
 struct RandomPracticeState {
     rng: SimpleRng,
+    seed: u64,
     scenario: Scenario,
     selection: Option<bool>,
 }
 
 impl Default for RandomPracticeState {
     fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
         let scenario = generate_scenario(&mut rng);
         Self {
             rng,
+            seed,
             scenario,
             selection: None,
         }
@@ -160,6 +143,13 @@ impl Default for RandomPracticeState {
 
 impl RandomPracticeState {
     fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
+    }
+
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
         self.scenario = generate_scenario(&mut self.rng);
         self.selection = None;
     }
@@ -326,38 +316,6 @@ fn decision_code_lines<Ctx>(decision: &Decision<Ctx>) -> Vec<String> {
     lines
 }
 
-fn code_frame(ui: &mut egui::Ui, job: LayoutJob) {
-    let bg = ui.visuals().code_bg_color;
-    let stroke = ui.visuals().widgets.inactive.bg_stroke;
-    egui::Frame::group(ui.style())
-        .fill(bg)
-        .stroke(stroke)
-        .inner_margin(egui::Margin::same(8))
-        .corner_radius(10.0)
-        .show(ui, |ui| {
-            ui.label(job);
-        });
-}
-
-fn highlight_line_job(ui: &egui::Ui, lines: &[&str], highlight: Option<usize>) -> LayoutJob {
-    let font = TextStyle::Monospace.resolve(ui.style());
-    let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
-    let highlight_format = egui::TextFormat::simple(font, GORBIE::themes::ral(2009));
-    let mut job = LayoutJob::default();
-    for (index, line) in lines.iter().enumerate() {
-        let format = if Some(index) == highlight {
-            &highlight_format
-        } else {
-            &normal
-        };
-        job.append(line, 0.0, format.clone());
-        if index + 1 < lines.len() {
-            job.append("\n", 0.0, normal.clone());
-        }
-    }
-    job
-}
-
 fn text_width(ui: &egui::Ui, text: &str, font_id: &egui::FontId) -> f32 {
     ui.fonts_mut(|fonts| {
         fonts
@@ -378,6 +336,7 @@ fn paint_if_else_flowchart<Ctx>(
     }
     let width = ui.available_width().max(240.0);
     let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
     let mut action_label_width = text_width(ui, else_action.label, &font_id);
     let mut condition_label_width: f32 = 0.0;
     for (condition, action) in &steps {
@@ -388,11 +347,12 @@ fn paint_if_else_flowchart<Ctx>(
     condition_label_width = condition_label_width.max(value_width);
 
     let action_box_w = (action_label_width + 24.0).clamp(96.0, width * 0.45);
-    let edge_width: f32 = 2.5;
+    let edge_width = Palette::from_ui(ui).stroke_width(2.5);
     let start_r: f32 = edge_width * 2.5;
-    let action_box_h: f32 = 28.0;
+    let action_box_h = row_height + 14.0;
     let condition_box_w = (condition_label_width + 28.0).clamp(120.0, width * 0.55);
-    let condition_box_h: f32 = 40.0;
+    // Two lines: the condition and its current value.
+    let condition_box_h = row_height * 2.0 + 12.0;
     let action_gap: f32 = 6.0;
     let action_drop: f32 = condition_box_h / 2.0 + action_box_h / 2.0 + action_gap;
     let row_gap = 28.0;
@@ -410,7 +370,7 @@ fn paint_if_else_flowchart<Ctx>(
         + bottom_padding
         + action_extra)
         .max(140.0);
-    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
     let mut style = FlowchartStyle::from_ui(ui);
     style.start_radius = start_r;
 
@@ -512,53 +472,51 @@ fn paint_if_else_flowchart<Ctx>(
             .active(chosen >= steps.len()),
     );
 
-    let chart = Flowchart { rect, nodes, edges };
+    let chart = Flowchart {
+        id: response.id,
+        rect,
+        nodes,
+        edges,
+    };
     paint_flowchart(ui, &chart, &style);
+    svg::export_button(ui, "flowchart", || flowchart_svg(&chart, &style));
 }
 
 pub fn if_else(nb: &mut NotebookCtx) {
-    nb.view(|ui| {
-        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            md!(
-                ui,
-                "# Forks in the Road\n\
-                 Programs often face choices: **if** something is true, do one thing,\n\
-                 **else** do something different. An `if/else` is the tool for those choices.\n\
-                 It always picks **one** path, never both.\n\
-                 This lets you turn real-world questions into clear, testable rules."
-            );
-        });
-    });
+    speech::lesson(
+        nb,
+        "# Forks in the Road\n\
+         Programs often face choices: **if** something is true, do one thing,\n\
+         **else** do something different. An `if/else` is the tool for those choices.\n\
+         It always picks **one** path, never both.\n\
+         This lets you turn real-world questions into clear, testable rules.",
+    );
 
-    nb.view(|ui| {
-        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            md!(
-                ui,
-                "## A tiny story\n\
-                 You walk outside and ask a simple question: *Is it raining?*\n\
-                 If yes, you grab an umbrella. If no, you keep walking.\n\
-                 The question is the **condition**, and the umbrella/keep-walking\n\
-                 are the two **branches**. A decision picks **one** branch."
-            );
-        });
-    });
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         You walk outside and ask a simple question: *Is it raining?*\n\
+         If yes, you grab an umbrella. If no, you keep walking.\n\
+         The question is the **condition**, and the umbrella/keep-walking\n\
+         are the two **branches**. A decision picks **one** branch.",
+    );
 
     nb.state(
-        &chapter_key("flowchart_intro_state"),
+        &state_key("flowchart_intro_state"),
         FlowchartIntroState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                md!(
+                speech::markdown(
                     ui,
                     "## A flowchart first\n\
                      A flowchart is a picture of a decision.\n\
                      The box asks a yes/no question, and the arrows show the two paths.\n\
                      You follow the arrow that matches the answer and ignore the other.\n\
-                     Flip the condition below and watch the highlighted path change."
+                     Flip the condition below and watch the highlighted path change.",
                 );
                 ui.add_space(8.0);
                 ui.horizontal(|ui| {
-                    ui.label("Condition:");
+                    glossary::text(ui, "[[Condition]]:");
                     ui.add(
                         widgets::ChoiceToggle::binary(&mut state.condition, "false", "true")
                             .small(),
@@ -572,11 +530,13 @@ pub fn if_else(nb: &mut NotebookCtx) {
     );
 
     nb.state(
-        &chapter_key("planner_state"),
+        &state_key("planner_state"),
         PlannerState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Plan your day (flowchart)").heading());
+                if !card_heading(ui, "Plan your day (flowchart)") {
+                    return;
+                }
                 ui.add_space(4.0);
                 ui.label("Try different weather and see the plan change.");
                 ui.add_space(6.0);
@@ -610,76 +570,62 @@ pub fn if_else(nb: &mut NotebookCtx) {
         );
     });
 
-    nb.view(|ui| {
-        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            md!(
-                ui,
-                "## Why this matters\n\
-                 If/else lets you **guard** actions. You can check a rule before you act.\n\
-                 That means safer programs: only spend coins if you have enough,\n\
-                 only open the door if the code is correct, only send a message if it is valid.\n\
-                 Decisions help your program match how the real world works."
-            );
-        });
-    });
+    speech::lesson(
+        nb,
+        "## Why this matters\n\
+         If/else lets you **guard** actions. You can check a rule before you act.\n\
+         That means safer programs: only spend coins if you have enough,\n\
+         only open the door if the code is correct, only send a message if it is valid.\n\
+         Decisions help your program match how the real world works.",
+    );
 
-    nb.view(|ui| {
-        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            md!(
-                ui,
-                "## Writing it as code\n\
-                 The flowchart above turns into `if/else` code like this:\n\
-                 ```text\n\
-                 if condition {{\n\
-                     do_this\n\
-                 }} else {{\n\
-                     do_that\n\
-                 }}\n\
-                 ```\n\
-                 The condition must be a boolean. The lines inside the braces form a block.\n\
-                 Only one block runs, so your program takes one clear path."
-            );
-        });
-    });
+    speech::lesson(
+        nb,
+        "## Writing it as code\n\
+         The flowchart above turns into `if/else` code like this:\n\
+         ```text\n\
+         if condition {{\n\
+         do_this\n\
+         }} else {{\n\
+         do_that\n\
+         }}\n\
+         ```\n\
+         The condition must be a boolean. The lines inside the braces form a block.\n\
+         Only one block runs, so your program takes one clear path.",
+    );
 
-    nb.view(|ui| {
-        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            md!(
-                ui,
-                "## Conditions are booleans\n\
-                 The `condition` in an if/else must be **true** or **false**.\n\
-                 That means any boolean expression works here.\n\
-                 You can use variables, comparisons, and logic operators to build a condition.\n\
-                 ```text\n\
-                 if true {{ ... }}\n\
-                 if (a and b) or not c {{ ... }}\n\
-                 ```"
-            );
-        });
-    });
+    speech::lesson(
+        nb,
+        "## Conditions are booleans\n\
+         The `condition` in an if/else must be **true** or **false**.\n\
+         That means any boolean expression works here.\n\
+         You can use variables, comparisons, and logic operators to build a condition.\n\
+         ```text\n\
+         if true {{ ... }}\n\
+         if (a and b) or not c {{ ... }}\n\
+         ```",
+    );
 
-    nb.view(|ui| {
-        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            md!(
-                ui,
-                "## Comparisons create booleans\n\
-                 Comparisons like `>` or `==` produce a boolean.\n\
-                 That lets us use numbers inside if/else.\n\
-                 Read them as questions: *Is apples greater than 3? Is coins equal to price?*\n\
-                 ```text\n\
-                 if apples > 3 {{ ... }}\n\
-                 if coins == price {{ ... }}\n\
-                 ```"
-            );
-        });
-    });
+    speech::lesson(
+        nb,
+        "## Comparisons create booleans\n\
+         Comparisons like `>` or `==` produce a boolean.\n\
+         That lets us use numbers inside if/else.\n\
+         Read them as questions: *Is apples greater than 3? Is coins equal to price?*\n\
+         ```text\n\
+         if apples > 3 {{ ... }}\n\
+         if coins == price {{ ... }}\n\
+         ```",
+    );
 
     nb.state(
-        &chapter_key("stepper_state"),
+        &state_key("stepper_state"),
         StepperState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Step through a decision").heading());
+                if !card_heading(ui, "Step through a decision") {
+                    return;
+                }
                 ui.add_space(4.0);
                 ui.label("Move through the decision one line at a time.");
                 ui.add_space(6.0);
@@ -702,42 +648,15 @@ pub fn if_else(nb: &mut NotebookCtx) {
 
                 let steps = build_steps(state.coins, state.price);
                 let max_step = steps.len().saturating_sub(1);
-                if state.step > max_step {
-                    state.step = max_step;
-                }
 
                 ui.add_space(6.0);
-                ui.horizontal(|ui| {
-                    if ui
-                        .add_enabled(state.step > 0, widgets::Button::new("Prev"))
-                        .clicked()
-                    {
-                        state.step = state.step.saturating_sub(1);
-                    }
-                    if ui
-                        .add_enabled(state.step < max_step, widgets::Button::new("Next"))
-                        .clicked()
-                    {
-                        state.step = (state.step + 1).min(max_step);
-                    }
-                    if ui.add(widgets::Button::new("Reset")).clicked() {
-                        state.step = 0;
-                    }
-                    ui.add_space(6.0);
-                    ui.label(format!(
-                        "Step {step}/{max_step}",
-                        step = state.step,
-                        max_step = max_step
-                    ));
-                });
+                Controls::new(&mut state.step, max_step).show(ui);
 
                 let step = &steps[state.step];
                 ui.add_space(8.0);
                 let decision = stepper_decision();
                 let code_lines = decision_code_lines(&decision);
-                let code_refs: Vec<&str> =
-                    code_lines.iter().map(String::as_str).collect();
-                code_frame(ui, highlight_line_job(ui, &code_refs, Some(step.line)));
+                ui.add(CodeBlock::new(&code_lines).highlight_line(Some(step.line)));
                 ui.add_space(6.0);
                 ui.label(&step.note);
                 let status = step.status.unwrap_or("(not set yet)");
@@ -754,17 +673,26 @@ pub fn if_else(nb: &mut NotebookCtx) {
     );
 
     nb.state(
-        &chapter_key("random_practice_state"),
+        &state_key("random_practice_state"),
         RandomPracticeState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Random practice").heading());
+                if !card_heading(ui, "Random practice") {
+                    return;
+                }
                 ui.add_space(6.0);
                 ui.label("Decide which branch runs.");
                 ui.add_space(6.0);
-                if ui.add(widgets::Button::new("New exercise")).clicked() {
-                    state.regenerate();
-                }
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New exercise")).clicked() {
+                        state.regenerate();
+                    }
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("random_practice_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                });
 
                 ui.add_space(6.0);
                 let coins = state.scenario.coins;
@@ -773,16 +701,23 @@ pub fn if_else(nb: &mut NotebookCtx) {
                 ui.label("If coins >= price, you buy it. Otherwise you do not.");
                 ui.add_space(6.0);
 
-                let mut toggle = widgets::ChoiceToggle::new(&mut state.selection).small();
+                let previous = state.selection;
+                let mut toggle = AnswerToggle::new(&mut state.selection);
                 toggle = toggle.choice(Some(true), "Buy");
                 toggle = toggle.choice(Some(false), "Do not buy");
                 ui.add(toggle);
                 ui.add_space(4.0);
+                if let Some(value) = state.selection.filter(|_| state.selection != previous) {
+                    progress::answer(
+                        chapter_key("random_practice_state"),
+                        value == state.scenario.can_buy,
+                    );
+                }
                 match state.selection {
                     Some(value) if value == state.scenario.can_buy => ui.label("Correct!"),
-                    Some(_) => ui.label("Not quite. Try again."),
+                    Some(_) => ui.label(exercise::not_quite("Try again.")),
                     None => ui.label("Pick a branch."),
-                }
+                };
             });
         },
     );
@@ -796,17 +731,13 @@ pub fn if_else(nb: &mut NotebookCtx) {
         );
     });
 
-    nb.view(|ui| {
-        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            md!(
-                ui,
-                "## Recap\n\
-                 - `if/else` chooses between two paths based on a question.\n\
-                 - The condition must be a boolean (true/false).\n\
-                 - Comparisons like `>` and `==` create booleans you can test.\n\
-                 - Only one branch runs; the other is skipped.\n\
-                 - Flowcharts and code are two views of the same decision."
-            );
-        });
-    });
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - `if/else` chooses between two paths based on a question.\n\
+         - The condition must be a boolean (true/false).\n\
+         - Comparisons like `>` and `==` create booleans you can test.\n\
+         - Only one branch runs; the other is skipped.\n\
+         - Flowcharts and code are two views of the same decision.",
+    );
 }
@@ -0,0 +1,463 @@
+use egui::TextStyle;
+
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::palette::Palette;
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::stepper::Controls;
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::Iterators;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Add(i64),
+    Mul(i64),
+    Square,
+    Even,
+    Odd,
+    Greater(i64),
+    Take(usize),
+}
+
+impl Op {
+    fn code(&self) -> String {
+        match self {
+            Op::Add(k) => format!("    .map(|x| x + {k})"),
+            Op::Mul(k) => format!("    .map(|x| x * {k})"),
+            Op::Square => "    .map(|x| x * x)".to_string(),
+            Op::Even => "    .filter(|x| *x % 2 == 0)".to_string(),
+            Op::Odd => "    .filter(|x| *x % 2 == 1)".to_string(),
+            Op::Greater(k) => format!("    .filter(|x| *x > {k})"),
+            Op::Take(n) => format!("    .take({n})"),
+        }
+    }
+
+    fn short(&self) -> String {
+        match self {
+            Op::Add(k) => format!("map x+{k}"),
+            Op::Mul(k) => format!("map x*{k}"),
+            Op::Square => "map x*x".to_string(),
+            Op::Even => "filter even".to_string(),
+            Op::Odd => "filter odd".to_string(),
+            Op::Greater(k) => format!("filter >{k}"),
+            Op::Take(n) => format!("take {n}"),
+        }
+    }
+
+    /// Applies a map or filter. `None` means the filter dropped the value.
+    fn apply(&self, value: i64) -> Option<i64> {
+        match self {
+            Op::Add(k) => Some(value + k),
+            Op::Mul(k) => Some(value * k),
+            Op::Square => Some(value * value),
+            Op::Even => (value % 2 == 0).then_some(value),
+            Op::Odd => (value % 2 == 1).then_some(value),
+            Op::Greater(k) => (value > *k).then_some(value),
+            Op::Take(_) => Some(value),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Pipeline {
+    end: i64,
+    ops: Vec<Op>,
+}
+
+impl Pipeline {
+    fn code_lines(&self) -> Vec<String> {
+        let mut lines = vec![format!("let out: Vec<i64> = (1..={})", self.end)];
+        lines.extend(self.ops.iter().map(Op::code));
+        lines.push("    .collect();".to_string());
+        lines
+    }
+}
+
+fn preset(index: usize) -> Pipeline {
+    match index {
+        0 => Pipeline {
+            end: 8,
+            ops: vec![Op::Mul(3), Op::Even, Op::Take(2)],
+        },
+        1 => Pipeline {
+            end: 10,
+            ops: vec![Op::Greater(4), Op::Add(1), Op::Take(3)],
+        },
+        _ => Pipeline {
+            end: 6,
+            ops: vec![Op::Square, Op::Take(4)],
+        },
+    }
+}
+
+const PRESET_TITLES: &[&str] = &["map, filter, take", "filter, map, take", "map, take"];
+
+/// What happened to one item pulled from the source.
+struct Pull {
+    input: i64,
+    stages: Vec<Option<i64>>,
+    output: Option<i64>,
+}
+
+/// Runs the pipeline lazily: items are pulled one at a time and the run
+/// stops as soon as a `take` is full, so later items are never touched.
+fn simulate(pipeline: &Pipeline) -> (Vec<Pull>, Vec<i64>) {
+    let mut taken = vec![0usize; pipeline.ops.len()];
+    let mut pulls = Vec::new();
+    let mut output = Vec::new();
+    for input in 1..=pipeline.end {
+        let full = pipeline
+            .ops
+            .iter()
+            .zip(&taken)
+            .any(|(op, count)| matches!(op, Op::Take(n) if count >= n));
+        if full {
+            break;
+        }
+        let mut stages = Vec::new();
+        let mut value = Some(input);
+        for (index, op) in pipeline.ops.iter().enumerate() {
+            value = value.and_then(|value| op.apply(value));
+            if value.is_some() && matches!(op, Op::Take(_)) {
+                taken[index] += 1;
+            }
+            stages.push(value);
+        }
+        if let Some(value) = value {
+            output.push(value);
+        }
+        pulls.push(Pull {
+            input,
+            stages,
+            output: value,
+        });
+    }
+    (pulls, output)
+}
+
+fn format_list(values: &[i64]) -> String {
+    let items: Vec<String> = values.iter().map(i64::to_string).collect();
+    format!("[{}]", items.join(", "))
+}
+
+#[derive(Default)]
+struct PipelineStepperState {
+    preset: usize,
+    step: usize,
+}
+
+impl Exercise for Pipeline {
+    type Answer = String;
+
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        let map = match rng.gen_range_i64(0, 2) {
+            0 => Op::Add(rng.gen_range_i64(1, 3)),
+            1 => Op::Mul(rng.gen_range_i64(2, 3)),
+            _ => Op::Square,
+        };
+        let filter = match rng.gen_range_i64(0, 2) {
+            0 => Op::Even,
+            1 => Op::Odd,
+            _ => Op::Greater(rng.gen_range_i64(2, 5)),
+        };
+        let take = Op::Take(rng.gen_range_i64(1, 3) as usize);
+        let ops = if rng.gen_range_i64(0, 1) == 0 {
+            vec![map, filter, take]
+        } else {
+            vec![filter, map, take]
+        };
+        Pipeline {
+            end: rng.gen_range_i64(5, 8),
+            ops,
+        }
+    }
+
+    fn prompt(&self) -> String {
+        "Pull the items through in your head. What ends up in out?".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::new(&self.code_lines()));
+    }
+
+    fn text(&self) -> String {
+        format!("```rust\n{}\n```", self.code_lines().join("\n"))
+    }
+
+    fn answer(&self) -> String {
+        format_list(&simulate(self).1)
+    }
+
+    /// Outputs from common misreadings: forgetting `take`, swapping the map
+    /// and filter, or taking before filtering.
+    fn distractors(&self, _rng: &mut SimpleRng) -> Vec<String> {
+        let mut no_take = self.clone();
+        no_take.ops.retain(|op| !matches!(op, Op::Take(_)));
+
+        let mut swapped = self.clone();
+        swapped.ops.swap(0, 1);
+
+        let mut take_first = self.clone();
+        if let Some(take) = take_first.ops.pop() {
+            take_first.ops.insert(0, take);
+        }
+
+        [no_take, swapped, take_first]
+            .iter()
+            .map(|variant| format_list(&simulate(variant).1))
+            .collect()
+    }
+
+    fn choice_label(answer: &String) -> String {
+        answer.clone()
+    }
+
+    fn hint(&self, _picked: &String) -> String {
+        "Follow one item at a time, in the order the steps are written.".to_string()
+    }
+}
+
+/// A pipeline to run on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<Pipeline>(rng, difficulty)
+}
+
+/// Checks a generated pipeline for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<Pipeline>(rng, difficulty)
+}
+
+/// One box per stage, with the item currently being pulled written under
+/// each stage it reached.
+fn paint_pipeline(ui: &mut egui::Ui, pipeline: &Pipeline, pull: Option<&Pull>, output: &[i64]) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
+    let box_h = row_height + 12.0;
+    let width = ui.available_width().max(360.0);
+    let height = box_h + row_height * 2.0 + 12.0;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let palette = Palette::from_ui(ui);
+    let highlight_color = palette.highlight;
+    let text_color = palette.text;
+    let weak_color = palette.weak_text;
+    let line_stroke = palette.line;
+    let fill = palette.code_fill;
+
+    let mut labels = vec![format!("1..={}", pipeline.end)];
+    labels.extend(pipeline.ops.iter().map(Op::short));
+    labels.push("collect".to_string());
+
+    let mut values: Vec<(String, egui::Color32)> = Vec::new();
+    match pull {
+        Some(pull) => {
+            values.push((pull.input.to_string(), highlight_color));
+            let mut dropped = false;
+            for stage in &pull.stages {
+                match (stage, dropped) {
+                    (_, true) => values.push((String::new(), weak_color)),
+                    (Some(value), false) => values.push((value.to_string(), highlight_color)),
+                    (None, false) => {
+                        dropped = true;
+                        values.push(("dropped".to_string(), weak_color));
+                    }
+                }
+            }
+        }
+        None => values.resize(labels.len() - 1, (String::new(), weak_color)),
+    }
+    values.push((format_list(output), text_color));
+
+    let gap = 14.0;
+    let columns = labels.len();
+    let box_w = (width - gap * (columns - 1) as f32) / columns as f32;
+    for (index, (label, (value, value_color))) in labels.iter().zip(&values).enumerate() {
+        let left = rect.left() + index as f32 * (box_w + gap);
+        let stage_rect =
+            egui::Rect::from_min_size(egui::pos2(left, rect.top() + 2.0), egui::vec2(box_w, box_h));
+        painter.rect(
+            stage_rect,
+            egui::CornerRadius::same(4),
+            fill,
+            line_stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.text(
+            stage_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            label,
+            font_id.clone(),
+            text_color,
+        );
+        painter.text(
+            stage_rect.center_bottom() + egui::vec2(0.0, 6.0),
+            egui::Align2::CENTER_TOP,
+            value,
+            font_id.clone(),
+            *value_color,
+        );
+        if index + 1 < columns {
+            let start = stage_rect.right_center();
+            painter.arrow(
+                start,
+                egui::vec2(gap - 2.0, 0.0),
+                egui::Stroke::new(1.5, line_stroke.color),
+            );
+        }
+    }
+}
+
+pub fn iterators(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Iterators\n\
+         An **iterator** hands out values one at a time.\n\
+         We can chain small steps onto it: `map` changes each value,\n\
+         `filter` keeps only some, and `take` stops after a few.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         Think of a sushi belt. Dishes come out one by one,\n\
+         the chef adds sauce, a helper removes the ones nobody ordered,\n\
+         and you stop eating when you are full.\n\
+         Nobody prepares dishes that will never be eaten: the kitchen only works when you pull.",
+    );
+
+    speech::lesson(
+        nb,
+        "## Lazy by design\n\
+         ```rust\n\
+         let out: Vec<i64> = (1..=8)\n    .map(|x| x * 3)\n    .filter(|x| *x % 2 == 0)\n    .take(2)\n    .collect();\n\
+         ```\n\
+         Nothing happens until `collect` starts asking for values.\n\
+         Each request pulls **one** item through the whole chain before the next one starts.",
+    );
+
+    nb.state(
+        &state_key("pipeline_stepper_state"),
+        PipelineStepperState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Pull one item at a time") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Each step pulls the next item from the range through every stage.");
+                ui.add_space(6.0);
+
+                let mut preset_index = state.preset;
+                let mut toggle = widgets::ChoiceToggle::new(&mut preset_index).small();
+                for (index, title) in PRESET_TITLES.iter().enumerate() {
+                    toggle = toggle.choice(index, *title);
+                }
+                ui.add(toggle);
+                if preset_index != state.preset {
+                    state.preset = preset_index;
+                    state.step = 0;
+                }
+
+                let pipeline = preset(state.preset);
+                let (pulls, _) = simulate(&pipeline);
+                let max_step = pulls.len();
+
+                ui.add_space(6.0);
+                Controls::new(&mut state.step, max_step)
+                    .next_label("Pull next")
+                    .counter_label("Pulls")
+                    .show(ui);
+
+                let lines = pipeline.code_lines();
+                ui.add_space(8.0);
+                ui.add(CodeBlock::new(&lines));
+                ui.add_space(8.0);
+
+                let current = state.step.checked_sub(1).map(|index| &pulls[index]);
+                let output: Vec<i64> = pulls[..state.step]
+                    .iter()
+                    .filter_map(|pull| pull.output)
+                    .collect();
+                paint_pipeline(ui, &pipeline, current, &output);
+                ui.add_space(6.0);
+
+                match current {
+                    None => {
+                        ui.label("Nothing has been pulled yet. The chain is just a plan.");
+                    }
+                    Some(pull) => match pull.output {
+                        Some(value) => {
+                            ui.label(format!("{} made it all the way and became {value}.", pull.input));
+                        }
+                        None => {
+                            ui.label(format!("{} was dropped by a filter.", pull.input));
+                        }
+                    },
+                }
+                if state.step == max_step {
+                    let untouched = pipeline.end - max_step as i64;
+                    if untouched > 0 {
+                        ui.label(format!(
+                            "take is full, so collect stops. The last {untouched} item(s) of the range were never touched."
+                        ));
+                    } else {
+                        ui.label("The range ran out, so collect stops.");
+                    }
+                }
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("output_quiz_state"),
+        MultipleChoice::<Pipeline>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Practice: predict the output") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("output_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<Pipeline>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("output_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - An iterator hands out one value at a time.\n\
+         - `map` changes values, `filter` keeps some, `take` stops early.\n\
+         - Iterators are lazy: nothing runs until something like `collect` pulls.\n\
+         - The order of the steps matters.\n\n\
+         Next up: **Strings and slices** looks at text, byte by byte.",
+    );
+}
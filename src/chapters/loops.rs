@@ -1,9 +1,14 @@
-use egui::text::LayoutJob;
-use egui::RichText;
-use egui::TextStyle;
-use std::time::{SystemTime, UNIX_EPOCH};
-
-use crate::chapters::Chapter;
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Quiz, Review};
+use crate::glossary;
+use crate::keyboard::AnswerToggle;
+use crate::progress;
+use crate::rng::{self, SimpleRng};
+use crate::speech;
+use crate::stepper::Controls;
+use crate::worksheet;
 use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
 use GORBIE::prelude::*;
 
@@ -13,6 +18,10 @@ fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
     (CHAPTER, key)
 }
 
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
 struct LoopStep {
     line: usize,
     count: i32,
@@ -46,42 +55,86 @@ impl Default for LoopVisualState {
     }
 }
 
-struct PracticeState {
-    rng: SimpleRng,
+struct CountingExercise {
     start: i32,
     limit: i32,
     answer: i32,
-    choices: Vec<i32>,
-    selection: Option<i32>,
 }
 
-impl Default for PracticeState {
-    fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
-        let (start, limit, answer) = generate_practice(&mut rng);
-        let choices = build_choices(&mut rng, answer);
+impl Exercise for CountingExercise {
+    type Answer = i32;
+
+    fn generate(rng: &mut SimpleRng, difficulty: Difficulty) -> Self {
+        let start = rng.gen_range_i32(0, 5);
+        // At most 6 runs at the start level, up to 7 higher up.
+        let most = (difficulty.max_value() - 3).clamp(3, 7);
+        let mut limit = rng.gen_range_i32(start + 2, start + most);
+        if limit > 12 {
+            limit = start + 4;
+        }
+        let answer = limit - start;
         Self {
-            rng,
             start,
             limit,
             answer,
-            choices,
-            selection: None,
         }
     }
-}
 
-impl PracticeState {
-    fn regenerate(&mut self) {
-        let (start, limit, answer) = generate_practice(&mut self.rng);
-        self.start = start;
-        self.limit = limit;
-        self.answer = answer;
-        self.choices = build_choices(&mut self.rng, answer);
-        self.selection = None;
+    fn prompt(&self) -> String {
+        "How many times does the loop body run?".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        for line in self.text().lines() {
+            ui.label(line);
+        }
+    }
+
+    fn text(&self) -> String {
+        format!(
+            "Start at {start}. Stop when count < {limit}.\nEach loop adds 1 to count.",
+            start = self.start,
+            limit = self.limit
+        )
+    }
+
+    fn answer(&self) -> i32 {
+        self.answer
+    }
+
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<i32> {
+        let mut distractors = Vec::new();
+        while distractors.len() < 3 {
+            let delta = rng.gen_range_i32(-3, 3);
+            if delta == 0 {
+                continue;
+            }
+            let candidate = self.answer + delta;
+            if !(0..=12).contains(&candidate) {
+                continue;
+            }
+            if !distractors.contains(&candidate) {
+                distractors.push(candidate);
+            }
+        }
+        distractors
+    }
+
+    fn choice_label(answer: &i32) -> String {
+        answer.to_string()
     }
 }
 
+/// A loop to count through on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<CountingExercise>(rng, difficulty)
+}
+
+/// Checks a generated counting loop for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<CountingExercise>(rng, difficulty)
+}
+
 struct TerminationScenario {
     start: i32,
     limit: i32,
@@ -92,16 +145,19 @@ struct TerminationScenario {
 
 struct TerminationPracticeState {
     rng: SimpleRng,
+    seed: u64,
     scenario: TerminationScenario,
     selection: Option<bool>,
 }
 
 impl Default for TerminationPracticeState {
     fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
         let scenario = pick_termination_scenario(&mut rng);
         Self {
             rng,
+            seed,
             scenario,
             selection: None,
         }
@@ -110,53 +166,18 @@ impl Default for TerminationPracticeState {
 
 impl TerminationPracticeState {
     fn regenerate(&mut self) {
-        self.scenario = pick_termination_scenario(&mut self.rng);
-        self.selection = None;
-    }
-}
-
-struct SimpleRng {
-    state: u64,
-}
-
-impl SimpleRng {
-    fn new(seed: u64) -> Self {
-        Self { state: seed.max(1) }
-    }
-
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
     }
 
-    fn gen_range_i32(&mut self, min: i32, max: i32) -> i32 {
-        let span = (max - min + 1) as u32;
-        let value = self.next_u32() % span;
-        min + value as i32
-    }
-
-    fn shuffle<T>(&mut self, values: &mut [T]) {
-        if values.len() <= 1 {
-            return;
-        }
-        for i in (1..values.len()).rev() {
-            let j = self.gen_range_i32(0, i as i32) as usize;
-            values.swap(i, j);
-        }
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        self.scenario = pick_termination_scenario(&mut self.rng);
+        self.selection = None;
     }
 }
 
-fn seed_from_time() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_nanos() as u64)
-        .unwrap_or(1)
-}
-
 fn build_steps(start: i32, limit: i32) -> Vec<LoopStep> {
     let mut steps = Vec::new();
     let mut count = start;
@@ -207,35 +228,6 @@ fn build_steps(start: i32, limit: i32) -> Vec<LoopStep> {
     steps
 }
 
-fn generate_practice(rng: &mut SimpleRng) -> (i32, i32, i32) {
-    let start = rng.gen_range_i32(0, 5);
-    let mut limit = rng.gen_range_i32(start + 2, start + 6);
-    if limit > 12 {
-        limit = start + 4;
-    }
-    let answer = limit - start;
-    (start, limit, answer)
-}
-
-fn build_choices(rng: &mut SimpleRng, answer: i32) -> Vec<i32> {
-    let mut choices = vec![answer];
-    while choices.len() < 4 {
-        let delta = rng.gen_range_i32(-3, 3);
-        if delta == 0 {
-            continue;
-        }
-        let candidate = answer + delta;
-        if candidate < 0 || candidate > 12 {
-            continue;
-        }
-        if !choices.contains(&candidate) {
-            choices.push(candidate);
-        }
-    }
-    rng.shuffle(&mut choices);
-    choices
-}
-
 fn pick_termination_scenario(rng: &mut SimpleRng) -> TerminationScenario {
     const SCENARIOS: &[TerminationScenario] = &[
         TerminationScenario {
@@ -291,42 +283,10 @@ fn pick_termination_scenario(rng: &mut SimpleRng) -> TerminationScenario {
     }
 }
 
-fn code_frame(ui: &mut egui::Ui, job: LayoutJob) {
-    let bg = ui.visuals().code_bg_color;
-    let stroke = ui.visuals().widgets.inactive.bg_stroke;
-    egui::Frame::group(ui.style())
-        .fill(bg)
-        .stroke(stroke)
-        .inner_margin(egui::Margin::same(8))
-        .corner_radius(10.0)
-        .show(ui, |ui| {
-            ui.label(job);
-        });
-}
-
-fn highlight_line_job(ui: &egui::Ui, lines: &[&str], highlight: Option<usize>) -> LayoutJob {
-    let font = TextStyle::Monospace.resolve(ui.style());
-    let normal = egui::TextFormat::simple(font.clone(), ui.visuals().text_color());
-    let highlight_format = egui::TextFormat::simple(font, GORBIE::themes::ral(2009));
-    let mut job = LayoutJob::default();
-    for (index, line) in lines.iter().enumerate() {
-        let format = if Some(index) == highlight {
-            &highlight_format
-        } else {
-            &normal
-        };
-        job.append(line, 0.0, format.clone());
-        if index + 1 < lines.len() {
-            job.append("\n", 0.0, normal.clone());
-        }
-    }
-    job
-}
-
-fn termination_code(ui: &egui::Ui, scenario: &TerminationScenario) -> LayoutJob {
+fn termination_code(scenario: &TerminationScenario) -> [String; 4] {
     let op = if scenario.delta >= 0 { "+" } else { "-" };
     let delta = scenario.delta.abs();
-    let lines = [
+    [
         format!("count <- {}", scenario.start),
         format!(
             "while count {} {} {{",
@@ -334,57 +294,51 @@ fn termination_code(ui: &egui::Ui, scenario: &TerminationScenario) -> LayoutJob
         ),
         format!("    count <- count {} {}", op, delta),
         "}".to_string(),
-    ];
-    let line_refs: Vec<&str> = lines.iter().map(String::as_str).collect();
-    highlight_line_job(ui, &line_refs, None)
+    ]
 }
 
 pub fn loops(nb: &mut NotebookCtx) {
-    nb.view(|ui| {
-        md!(
-            ui,
-            "# Loops and counting\n\
-             A **loop** repeats a block of steps until a rule says to stop.\n\
-             Counting gives the loop a clear goal and keeps it from running forever.\n\
-             The loop checks a **condition**, runs the **body**, and then updates the count."
-        );
-    });
+    speech::lesson(
+        nb,
+        "# Loops and counting\n\
+         A **loop** repeats a block of steps until a rule says to stop.\n\
+         Counting gives the loop a clear goal and keeps it from running forever.\n\
+         The loop checks a **condition**, runs the **body**, and then updates the count.",
+    );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## A tiny story\n\
-             You water three plants. Each plant needs one cup of water.\n\
-             The steps are the same each time: pour water, move to the next plant.\n\
-             A loop lets the computer repeat the steps and count how many are done.\n\
-             When the count reaches **3**, you stop."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         You water three plants. Each plant needs one cup of water.\n\
+         The steps are the same each time: pour water, move to the next plant.\n\
+         A loop lets the computer repeat the steps and count how many are done.\n\
+         When the count reaches **3**, you stop.",
+    );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## The loop shape\n\
-             A counting loop usually has three parts:\n\
-             1. **Start** the counter.\n\
-             2. **Check** the condition.\n\
-             3. **Update** the counter.\n\
-             ```text\n\
-             count <- 0\n\
-             while count < 5 {{\n\
-                 do_work\n\
-                 count <- count + 1\n\
-             }}\n\
-             ```"
-        );
-    });
+    speech::lesson(
+        nb,
+        "## The loop shape\n\
+         A counting loop usually has three parts:\n\
+         1. **Start** the counter.\n\
+         2. **Check** the condition.\n\
+         3. **Update** the counter.\n\
+         ```text\n\
+         count <- 0\n\
+         while count < 5 {{\n\
+         do_work\n\
+         count <- count + 1\n\
+         }}\n\
+         ```",
+    );
 
     nb.state(
-        &chapter_key("loop_visual_state"),
+        &state_key("loop_visual_state"),
         LoopVisualState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Counting visual").heading());
+                if !card_heading(ui, "Counting visual") {
+                    return;
+                }
                 ui.add_space(4.0);
                 ui.label("Each step runs the loop body once and fills one segment.");
                 ui.add_space(6.0);
@@ -432,11 +386,13 @@ pub fn loops(nb: &mut NotebookCtx) {
     });
 
     nb.state(
-        &chapter_key("loop_stepper_state"),
+        &state_key("loop_stepper_state"),
         LoopStepperState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Step through a loop").heading());
+                if !card_heading(ui, "Step through a loop") {
+                    return;
+                }
                 ui.add_space(4.0);
                 ui.label("Watch the counter grow one step at a time.");
                 ui.add_space(6.0);
@@ -462,34 +418,9 @@ pub fn loops(nb: &mut NotebookCtx) {
 
                 let steps = build_steps(state.start, state.limit);
                 let max_step = steps.len().saturating_sub(1);
-                if state.step > max_step {
-                    state.step = max_step;
-                }
 
                 ui.add_space(6.0);
-                ui.horizontal(|ui| {
-                    if ui
-                        .add_enabled(state.step > 0, widgets::Button::new("Prev"))
-                        .clicked()
-                    {
-                        state.step = state.step.saturating_sub(1);
-                    }
-                    if ui
-                        .add_enabled(state.step < max_step, widgets::Button::new("Next"))
-                        .clicked()
-                    {
-                        state.step = (state.step + 1).min(max_step);
-                    }
-                    if ui.add(widgets::Button::new("Reset")).clicked() {
-                        state.step = 0;
-                    }
-                    ui.add_space(6.0);
-                    ui.label(format!(
-                        "Step {step}/{max_step}",
-                        step = state.step,
-                        max_step = max_step
-                    ));
-                });
+                Controls::new(&mut state.step, max_step).show(ui);
 
                 let step = &steps[state.step];
                 ui.add_space(8.0);
@@ -500,8 +431,7 @@ pub fn loops(nb: &mut NotebookCtx) {
                     "    count <- count + 1".to_string(),
                     "}".to_string(),
                 ];
-                let line_refs: Vec<&str> = lines.iter().map(String::as_str).collect();
-                code_frame(ui, highlight_line_job(ui, &line_refs, Some(step.line)));
+                ui.add(CodeBlock::new(&lines).highlight_line(Some(step.line)));
                 ui.add_space(6.0);
                 ui.label(&step.note);
                 ui.label(format!("count = {}", step.count));
@@ -510,77 +440,100 @@ pub fn loops(nb: &mut NotebookCtx) {
     );
 
     nb.state(
-        &chapter_key("loop_termination_state"),
+        &state_key("loop_termination_state"),
         TerminationPracticeState::default(),
         |ui, state| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Will it stop?").heading());
+                if !card_heading(ui, "Will it stop?") {
+                    return;
+                }
                 ui.add_space(6.0);
-                ui.label("Decide whether the loop eventually stops.");
+                glossary::text(ui, "Decide whether the [[loop]] eventually stops.");
                 ui.add_space(6.0);
-                if ui.add(widgets::Button::new("New exercise")).clicked() {
-                    state.regenerate();
-                }
+                ui.horizontal(|ui| {
+                    if ui.add(widgets::Button::new("New exercise")).clicked() {
+                        state.regenerate();
+                    }
+                    if let Some(seed) =
+                        rng::seed_field(ui, chapter_key("loop_termination_state"), state.seed)
+                    {
+                        state.load_seed(seed);
+                    }
+                });
                 ui.add_space(6.0);
-                let job = termination_code(ui, &state.scenario);
-                code_frame(ui, job);
+                let lines = termination_code(&state.scenario);
+                ui.add(CodeBlock::new(&lines));
                 ui.add_space(6.0);
 
-                let mut toggle = widgets::ChoiceToggle::new(&mut state.selection).small();
+                let previous = state.selection;
+                let mut toggle = AnswerToggle::new(&mut state.selection);
                 toggle = toggle.choice(Some(true), "Stops");
                 toggle = toggle.choice(Some(false), "Runs forever");
                 ui.add(toggle);
                 ui.add_space(4.0);
+                if let Some(value) = state.selection.filter(|_| state.selection != previous) {
+                    progress::answer(
+                        chapter_key("loop_termination_state"),
+                        value == state.scenario.stops,
+                    );
+                }
                 match state.selection {
                     Some(value) if value == state.scenario.stops => ui.label("Correct!"),
-                    Some(_) => ui.label("Not quite. Watch how count changes."),
+                    Some(_) => ui.label(exercise::not_quite("Watch how count changes.")),
                     None => ui.label("Pick an answer."),
-                }
+                };
             });
         },
     );
 
     nb.state(
-        &chapter_key("loop_practice_state"),
-        PracticeState::default(),
-        |ui, state| {
+        &state_key("loop_practice_state"),
+        MultipleChoice::<CountingExercise>::default(),
+        |ui, practice| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                ui.label(RichText::new("Quick practice").heading());
-                ui.add_space(6.0);
-                ui.label("How many times does the loop body run?");
-                ui.add_space(6.0);
-                if ui.add(widgets::Button::new("New exercise")).clicked() {
-                    state.regenerate();
+                if !card_heading(ui, "Quick practice") {
+                    return;
                 }
-
-                ui.add_space(6.0);
-                ui.label(format!("Start at {start}. Stop when count < {limit}.", start = state.start, limit = state.limit));
-                ui.label("Each loop adds 1 to count.");
                 ui.add_space(6.0);
+                practice.show(ui, chapter_key("loop_practice_state"));
+            });
+        },
+    );
 
-                let mut toggle = widgets::ChoiceToggle::new(&mut state.selection).small();
-                for choice in &state.choices {
-                    toggle = toggle.choice(Some(*choice), choice.to_string());
-                }
-                ui.add(toggle);
-                ui.add_space(4.0);
-                match state.selection {
-                    Some(value) if value == state.answer => ui.label("Correct!"),
-                    Some(_) => ui.label("Not quite. Try again."),
-                    None => ui.label("Pick an answer."),
+    nb.state(
+        &state_key("review_state"),
+        Review::<CountingExercise>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
                 }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("loop_practice_state"));
             });
         },
     );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## Recap\n\
-             - A loop repeats steps until a condition becomes false.\n\
-             - Counting gives the loop a clear stop point.\n\
-             - A counting loop has start, check, body, and update.\n\
-             - If you forget the update, the loop can run forever."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - A loop repeats steps until a condition becomes false.\n\
+         - Counting gives the loop a clear stop point.\n\
+         - A counting loop has start, check, body, and update.\n\
+         - If you forget the update, the loop can run forever.",
+    );
+
+    nb.state(
+        &state_key("quiz_state"),
+        Quiz::<CountingExercise>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Chapter quiz") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("quiz_state"));
+            });
+        },
+    );
 }
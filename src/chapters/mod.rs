@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::sync::{OnceLock, RwLock};
 
 use egui::RichText;
@@ -5,13 +6,27 @@ use egui::RichText;
 use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
 use GORBIE::prelude::*;
 
+use crate::i18n::tr;
+
 mod booleans;
+mod borrowing;
+mod cli_project;
+mod concurrency;
+mod dashboard;
 mod expressions;
 mod functions;
 mod if_else;
+mod iterators;
 mod loops;
+mod modules;
 mod overview;
+mod ownership;
+mod placement;
+mod results;
 mod state;
+mod strings;
+mod structs_enums;
+mod traits;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Chapter {
@@ -22,6 +37,355 @@ pub enum Chapter {
     IfElse,
     Loops,
     Functions,
+    Ownership,
+    Borrowing,
+    StructsEnums,
+    Results,
+    Traits,
+    Iterators,
+    Strings,
+    Modules,
+    Concurrency,
+    CliProject,
+    Dashboard,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Track {
+    Start,
+    Foundations,
+    Rust,
+    /// Pages outside the lesson order, like the dashboard. They have a
+    /// button of their own in the selector.
+    Page,
+}
+
+/// Everything the app needs to know about a chapter. The selector, the
+/// overview and the router in `main.rs` all read [`CHAPTERS`].
+pub struct ChapterDef {
+    pub chapter: Chapter,
+    /// Stable name used in saved progress, e.g. `"if_else"`.
+    pub id: &'static str,
+    pub track: Track,
+    /// Position within the track, as printed in the overview.
+    pub number: u32,
+    pub title: &'static str,
+    pub render: fn(&mut NotebookCtx),
+    pub prerequisites: &'static [Chapter],
+    /// Keys of the cards that count towards completion.
+    pub exercises: &'static [&'static str],
+    /// The chapter's source file, which search reads headings and
+    /// markdown from.
+    pub source: &'static str,
+}
+
+impl Track {
+    /// Heading in the table of contents; the start track has none.
+    pub fn title(self) -> Option<&'static str> {
+        match self {
+            Track::Start | Track::Page => None,
+            Track::Foundations => Some("Track A - Programming foundations"),
+            Track::Rust => Some("Track C - Rust"),
+        }
+    }
+}
+
+impl ChapterDef {
+    /// Short label for the chapter selector: `"4"` or `"C4"`.
+    pub fn label(&self) -> String {
+        match self.track {
+            Track::Start | Track::Foundations | Track::Page => self.number.to_string(),
+            Track::Rust => format!("C{}", self.number),
+        }
+    }
+
+    /// Whether the chapter is part of the course: listed in the table of
+    /// contents, reached with Previous/Next and searched.
+    pub fn is_lesson(&self) -> bool {
+        self.track != Track::Page
+    }
+}
+
+pub const CHAPTERS: &[ChapterDef] = &[
+    ChapterDef {
+        chapter: Chapter::Overview,
+        id: "overview",
+        track: Track::Start,
+        number: 0,
+        title: "Overview",
+        render: overview::overview,
+        prerequisites: &[],
+        exercises: &[],
+        source: include_str!("overview.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Expressions,
+        id: "expressions",
+        track: Track::Foundations,
+        number: 1,
+        title: "Hello, expressions (values and math)",
+        render: expressions::expressions,
+        prerequisites: &[],
+        exercises: &[
+            "tree_exercise_state",
+            "tree_to_code_state",
+            "random_exercise_state",
+            "compare_state",
+            "dictation_state",
+            "builder_state",
+            "target_state",
+            "paren_state",
+        ],
+        source: include_str!("expressions.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Booleans,
+        id: "booleans",
+        track: Track::Foundations,
+        number: 2,
+        title: "To Bool or Not to Bool (yes/no logic)",
+        render: booleans::booleans,
+        prerequisites: &[Chapter::Expressions],
+        exercises: &[
+            "tree_exercise_state",
+            "random_exercise_state",
+            "simplify_state",
+            "assignment_state",
+            "reconstruct_state",
+        ],
+        source: include_str!("booleans.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::State,
+        id: "state",
+        track: Track::Foundations,
+        number: 3,
+        title: "Hello, state (variables and change)",
+        render: state::state,
+        prerequisites: &[Chapter::Expressions],
+        exercises: &["practice_state", "quiz_state"],
+        source: include_str!("state.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::IfElse,
+        id: "if_else",
+        track: Track::Foundations,
+        number: 4,
+        title: "Forks in the Road (if/else decisions)",
+        render: if_else::if_else,
+        prerequisites: &[Chapter::Booleans, Chapter::State],
+        exercises: &["random_practice_state"],
+        source: include_str!("if_else.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Loops,
+        id: "loops",
+        track: Track::Foundations,
+        number: 5,
+        title: "Loops and counting",
+        render: loops::loops,
+        prerequisites: &[Chapter::IfElse],
+        exercises: &[
+            "loop_termination_state",
+            "loop_practice_state",
+            "quiz_state",
+        ],
+        source: include_str!("loops.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Functions,
+        id: "functions",
+        track: Track::Foundations,
+        number: 6,
+        title: "Functions as reusable steps",
+        render: functions::functions,
+        prerequisites: &[Chapter::Loops],
+        exercises: &["function_practice_state", "quiz_state"],
+        source: include_str!("functions.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Ownership,
+        id: "ownership",
+        track: Track::Rust,
+        number: 1,
+        title: "Ownership and moves",
+        render: ownership::ownership,
+        prerequisites: &[Chapter::Functions],
+        exercises: &["compile_quiz_state"],
+        source: include_str!("ownership.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Borrowing,
+        id: "borrowing",
+        track: Track::Rust,
+        number: 2,
+        title: "Borrowing and references",
+        render: borrowing::borrowing,
+        prerequisites: &[Chapter::Ownership],
+        exercises: &["borrow_quiz_state"],
+        source: include_str!("borrowing.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::StructsEnums,
+        id: "structs_enums",
+        track: Track::Rust,
+        number: 3,
+        title: "Structs, enums, and pattern matching",
+        render: structs_enums::structs_enums,
+        prerequisites: &[Chapter::Ownership],
+        exercises: &["arm_quiz_state"],
+        source: include_str!("structs_enums.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Results,
+        id: "results",
+        track: Track::Rust,
+        number: 4,
+        title: "Errors and Result",
+        render: results::results,
+        prerequisites: &[Chapter::StructsEnums],
+        exercises: &["outcome_quiz_state"],
+        source: include_str!("results.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Traits,
+        id: "traits",
+        track: Track::Rust,
+        number: 5,
+        title: "Traits and generics (lightweight)",
+        render: traits::traits,
+        prerequisites: &[Chapter::StructsEnums],
+        exercises: &["bound_quiz_state"],
+        source: include_str!("traits.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Iterators,
+        id: "iterators",
+        track: Track::Rust,
+        number: 6,
+        title: "Iterators and loops",
+        render: iterators::iterators,
+        prerequisites: &[Chapter::Traits],
+        exercises: &["output_quiz_state"],
+        source: include_str!("iterators.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Strings,
+        id: "strings",
+        track: Track::Rust,
+        number: 7,
+        title: "Strings and slices",
+        render: strings::strings,
+        prerequisites: &[Chapter::Borrowing],
+        exercises: &["slice_quiz_state"],
+        source: include_str!("strings.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Modules,
+        id: "modules",
+        track: Track::Rust,
+        number: 8,
+        title: "Modules and crates",
+        render: modules::modules,
+        prerequisites: &[Chapter::Functions],
+        exercises: &["path_quiz_state"],
+        source: include_str!("modules.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Concurrency,
+        id: "concurrency",
+        track: Track::Rust,
+        number: 9,
+        title: "Concurrency basics",
+        render: concurrency::concurrency,
+        prerequisites: &[Chapter::Ownership, Chapter::Borrowing],
+        exercises: &["order_quiz_state"],
+        source: include_str!("concurrency.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::CliProject,
+        id: "cli_project",
+        track: Track::Rust,
+        number: 12,
+        title: "Mini project: a small CLI tool",
+        render: cli_project::cli_project,
+        prerequisites: &[Chapter::Results, Chapter::Iterators, Chapter::Strings],
+        exercises: &["project_state"],
+        source: include_str!("cli_project.rs"),
+    },
+    ChapterDef {
+        chapter: Chapter::Dashboard,
+        id: "dashboard",
+        track: Track::Page,
+        number: 0,
+        title: "Dashboard",
+        render: dashboard::dashboard,
+        prerequisites: &[],
+        exercises: &[],
+        source: include_str!("dashboard.rs"),
+    },
+];
+
+/// A generated exercise from `chapter` for a printed worksheet, or `None`
+/// for chapters whose exercises only work on screen.
+pub fn worksheet_item(
+    chapter: Chapter,
+    rng: &mut crate::rng::SimpleRng,
+    difficulty: crate::difficulty::Difficulty,
+) -> Option<crate::worksheet::Item> {
+    let item = match chapter {
+        Chapter::Expressions => expressions::worksheet_item,
+        Chapter::Booleans => booleans::worksheet_item,
+        Chapter::State => state::worksheet_item,
+        Chapter::Loops => loops::worksheet_item,
+        Chapter::Functions => functions::worksheet_item,
+        Chapter::Ownership => ownership::worksheet_item,
+        Chapter::Borrowing => borrowing::worksheet_item,
+        Chapter::StructsEnums => structs_enums::worksheet_item,
+        Chapter::Results => results::worksheet_item,
+        Chapter::Traits => traits::worksheet_item,
+        Chapter::Iterators => iterators::worksheet_item,
+        Chapter::Strings => strings::worksheet_item,
+        Chapter::Modules => modules::worksheet_item,
+        Chapter::Concurrency => concurrency::worksheet_item,
+        _ => return None,
+    };
+    Some(item(rng, difficulty))
+}
+
+/// One round of `--self-check` on the generators of `chapter`, or `None`
+/// for chapters without any.
+pub fn self_check(
+    chapter: Chapter,
+    rng: &mut crate::rng::SimpleRng,
+    difficulty: crate::difficulty::Difficulty,
+) -> Option<Result<(), String>> {
+    let check = match chapter {
+        Chapter::Expressions => expressions::self_check,
+        Chapter::Booleans => booleans::self_check,
+        Chapter::State => state::self_check,
+        Chapter::Loops => loops::self_check,
+        Chapter::Functions => functions::self_check,
+        Chapter::Ownership => ownership::self_check,
+        Chapter::Borrowing => borrowing::self_check,
+        Chapter::StructsEnums => structs_enums::self_check,
+        Chapter::Results => results::self_check,
+        Chapter::Traits => traits::self_check,
+        Chapter::Iterators => iterators::self_check,
+        Chapter::Strings => strings::self_check,
+        Chapter::Modules => modules::self_check,
+        Chapter::Concurrency => concurrency::self_check,
+        _ => return None,
+    };
+    Some(check(rng, difficulty))
+}
+
+/// The registry entry for `chapter`.
+pub fn def(chapter: Chapter) -> &'static ChapterDef {
+    CHAPTERS
+        .iter()
+        .find(|def| def.chapter == chapter)
+        .expect("every chapter is registered in CHAPTERS")
 }
 
 static CURRENT_CHAPTER: OnceLock<RwLock<Chapter>> = OnceLock::new();
@@ -31,7 +395,7 @@ fn chapter_lock() -> &'static RwLock<Chapter> {
         let default = if std::env::args().any(|arg| arg == "--headless") {
             Chapter::IfElse
         } else {
-            Chapter::Overview
+            crate::session::saved_chapter().unwrap_or(Chapter::Overview)
         };
         RwLock::new(default)
     })
@@ -45,56 +409,310 @@ pub fn set_chapter(chapter: Chapter) {
     *chapter_lock().write().expect("chapter lock poisoned") = chapter;
 }
 
-pub fn chapter_selector(nb: &mut NotebookCtx) {
-    nb.view(|ui| {
-        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            ui.label(RichText::new("Teaching notebooks").heading());
-            ui.add_space(6.0);
-            ui.label("Pick a chapter to open.");
-            ui.add_space(6.0);
+/// Key for a card's `nb.state`. The generation goes up when the chapter is
+/// reset, so its cards get fresh keys and start over from their initial
+/// values.
+pub type StateKey = (Chapter, u32, &'static str);
+
+static GENERATIONS: OnceLock<RwLock<HashMap<Chapter, u32>>> = OnceLock::new();
+
+fn generations_lock() -> &'static RwLock<HashMap<Chapter, u32>> {
+    GENERATIONS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+pub fn state_key(chapter: Chapter, key: &'static str) -> StateKey {
+    let generation = generations_lock()
+        .read()
+        .expect("generations lock poisoned")
+        .get(&chapter)
+        .copied()
+        .unwrap_or(0);
+    (chapter, generation, key)
+}
+
+/// Puts every card of `chapter` back to its initial state. Saved progress
+/// is kept.
+pub fn reset_chapter(chapter: Chapter) {
+    *generations_lock()
+        .write()
+        .expect("generations lock poisoned")
+        .entry(chapter)
+        .or_default() += 1;
+}
+
+/// Starts the course over for a new learner: every card in every chapter,
+/// unfolded, the saved progress, score and mistakes, and the overview as
+/// the open chapter.
+pub fn reset_all() {
+    for def in CHAPTERS {
+        reset_chapter(def.chapter);
+    }
+    crate::collapsed::clear();
+    crate::difficulty::clear();
+    crate::mastery::clear();
+    crate::patterns::clear();
+    crate::progress::clear();
+    crate::review::clear();
+    crate::schedule::clear();
+    crate::score::clear();
+    crate::stats::clear();
+    set_chapter(Chapter::Overview);
+}
+
+static PENDING_CARD: OnceLock<RwLock<Option<String>>> = OnceLock::new();
 
-            let mut selection = current_chapter();
-            let mut toggle = widgets::ChoiceToggle::new(&mut selection).small();
-            toggle = toggle.choice(Chapter::Overview, "0");
-            toggle = toggle.choice(Chapter::Expressions, "1");
-            toggle = toggle.choice(Chapter::Booleans, "2");
-            toggle = toggle.choice(Chapter::State, "3");
-            toggle = toggle.choice(Chapter::IfElse, "4");
-            toggle = toggle.choice(Chapter::Loops, "5");
-            toggle = toggle.choice(Chapter::Functions, "6");
-            ui.add(toggle);
-
-            if selection != current_chapter() {
-                set_chapter(selection);
+fn pending_card_lock() -> &'static RwLock<Option<String>> {
+    PENDING_CARD.get_or_init(|| RwLock::new(None))
+}
+
+/// `"Tree practice"` becomes `"tree_practice"`.
+pub fn card_slug(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
             }
-        });
-    });
+        })
+        .collect()
 }
 
-pub fn overview(nb: &mut NotebookCtx) {
-    overview::overview(nb);
+/// Opens the chapter named by a `--chapter` argument such as `loops` or
+/// `expressions#tree_practice`. The part after `#` is a card heading in
+/// lower case with underscores for spaces.
+pub fn open_target(target: &str) -> Result<(), String> {
+    let (id, card) = match target.split_once('#') {
+        Some((id, card)) => (id, Some(card)),
+        None => (target, None),
+    };
+    let Some(def) = CHAPTERS.iter().find(|def| def.id == id) else {
+        let ids: Vec<&str> = CHAPTERS.iter().map(|def| def.id).collect();
+        return Err(format!(
+            "Unknown chapter `{id}`. Known chapters: {}",
+            ids.join(", ")
+        ));
+    };
+    set_chapter(def.chapter);
+    crate::session::forget_scroll();
+    *pending_card_lock().write().expect("card lock poisoned") = card.map(str::to_string);
+    Ok(())
 }
 
-pub fn expressions(nb: &mut NotebookCtx) {
-    expressions::expressions(nb);
+/// Opens the chapter of the exercise `key` and scrolls to its card, found
+/// by the card's heading in the chapter source.
+pub fn open_exercise((chapter, key): crate::progress::ExerciseKey) {
+    let def = def(chapter);
+    let target = match crate::search::card_title(chapter, key) {
+        Some(title) => format!("{}#{}", def.id, card_slug(&title)),
+        None => def.id.to_string(),
+    };
+    if let Err(error) = open_target(&target) {
+        eprintln!("{error}");
+    }
 }
 
-pub fn booleans(nb: &mut NotebookCtx) {
-    booleans::booleans(nb);
+/// The heading at the top of an interactive card. Clicking it folds the
+/// card, and the fold is remembered; returns whether the rest of the card
+/// should be drawn. Also the scroll target for `--chapter <id>#<card>`,
+/// which unfolds the card.
+pub fn card_heading(ui: &mut egui::Ui, title: &str) -> bool {
+    let chapter = current_chapter();
+    let mut collapsed = crate::collapsed::is_collapsed(chapter, title);
+    let (arrow, hint) = if collapsed {
+        ("\u{25B6}", tr("card.expand"))
+    } else {
+        ("\u{25BC}", tr("card.collapse"))
+    };
+    let response = ui
+        .horizontal(|ui| {
+            let response = ui
+                .add(
+                    egui::Label::new(RichText::new(format!("{arrow} {title}")).heading())
+                        .sense(egui::Sense::click()),
+                )
+                .on_hover_cursor(egui::CursorIcon::PointingHand)
+                .on_hover_text(hint);
+            crate::speech::button(ui, title);
+            response
+        })
+        .inner;
+    crate::presentation::card_stop(ui);
+    let mut pending = pending_card_lock().write().expect("card lock poisoned");
+    if pending.as_deref() == Some(card_slug(title).as_str()) {
+        response.scroll_to_me(Some(egui::Align::TOP));
+        *pending = None;
+        collapsed = false;
+    } else if response.clicked() {
+        collapsed = !collapsed;
+    }
+    crate::collapsed::set_collapsed(chapter, title, collapsed);
+    crate::handout::card(title);
+    crate::stats::card_drawn(ui, title);
+    !collapsed || crate::handout::capturing()
 }
 
-pub fn state(nb: &mut NotebookCtx) {
-    state::state(nb);
+/// One line of the table of contents: a completion mark, the number and the
+/// title. A check means every exercise is solved, a dot means some are.
+fn toc_entry(ui: &mut egui::Ui, def: &ChapterDef, current: Chapter) {
+    let mark = match crate::progress::completion(def.chapter) {
+        Some((solved, total)) if solved == total => "\u{2713}",
+        Some((0, _)) => "\u{25CB}",
+        Some(_) => "\u{25CF}",
+        None => " ",
+    };
+    let text = format!("{mark} {}. {}", def.label(), def.title);
+    if ui.selectable_label(def.chapter == current, text).clicked() {
+        set_chapter(def.chapter);
+    }
+}
+
+pub fn chapter_selector(nb: &mut NotebookCtx) {
+    nb.state(&"chapter_selector", String::new(), |ui, query| {
+        crate::session::track_scroll(ui);
+        crate::stats::tick();
+        crate::handout::flush(ui.ctx());
+        crate::classroom::tick(ui.ctx());
+        crate::celebrate::paint(ui.ctx());
+        crate::settings::restore(ui.ctx());
+        chapter_keys(ui);
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            ui.label(RichText::new(tr("selector.title")).heading());
+            ui.add_space(6.0);
+            ui.label(tr("selector.intro"));
+            ui.label(RichText::new(tr("selector.keys")).weak());
+            ui.add_space(6.0);
+
+            ui.horizontal(|ui| {
+                ui.label(tr("selector.search"));
+                ui.add(widgets::TextField::singleline(query));
+            });
+            let hits = crate::search::search(query);
+            for hit in &hits {
+                let text = format!("{} \u{203A} {}", def(hit.chapter).title, hit.text);
+                if ui.selectable_label(false, text).clicked() {
+                    crate::search::open(hit);
+                    query.clear();
+                }
+            }
+            if !query.trim().is_empty() && hits.is_empty() {
+                ui.label(RichText::new(tr("selector.nothing_found")).weak());
+            }
+            ui.add_space(6.0);
+
+            let current = current_chapter();
+            for track in [Track::Start, Track::Foundations, Track::Rust] {
+                let entries = CHAPTERS.iter().filter(|def| def.track == track);
+                let Some(title) = track.title() else {
+                    for def in entries {
+                        toc_entry(ui, def, current);
+                    }
+                    continue;
+                };
+                egui::CollapsingHeader::new(title)
+                    .default_open(def(current).track == track)
+                    .show(ui, |ui| {
+                        for def in entries {
+                            toc_entry(ui, def, current);
+                        }
+                    });
+            }
+
+            if let Some(percent) = crate::progress::percent(current) {
+                ui.add_space(4.0);
+                ui.label(tr("selector.solved").replace("{percent}", &percent.to_string()));
+            }
+            crate::mastery::meter(ui, current);
+            if let Some(summary) = crate::score::summary() {
+                ui.label(summary);
+            }
+            ui.label(crate::difficulty::summary());
+            if ui
+                .add(widgets::Button::new(tr("selector.reset")).small())
+                .on_hover_text(tr("selector.reset_hover"))
+                .clicked()
+            {
+                reset_chapter(current);
+            }
+            crate::handout::button(ui, current);
+            if ui
+                .add(widgets::Button::new(tr("selector.dashboard")).small())
+                .clicked()
+            {
+                set_chapter(Chapter::Dashboard);
+            }
+            crate::share::load_box(ui);
+
+            ui.add_space(6.0);
+            crate::presentation::controls(ui);
+            egui::CollapsingHeader::new(tr("classroom.title")).show(ui, crate::classroom::controls);
+            egui::CollapsingHeader::new(tr("settings.title")).show(ui, crate::settings::controls);
+        });
+    });
 }
 
-pub fn if_else(nb: &mut NotebookCtx) {
-    if_else::if_else(nb);
+/// The lessons before and after `chapter` in [`CHAPTERS`]. Pages outside
+/// the lesson order have neither.
+fn neighbours(chapter: Chapter) -> (Option<&'static ChapterDef>, Option<&'static ChapterDef>) {
+    let lessons: Vec<&'static ChapterDef> = CHAPTERS.iter().filter(|def| def.is_lesson()).collect();
+    let Some(index) = lessons.iter().position(|def| def.chapter == chapter) else {
+        return (None, None);
+    };
+    (
+        index.checked_sub(1).map(|index| lessons[index]),
+        lessons.get(index + 1).copied(),
+    )
 }
 
-pub fn loops(nb: &mut NotebookCtx) {
-    loops::loops(nb);
+/// Page Up and Page Down open the previous and next chapter. Projector mode
+/// keeps those keys for scrolling.
+fn chapter_keys(ui: &egui::Ui) {
+    if crate::presentation::enabled() || ui.ctx().wants_keyboard_input() {
+        return;
+    }
+    let (up, down) = ui.input(|input| {
+        (
+            input.key_pressed(egui::Key::PageUp),
+            input.key_pressed(egui::Key::PageDown),
+        )
+    });
+    let (previous, next) = neighbours(current_chapter());
+    let target = match (up, down) {
+        (true, _) => previous,
+        (_, true) => next,
+        _ => None,
+    };
+    if let Some(def) = target {
+        set_chapter(def.chapter);
+    }
 }
 
-pub fn functions(nb: &mut NotebookCtx) {
-    functions::functions(nb);
+/// "Previous" and "Next" buttons at the end of every chapter, following the
+/// order of [`CHAPTERS`].
+pub fn chapter_nav(nb: &mut NotebookCtx) {
+    nb.view(|ui| {
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            let current = current_chapter();
+            let (previous, next) = neighbours(current);
+
+            ui.label(RichText::new(def(current).title).heading());
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                let response =
+                    ui.add_enabled(previous.is_some(), widgets::Button::new(tr("nav.previous")));
+                if let Some(def) = previous {
+                    if response.on_hover_text(def.title).clicked() {
+                        set_chapter(def.chapter);
+                    }
+                }
+                let response = ui.add_enabled(next.is_some(), widgets::Button::new(tr("nav.next")));
+                if let Some(def) = next {
+                    if response.on_hover_text(def.title).clicked() {
+                        set_chapter(def.chapter);
+                    }
+                }
+            });
+        });
+    });
 }
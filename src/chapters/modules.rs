@@ -0,0 +1,558 @@
+use egui::TextStyle;
+
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::palette::Palette;
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::Modules;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+struct Item {
+    name: &'static str,
+    parent: Option<usize>,
+    module: bool,
+}
+
+const ROOT: usize = 0;
+
+const ITEMS: &[Item] = &[
+    Item {
+        name: "crate",
+        parent: None,
+        module: true,
+    },
+    Item {
+        name: "main",
+        parent: Some(0),
+        module: false,
+    },
+    Item {
+        name: "garden",
+        parent: Some(0),
+        module: true,
+    },
+    Item {
+        name: "water",
+        parent: Some(2),
+        module: false,
+    },
+    Item {
+        name: "shed",
+        parent: Some(2),
+        module: true,
+    },
+    Item {
+        name: "rake",
+        parent: Some(4),
+        module: false,
+    },
+    Item {
+        name: "kitchen",
+        parent: Some(0),
+        module: true,
+    },
+    Item {
+        name: "cook",
+        parent: Some(6),
+        module: false,
+    },
+];
+
+const MODULES: &[usize] = &[0, 2, 4, 6];
+const FUNCTIONS: &[usize] = &[1, 3, 5, 7];
+
+fn depth(item: usize) -> usize {
+    let mut depth = 0;
+    let mut current = item;
+    while let Some(parent) = ITEMS[current].parent {
+        depth += 1;
+        current = parent;
+    }
+    depth
+}
+
+fn is_inside(module: usize, location: usize) -> bool {
+    let mut current = Some(location);
+    while let Some(index) = current {
+        if index == module {
+            return true;
+        }
+        current = ITEMS[index].parent;
+    }
+    false
+}
+
+/// Rust's privacy rule: every step on the way to the item must be `pub`,
+/// unless the step lives in a module that contains the place we look from.
+fn visible(target: usize, location: usize, public: &[bool]) -> bool {
+    let mut current = target;
+    while let Some(parent) = ITEMS[current].parent {
+        if !public[current] && !is_inside(parent, location) {
+            return false;
+        }
+        current = parent;
+    }
+    true
+}
+
+fn full_path(item: usize) -> String {
+    let mut names = Vec::new();
+    let mut current = item;
+    while let Some(parent) = ITEMS[current].parent {
+        names.push(ITEMS[current].name);
+        current = parent;
+    }
+    names.push("crate");
+    names.reverse();
+    names.join("::")
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Resolution {
+    Item(usize),
+    Private,
+    NotFound,
+}
+
+fn resolve(path: &str, location: usize, public: &[bool]) -> Resolution {
+    let mut current = location;
+    let mut found = None;
+    for segment in path.split("::") {
+        if found.is_some() {
+            return Resolution::NotFound;
+        }
+        match segment {
+            "crate" => current = ROOT,
+            "self" => {}
+            "super" => match ITEMS[current].parent {
+                Some(parent) => current = parent,
+                None => return Resolution::NotFound,
+            },
+            name => {
+                let child = (0..ITEMS.len()).find(|index| {
+                    ITEMS[*index].parent == Some(current) && ITEMS[*index].name == name
+                });
+                match child {
+                    Some(child) if ITEMS[child].module => current = child,
+                    Some(child) => found = Some(child),
+                    None => return Resolution::NotFound,
+                }
+            }
+        }
+    }
+    match found {
+        Some(item) if visible(item, location, public) => Resolution::Item(item),
+        Some(_) => Resolution::Private,
+        None => Resolution::NotFound,
+    }
+}
+
+fn code_lines(public: &[bool]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    for (index, item) in ITEMS.iter().enumerate().skip(1) {
+        let parent = item.parent.unwrap_or(ROOT);
+        while open.last().is_some_and(|module| *module != parent) {
+            open.pop();
+            lines.push(format!("{}}}", "    ".repeat(open.len())));
+        }
+        let indent = "    ".repeat(open.len());
+        let vis = if public[index] { "pub " } else { "" };
+        if item.module {
+            lines.push(format!("{indent}{vis}mod {} {{", item.name));
+            open.push(index);
+        } else {
+            lines.push(format!("{indent}{vis}fn {}() {{}}", item.name));
+        }
+    }
+    while open.pop().is_some() {
+        lines.push(format!("{}}}", "    ".repeat(open.len())));
+    }
+    lines
+}
+
+struct TreeState {
+    public: Vec<bool>,
+    location: usize,
+}
+
+impl Default for TreeState {
+    fn default() -> Self {
+        let mut public = vec![false; ITEMS.len()];
+        public[2] = true;
+        public[3] = true;
+        Self {
+            public,
+            location: 6,
+        }
+    }
+}
+
+/// A path written inside one function of a module tree with random `pub`s.
+struct PathQuestion {
+    public: Vec<bool>,
+    caller: usize,
+    path: String,
+}
+
+impl PathQuestion {
+    /// The module tree with the caller opened up around the call, and the
+    /// index of the line with the call.
+    fn lines(&self) -> (Vec<String>, usize) {
+        let mut lines = code_lines(&self.public);
+        let caller_name = ITEMS[self.caller].name;
+        let caller_line = lines
+            .iter()
+            .position(|line| {
+                line.trim_start().trim_start_matches("pub ") == format!("fn {caller_name}() {{}}")
+            })
+            .unwrap_or(0);
+        let indent = lines[caller_line].len() - lines[caller_line].trim_start().len();
+        lines[caller_line] = lines[caller_line].replace("{}", "{");
+        lines.insert(
+            caller_line + 1,
+            format!("{}    {}();", " ".repeat(indent), self.path),
+        );
+        lines.insert(caller_line + 2, format!("{}}}", " ".repeat(indent)));
+        (lines, caller_line + 1)
+    }
+}
+
+impl Exercise for PathQuestion {
+    type Answer = Resolution;
+
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        let mut public = vec![false; ITEMS.len()];
+        for flag in public.iter_mut().skip(2) {
+            *flag = rng.gen_range_i32(0, 1) == 1;
+        }
+        let caller = FUNCTIONS[rng.gen_range_i32(0, FUNCTIONS.len() as i32 - 1) as usize];
+        let mut target = caller;
+        while target == caller {
+            target = FUNCTIONS[rng.gen_range_i32(0, FUNCTIONS.len() as i32 - 1) as usize];
+        }
+        let location = ITEMS[caller].parent.unwrap_or(ROOT);
+        let path = match rng.gen_range_i32(0, 2) {
+            0 => full_path(target),
+            1 => relative_path(target, location),
+            _ => full_path(target).trim_start_matches("crate::").to_string(),
+        };
+        PathQuestion {
+            public,
+            caller,
+            path,
+        }
+    }
+
+    fn prompt(&self) -> String {
+        "The highlighted line sits inside the function marked below. What does it do?".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        let (lines, call) = self.lines();
+        ui.add(CodeBlock::new(&lines).highlight_line(Some(call)));
+    }
+
+    /// The module tree with the call marked by a comment.
+    fn text(&self) -> String {
+        let (mut lines, call) = self.lines();
+        lines[call].push_str(" // <- this line");
+        format!("```rust\n{}\n```", lines.join("\n"))
+    }
+
+    fn answer(&self) -> Resolution {
+        let location = ITEMS[self.caller].parent.unwrap_or(ROOT);
+        resolve(&self.path, location, &self.public)
+    }
+
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<Resolution> {
+        let answer = self.answer();
+        self.choices(rng)
+            .into_iter()
+            .filter(|choice| *choice != answer)
+            .collect()
+    }
+
+    /// Every function of the tree, then the two errors.
+    fn choices(&self, _rng: &mut SimpleRng) -> Vec<Resolution> {
+        let mut choices: Vec<Resolution> = FUNCTIONS
+            .iter()
+            .map(|function| Resolution::Item(*function))
+            .collect();
+        choices.push(Resolution::Private);
+        choices.push(Resolution::NotFound);
+        choices
+    }
+
+    fn choice_label(answer: &Resolution) -> String {
+        match answer {
+            Resolution::Item(item) => ITEMS[*item].name.to_string(),
+            Resolution::Private => "error: private".to_string(),
+            Resolution::NotFound => "error: not found".to_string(),
+        }
+    }
+
+    fn hint(&self, _picked: &Resolution) -> String {
+        "Start where the path starts, then walk one box at a time.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, correct: bool) {
+        if correct {
+            ui.label(resolution_label(self.answer()));
+        }
+    }
+}
+
+/// A path to follow on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<PathQuestion>(rng, difficulty)
+}
+
+/// Checks a generated path for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<PathQuestion>(rng, difficulty)
+}
+
+fn relative_path(target: usize, location: usize) -> String {
+    let mut common = location;
+    while !is_inside(common, target) {
+        common = ITEMS[common].parent.unwrap_or(ROOT);
+    }
+    let mut segments = vec!["super"; depth(location) - depth(common)];
+    if segments.is_empty() {
+        segments.push("self");
+    }
+    let mut below = Vec::new();
+    let mut current = target;
+    while current != common {
+        below.push(ITEMS[current].name);
+        current = ITEMS[current].parent.unwrap_or(ROOT);
+    }
+    below.reverse();
+    segments.extend(below);
+    segments.join("::")
+}
+
+/// Draws the module tree as an indented list of boxes. Clicking a box
+/// toggles its `pub`; items hidden from `location` are drawn faded.
+fn module_tree_interactive(ui: &mut egui::Ui, public: &mut [bool], location: usize) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
+    let box_h = row_height + 10.0;
+    let row_gap = 6.0;
+    let indent = 28.0;
+    let box_w = 190.0;
+    let width = ui.available_width().max(320.0);
+    let height = ITEMS.len() as f32 * (box_h + row_gap);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+
+    let palette = Palette::from_ui(ui);
+    let highlight_color = palette.highlight;
+    let text_color = palette.text;
+    let weak_color = palette.weak_text;
+    let line_stroke = palette.line;
+    let fill = palette.code_fill;
+    let here_fill = palette.tint(0.2);
+
+    let row_rect = |index: usize| {
+        let left = rect.left() + depth(index) as f32 * indent;
+        let top = rect.top() + index as f32 * (box_h + row_gap);
+        egui::Rect::from_min_size(egui::pos2(left, top), egui::vec2(box_w, box_h))
+    };
+
+    for (index, item) in ITEMS.iter().enumerate() {
+        let item_rect = row_rect(index);
+        if let Some(parent) = item.parent {
+            let parent_rect = row_rect(parent);
+            let x = parent_rect.left() + indent / 2.0;
+            let points = vec![
+                egui::pos2(x, parent_rect.bottom()),
+                egui::pos2(x, item_rect.center().y),
+                item_rect.left_center(),
+            ];
+            ui.painter().add(egui::Shape::line(
+                points,
+                egui::Stroke::new(1.0, line_stroke.color),
+            ));
+        }
+
+        let can_toggle = index != ROOT && index != 1;
+        if can_toggle {
+            let id = ui.make_persistent_id(("module-tree-node", index));
+            let response = ui.interact(item_rect, id, egui::Sense::click());
+            if response.clicked() {
+                public[index] = !public[index];
+            }
+            if response.hovered() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            }
+        }
+
+        let seen = visible(index, location, public);
+        let stroke = if seen {
+            egui::Stroke::new(2.0, highlight_color)
+        } else {
+            line_stroke
+        };
+        let painter = ui.painter();
+        painter.rect(
+            item_rect,
+            egui::CornerRadius::same(4),
+            if index == location { here_fill } else { fill },
+            stroke,
+            egui::StrokeKind::Inside,
+        );
+        let label = match (index, item.module, public[index]) {
+            (ROOT, _, _) => "crate".to_string(),
+            (_, true, true) => format!("pub mod {}", item.name),
+            (_, true, false) => format!("mod {}", item.name),
+            (_, false, true) => format!("pub fn {}", item.name),
+            (_, false, false) => format!("fn {}", item.name),
+        };
+        painter.text(
+            item_rect.left_center() + egui::vec2(8.0, 0.0),
+            egui::Align2::LEFT_CENTER,
+            label,
+            font_id.clone(),
+            if seen { text_color } else { weak_color },
+        );
+        if index == location {
+            painter.text(
+                item_rect.right_center() + egui::vec2(8.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                "<- looking from here",
+                font_id.clone(),
+                weak_color,
+            );
+        }
+    }
+}
+
+fn resolution_label(resolution: Resolution) -> String {
+    match resolution {
+        Resolution::Item(item) => format!("calls {}", full_path(item)),
+        Resolution::Private => "error: private".to_string(),
+        Resolution::NotFound => "error: not found".to_string(),
+    }
+}
+
+pub fn modules(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Modules and crates\n\
+         As programs grow, we sort code into **modules**: named boxes inside boxes.\n\
+         A whole program or library is a **crate**, and its outermost box is called `crate`.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         A house has rooms, and rooms have cupboards.\n\
+         People inside a room can open its cupboards.\n\
+         People outside can only use what has been put on the shelf by the door.\n\
+         In Rust, that shelf is the word `pub`.",
+    );
+
+    speech::lesson(
+        nb,
+        "## Minimal code\n\
+         ```rust\n\
+         mod garden {\n    pub fn water() {}\n    fn secret() {}\n}\n\n\
+         fn main() {\n    garden::water();   // fine\n    // garden::secret(); // error: private\n}\n\
+         ```\n\
+         Everything is private by default. Private items can be used inside their own module\n\
+         and any module nested inside it.",
+    );
+
+    nb.state(
+        &state_key("tree_state"),
+        TreeState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Who can see what?") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label(
+                    "Click a box to toggle pub. Highlighted boxes are visible from where you look.",
+                );
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Looking from:");
+                    let mut toggle = widgets::ChoiceToggle::new(&mut state.location).small();
+                    for module in MODULES {
+                        toggle = toggle.choice(*module, ITEMS[*module].name);
+                    }
+                    ui.add(toggle);
+                });
+                ui.add_space(8.0);
+                module_tree_interactive(ui, &mut state.public, state.location);
+                ui.add_space(6.0);
+                let lines = code_lines(&state.public);
+                ui.add(CodeBlock::new(&lines));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Paths\n\
+         To name an item we write a **path**, with `::` between the boxes.\n\
+         - `crate::garden::water` starts at the outermost box.\n\
+         - `self::...` starts in the current module.\n\
+         - `super::...` starts one box further out.\n\
+         - A plain name like `garden::water` starts in the current module too.",
+    );
+
+    nb.state(
+        &state_key("path_quiz_state"),
+        MultipleChoice::<PathQuestion>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Practice: follow the path") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("path_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<PathQuestion>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("path_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - Modules are named boxes; the outermost box is `crate`.\n\
+         - Items are private by default; `pub` makes them usable from outside.\n\
+         - A private item is still visible inside its own module and nested modules.\n\
+         - Paths start at `crate`, `self`, or `super` and walk one box at a time.\n\n\
+         Next up: **Concurrency basics** lets several workers run at once.",
+    );
+}
@@ -3,174 +3,234 @@ use egui::RichText;
 use GORBIE::prelude::*;
 use GORBIE::cards::DEFAULT_CARD_PADDING;
 
-use super::{Chapter, current_chapter, set_chapter};
+use crate::i18n::tr;
+use crate::palette::Palette;
+use crate::progress::{self, ExerciseKey};
+use crate::schedule;
+use crate::search;
+use crate::speech;
 
-fn chapter_entry(ui: &mut egui::Ui, chapter: Chapter, label: &str) {
-    let is_current = current_chapter() == chapter;
+use super::{
+    card_heading, current_chapter, open_exercise, placement, set_chapter, ChapterDef, Track,
+    CHAPTERS,
+};
+
+/// Chapters that are planned but not written yet, listed between the
+/// registered ones by their number.
+const PLANNED_FOUNDATIONS: &[(u32, &str)] = &[
+    (7, "Lists and indexing"),
+    (8, "Maps and lookup tables"),
+    (9, "Debugging as a method"),
+    (10, "Sorting and searching basics"),
+    (11, "Complexity intuition (fast vs slow)"),
+    (12, "Mini project: a tiny text game"),
+];
+
+/// Weeks the practice calendar reaches back, this one included.
+const CALENDAR_WEEKS: u64 = 12;
+const CALENDAR_CELL: f32 = 12.0;
+const CALENDAR_GAP: f32 = 3.0;
+
+const PLANNED_RUST: &[(u32, &str)] = &[(10, "Interior mutability"), (11, "Lifetimes intuition")];
+
+fn chapter_entry(ui: &mut egui::Ui, def: &ChapterDef) {
+    let label = format!("{}. {}", def.number, def.title);
+    let is_current = current_chapter() == def.chapter;
     let text = if is_current {
         RichText::new(label).strong()
     } else {
         RichText::new(label)
     };
-    let response = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
-    if response.clicked() {
-        set_chapter(chapter);
+    ui.horizontal(|ui| {
+        let mut response = ui.add(egui::Label::new(text).sense(egui::Sense::click()));
+        if !def.prerequisites.is_empty() {
+            let titles: Vec<&str> = def
+                .prerequisites
+                .iter()
+                .map(|chapter| super::def(*chapter).title)
+                .collect();
+            response = response.on_hover_text(format!("Builds on: {}", titles.join(", ")));
+        }
+        if response.clicked() {
+            set_chapter(def.chapter);
+        }
+        if response.hovered() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+        }
+        if let Some((solved, total)) = progress::completion(def.chapter) {
+            ui.label(RichText::new(format!("{solved}/{total} solved")).weak());
+        }
+        if progress::badge_earned(def.chapter) {
+            ui.label(RichText::new("\u{1F3C5}").strong())
+                .on_hover_text(tr("overview.badge"));
+        }
+    });
+}
+
+/// Exercises the schedule brings back today, each opening its card.
+fn todays_review(ui: &mut egui::Ui) {
+    let due = schedule::due_today();
+    if due.is_empty() {
+        ui.label(tr("schedule.nothing_due"));
+        return;
+    }
+    ui.label(tr("schedule.intro"));
+    ui.add_space(4.0);
+    for key in due {
+        exercise_link(ui, key);
+    }
+}
+
+/// A square per day of the last weeks, a column per week from Monday down,
+/// filled on days with an answer. Today has an outline.
+fn practice_calendar(ui: &mut egui::Ui) {
+    let palette = Palette::from_ui(ui);
+    let today = schedule::today();
+    // Day 0, 1970-01-01, was a Thursday.
+    let monday = today - (today + 3) % 7;
+    let first = monday.saturating_sub((CALENDAR_WEEKS - 1) * 7);
+    let step = CALENDAR_CELL + CALENDAR_GAP;
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(CALENDAR_WEEKS as f32 * step, 7.0 * step),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let mut practiced = 0;
+    for day in first..=today {
+        let offset = day - first;
+        let square = egui::Rect::from_min_size(
+            rect.min + egui::vec2((offset / 7) as f32 * step, (offset % 7) as f32 * step),
+            egui::vec2(CALENDAR_CELL, CALENDAR_CELL),
+        );
+        let fill = if progress::practiced_on(day) {
+            practiced += 1;
+            palette.highlight
+        } else {
+            palette.code_fill
+        };
+        painter.rect_filled(square, 2.0, fill);
+        if day == today {
+            painter.rect_stroke(square, 2.0, palette.line, egui::StrokeKind::Outside);
+        }
+    }
+
+    ui.add_space(4.0);
+    ui.label(tr("calendar.streak").replace("{days}", &progress::daily_streak().to_string()));
+    ui.label(
+        RichText::new(
+            tr("calendar.total")
+                .replace("{practiced}", &practiced.to_string())
+                .replace("{days}", &(today - first + 1).to_string()),
+        )
+        .weak(),
+    );
+}
+
+/// A link that opens the card of the exercise `key`, labelled with the
+/// chapter and card titles.
+pub(super) fn exercise_link(ui: &mut egui::Ui, (chapter, key): ExerciseKey) {
+    let def = super::def(chapter);
+    let title = search::card_title(chapter, key);
+    let label = match &title {
+        Some(title) => format!("{} \u{203A} {title}", def.title),
+        None => def.title.to_string(),
+    };
+    if ui.link(label).clicked() {
+        open_exercise((chapter, key));
     }
-    if response.hovered() {
-        ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+}
+
+/// Registered chapters of `track` and the planned ones, in number order.
+fn track_entries(ui: &mut egui::Ui, track: Track, planned: &[(u32, &str)]) {
+    let mut entries: Vec<(u32, Option<&ChapterDef>, &str)> = CHAPTERS
+        .iter()
+        .filter(|def| def.track == track)
+        .map(|def| (def.number, Some(def), def.title))
+        .collect();
+    entries.extend(
+        planned
+            .iter()
+            .map(|(number, title)| (*number, None, *title)),
+    );
+    entries.sort_by_key(|(number, _, _)| *number);
+
+    for (index, (number, def, title)) in entries.into_iter().enumerate() {
+        if index > 0 {
+            ui.add_space(2.0);
+        }
+        match def {
+            Some(def) => chapter_entry(ui, def),
+            None => {
+                ui.label(format!("{number}. {title}"));
+            }
+        }
     }
 }
 
 pub fn overview(nb: &mut NotebookCtx) {
     nb.view(|ui| {
-        md!(
-            ui,
-            "# Teaching notebooks plan\n\
-             A practical learning path for absolute beginners.\n\n\
-             This series is designed for learners with little or no formal math background.\n\
-             Every concept is grounded in simple language, concrete examples, and visible feedback."
-        );
+        speech::markdown(ui, tr("overview.intro"));
     });
 
     nb.view(|ui| {
-        md!(
-            ui,
-            "## Principles\n\
-             - **Tiny steps**: one new idea per notebook.\n\
-             - **See the effect**: every concept has a visual or interactive demo.\n\
-             - **Practice > lecture**: short exercises after each demo.\n\
-             - **Build confidence**: celebrate correctness, then improve style.\n\
-             - **Vocabulary grows slowly**: define terms once and reuse them."
-        );
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            if !card_heading(ui, "Today's review") {
+                return;
+            }
+            ui.add_space(4.0);
+            todays_review(ui);
+        });
     });
 
     nb.view(|ui| {
         with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            ui.label(
-                RichText::new("Track A - Programming foundations (10-12 notebooks)").heading(),
-            );
+            if !card_heading(ui, "Practice calendar") {
+                return;
+            }
             ui.add_space(4.0);
-            chapter_entry(
-                ui,
-                Chapter::Expressions,
-                "1. Hello, expressions (values and math)",
-            );
-            ui.add_space(2.0);
-            chapter_entry(
-                ui,
-                Chapter::Booleans,
-                "2. To Bool or Not to Bool (yes/no logic)",
-            );
-            ui.add_space(2.0);
-            chapter_entry(ui, Chapter::State, "3. Hello, state (variables and change)");
-            ui.add_space(2.0);
-            chapter_entry(
-                ui,
-                Chapter::IfElse,
-                "4. Forks in the Road (if/else decisions)",
-            );
-            ui.add_space(2.0);
-            chapter_entry(ui, Chapter::Loops, "5. Loops and counting");
-            ui.add_space(2.0);
-            chapter_entry(ui, Chapter::Functions, "6. Functions as reusable steps");
-            ui.add_space(2.0);
-            ui.label("7. Lists and indexing");
-            ui.add_space(2.0);
-            ui.label("8. Maps and lookup tables");
-            ui.add_space(2.0);
-            ui.label("9. Debugging as a method");
-            ui.add_space(2.0);
-            ui.label("10. Sorting and searching basics");
-            ui.add_space(2.0);
-            ui.label("11. Complexity intuition (fast vs slow)");
-            ui.add_space(2.0);
-            ui.label("12. Mini project: a tiny text game");
+            practice_calendar(ui);
         });
     });
 
+    placement::placement_quiz(nb);
+
     nb.view(|ui| {
-        md!(
-            ui,
-            "## Track B - Theoretical CS (10-12 notebooks)\n\
-             1. Sets, relations, and graphs\n\
-             2. Finite state machines (DFA)\n\
-             3. Regular expressions as machines\n\
-             4. Context-free grammars\n\
-             5. Parse trees by hand\n\
-             6. Turing machines (tape + rules)\n\
-             7. Halting problem intuition\n\
-             8. Reductions and NP overview\n\
-             9. Why some problems stay hard\n\
-             10. Mini project: build a tiny parser"
-        );
+        speech::markdown(ui, tr("overview.principles"));
     });
 
     nb.view(|ui| {
-        md!(
-            ui,
-            "## Track C - Rust (12-15 notebooks)\n\
-             1. Ownership and moves\n\
-             2. Borrowing and references\n\
-             3. Structs, enums, and pattern matching\n\
-             4. Errors and `Result`\n\
-             5. Traits and generics (lightweight)\n\
-             6. Iterators and loops\n\
-             7. Strings and slices\n\
-             8. Modules and crates\n\
-             9. Concurrency basics\n\
-             10. Interior mutability\n\
-             11. Lifetimes intuition\n\
-             12. Mini project: a small CLI tool"
-        );
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            ui.label(RichText::new(tr("overview.track_a")).heading());
+            ui.add_space(4.0);
+            track_entries(ui, Track::Foundations, PLANNED_FOUNDATIONS);
+        });
     });
 
     nb.view(|ui| {
-        md!(
-            ui,
-            "## Shared visual widgets\n\
-             - Stack and call-frame viewer\n\
-             - Memory map (owned vs borrowed)\n\
-             - Tape simulator (Turing machines)\n\
-             - Parse tree explorer\n\
-             - Stepper for algorithms\n\
-             - Tiny code runner with logs"
-        );
+        speech::markdown(ui, tr("overview.track_b"));
     });
 
     nb.view(|ui| {
-        md!(
-            ui,
-            "## Lesson template (every notebook)\n\
-             1. Short story or real-life analogy\n\
-             2. Minimal code demo\n\
-             3. Interactive widget\n\
-             4. Exercise (3-5 minutes)\n\
-             5. Recap in one paragraph"
-        );
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            ui.label(RichText::new(tr("overview.track_c")).heading());
+            ui.add_space(4.0);
+            track_entries(ui, Track::Rust, PLANNED_RUST);
+        });
     });
 
     nb.view(|ui| {
-        md!(
-            ui,
-            "## Milestones\n\
-             - **Week 1**: basic variables, conditions, and loops\n\
-             - **Week 2**: functions + lists + small projects\n\
-             - **Week 3**: automata and parsing intuition\n\
-             - **Week 4**: Rust ownership and references\n\
-             - **Week 5**: build a mini project together"
-        );
+        speech::markdown(ui, tr("overview.widgets"));
     });
 
     nb.view(|ui| {
-        note!(
-            ui,
-            "Start with five pilot notebooks:\n\
-             - **Hello, expressions** (programming)\n\
-             - **To Bool or Not to Bool** (programming)\n\
-             - **Hello, state** (programming)\n\
-             - **DFA basics** (theory)\n\
-             - **Ownership 101** (Rust)\n\n\
-             We will test them, refine the language, and then expand."
-        );
+        speech::markdown(ui, tr("overview.template"));
+    });
+
+    nb.view(|ui| {
+        speech::markdown(ui, tr("overview.milestones"));
+    });
+
+    nb.view(|ui| {
+        note!(ui, "{}", tr("overview.pilot"));
     });
 }
@@ -0,0 +1,411 @@
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::memory_map::{self, inline, moved, owns, Owner};
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::stepper::Controls;
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::Ownership;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+struct MemoryStep {
+    line: usize,
+    owners: &'static [Owner],
+    note: &'static str,
+}
+
+struct Snippet {
+    title: &'static str,
+    lines: &'static [&'static str],
+    steps: &'static [MemoryStep],
+}
+
+const SNIPPETS: &[Snippet] = &[
+    Snippet {
+        title: "Copy a number",
+        lines: &["let a = 5;", "let b = a;", "println!(\"{a} {b}\");"],
+        steps: &[
+            MemoryStep {
+                line: 0,
+                owners: &[inline("a", "5")],
+                note: "a owns the number 5. Small numbers live right inside the owner.",
+            },
+            MemoryStep {
+                line: 1,
+                owners: &[inline("a", "5"), inline("b", "5")],
+                note: "Numbers are Copy: b gets its own 5 and a keeps its value.",
+            },
+            MemoryStep {
+                line: 2,
+                owners: &[inline("a", "5"), inline("b", "5")],
+                note: "Both names still work, so printing both is fine.",
+            },
+        ],
+    },
+    Snippet {
+        title: "Move a String",
+        lines: &[
+            "let s = String::from(\"hi\");",
+            "let t = s;",
+            "println!(\"{t}\");",
+        ],
+        steps: &[
+            MemoryStep {
+                line: 0,
+                owners: &[owns("s", "\"hi\"")],
+                note: "s owns a String. The text lives on the heap.",
+            },
+            MemoryStep {
+                line: 1,
+                owners: &[moved("s"), owns("t", "\"hi\"")],
+                note: "The String moves to t. There is still only one owner, and s is empty.",
+            },
+            MemoryStep {
+                line: 2,
+                owners: &[moved("s"), owns("t", "\"hi\"")],
+                note: "We print through t, the current owner.",
+            },
+        ],
+    },
+    Snippet {
+        title: "Move into a function",
+        lines: &[
+            "let s = String::from(\"hi\");",
+            "shout(s);",
+            "// s cannot be used here",
+        ],
+        steps: &[
+            MemoryStep {
+                line: 0,
+                owners: &[owns("s", "\"hi\"")],
+                note: "s owns the String.",
+            },
+            MemoryStep {
+                line: 1,
+                owners: &[moved("s"), owns("text", "\"hi\"")],
+                note: "Calling shout(s) moves the String into the parameter text.",
+            },
+            MemoryStep {
+                line: 2,
+                owners: &[moved("s")],
+                note: "shout is finished. Its owner text is gone, so the String was dropped.",
+            },
+        ],
+    },
+    Snippet {
+        title: "Clone makes a copy",
+        lines: &[
+            "let s = String::from(\"hi\");",
+            "let t = s.clone();",
+            "println!(\"{s} {t}\");",
+        ],
+        steps: &[
+            MemoryStep {
+                line: 0,
+                owners: &[owns("s", "\"hi\"")],
+                note: "s owns the String.",
+            },
+            MemoryStep {
+                line: 1,
+                owners: &[owns("s", "\"hi\""), owns("t", "\"hi\"")],
+                note: "clone builds a second String, so each name owns its own value.",
+            },
+            MemoryStep {
+                line: 2,
+                owners: &[owns("s", "\"hi\""), owns("t", "\"hi\"")],
+                note: "Two owners, two values. Both names work.",
+            },
+        ],
+    },
+];
+
+struct MemoryStepperState {
+    snippet: usize,
+    step: usize,
+}
+
+impl Default for MemoryStepperState {
+    fn default() -> Self {
+        Self {
+            snippet: 1,
+            step: 0,
+        }
+    }
+}
+
+struct CompileQuestion {
+    lines: &'static [&'static str],
+    failing_line: Option<usize>,
+    explanation: &'static str,
+}
+
+const COMPILE_QUESTIONS: &[CompileQuestion] = &[
+    CompileQuestion {
+        lines: &[
+            "let s = String::from(\"hi\");",
+            "let t = s;",
+            "println!(\"{s}\");",
+        ],
+        failing_line: Some(2),
+        explanation: "s was moved into t on line 2, so s has nothing left to print.",
+    },
+    CompileQuestion {
+        lines: &["let a = 5;", "let b = a;", "println!(\"{a} {b}\");"],
+        failing_line: None,
+        explanation: "Numbers are Copy, so a still has its own 5.",
+    },
+    CompileQuestion {
+        lines: &[
+            "let name = String::from(\"Ada\");",
+            "greet(name);",
+            "greet(name);",
+        ],
+        failing_line: Some(2),
+        explanation: "The first call moved name into greet. The second call has nothing to give.",
+    },
+    CompileQuestion {
+        lines: &[
+            "let s = String::from(\"hi\");",
+            "let t = s.clone();",
+            "println!(\"{s}\");",
+            "let u = t;",
+            "println!(\"{t}\");",
+        ],
+        failing_line: Some(4),
+        explanation: "The clone kept s usable, but t moved into u on line 4.",
+    },
+    CompileQuestion {
+        lines: &[
+            "let v = vec![1, 2, 3];",
+            "let w = v.clone();",
+            "println!(\"{}\", v.len());",
+            "println!(\"{}\", w.len());",
+        ],
+        failing_line: None,
+        explanation: "clone gives w its own vector, so v and w are both owners.",
+    },
+    CompileQuestion {
+        lines: &[
+            "let v = vec![1, 2, 3];",
+            "let w = v;",
+            "let x = w;",
+            "println!(\"{}\", w.len());",
+        ],
+        failing_line: Some(3),
+        explanation: "The vector moved from v to w, then from w to x. Only x owns it now.",
+    },
+];
+
+/// One of the [`COMPILE_QUESTIONS`], picked by the seed.
+struct CompileExercise(&'static CompileQuestion);
+
+impl Exercise for CompileExercise {
+    type Answer = Option<usize>;
+
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        let index = rng.gen_range_i32(0, (COMPILE_QUESTIONS.len() - 1) as i32) as usize;
+        Self(&COMPILE_QUESTIONS[index])
+    }
+
+    fn prompt(&self) -> String {
+        "Find the line the compiler rejects, or decide that it compiles.".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::new(&numbered_lines(self.0.lines)));
+    }
+
+    fn text(&self) -> String {
+        format!("```text\n{}\n```", numbered_lines(self.0.lines).join("\n"))
+    }
+
+    fn answer(&self) -> Option<usize> {
+        self.0.failing_line
+    }
+
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<Option<usize>> {
+        let answer = self.answer();
+        self.choices(rng)
+            .into_iter()
+            .filter(|choice| *choice != answer)
+            .collect()
+    }
+
+    /// The lines in order, then "it compiles".
+    fn choices(&self, _rng: &mut SimpleRng) -> Vec<Option<usize>> {
+        (0..self.0.lines.len()).map(Some).chain([None]).collect()
+    }
+
+    fn choice_label(answer: &Option<usize>) -> String {
+        match answer {
+            Some(line) => format!("line {}", line + 1),
+            None => "it compiles".to_string(),
+        }
+    }
+
+    fn hint(&self, _picked: &Option<usize>) -> String {
+        "Follow each value and ask who owns it.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, correct: bool) {
+        if correct {
+            ui.label(self.0.explanation);
+        }
+    }
+}
+
+/// A listing to check on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<CompileExercise>(rng, difficulty)
+}
+
+/// Checks a picked listing for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<CompileExercise>(rng, difficulty)
+}
+
+fn numbered_lines(lines: &[&str]) -> Vec<String> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| format!("{:>2}  {line}", index + 1))
+        .collect()
+}
+
+pub fn ownership(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Ownership and moves\n\
+         In Rust every value has exactly **one owner**.\n\
+         The owner is the variable that is responsible for the value.\n\
+         When the owner goes away, the value is cleaned up with it.\n\n\
+         This one rule lets Rust manage memory without a garbage collector\n\
+         and without you freeing things by hand.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         A library book can only sit in one backpack at a time.\n\
+         If you hand the book to a friend, it is in *their* backpack now.\n\
+         Your backpack is empty, even though you remember which book it was.\n\n\
+         In Rust, handing a value to another variable is called a **move**.",
+    );
+
+    speech::lesson(
+        nb,
+        "## The three rules\n\
+         - Each value has one owner.\n\
+         - There can only be one owner at a time.\n\
+         - When the owner goes out of scope, the value is dropped.\n\n\
+         ```rust\n\
+         let s = String::from(\"hi\");\n\
+         let t = s;          // the String moves to t\n\
+         println!(\"{t}\"); // fine\n\
+         println!(\"{s}\"); // error: s was moved\n\
+         ```",
+    );
+
+    nb.state(
+        &state_key("memory_stepper_state"),
+        MemoryStepperState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Memory map") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Step through a snippet and watch values move between owners.");
+                ui.add_space(6.0);
+
+                let mut snippet = state.snippet;
+                let mut toggle = widgets::ChoiceToggle::new(&mut snippet).small();
+                for (index, entry) in SNIPPETS.iter().enumerate() {
+                    toggle = toggle.choice(index, entry.title);
+                }
+                ui.add(toggle);
+                if snippet != state.snippet {
+                    state.snippet = snippet;
+                    state.step = 0;
+                }
+
+                let snippet = &SNIPPETS[state.snippet];
+                let max_step = snippet.steps.len().saturating_sub(1);
+
+                ui.add_space(6.0);
+                Controls::new(&mut state.step, max_step).show(ui);
+
+                let step = &snippet.steps[state.step];
+                let previous = state
+                    .step
+                    .checked_sub(1)
+                    .map(|index| snippet.steps[index].owners);
+                ui.add_space(8.0);
+                ui.add(CodeBlock::new(snippet.lines).highlight_line(Some(step.line)));
+                ui.add_space(8.0);
+                memory_map::paint(ui, step.owners, previous, &[]);
+                ui.add_space(6.0);
+                ui.label(step.note);
+            });
+        },
+    );
+
+    nb.view(|ui| {
+        note!(
+            ui,
+            "Small values like numbers and booleans are **Copy**.\n\
+             Assigning them makes a fresh copy, so nothing moves and both names keep working.\n\
+             Values that own heap memory, like `String` and `Vec`, move instead."
+        );
+    });
+
+    nb.state(
+        &state_key("compile_quiz_state"),
+        MultipleChoice::<CompileExercise>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Which line fails?") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("compile_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<CompileExercise>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("compile_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - Every value has exactly one owner.\n\
+         - Assigning or passing a heap value **moves** it; the old name becomes unusable.\n\
+         - Copy values like numbers are duplicated instead of moved.\n\
+         - `clone` makes an explicit copy when you really need two owners.\n\n\
+         Next up: **Borrowing and references** shows how to use a value without taking it.",
+    );
+}
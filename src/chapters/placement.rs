@@ -0,0 +1,213 @@
+use egui::RichText;
+
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+use super::{card_heading, set_chapter, Chapter};
+use crate::difficulty;
+
+struct PlacementQuestion {
+    chapter: Chapter,
+    prompt: &'static str,
+    code: &'static str,
+    choices: &'static [&'static str],
+    answer: usize,
+}
+
+const QUESTIONS: &[PlacementQuestion] = &[
+    PlacementQuestion {
+        chapter: Chapter::Expressions,
+        prompt: "What is the value?",
+        code: "3 + 2 * 4",
+        choices: &["20", "11", "9"],
+        answer: 1,
+    },
+    PlacementQuestion {
+        chapter: Chapter::Expressions,
+        prompt: "What is the value?",
+        code: "8 - 3 - 2",
+        choices: &["3", "7", "-3"],
+        answer: 0,
+    },
+    PlacementQuestion {
+        chapter: Chapter::Booleans,
+        prompt: "Is it true or false?",
+        code: "not true or false",
+        choices: &["true", "false"],
+        answer: 1,
+    },
+    PlacementQuestion {
+        chapter: Chapter::Booleans,
+        prompt: "Is it true or false?",
+        code: "true and (false or true)",
+        choices: &["true", "false"],
+        answer: 0,
+    },
+    PlacementQuestion {
+        chapter: Chapter::Loops,
+        prompt: "How many times does the body run?",
+        code: "count <- 0\nwhile count < 3 {\n    count <- count + 1\n}",
+        choices: &["2", "3", "4"],
+        answer: 1,
+    },
+    PlacementQuestion {
+        chapter: Chapter::Loops,
+        prompt: "How many times does the body run?",
+        code: "count <- 5\nwhile count < 3 {\n    count <- count + 1\n}",
+        choices: &["0", "3", "forever"],
+        answer: 0,
+    },
+];
+
+const SUGGESTION_ORDER: &[Chapter] = &[Chapter::Expressions, Chapter::Booleans, Chapter::Loops];
+
+struct PlacementState {
+    open: bool,
+    answers: Vec<Option<usize>>,
+    submitted: bool,
+}
+
+impl Default for PlacementState {
+    fn default() -> Self {
+        Self {
+            open: false,
+            answers: vec![None; QUESTIONS.len()],
+            submitted: false,
+        }
+    }
+}
+
+impl PlacementState {
+    fn restart(&mut self) {
+        self.answers = vec![None; QUESTIONS.len()];
+        self.submitted = false;
+    }
+
+    fn score(&self) -> usize {
+        QUESTIONS
+            .iter()
+            .zip(&self.answers)
+            .filter(|(question, answer)| **answer == Some(question.answer))
+            .count()
+    }
+
+    /// The practice level to start at: one for none or one right, up to the
+    /// highest for all of them.
+    fn level(&self) -> u8 {
+        1 + (self.score() * 4 / QUESTIONS.len()) as u8
+    }
+
+    fn suggestion(&self) -> (Chapter, &'static str) {
+        for chapter in SUGGESTION_ORDER {
+            let missed = QUESTIONS
+                .iter()
+                .zip(&self.answers)
+                .any(|(question, answer)| {
+                    question.chapter == *chapter && *answer != Some(question.answer)
+                });
+            if missed {
+                return (*chapter, chapter_title(*chapter));
+            }
+        }
+        (Chapter::Functions, chapter_title(Chapter::Functions))
+    }
+}
+
+fn chapter_title(chapter: Chapter) -> &'static str {
+    match chapter {
+        Chapter::Expressions => "Hello, expressions",
+        Chapter::Booleans => "To Bool or Not to Bool",
+        Chapter::Loops => "Loops and counting",
+        Chapter::Functions => "Functions as reusable steps",
+        _ => "Overview",
+    }
+}
+
+pub fn placement_quiz(nb: &mut NotebookCtx) {
+    nb.state(
+        &(Chapter::Overview, "placement_state"),
+        PlacementState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Warm-up quiz (optional)") {
+                    return;
+                }
+                ui.add_space(4.0);
+                if difficulty::placed() && !state.open {
+                    ui.label("You took the warm-up already; practice started at your level.");
+                    if ui.add(widgets::Button::new("Take it again")).clicked() {
+                        state.restart();
+                        state.open = true;
+                    }
+                    return;
+                }
+                ui.label("Not sure where to begin? Answer a few quick questions.");
+                ui.label("We will suggest a chapter that fits what you already know.");
+                ui.add_space(6.0);
+
+                if !state.open {
+                    if ui.add(widgets::Button::new("Start the warm-up")).clicked() {
+                        state.open = true;
+                    }
+                    return;
+                }
+
+                for (index, question) in QUESTIONS.iter().enumerate() {
+                    ui.add_space(6.0);
+                    ui.label(format!("{}. {}", index + 1, question.prompt));
+                    ui.label(RichText::new(question.code).monospace());
+                    let answer = &mut state.answers[index];
+                    let mut toggle = widgets::ChoiceToggle::new(answer).small();
+                    for (choice_index, choice) in question.choices.iter().enumerate() {
+                        toggle = toggle.choice(Some(choice_index), *choice);
+                    }
+                    ui.add_enabled(!state.submitted, toggle);
+                }
+
+                ui.add_space(8.0);
+                let answered = state.answers.iter().all(Option::is_some);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            answered && !state.submitted,
+                            widgets::Button::new("Check answers"),
+                        )
+                        .clicked()
+                    {
+                        state.submitted = true;
+                        difficulty::place(state.level());
+                    }
+                    if ui.add(widgets::Button::new("Start over")).clicked() {
+                        state.restart();
+                    }
+                    if ui.add(widgets::Button::new("Skip")).clicked() {
+                        state.open = false;
+                    }
+                });
+
+                if state.submitted {
+                    let (chapter, title) = state.suggestion();
+                    ui.add_space(6.0);
+                    ui.label(format!(
+                        "You got {} of {} right.",
+                        state.score(),
+                        QUESTIONS.len()
+                    ));
+                    ui.label(format!("Suggested start: {title}."));
+                    ui.label(format!(
+                        "Practice starts at level {} of {} and adapts from there.",
+                        state.level(),
+                        difficulty::Difficulty::all().count()
+                    ));
+                    ui.add_space(4.0);
+                    if ui.add(widgets::Button::new("Open chapter")).clicked() {
+                        set_chapter(chapter);
+                    }
+                } else if !answered {
+                    ui.add_space(4.0);
+                    ui.label("Answer every question, then check.");
+                }
+            });
+        },
+    );
+}
@@ -0,0 +1,516 @@
+use egui::TextStyle;
+
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::palette::Palette;
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::stepper::Controls;
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::Results;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+const MAX_AGE: u32 = 150;
+
+const READ_AGE_LINES: &[&str] = &[
+    "fn read_age(input: &str) -> Result<u32, String> {",
+    "    let text = not_empty(input)?;",
+    "    let number = parse_number(text)?;",
+    "    let age = check_range(number)?;",
+    "    Ok(age)",
+    "}",
+];
+
+const PRESET_INPUTS: &[&str] = &["12", "", "abc", "250"];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Failure {
+    Empty,
+    NotANumber,
+    TooLarge,
+}
+
+impl Failure {
+    fn message(&self) -> &'static str {
+        match self {
+            Failure::Empty => "empty input",
+            Failure::NotANumber => "not a number",
+            Failure::TooLarge => "too large",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Ok,
+    Err(Failure),
+}
+
+struct Stage {
+    name: &'static str,
+    result: Result<String, Failure>,
+}
+
+/// Runs the `read_age` pipeline and records what each stage produced.
+/// Stages after the first error never run, so they are left out.
+fn run_pipeline(input: &str) -> (Vec<Stage>, Result<u32, Failure>) {
+    let mut stages = Vec::new();
+
+    let text = input.trim();
+    if text.is_empty() {
+        stages.push(Stage {
+            name: "not_empty",
+            result: Err(Failure::Empty),
+        });
+        return (stages, Err(Failure::Empty));
+    }
+    stages.push(Stage {
+        name: "not_empty",
+        result: Ok(format!("{text:?}")),
+    });
+
+    let Ok(number) = text.parse::<u32>() else {
+        stages.push(Stage {
+            name: "parse_number",
+            result: Err(Failure::NotANumber),
+        });
+        return (stages, Err(Failure::NotANumber));
+    };
+    stages.push(Stage {
+        name: "parse_number",
+        result: Ok(number.to_string()),
+    });
+
+    if number > MAX_AGE {
+        stages.push(Stage {
+            name: "check_range",
+            result: Err(Failure::TooLarge),
+        });
+        return (stages, Err(Failure::TooLarge));
+    }
+    stages.push(Stage {
+        name: "check_range",
+        result: Ok(number.to_string()),
+    });
+    (stages, Ok(number))
+}
+
+const STAGE_NAMES: &[&str] = &["not_empty", "parse_number", "check_range"];
+
+fn outcome_of(result: &Result<u32, Failure>) -> Outcome {
+    match result {
+        Ok(_) => Outcome::Ok,
+        Err(failure) => Outcome::Err(*failure),
+    }
+}
+
+fn result_text(result: &Result<u32, Failure>) -> String {
+    match result {
+        Ok(age) => format!("Ok({age})"),
+        Err(failure) => format!("Err(\"{}\")", failure.message()),
+    }
+}
+
+struct PipelineState {
+    input: String,
+}
+
+impl Default for PipelineState {
+    fn default() -> Self {
+        Self {
+            input: "12".to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct EarlyReturnState {
+    preset: usize,
+    step: usize,
+}
+
+/// An input to run through `read_age`.
+struct OutcomeQuestion {
+    input: String,
+}
+
+impl OutcomeQuestion {
+    fn call(&self) -> String {
+        format!("read_age({:?})", self.input)
+    }
+}
+
+impl Exercise for OutcomeQuestion {
+    type Answer = Outcome;
+
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        const WORDS: &[&str] = &["ten", "abc", "12a", "4.5", "-3", "x7"];
+        let input = match rng.gen_range_i32(0, 4) {
+            0 => rng.gen_range_i32(0, MAX_AGE as i32).to_string(),
+            1 => format!(" {} ", rng.gen_range_i32(1, 99)),
+            2 => rng.gen_range_i32(MAX_AGE as i32 + 1, 999).to_string(),
+            3 => WORDS[rng.gen_range_i32(0, WORDS.len() as i32 - 1) as usize].to_string(),
+            _ => ["", "   "][rng.gen_range_i32(0, 1) as usize].to_string(),
+        };
+        Self { input }
+    }
+
+    fn prompt(&self) -> String {
+        "What does read_age return for this input?".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::line(&self.call()));
+    }
+
+    /// The call and `read_age` itself, which the card shows further up.
+    fn text(&self) -> String {
+        format!(
+            "```rust\n{}\n\n{}\n```",
+            READ_AGE_LINES.join("\n"),
+            self.call()
+        )
+    }
+
+    fn answer(&self) -> Outcome {
+        outcome_of(&run_pipeline(&self.input).1)
+    }
+
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<Outcome> {
+        let answer = self.answer();
+        self.choices(rng)
+            .into_iter()
+            .filter(|choice| *choice != answer)
+            .collect()
+    }
+
+    /// `Ok` first, then the errors in the order the stages can fail.
+    fn choices(&self, _rng: &mut SimpleRng) -> Vec<Outcome> {
+        let failures = [Failure::Empty, Failure::NotANumber, Failure::TooLarge];
+        [Outcome::Ok]
+            .into_iter()
+            .chain(failures.map(Outcome::Err))
+            .collect()
+    }
+
+    fn choice_label(answer: &Outcome) -> String {
+        match answer {
+            Outcome::Ok => "Ok(age)".to_string(),
+            Outcome::Err(failure) => format!("Err(\"{}\")", failure.message()),
+        }
+    }
+
+    fn hint(&self, _picked: &Outcome) -> String {
+        "Walk through the steps in order and stop at the first Err.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, correct: bool) {
+        if correct {
+            let (_, result) = run_pipeline(&self.input);
+            ui.label(format!("{} = {}", self.call(), result_text(&result)));
+        }
+    }
+}
+
+/// An input to follow through `read_age` on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<OutcomeQuestion>(rng, difficulty)
+}
+
+/// Checks a generated input for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<OutcomeQuestion>(rng, difficulty)
+}
+
+fn paint_pipeline(ui: &mut egui::Ui, stages: &[Stage], result: &Result<u32, Failure>) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
+    let box_h = row_height * 2.0 + 16.0;
+    let width = ui.available_width().max(320.0);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, box_h + 24.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let palette = Palette::from_ui(ui);
+    let highlight_color = palette.highlight;
+    let text_color = palette.text;
+    let weak_color = palette.weak_text;
+    let line_stroke = palette.line;
+    let fill = palette.code_fill;
+
+    let columns = STAGE_NAMES.len() + 1;
+    let gap = 24.0;
+    let box_w = (width - gap * (columns - 1) as f32) / columns as f32;
+    let box_rect = |index: usize| {
+        let left = rect.left() + index as f32 * (box_w + gap);
+        egui::Rect::from_min_size(egui::pos2(left, rect.top() + 4.0), egui::vec2(box_w, box_h))
+    };
+
+    for (index, name) in STAGE_NAMES.iter().enumerate() {
+        let stage_rect = box_rect(index);
+        let (value, value_color, stroke) = match stages.get(index).map(|stage| &stage.result) {
+            Some(Ok(value)) => (format!("Ok({value})"), text_color, line_stroke),
+            Some(Err(failure)) => (
+                format!("Err({})", failure.message()),
+                highlight_color,
+                egui::Stroke::new(2.0, highlight_color),
+            ),
+            None => ("skipped".to_string(), weak_color, line_stroke),
+        };
+        painter.rect(
+            stage_rect,
+            egui::CornerRadius::same(4),
+            fill,
+            stroke,
+            egui::StrokeKind::Inside,
+        );
+        let name_color = if index < stages.len() {
+            text_color
+        } else {
+            weak_color
+        };
+        painter.text(
+            stage_rect.center_top() + egui::vec2(0.0, 8.0),
+            egui::Align2::CENTER_TOP,
+            *name,
+            font_id.clone(),
+            name_color,
+        );
+        painter.text(
+            stage_rect.center_bottom() - egui::vec2(0.0, 8.0),
+            egui::Align2::CENTER_BOTTOM,
+            value,
+            font_id.clone(),
+            value_color,
+        );
+
+        let next_rect = box_rect(index + 1);
+        let ran_next = index + 1 < stages.len();
+        let failed = matches!(stages.get(index).map(|stage| &stage.result), Some(Err(_)));
+        let start = stage_rect.right_center() + egui::vec2(2.0, 0.0);
+        let end = next_rect.left_center() - egui::vec2(2.0, 0.0);
+        if ran_next || (index + 1 == STAGE_NAMES.len() && result.is_ok()) {
+            painter.arrow(start, end - start, egui::Stroke::new(2.0, text_color));
+        } else if failed {
+            // The error skips straight past the remaining stages to the result.
+            let result_rect = box_rect(STAGE_NAMES.len());
+            let lane_y = rect.bottom() - 6.0;
+            let points = vec![
+                stage_rect.center_bottom(),
+                egui::pos2(stage_rect.center().x, lane_y),
+                egui::pos2(result_rect.center().x, lane_y),
+            ];
+            painter.add(egui::Shape::line(
+                points,
+                egui::Stroke::new(2.0, highlight_color),
+            ));
+            painter.arrow(
+                egui::pos2(result_rect.center().x, lane_y),
+                result_rect.center_bottom() - egui::pos2(result_rect.center().x, lane_y),
+                egui::Stroke::new(2.0, highlight_color),
+            );
+        }
+    }
+
+    let result_rect = box_rect(STAGE_NAMES.len());
+    let result_color = if result.is_ok() {
+        text_color
+    } else {
+        highlight_color
+    };
+    painter.rect(
+        result_rect,
+        egui::CornerRadius::same(4),
+        fill,
+        egui::Stroke::new(2.0, result_color),
+        egui::StrokeKind::Inside,
+    );
+    painter.text(
+        result_rect.center_top() + egui::vec2(0.0, 8.0),
+        egui::Align2::CENTER_TOP,
+        "read_age",
+        font_id.clone(),
+        text_color,
+    );
+    painter.text(
+        result_rect.center_bottom() - egui::vec2(0.0, 8.0),
+        egui::Align2::CENTER_BOTTOM,
+        result_text(result),
+        font_id,
+        result_color,
+    );
+}
+
+/// One entry per executed line of `read_age`: the line index and what happened there.
+fn early_return_steps(input: &str) -> Vec<(usize, String)> {
+    let (stages, result) = run_pipeline(input);
+    let mut steps = Vec::new();
+    for (index, stage) in stages.iter().enumerate() {
+        let note = match &stage.result {
+            Ok(value) => format!("{} gave Ok({value}). The ? unwraps it and we keep going.", stage.name),
+            Err(failure) => format!(
+                "{} gave Err(\"{}\"). The ? returns this error right away; the lines below never run.",
+                stage.name,
+                failure.message()
+            ),
+        };
+        steps.push((index + 1, note));
+    }
+    if let Ok(age) = result {
+        steps.push((
+            4,
+            format!("Every step worked, so read_age returns Ok({age})."),
+        ));
+    }
+    steps
+}
+
+pub fn results(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Errors and Result\n\
+         Some things can fail: a file may be missing, a number may be typed wrong.\n\
+         Rust does not hide this. A function that can fail returns a **Result**,\n\
+         which is either `Ok(value)` or `Err(problem)`.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         A letter passes through several hands: sorting, stamping, delivery.\n\
+         If the address is unreadable at sorting, nobody stamps it.\n\
+         It comes straight back to the sender with a note saying what went wrong.",
+    );
+
+    speech::lesson(
+        nb,
+        "## Minimal code\n\
+         ```rust\n\
+         enum Result<T, E> {\n    Ok(T),\n    Err(E),\n}\n\n\
+         match \"42\".parse::<u32>() {\n    Ok(n) => println!(\"got {n}\"),\n    Err(e) => println!(\"oops: {e}\"),\n}\n\
+         ```\n\
+         `Result` is just an enum, so we handle it with `match` like any other.",
+    );
+
+    nb.state(
+        &state_key("pipeline_state"),
+        PipelineState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "A pipeline that can fail") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Type an age. Each step either passes Ok along or stops with Err.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Input:");
+                    ui.add(widgets::TextField::singleline(&mut state.input));
+                });
+                ui.add_space(8.0);
+                let (stages, result) = run_pipeline(&state.input);
+                paint_pipeline(ui, &stages, &result);
+                ui.add_space(6.0);
+                match &result {
+                    Ok(_) => ui.label("Every step said Ok, so the value reaches the end."),
+                    Err(_) => ui.label("One Err is enough: the rest of the pipeline is skipped."),
+                };
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## The question mark\n\
+         Writing a `match` after every step gets long.\n\
+         The `?` operator is a shortcut: on `Ok(v)` it gives you `v`,\n\
+         on `Err(e)` it **returns** `Err(e)` from the whole function at once.",
+    );
+
+    nb.state(
+        &state_key("early_return_state"),
+        EarlyReturnState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Step through ?") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Pick an input and watch where read_age stops.");
+                ui.add_space(6.0);
+
+                let mut preset = state.preset;
+                let mut toggle = widgets::ChoiceToggle::new(&mut preset).small();
+                for (index, input) in PRESET_INPUTS.iter().enumerate() {
+                    toggle = toggle.choice(index, format!("{input:?}"));
+                }
+                ui.add(toggle);
+                if preset != state.preset {
+                    state.preset = preset;
+                    state.step = 0;
+                }
+
+                let steps = early_return_steps(PRESET_INPUTS[state.preset]);
+                let max_step = steps.len().saturating_sub(1);
+
+                ui.add_space(6.0);
+                Controls::new(&mut state.step, max_step).show(ui);
+
+                let (line, note) = &steps[state.step];
+                ui.add_space(8.0);
+                ui.add(CodeBlock::new(READ_AGE_LINES).highlight_line(Some(*line)));
+                ui.add_space(6.0);
+                ui.label(note);
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("outcome_quiz_state"),
+        MultipleChoice::<OutcomeQuestion>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Practice: predict the outcome") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("outcome_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<OutcomeQuestion>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("outcome_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - A function that can fail returns `Result`: `Ok(value)` or `Err(problem)`.\n\
+         - We handle a `Result` with `match`, just like any enum.\n\
+         - `?` unwraps an `Ok` and returns early on an `Err`.\n\
+         - The first error stops the pipeline; later steps never run.\n\n\
+         Next up: **Traits and generics** lets one function work with many types.",
+    );
+}
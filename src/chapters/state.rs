@@ -1,6 +1,12 @@
-use crate::chapters::Chapter;
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Quiz, Review};
+use crate::glossary;
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::stepper::Controls;
+use crate::worksheet;
 use egui::RichText;
-use std::time::{SystemTime, UNIX_EPOCH};
 use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
 use GORBIE::prelude::*;
 
@@ -10,40 +16,85 @@ fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
     (CHAPTER, key)
 }
 
-struct PracticeState {
-    rng: SimpleRng,
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+struct UpdateExercise {
     start: i32,
     ops: Vec<Op>,
     result: i32,
-    choices: Vec<i32>,
-    selection: Option<i32>,
 }
 
-impl Default for PracticeState {
-    fn default() -> Self {
-        let mut rng = SimpleRng::new(seed_from_time());
-        let (start, ops, result) = generate_practice(&mut rng);
-        let choices = build_choices(&mut rng, result);
-        Self {
-            rng,
-            start,
-            ops,
-            result,
-            choices,
-            selection: None,
+impl Exercise for UpdateExercise {
+    type Answer = i32;
+
+    fn generate(rng: &mut SimpleRng, difficulty: Difficulty) -> Self {
+        let (start, ops, result) = generate_practice(rng, difficulty);
+        Self { start, ops, result }
+    }
+
+    fn prompt(&self) -> String {
+        "Apply the updates in order, then choose the final value.".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        glossary::text(ui, "[[State]] is just the current value in the place.");
+        ui.label("Each line uses the current value and writes back a new one.");
+        ui.add_space(6.0);
+        widgets::markdown(ui, &self.text());
+    }
+
+    fn text(&self) -> String {
+        let arrow = "\u{2190}";
+        let mut lines = Vec::with_capacity(self.ops.len() + 1);
+        lines.push(format!("apples {arrow} {}", self.start));
+        for op in &self.ops {
+            lines.push(op.update_line("apples", arrow));
         }
+        let code = lines.join("\n");
+        format!("```text\n{code}\n```")
     }
-}
 
-impl PracticeState {
-    fn regenerate(&mut self) {
-        let (start, ops, result) = generate_practice(&mut self.rng);
-        self.start = start;
-        self.ops = ops;
-        self.result = result;
-        self.choices = build_choices(&mut self.rng, result);
-        self.selection = None;
+    fn answer(&self) -> i32 {
+        self.result
     }
+
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<i32> {
+        let mut distractors = Vec::new();
+        while distractors.len() < 3 {
+            let delta = rng.gen_range_i32(-6, 6);
+            if delta == 0 {
+                continue;
+            }
+            let candidate = self.result + delta;
+            if !(0..=99).contains(&candidate) {
+                continue;
+            }
+            if !distractors.contains(&candidate) {
+                distractors.push(candidate);
+            }
+        }
+        distractors
+    }
+
+    fn choice_label(answer: &i32) -> String {
+        answer.to_string()
+    }
+
+    fn hint(&self, _picked: &i32) -> String {
+        "Try another answer.".to_string()
+    }
+}
+
+/// A list of updates to apply on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<UpdateExercise>(rng, difficulty)
+}
+
+/// Checks a generated update exercise for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<UpdateExercise>(rng, difficulty)
 }
 
 #[derive(Clone, Copy)]
@@ -71,57 +122,19 @@ impl Op {
     }
 }
 
-struct SimpleRng {
-    state: u64,
-}
-
-impl SimpleRng {
-    fn new(seed: u64) -> Self {
-        Self { state: seed.max(1) }
-    }
-
-    fn next_u32(&mut self) -> u32 {
-        let mut x = self.state;
-        x ^= x << 13;
-        x ^= x >> 7;
-        x ^= x << 17;
-        self.state = x;
-        (x >> 32) as u32
-    }
-
-    fn gen_range_i32(&mut self, min: i32, max: i32) -> i32 {
-        let span = (max - min + 1) as u32;
-        let value = self.next_u32() % span;
-        min + value as i32
-    }
-
-    fn shuffle<T>(&mut self, values: &mut [T]) {
-        if values.len() <= 1 {
-            return;
-        }
-        for i in (1..values.len()).rev() {
-            let j = self.gen_range_i32(0, i as i32) as usize;
-            values.swap(i, j);
-        }
-    }
-}
-
-fn seed_from_time() -> u64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_nanos() as u64)
-        .unwrap_or(1)
-}
-
-fn generate_practice(rng: &mut SimpleRng) -> (i32, Vec<Op>, i32) {
+/// Higher levels start from larger numbers, apply more updates and mix in
+/// doubling and tripling.
+fn generate_practice(rng: &mut SimpleRng, difficulty: Difficulty) -> (i32, Vec<Op>, i32) {
+    let last_op = if difficulty.harder_ops() { 2 } else { 1 };
+    let step = (difficulty.max_value() / 2).max(2);
     for _ in 0..200 {
-        let start = rng.gen_range_i32(2, 9);
-        let op_count = rng.gen_range_i32(2, 4);
+        let start = rng.gen_range_i32(2, difficulty.max_value());
+        let op_count = rng.gen_range_i32(2, difficulty.max_depth() as i32 + 1);
         let mut ops = Vec::with_capacity(op_count as usize);
         for _ in 0..op_count {
-            let op = match rng.gen_range_i32(0, 2) {
-                0 => Op::Add(rng.gen_range_i32(1, 4)),
-                1 => Op::Sub(rng.gen_range_i32(1, 4)),
+            let op = match rng.gen_range_i32(0, last_op) {
+                0 => Op::Add(rng.gen_range_i32(1, step)),
+                1 => Op::Sub(rng.gen_range_i32(1, step)),
                 _ => Op::Mul(rng.gen_range_i32(2, 3)),
             };
             ops.push(op);
@@ -151,90 +164,64 @@ fn generate_practice(rng: &mut SimpleRng) -> (i32, Vec<Op>, i32) {
     (3, ops, 10)
 }
 
-fn build_choices(rng: &mut SimpleRng, answer: i32) -> Vec<i32> {
-    let mut choices = vec![answer];
-    while choices.len() < 4 {
-        let delta = rng.gen_range_i32(-6, 6);
-        if delta == 0 {
-            continue;
-        }
-        let candidate = answer + delta;
-        if !(0..=99).contains(&candidate) {
-            continue;
-        }
-        if !choices.contains(&candidate) {
-            choices.push(candidate);
-        }
-    }
-    rng.shuffle(&mut choices);
-    choices
-}
-
 pub fn state(nb: &mut NotebookCtx) {
-    nb.view(|ui| {
-        md!(
-            ui,
-            "# Hello, state\n\
-             A **variable** is a named place that holds a value over time.\n\
-             The *place* stays the same, the *value* can change.\n\
-             The current value in the place is its **state**.\n\n\
-             If expressions are new, start with **Hello, expressions** first.\n\n\
-             The *name* tells us which place we mean.\n\
-             The *value* is what is inside the place.\n\
-             We can change the value as the story changes.\n\n\
-             We update a variable with a left arrow (←).\n\
-             The right side is an expression we evaluate.\n\
-             The left side is the place that gets the new value."
-        );
-    });
+    speech::lesson(
+        nb,
+        "# Hello, state\n\
+         A **variable** is a named place that holds a value over time.\n\
+         The *place* stays the same, the *value* can change.\n\
+         The current value in the place is its **state**.\n\n\
+         If expressions are new, start with **Hello, expressions** first.\n\n\
+         The *name* tells us which place we mean.\n\
+         The *value* is what is inside the place.\n\
+         We can change the value as the story changes.\n\n\
+         We update a variable with a left arrow (←).\n\
+         The right side is an expression we evaluate.\n\
+         The left side is the place that gets the new value.",
+    );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## A tiny story\n\
-             We have a place called `apples`.\n\
-             At the start, the place has **3** apples.\n\n\
-             If we add one apple, the number grows.\n\
-             If we take one apple, the number shrinks.\n\n\
-             The place stays the same.\n\
-             Only the value inside changes.\n\
-             This is why we use state: the world changes and we need to remember it."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         We have a place called `apples`.\n\
+         At the start, the place has **3** apples.\n\n\
+         If we add one apple, the number grows.\n\
+         If we take one apple, the number shrinks.\n\n\
+         The place stays the same.\n\
+         Only the value inside changes.\n\
+         This is why we use state: the world changes and we need to remember it.",
+    );
 
-    nb.view(|ui| {
-        let arrow = "\u{2190}";
-        md!(
-            ui,
-            "## Assignment and update\n\
-             We *introduce* a variable by giving it a name and a starting value.\n\
-             Then we update it by writing a new value into the same place.\n\n\
-             ```text\n\
-             apples {arrow} 3\n\
-             apples {arrow} apples + 1\n\
-             ```\n\n\
-             Read this as: “put 3 into the apples place, then add 1.”\n\
-             The second line is **self-referential**: it uses `apples` to compute\n\
-             the new value for `apples`.\n\
-             The right side is evaluated first, using the current value.\n\
-             Then we store the result in the same place."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## Assignment and update\n\
+         We *introduce* a variable by giving it a name and a starting value.\n\
+         Then we update it by writing a new value into the same place.\n\n\
+         ```text\n\
+         apples \u{2190} 3\n\
+         apples \u{2190} apples + 1\n\
+         ```\n\n\
+         Read this as: “put 3 into the apples place, then add 1.”\n\
+         The second line is **self-referential**: it uses `apples` to compute\n\
+         the new value for `apples`.\n\
+         The right side is evaluated first, using the current value.\n\
+         Then we store the result in the same place.",
+    );
 
-    nb.view(|ui| {
-        md!(
-            ui,
-            "## Some values stay fixed\n\
-             Not everything should change. Sometimes we want a **constant** value\n\
-             that stays the same while other values move around.\n\
-             Constants make programs easier to understand because the rule never shifts.\n\
-             We will use fixed values more in the Rust track."
-        );
-    });
+    speech::lesson(
+        nb,
+        "## Some values stay fixed\n\
+         Not everything should change. Sometimes we want a **constant** value\n\
+         that stays the same while other values move around.\n\
+         Constants make programs easier to understand because the rule never shifts.\n\
+         We will use fixed values more in the Rust track.",
+    );
 
-    nb.state(&chapter_key("immutability_demo"), 2_i32, |ui, count| {
+    nb.state(&state_key("immutability_demo"), 2_i32, |ui, count| {
         with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            ui.label(RichText::new("A fixed rule, a changing value").heading());
+            if !card_heading(ui, "A fixed rule, a changing value") {
+                return;
+            }
             ui.add_space(6.0);
 
             let limit = 5;
@@ -266,9 +253,11 @@ pub fn state(nb: &mut NotebookCtx) {
         });
     });
 
-    let apples = nb.state(&chapter_key("apples"), 3_i32, |ui, value| {
+    let apples = nb.state(&state_key("apples"), 3_i32, |ui, value| {
         with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-            ui.label(RichText::new("Try changing the value.").heading());
+            if !card_heading(ui, "Try changing the value.") {
+                return;
+            }
             ui.add_space(6.0);
 
             ui.label(RichText::new(format!("apples = {value}")).heading());
@@ -310,12 +299,9 @@ pub fn state(nb: &mut NotebookCtx) {
         });
     });
 
-    nb.state(&chapter_key("assignment_step"), 0_usize, |ui, step| {
+    nb.state(&state_key("assignment_step"), 0_usize, |ui, step| {
         with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
             let max_step = 3_usize;
-            if *step > max_step {
-                *step = max_step;
-            }
 
             let arrow = "\u{2190}";
             let lines = [
@@ -325,25 +311,14 @@ pub fn state(nb: &mut NotebookCtx) {
                 format!("apples {arrow} apples * 2"),
             ];
 
-            ui.label(RichText::new("Step through the updates").heading());
+            if !card_heading(ui, "Step through the updates") {
+                return;
+            }
             ui.add_space(4.0);
             ui.label("Use the buttons to move the marker.");
             ui.add_space(6.0);
 
-            ui.horizontal(|ui| {
-                if ui.add(widgets::Button::new("Prev")).clicked() {
-                    *step = step.saturating_sub(1);
-                }
-                if ui.add(widgets::Button::new("Next")).clicked() {
-                    *step = (*step + 1).min(max_step);
-                }
-                if ui.add(widgets::Button::new("Reset")).clicked() {
-                    *step = 0;
-                }
-                ui.add_space(6.0);
-                let step_value = *step;
-                ui.label(format!("Step {step_value}/{max_step}"));
-            });
+            Controls::new(step, max_step).show(ui);
 
             ui.add_space(8.0);
             let mut code = String::new();
@@ -381,54 +356,57 @@ pub fn state(nb: &mut NotebookCtx) {
     });
 
     nb.state(
-        &chapter_key("practice_state"),
-        PracticeState::default(),
-        |ui, state| {
+        &state_key("practice_state"),
+        MultipleChoice::<UpdateExercise>::default(),
+        |ui, practice| {
             with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
-                let arrow = "\u{2190}";
-                ui.label(RichText::new("Random practice").heading());
-                ui.add_space(6.0);
-                ui.label("Apply the updates in order, then choose the final value.");
-                ui.label("State is just the current value in the place.");
-                ui.label("Each line uses the current value and writes back a new one.");
-                ui.add_space(6.0);
-                if ui.add(widgets::Button::new("New sequence")).clicked() {
-                    state.regenerate();
+                if !card_heading(ui, "Random practice") {
+                    return;
                 }
                 ui.add_space(6.0);
+                practice.show(ui, chapter_key("practice_state"));
+            });
+        },
+    );
 
-                let mut lines = Vec::with_capacity(state.ops.len() + 1);
-                lines.push(format!("apples {arrow} {}", state.start));
-                for op in &state.ops {
-                    lines.push(op.update_line("apples", arrow));
+    nb.state(
+        &state_key("review_state"),
+        Review::<UpdateExercise>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
                 }
-                let code = lines.join("\n");
-                widgets::markdown(ui, &format!("```text\n{code}\n```"));
-
                 ui.add_space(6.0);
-                let mut toggle = widgets::ChoiceToggle::new(&mut state.selection).small();
-                for choice in &state.choices {
-                    toggle = toggle.choice(Some(*choice), choice.to_string());
-                }
-                ui.add(toggle);
-                ui.add_space(4.0);
-                match state.selection {
-                    Some(value) if value == state.result => ui.label("Correct!"),
-                    Some(_) => ui.label("Not quite. Try another answer."),
-                    None => ui.label("Pick an answer."),
-                }
+                review.show(ui, chapter_key("practice_state"));
             });
         },
     );
 
     nb.view(move |ui| {
         let value = apples.read(ui);
-        md!(
+        speech::markdown(
             ui,
-            "## What just happened\n\
-             A variable keeps its value until you change it.\n\
-             Buttons change the value, so the number updates.\n\n\
-             Current value: **{value}**"
+            &format!(
+                "## What just happened\n\
+                 A variable keeps its value until you change it.\n\
+                 Buttons change the value, so the number updates.\n\n\
+                 Current value: **{value}**"
+            ),
         );
     });
+
+    nb.state(
+        &state_key("quiz_state"),
+        Quiz::<UpdateExercise>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Chapter quiz") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("quiz_state"));
+            });
+        },
+    );
 }
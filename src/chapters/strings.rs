@@ -0,0 +1,447 @@
+use egui::TextStyle;
+
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::glossary;
+use crate::palette::Palette;
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::Strings;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+const WORDS: &[&str] = &[
+    "café",
+    "naïve",
+    "héllo",
+    "über",
+    "smörgås",
+    "日本",
+    "a→b",
+    "hi🙂",
+];
+
+/// Describes `&s[start..end]`: the slice on success, or why it would panic.
+fn slice_result(text: &str, start: usize, end: usize) -> Result<&str, String> {
+    if start > end {
+        return Err(format!(
+            "start {start} is after end {end}, so the slice panics."
+        ));
+    }
+    if end > text.len() {
+        return Err(format!(
+            "end {end} is past the last byte ({}), so the slice panics.",
+            text.len()
+        ));
+    }
+    for index in [start, end] {
+        if !text.is_char_boundary(index) {
+            let (char_start, ch) = text
+                .char_indices()
+                .take_while(|(offset, _)| *offset < index)
+                .last()
+                .unwrap_or((0, ' '));
+            return Err(format!(
+                "byte {index} is inside '{ch}' (bytes {char_start}..{}), so the slice panics.",
+                char_start + ch.len_utf8()
+            ));
+        }
+    }
+    Ok(&text[start..end])
+}
+
+struct SliceState {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+impl Default for SliceState {
+    fn default() -> Self {
+        Self {
+            text: "héllo, 世界".to_string(),
+            start: 0,
+            end: 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum QuestionKind {
+    Slice,
+    Length,
+}
+
+/// A byte count or a slice of one of [`WORDS`].
+struct SliceQuestion {
+    word: &'static str,
+    kind: QuestionKind,
+    start: usize,
+    end: usize,
+}
+
+#[derive(PartialEq)]
+enum SliceAnswer {
+    Bytes(usize),
+    Fine,
+    Panics,
+}
+
+impl SliceQuestion {
+    fn code(&self) -> String {
+        match self.kind {
+            QuestionKind::Length => format!("let s = \"{}\";\ns.len()", self.word),
+            QuestionKind::Slice => format!(
+                "let s = \"{}\";\n&s[{}..{}]",
+                self.word, self.start, self.end
+            ),
+        }
+    }
+}
+
+impl Exercise for SliceQuestion {
+    type Answer = SliceAnswer;
+
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        let word = WORDS[rng.gen_range_i32(0, WORDS.len() as i32 - 1) as usize];
+        let len = word.len() as i32;
+        if rng.gen_range_i32(0, 2) == 0 {
+            SliceQuestion {
+                word,
+                kind: QuestionKind::Length,
+                start: 0,
+                end: 0,
+            }
+        } else {
+            let start = rng.gen_range_i32(0, len - 1) as usize;
+            let end = rng.gen_range_i32(start as i32 + 1, len) as usize;
+            SliceQuestion {
+                word,
+                kind: QuestionKind::Slice,
+                start,
+                end,
+            }
+        }
+    }
+
+    fn prompt(&self) -> String {
+        match self.kind {
+            QuestionKind::Length => "How many bytes long is s?",
+            QuestionKind::Slice => "Is this slice fine, or does it panic?",
+        }
+        .to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::line(&self.code()));
+    }
+
+    fn text(&self) -> String {
+        format!("```rust\n{}\n```", self.code())
+    }
+
+    fn answer(&self) -> SliceAnswer {
+        match self.kind {
+            QuestionKind::Length => SliceAnswer::Bytes(self.word.len()),
+            QuestionKind::Slice if slice_result(self.word, self.start, self.end).is_ok() => {
+                SliceAnswer::Fine
+            }
+            QuestionKind::Slice => SliceAnswer::Panics,
+        }
+    }
+
+    /// For a length, the count of chars instead of bytes and one byte too
+    /// many.
+    fn distractors(&self, _rng: &mut SimpleRng) -> Vec<SliceAnswer> {
+        match self.answer() {
+            SliceAnswer::Bytes(bytes) => vec![
+                SliceAnswer::Bytes(self.word.chars().count()),
+                SliceAnswer::Bytes(bytes + 1),
+            ],
+            SliceAnswer::Fine => vec![SliceAnswer::Panics],
+            SliceAnswer::Panics => vec![SliceAnswer::Fine],
+        }
+    }
+
+    fn choices(&self, rng: &mut SimpleRng) -> Vec<SliceAnswer> {
+        match self.kind {
+            QuestionKind::Length => {
+                let mut choices = vec![self.answer()];
+                for distractor in self.distractors(rng) {
+                    if !choices.contains(&distractor) {
+                        choices.push(distractor);
+                    }
+                }
+                rng.shuffle(&mut choices);
+                choices
+            }
+            QuestionKind::Slice => vec![SliceAnswer::Fine, SliceAnswer::Panics],
+        }
+    }
+
+    fn choice_label(answer: &SliceAnswer) -> String {
+        match answer {
+            SliceAnswer::Bytes(count) => count.to_string(),
+            SliceAnswer::Fine => "Fine".to_string(),
+            SliceAnswer::Panics => "Panics".to_string(),
+        }
+    }
+
+    fn hint(&self, _picked: &SliceAnswer) -> String {
+        "Count the bytes of each character, not the letters.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, correct: bool) {
+        if !correct {
+            return;
+        }
+        match self.kind {
+            QuestionKind::Length => {
+                ui.label(format!(
+                    "{} bytes, but only {} chars.",
+                    self.word.len(),
+                    self.word.chars().count()
+                ));
+            }
+            QuestionKind::Slice => {
+                match slice_result(self.word, self.start, self.end) {
+                    Ok(slice) => ui.label(format!("It gives {slice:?}.")),
+                    Err(reason) => ui.label(reason),
+                };
+                glossary::text(ui, "Read more about the [[slice]].");
+            }
+        }
+    }
+}
+
+/// A byte count or slice to work out on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<SliceQuestion>(rng, difficulty)
+}
+
+/// Checks a generated byte count or slice for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<SliceQuestion>(rng, difficulty)
+}
+
+/// Paints the bytes of `text` in a row, the chars they form underneath,
+/// and marks the selected byte range `start..end`.
+fn paint_bytes(ui: &mut egui::Ui, text: &str, start: usize, end: usize) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
+    let cell_h = row_height + 10.0;
+    let width = ui.available_width().max(240.0);
+    let bytes = text.as_bytes();
+    let count = bytes.len().max(1);
+    let cell_w = (width / count as f32).min(40.0);
+    let height = row_height + cell_h * 2.0 + 16.0;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let palette = Palette::from_ui(ui);
+    let highlight_color = palette.highlight;
+    let text_color = palette.text;
+    let weak_color = palette.weak_text;
+    let line_stroke = palette.line;
+    let fill = palette.code_fill;
+    let selected_fill = palette.tint(0.25);
+
+    let index_top = rect.top();
+    let byte_top = index_top + row_height + 2.0;
+    let char_top = byte_top + cell_h + 6.0;
+
+    for (index, byte) in bytes.iter().enumerate() {
+        let left = rect.left() + index as f32 * cell_w;
+        let cell =
+            egui::Rect::from_min_size(egui::pos2(left, byte_top), egui::vec2(cell_w, cell_h));
+        let selected = (start..end).contains(&index);
+        painter.rect(
+            cell,
+            egui::CornerRadius::ZERO,
+            if selected { selected_fill } else { fill },
+            line_stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.text(
+            cell.center(),
+            egui::Align2::CENTER_CENTER,
+            format!("{byte:02X}"),
+            font_id.clone(),
+            text_color,
+        );
+        painter.text(
+            egui::pos2(cell.left() + 2.0, index_top),
+            egui::Align2::LEFT_TOP,
+            index.to_string(),
+            font_id.clone(),
+            weak_color,
+        );
+    }
+
+    for (offset, ch) in text.char_indices() {
+        let left = rect.left() + offset as f32 * cell_w;
+        let cell = egui::Rect::from_min_size(
+            egui::pos2(left, char_top),
+            egui::vec2(cell_w * ch.len_utf8() as f32, cell_h),
+        );
+        painter.rect(
+            cell.shrink(1.0),
+            egui::CornerRadius::same(4),
+            fill,
+            line_stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.text(
+            cell.center(),
+            egui::Align2::CENTER_CENTER,
+            ch.to_string(),
+            font_id.clone(),
+            text_color,
+        );
+    }
+
+    let marker_stroke = egui::Stroke::new(2.0, highlight_color);
+    for index in [start, end] {
+        let x = rect.left() + index.min(bytes.len()) as f32 * cell_w;
+        painter.line_segment(
+            [
+                egui::pos2(x, byte_top - 2.0),
+                egui::pos2(x, char_top + cell_h),
+            ],
+            marker_stroke,
+        );
+    }
+}
+
+pub fn strings(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Strings and slices\n\
+         Text in Rust is stored as **UTF-8 bytes**.\n\
+         Plain letters like `a` take one byte, but `é` takes two and `世` takes three.\n\
+         So a string has a number of bytes *and* a number of characters, and they can differ.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         Think of a train where most passengers fit in one seat,\n\
+         but some need two or three seats side by side.\n\
+         You can cut the train between passengers, but never through the middle of one.",
+    );
+
+    speech::lesson(
+        nb,
+        "## Minimal code\n\
+         ```rust\n\
+         let s = String::from(\"héllo\");\n\
+         println!(\"{}\", s.len());           // 6 bytes\n\
+         println!(\"{}\", s.chars().count()); // 5 chars\n\
+         let first = &s[0..1];                 // \"h\"\n\
+         let oops = &s[0..2];                  // panics: cuts through é\n\
+         ```\n\
+         A **slice** `&s[a..b]` borrows bytes `a` up to (not including) `b`.",
+    );
+
+    nb.state(
+        &state_key("slice_state"),
+        SliceState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Bytes, chars, and slices") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Type some text and move the range. Top row: bytes. Bottom row: chars.");
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Text:");
+                    ui.add(widgets::TextField::singleline(&mut state.text));
+                });
+
+                let len = state.text.len();
+                state.start = state.start.min(len);
+                state.end = state.end.min(len);
+                ui.add_space(6.0);
+                ui.add(widgets::Slider::new(&mut state.start, 0..=len).text("start"));
+                ui.add(widgets::Slider::new(&mut state.end, 0..=len).text("end"));
+                ui.add_space(8.0);
+                paint_bytes(ui, &state.text, state.start, state.end);
+                ui.add_space(6.0);
+
+                ui.label(format!(
+                    "len() = {} bytes, chars().count() = {}",
+                    len,
+                    state.text.chars().count()
+                ));
+                let code = format!("&s[{}..{}]", state.start, state.end);
+                ui.add(CodeBlock::line(&code));
+                match slice_result(&state.text, state.start, state.end) {
+                    Ok(slice) => {
+                        ui.label(format!("gives {slice:?}"));
+                    }
+                    Err(reason) => {
+                        ui.label(reason);
+                    }
+                }
+            });
+        },
+    );
+
+    nb.view(|ui| {
+        note!(
+            ui,
+            "If you need the n-th character, use `s.chars().nth(n)`.\n\
+             Byte indices are fast, but you must cut on character boundaries."
+        );
+    });
+
+    nb.state(
+        &state_key("slice_quiz_state"),
+        MultipleChoice::<SliceQuestion>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Practice: bytes and boundaries") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("slice_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<SliceQuestion>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("slice_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - Strings are UTF-8 bytes; one character can take 1 to 4 bytes.\n\
+         - `len()` counts bytes, `chars().count()` counts characters.\n\
+         - `&s[a..b]` slices by byte index and must cut on character boundaries.\n\n\
+         Next up: **Modules and crates** organizes code into named boxes.",
+    );
+}
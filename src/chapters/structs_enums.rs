@@ -0,0 +1,422 @@
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::palette::Palette;
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::stepper::{stepper, Autoplay, Steps};
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::StructsEnums;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shape {
+    Dot,
+    Circle { r: i32 },
+    Rect { w: i32, h: i32 },
+}
+
+impl Shape {
+    fn literal(&self) -> String {
+        match self {
+            Shape::Dot => "Shape::Dot".to_string(),
+            Shape::Circle { r } => format!("Shape::Circle {{ r: {r} }}"),
+            Shape::Rect { w, h } => format!("Shape::Rect {{ w: {w}, h: {h} }}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Variant {
+    Dot,
+    Circle,
+    Rect,
+}
+
+const MATCH_LINES: &[&str] = &[
+    "let size = match shape {",
+    "    Shape::Dot => 0,",
+    "    Shape::Circle { r } => 3 * r * r,",
+    "    Shape::Rect { w, h } if w == h => w * w,",
+    "    Shape::Rect { w, h } => w * h,",
+    "};",
+];
+
+const ARM_COUNT: usize = 4;
+
+const PRESETS: &[Shape] = &[
+    Shape::Dot,
+    Shape::Circle { r: 2 },
+    Shape::Rect { w: 3, h: 3 },
+    Shape::Rect { w: 4, h: 2 },
+];
+
+fn arm_matches(shape: Shape, arm: usize) -> bool {
+    match (arm, shape) {
+        (0, Shape::Dot) => true,
+        (1, Shape::Circle { .. }) => true,
+        (2, Shape::Rect { w, h }) => w == h,
+        (3, Shape::Rect { .. }) => true,
+        _ => false,
+    }
+}
+
+fn firing_arm(shape: Shape) -> usize {
+    (0..ARM_COUNT)
+        .find(|arm| arm_matches(shape, *arm))
+        .unwrap_or(ARM_COUNT - 1)
+}
+
+fn match_result(shape: Shape) -> i32 {
+    match shape {
+        Shape::Dot => 0,
+        Shape::Circle { r } => 3 * r * r,
+        Shape::Rect { w, h } if w == h => w * w,
+        Shape::Rect { w, h } => w * h,
+    }
+}
+
+fn arm_note(shape: Shape, arm: usize) -> String {
+    if arm_matches(shape, arm) {
+        return format!("This arm fires. size = {}.", match_result(shape));
+    }
+    match (arm, shape) {
+        (2, Shape::Rect { .. }) => {
+            "The pattern fits a Rect, but the guard w == h is false. Keep looking.".to_string()
+        }
+        (0, _) => "The value is not a Dot. Keep looking.".to_string(),
+        (1, _) => "The value is not a Circle. Keep looking.".to_string(),
+        _ => "The value is not a Rect. Keep looking.".to_string(),
+    }
+}
+
+struct BuilderState {
+    variant: Variant,
+    radius: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Default for BuilderState {
+    fn default() -> Self {
+        Self {
+            variant: Variant::Circle,
+            radius: 2,
+            width: 3,
+            height: 2,
+        }
+    }
+}
+
+impl BuilderState {
+    fn shape(&self) -> Shape {
+        match self.variant {
+            Variant::Dot => Shape::Dot,
+            Variant::Circle => Shape::Circle { r: self.radius },
+            Variant::Rect => Shape::Rect {
+                w: self.width,
+                h: self.height,
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+struct MatchStepperState {
+    preset: usize,
+    step: usize,
+    play: Autoplay,
+}
+
+/// The arms of [`MATCH_LINES`] tried one by one on a shape, up to the one
+/// that fires.
+struct MatchRun(Shape);
+
+impl Steps for MatchRun {
+    fn count(&self) -> usize {
+        firing_arm(self.0) + 1
+    }
+
+    fn show(&self, ui: &mut egui::Ui, step: usize) {
+        ui.label(format!("shape = {}", self.0.literal()));
+        ui.add_space(4.0);
+        ui.add(CodeBlock::new(MATCH_LINES).highlight_line(Some(step + 1)));
+        ui.add_space(6.0);
+        ui.label(arm_note(self.0, step));
+    }
+}
+
+/// A shape to run through the match in [`MATCH_LINES`].
+struct ArmQuestion(Shape);
+
+impl Exercise for ArmQuestion {
+    type Answer = usize;
+
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        let shape = match rng.gen_range_i32(0, 3) {
+            0 => Shape::Dot,
+            1 => Shape::Circle {
+                r: rng.gen_range_i32(1, 5),
+            },
+            2 => {
+                let side = rng.gen_range_i32(1, 5);
+                Shape::Rect { w: side, h: side }
+            }
+            _ => {
+                let w = rng.gen_range_i32(1, 5);
+                let mut h = rng.gen_range_i32(1, 5);
+                if h == w {
+                    h = if w == 5 { 1 } else { w + 1 };
+                }
+                Shape::Rect { w, h }
+            }
+        };
+        Self(shape)
+    }
+
+    fn prompt(&self) -> String {
+        "Which arm of the match above runs for this value?".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::line(&format!(
+            "let shape = {};",
+            self.0.literal()
+        )));
+    }
+
+    /// The value and the match, which the card shows further up.
+    fn text(&self) -> String {
+        format!(
+            "```rust\nlet shape = {};\n{}\n```",
+            self.0.literal(),
+            MATCH_LINES.join("\n")
+        )
+    }
+
+    fn answer(&self) -> usize {
+        firing_arm(self.0)
+    }
+
+    fn distractors(&self, _rng: &mut SimpleRng) -> Vec<usize> {
+        (0..ARM_COUNT).filter(|arm| *arm != self.answer()).collect()
+    }
+
+    fn choices(&self, _rng: &mut SimpleRng) -> Vec<usize> {
+        (0..ARM_COUNT).collect()
+    }
+
+    fn choice_label(answer: &usize) -> String {
+        format!("Arm {}", answer + 1)
+    }
+
+    fn hint(&self, picked: &usize) -> String {
+        arm_note(self.0, *picked)
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, correct: bool) {
+        if correct {
+            ui.label(format!("size = {}", match_result(self.0)));
+        }
+    }
+}
+
+/// A value to match on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<ArmQuestion>(rng, difficulty)
+}
+
+/// Checks a generated shape for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<ArmQuestion>(rng, difficulty)
+}
+
+fn paint_shape(ui: &mut egui::Ui, shape: Shape) {
+    let unit = 14.0;
+    let size = egui::vec2(ui.available_width().max(200.0), 5.0 * unit * 2.0 + 16.0);
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let color = Palette::from_ui(ui).highlight;
+    let stroke = egui::Stroke::new(2.0, ui.visuals().text_color());
+    let center = rect.center();
+    match shape {
+        Shape::Dot => {
+            painter.circle_filled(center, 4.0, ui.visuals().text_color());
+        }
+        Shape::Circle { r } => {
+            painter.circle(center, r as f32 * unit, color, stroke);
+        }
+        Shape::Rect { w, h } => {
+            let shape_rect =
+                egui::Rect::from_center_size(center, egui::vec2(w as f32, h as f32) * unit * 2.0);
+            painter.rect(
+                shape_rect,
+                egui::CornerRadius::ZERO,
+                color,
+                stroke,
+                egui::StrokeKind::Inside,
+            );
+        }
+    }
+}
+
+pub fn structs_enums(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Structs, enums, and pattern matching\n\
+         So far our values were numbers, text, and lists.\n\
+         Now we build **our own types** that describe exactly the things our program talks about.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         A library card has several fields: a name, a number, a due date.\n\
+         They always travel together, so we bundle them into one **struct**.\n\n\
+         A traffic light is different: it is *either* red, *or* yellow, *or* green.\n\
+         Exactly one at a time. That is an **enum**.",
+    );
+
+    speech::lesson(
+        nb,
+        "## Minimal code\n\
+         ```rust\n\
+         struct Card {\n    name: String,\n    number: u32,\n}\n\n\
+         enum Shape {\n    Dot,\n    Circle { r: i32 },\n    Rect { w: i32, h: i32 },\n}\n\
+         ```\n\
+         A struct has **all** of its fields. An enum value is **one** of its variants,\n\
+         and each variant can carry its own fields.",
+    );
+
+    nb.state(
+        &state_key("builder_state"),
+        BuilderState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Build a Shape") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Pick a variant, then fill in its fields.");
+                ui.add_space(6.0);
+
+                let mut toggle = widgets::ChoiceToggle::new(&mut state.variant).small();
+                toggle = toggle.choice(Variant::Dot, "Dot");
+                toggle = toggle.choice(Variant::Circle, "Circle");
+                toggle = toggle.choice(Variant::Rect, "Rect");
+                ui.add(toggle);
+                ui.add_space(6.0);
+
+                match state.variant {
+                    Variant::Dot => {
+                        ui.label("A Dot carries no fields at all.");
+                    }
+                    Variant::Circle => {
+                        ui.add(widgets::Slider::new(&mut state.radius, 1..=5).text("r"));
+                    }
+                    Variant::Rect => {
+                        ui.add(widgets::Slider::new(&mut state.width, 1..=5).text("w"));
+                        ui.add(widgets::Slider::new(&mut state.height, 1..=5).text("h"));
+                    }
+                }
+
+                let shape = state.shape();
+                ui.add_space(6.0);
+                let literal = format!("let shape = {};", shape.literal());
+                ui.add(CodeBlock::line(&literal));
+                ui.add_space(6.0);
+                paint_shape(ui, shape);
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Pattern matching\n\
+         To use an enum we ask *which variant is it?* with `match`.\n\
+         Rust tries the arms **from top to bottom** and runs the first one that fits.\n\
+         An arm can also have a guard (`if ...`) that must be true as well.\n\
+         The compiler checks that every variant is handled.",
+    );
+
+    nb.state(
+        &state_key("match_stepper_state"),
+        MatchStepperState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Which arm fires?") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("Pick a value and step through the arms one by one.");
+                ui.add_space(6.0);
+
+                let mut preset = state.preset;
+                let mut toggle = widgets::ChoiceToggle::new(&mut preset).small();
+                for (index, shape) in PRESETS.iter().enumerate() {
+                    toggle = toggle.choice(index, shape.literal());
+                }
+                ui.add(toggle);
+                if preset != state.preset {
+                    state.preset = preset;
+                    state.step = 0;
+                    state.play.stop();
+                }
+
+                let run = MatchRun(PRESETS[state.preset]);
+                ui.add_space(6.0);
+                state.play.show(ui, &mut state.step, run.count() - 1);
+                stepper(ui, &mut state.step, &run);
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("arm_quiz_state"),
+        MultipleChoice::<ArmQuestion>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Practice: predict the arm") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("arm_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<ArmQuestion>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("arm_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - A struct bundles fields that always belong together.\n\
+         - An enum value is exactly one of its variants, and variants can carry data.\n\
+         - `match` tries arms from top to bottom and runs the first one that fits.\n\
+         - Guards add an extra condition to an arm.\n\n\
+         Next up: **Errors and Result** uses an enum to talk about things that can go wrong.",
+    );
+}
@@ -0,0 +1,502 @@
+use egui::TextStyle;
+
+use crate::chapters::{self, card_heading, Chapter, StateKey};
+use crate::codeview::CodeBlock;
+use crate::difficulty::Difficulty;
+use crate::exercise::{self, Exercise, MultipleChoice, Review};
+use crate::palette::Palette;
+use crate::rng::SimpleRng;
+use crate::speech;
+use crate::worksheet;
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+const CHAPTER: Chapter = Chapter::Traits;
+
+fn chapter_key(key: &'static str) -> (Chapter, &'static str) {
+    (CHAPTER, key)
+}
+
+fn state_key(key: &'static str) -> StateKey {
+    chapters::state_key(CHAPTER, key)
+}
+
+struct SlotType {
+    name: &'static str,
+    impl_lines: &'static [&'static str],
+    speaks: Option<&'static str>,
+}
+
+const SLOT_TYPES: &[SlotType] = &[
+    SlotType {
+        name: "Dog",
+        impl_lines: &[
+            "impl Speak for Dog {",
+            "    fn speak(&self) -> String {",
+            "        \"Woof!\".to_string()",
+            "    }",
+            "}",
+        ],
+        speaks: Some("Woof!"),
+    },
+    SlotType {
+        name: "Cat",
+        impl_lines: &[
+            "impl Speak for Cat {",
+            "    fn speak(&self) -> String {",
+            "        \"Meow.\".to_string()",
+            "    }",
+            "}",
+        ],
+        speaks: Some("Meow."),
+    },
+    SlotType {
+        name: "Robot",
+        impl_lines: &[
+            "impl Speak for Robot {",
+            "    fn speak(&self) -> String {",
+            "        \"Beep boop.\".to_string()",
+            "    }",
+            "}",
+        ],
+        speaks: Some("Beep boop."),
+    },
+    SlotType {
+        name: "i32",
+        impl_lines: &["// nobody wrote `impl Speak for i32`"],
+        speaks: None,
+    },
+];
+
+const GREET_LINES: &[&str] = &[
+    "trait Speak {",
+    "    fn speak(&self) -> String;",
+    "}",
+    "",
+    "fn greet<T: Speak>(thing: T) -> String {",
+    "    format!(\"Hello! {}\", thing.speak())",
+    "}",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Copy,
+    Clone,
+    Display,
+    Eq,
+    Ord,
+}
+
+const BOUNDS: &[Bound] = &[
+    Bound::Copy,
+    Bound::Clone,
+    Bound::Display,
+    Bound::Eq,
+    Bound::Ord,
+];
+
+impl Bound {
+    fn name(&self) -> &'static str {
+        match self {
+            Bound::Copy => "Copy",
+            Bound::Clone => "Clone",
+            Bound::Display => "Display",
+            Bound::Eq => "Eq",
+            Bound::Ord => "Ord",
+        }
+    }
+
+    fn meaning(&self) -> &'static str {
+        match self {
+            Bound::Copy => "Copy: the value is duplicated by a plain `let b = a;`.",
+            Bound::Clone => "Clone: the value can be duplicated with `.clone()`.",
+            Bound::Display => "Display: the value can be printed with `{}`.",
+            Bound::Eq => "Eq: `==` always gives a clear yes or no.",
+            Bound::Ord => "Ord: any two values can be put in order.",
+        }
+    }
+}
+
+struct BoundType {
+    name: &'static str,
+    satisfies: &'static [Bound],
+}
+
+const BOUND_TYPES: &[BoundType] = &[
+    BoundType {
+        name: "i32",
+        satisfies: &[
+            Bound::Copy,
+            Bound::Clone,
+            Bound::Display,
+            Bound::Eq,
+            Bound::Ord,
+        ],
+    },
+    BoundType {
+        name: "f64",
+        satisfies: &[Bound::Copy, Bound::Clone, Bound::Display],
+    },
+    BoundType {
+        name: "bool",
+        satisfies: &[
+            Bound::Copy,
+            Bound::Clone,
+            Bound::Display,
+            Bound::Eq,
+            Bound::Ord,
+        ],
+    },
+    BoundType {
+        name: "char",
+        satisfies: &[
+            Bound::Copy,
+            Bound::Clone,
+            Bound::Display,
+            Bound::Eq,
+            Bound::Ord,
+        ],
+    },
+    BoundType {
+        name: "String",
+        satisfies: &[Bound::Clone, Bound::Display, Bound::Eq, Bound::Ord],
+    },
+    BoundType {
+        name: "&str",
+        satisfies: &[
+            Bound::Copy,
+            Bound::Clone,
+            Bound::Display,
+            Bound::Eq,
+            Bound::Ord,
+        ],
+    },
+    BoundType {
+        name: "Vec<i32>",
+        satisfies: &[Bound::Clone, Bound::Eq, Bound::Ord],
+    },
+    BoundType {
+        name: "(i32, i32)",
+        satisfies: &[Bound::Copy, Bound::Clone, Bound::Eq, Bound::Ord],
+    },
+];
+
+const QUIZ_TYPES: usize = 4;
+
+#[derive(Default)]
+struct SlotState {
+    selection: usize,
+}
+
+/// A bound and the types to hold against it.
+struct BoundQuestion {
+    bound: Bound,
+    types: Vec<usize>,
+}
+
+impl BoundQuestion {
+    fn signature(&self) -> String {
+        format!("fn use_it<T: {}>(value: T)", self.bound.name())
+    }
+
+    fn type_names(&self) -> String {
+        let names: Vec<&str> = self
+            .types
+            .iter()
+            .map(|index| BOUND_TYPES[*index].name)
+            .collect();
+        names.join(", ")
+    }
+
+    /// The types, in the order shown, that satisfy the bound, with `flip`
+    /// moved to the other side.
+    fn fitting(&self, flip: Option<usize>) -> Vec<usize> {
+        self.types
+            .iter()
+            .copied()
+            .filter(|index| {
+                BOUND_TYPES[*index].satisfies.contains(&self.bound) != (flip == Some(*index))
+            })
+            .collect()
+    }
+}
+
+impl Exercise for BoundQuestion {
+    type Answer = Vec<usize>;
+
+    fn generate(rng: &mut SimpleRng, _difficulty: Difficulty) -> Self {
+        let bound = BOUNDS[rng.gen_range_i32(0, BOUNDS.len() as i32 - 1) as usize];
+        let mut types: Vec<usize> = (0..BOUND_TYPES.len()).collect();
+        rng.shuffle(&mut types);
+        types.truncate(QUIZ_TYPES);
+        BoundQuestion { bound, types }
+    }
+
+    fn prompt(&self) -> String {
+        "Which of these types can be passed to `use_it`?".to_string()
+    }
+
+    fn render(&self, ui: &mut egui::Ui) {
+        ui.add(CodeBlock::line(&self.signature()));
+        ui.add_space(6.0);
+        ui.label(format!("Types: {}", self.type_names()));
+    }
+
+    fn text(&self) -> String {
+        format!(
+            "```rust\n{}\n```\n\nTypes: {}",
+            self.signature(),
+            self.type_names()
+        )
+    }
+
+    fn answer(&self) -> Vec<usize> {
+        self.fitting(None)
+    }
+
+    /// The answer with one type put on the wrong side.
+    fn distractors(&self, _rng: &mut SimpleRng) -> Vec<Vec<usize>> {
+        self.types
+            .iter()
+            .map(|index| self.fitting(Some(*index)))
+            .collect()
+    }
+
+    fn choice_label(answer: &Vec<usize>) -> String {
+        if answer.is_empty() {
+            return "None of them".to_string();
+        }
+        let names: Vec<&str> = answer
+            .iter()
+            .map(|index| BOUND_TYPES[*index].name)
+            .collect();
+        names.join(", ")
+    }
+
+    fn hint(&self, _picked: &Vec<usize>) -> String {
+        "Go through the types one at a time and ask if each has the ability.".to_string()
+    }
+
+    fn explain(&mut self, ui: &mut egui::Ui, _correct: bool) {
+        ui.label(self.bound.meaning());
+    }
+}
+
+/// A bound to check types against on paper, for a worksheet.
+pub fn worksheet_item(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    exercise::worksheet_item::<BoundQuestion>(rng, difficulty)
+}
+
+/// Checks a generated bound question for `--self-check`.
+pub fn self_check(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    exercise::self_check::<BoundQuestion>(rng, difficulty)
+}
+
+/// Draws every candidate type on the left and the generic function on the
+/// right, with the selected type plugged into the `T: Speak` slot.
+fn paint_generic_slot(ui: &mut egui::Ui, selection: usize) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
+    let box_h = row_height + 12.0;
+    let row_gap = 8.0;
+    let width = ui.available_width().max(320.0);
+    let height = SLOT_TYPES.len() as f32 * (box_h + row_gap);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let palette = Palette::from_ui(ui);
+    let highlight_color = palette.highlight;
+    let text_color = palette.text;
+    let weak_color = palette.weak_text;
+    let line_stroke = palette.line;
+    let fill = palette.code_fill;
+
+    let type_w = (width * 0.25).min(140.0);
+    let function_w = (width * 0.45).min(280.0);
+    let function_rect = egui::Rect::from_min_size(
+        egui::pos2(rect.right() - function_w, rect.center().y - box_h),
+        egui::vec2(function_w, box_h * 2.0),
+    );
+
+    let fits = SLOT_TYPES[selection].speaks.is_some();
+    let function_stroke = if fits {
+        egui::Stroke::new(2.0, highlight_color)
+    } else {
+        line_stroke
+    };
+    painter.rect(
+        function_rect,
+        egui::CornerRadius::same(4),
+        fill,
+        function_stroke,
+        egui::StrokeKind::Inside,
+    );
+    painter.text(
+        function_rect.center_top() + egui::vec2(0.0, 6.0),
+        egui::Align2::CENTER_TOP,
+        "greet<T: Speak>",
+        font_id.clone(),
+        text_color,
+    );
+    painter.text(
+        function_rect.center_bottom() - egui::vec2(0.0, 6.0),
+        egui::Align2::CENTER_BOTTOM,
+        format!("T = {}", SLOT_TYPES[selection].name),
+        font_id.clone(),
+        if fits { highlight_color } else { weak_color },
+    );
+
+    for (index, slot_type) in SLOT_TYPES.iter().enumerate() {
+        let top = rect.top() + index as f32 * (box_h + row_gap);
+        let type_rect =
+            egui::Rect::from_min_size(egui::pos2(rect.left(), top), egui::vec2(type_w, box_h));
+        let selected = index == selection;
+        let stroke = if selected {
+            egui::Stroke::new(2.0, text_color)
+        } else {
+            line_stroke
+        };
+        painter.rect(
+            type_rect,
+            egui::CornerRadius::same(4),
+            fill,
+            stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.text(
+            type_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            slot_type.name,
+            font_id.clone(),
+            if selected { text_color } else { weak_color },
+        );
+        if !selected {
+            continue;
+        }
+        let start = type_rect.right_center() + egui::vec2(4.0, 0.0);
+        let end = function_rect.left_center() - egui::vec2(4.0, 0.0);
+        if fits {
+            painter.arrow(start, end - start, egui::Stroke::new(2.0, highlight_color));
+        } else {
+            let mid = start + (end - start) / 2.0;
+            painter.line_segment([start, mid], egui::Stroke::new(2.0, weak_color));
+            painter.text(
+                mid,
+                egui::Align2::LEFT_CENTER,
+                " does not fit",
+                font_id.clone(),
+                weak_color,
+            );
+        }
+    }
+}
+
+pub fn traits(nb: &mut NotebookCtx) {
+    speech::lesson(
+        nb,
+        "# Traits and generics\n\
+         Sometimes we want one function to work for many types.\n\
+         A **trait** names an ability, like *can speak* or *can be printed*.\n\
+         A **generic** function has a slot `T` that accepts any type with the right abilities.",
+    );
+
+    speech::lesson(
+        nb,
+        "## A tiny story\n\
+         A wall socket does not care whether you plug in a lamp or a radio.\n\
+         It only asks one thing: *does the plug fit?*\n\
+         A trait is the shape of the plug. A generic function is the socket.",
+    );
+
+    nb.state(
+        &state_key("slot_state"),
+        SlotState::default(),
+        |ui, state| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Plug a type into the slot") {
+                    return;
+                }
+                ui.add_space(4.0);
+                ui.label("greet accepts any T that implements Speak. Try each type.");
+                ui.add_space(6.0);
+                ui.add(CodeBlock::new(GREET_LINES).highlight_line(Some(4)));
+                ui.add_space(6.0);
+
+                let mut toggle = widgets::ChoiceToggle::new(&mut state.selection).small();
+                for (index, slot_type) in SLOT_TYPES.iter().enumerate() {
+                    toggle = toggle.choice(index, slot_type.name);
+                }
+                ui.add(toggle);
+                ui.add_space(8.0);
+                paint_generic_slot(ui, state.selection);
+                ui.add_space(6.0);
+
+                let slot_type = &SLOT_TYPES[state.selection];
+                ui.add(CodeBlock::new(slot_type.impl_lines));
+                ui.add_space(6.0);
+                match slot_type.speaks {
+                    Some(sound) => {
+                        ui.label(format!(
+                            "greet({}) returns \"Hello! {sound}\"",
+                            slot_type.name.to_lowercase()
+                        ));
+                    }
+                    None => {
+                        ui.label(format!(
+                            "Compile error: the trait bound `{}: Speak` is not satisfied.",
+                            slot_type.name
+                        ));
+                    }
+                }
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Traits you already use\n\
+         The standard library has many traits. Some common ones:\n\
+         - `Copy` and `Clone`: the value can be duplicated.\n\
+         - `Display`: the value can be printed with `{}`.\n\
+         - `Eq` and `Ord`: values can be compared and sorted.\n\n\
+         A bound like `T: Ord` lets only types with that ability into the slot.",
+    );
+
+    nb.state(
+        &state_key("bound_quiz_state"),
+        MultipleChoice::<BoundQuestion>::default(),
+        |ui, quiz| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Practice: which types fit?") {
+                    return;
+                }
+                ui.add_space(6.0);
+                quiz.show(ui, chapter_key("bound_quiz_state"));
+            });
+        },
+    );
+
+    nb.state(
+        &state_key("review_state"),
+        Review::<BoundQuestion>::default(),
+        |ui, review| {
+            with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+                if !card_heading(ui, "Review mistakes") {
+                    return;
+                }
+                ui.add_space(6.0);
+                review.show(ui, chapter_key("bound_quiz_state"));
+            });
+        },
+    );
+
+    speech::lesson(
+        nb,
+        "## Recap\n\
+         - A trait names an ability that types can have.\n\
+         - `impl Trait for Type` gives a type that ability.\n\
+         - A generic function `fn f<T: Trait>` accepts any type with the ability.\n\
+         - If a type is missing the ability, the compiler refuses before the program runs.\n\n\
+         Next up: **Iterators** pulls values through a chain of steps.",
+    );
+}
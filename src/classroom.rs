@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::net::UdpSocket;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use web_time::Instant;
+use GORBIE::prelude::*;
+
+use crate::chapters::{self, Chapter, CHAPTERS};
+use crate::i18n::tr;
+use crate::progress::ExerciseKey;
+use crate::share;
+
+/// The UDP port teachers broadcast on and students listen on.
+const PORT: u16 = 47_311;
+/// First field of every message, so other traffic on the port is ignored.
+const HEADER: &str = "gorbie_teaches_cs classroom 1";
+const SEND_EVERY: Duration = Duration::from_secs(1);
+const POLL_EVERY: Duration = Duration::from_millis(500);
+/// A teacher not heard from for this long shows as gone.
+const LOST_AFTER: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Role {
+    Off,
+    Teach,
+    Follow,
+}
+
+struct Classroom {
+    role: Role,
+    /// Teachers and students pick the same room name, so two classes on
+    /// one network keep to themselves.
+    room: String,
+    socket: Option<UdpSocket>,
+    error: Option<String>,
+    /// Teaching: the seed every exercise card of the open chapter showed
+    /// last.
+    shown: HashMap<ExerciseKey, u64>,
+    last_sent: Option<Instant>,
+    /// Following: the teacher's chapter and seeds, and the seeds the cards
+    /// here have switched to.
+    teacher_chapter: Option<Chapter>,
+    received: HashMap<ExerciseKey, u64>,
+    applied: HashMap<ExerciseKey, u64>,
+    last_heard: Option<Instant>,
+}
+
+static CLASSROOM: OnceLock<RwLock<Classroom>> = OnceLock::new();
+
+fn classroom_lock() -> &'static RwLock<Classroom> {
+    CLASSROOM.get_or_init(|| {
+        RwLock::new(Classroom {
+            role: Role::Off,
+            room: "class".to_string(),
+            socket: None,
+            error: None,
+            shown: HashMap::new(),
+            last_sent: None,
+            teacher_chapter: None,
+            received: HashMap::new(),
+            applied: HashMap::new(),
+            last_heard: None,
+        })
+    })
+}
+
+/// A socket that broadcasts for a teacher, or listens on [`PORT`] for a
+/// student.
+fn open(role: Role) -> Result<Option<UdpSocket>, String> {
+    let socket = match role {
+        Role::Off => return Ok(None),
+        Role::Teach => {
+            let socket = UdpSocket::bind(("0.0.0.0", 0)).map_err(|error| error.to_string())?;
+            socket
+                .set_broadcast(true)
+                .map_err(|error| error.to_string())?;
+            socket
+        }
+        Role::Follow => {
+            UdpSocket::bind(("0.0.0.0", PORT)).map_err(|error| format!("port {PORT}: {error}"))?
+        }
+    };
+    socket
+        .set_nonblocking(true)
+        .map_err(|error| error.to_string())?;
+    Ok(Some(socket))
+}
+
+/// `HEADER<TAB>room<TAB>chapter id<TAB>code<TAB>code...`, with an exercise
+/// code for each card of the teacher's chapter.
+fn message(classroom: &Classroom) -> String {
+    let chapter = chapters::current_chapter();
+    let mut fields = vec![
+        HEADER.to_string(),
+        classroom.room.trim().to_string(),
+        chapters::def(chapter).id.to_string(),
+    ];
+    fields.extend(
+        classroom
+            .shown
+            .iter()
+            .filter(|((card_chapter, _), _)| *card_chapter == chapter)
+            .filter_map(|(key, seed)| share::code(*key, *seed)),
+    );
+    fields.join("\t")
+}
+
+fn send(classroom: &mut Classroom) {
+    let text = message(classroom);
+    let Some(socket) = &classroom.socket else {
+        return;
+    };
+    classroom.error = socket
+        .send_to(text.as_bytes(), ("255.255.255.255", PORT))
+        .err()
+        .map(|error| error.to_string());
+    classroom.last_sent = Some(Instant::now());
+}
+
+/// Reads every message that arrived since the last frame. A new chapter
+/// from the teacher opens here too; the seeds wait for their cards.
+fn receive(classroom: &mut Classroom) {
+    let mut buffer = [0; 2048];
+    while let Some(Ok((length, _))) = classroom
+        .socket
+        .as_ref()
+        .map(|socket| socket.recv_from(&mut buffer))
+    {
+        let Ok(text) = std::str::from_utf8(&buffer[..length]) else {
+            continue;
+        };
+        let mut fields = text.split('\t');
+        if fields.next() != Some(HEADER) || fields.next() != Some(classroom.room.trim()) {
+            continue;
+        }
+        let Some(def) = fields
+            .next()
+            .and_then(|id| CHAPTERS.iter().find(|def| def.id == id))
+        else {
+            continue;
+        };
+        if classroom.teacher_chapter != Some(def.chapter) {
+            classroom.teacher_chapter = Some(def.chapter);
+            chapters::set_chapter(def.chapter);
+        }
+        for (key, seed) in fields.filter_map(|code| share::parse(code).ok()) {
+            classroom.received.insert(key, seed);
+        }
+        classroom.last_heard = Some(Instant::now());
+    }
+}
+
+/// Sends or reads the classroom messages due this frame. Called once per
+/// frame.
+pub fn tick(ctx: &egui::Context) {
+    let mut classroom = classroom_lock().write().expect("classroom lock poisoned");
+    match classroom.role {
+        Role::Off => {}
+        Role::Teach => {
+            if classroom
+                .last_sent
+                .is_none_or(|sent| sent.elapsed() >= SEND_EVERY)
+            {
+                send(&mut classroom);
+            }
+            ctx.request_repaint_after(SEND_EVERY);
+        }
+        Role::Follow => {
+            receive(&mut classroom);
+            ctx.request_repaint_after(POLL_EVERY);
+        }
+    }
+}
+
+/// Notes the exercise a card shows, for a teacher to send out.
+pub fn observe(key: ExerciseKey, seed: u64) {
+    let mut classroom = classroom_lock().write().expect("classroom lock poisoned");
+    if classroom.role == Role::Teach {
+        classroom.shown.insert(key, seed);
+    }
+}
+
+/// The teacher's seed for the card `key`, once each time the teacher moves
+/// on to another exercise.
+pub fn take(key: ExerciseKey) -> Option<u64> {
+    let mut classroom = classroom_lock().write().expect("classroom lock poisoned");
+    if classroom.role != Role::Follow {
+        return None;
+    }
+    let seed = *classroom.received.get(&key)?;
+    if classroom.applied.insert(key, seed) == Some(seed) {
+        return None;
+    }
+    Some(seed)
+}
+
+/// Switches between off, teaching and following, with the room name and
+/// how the connection is doing.
+pub fn controls(ui: &mut egui::Ui) {
+    let mut classroom = classroom_lock().write().expect("classroom lock poisoned");
+    ui.label(tr("classroom.intro"));
+    ui.horizontal(|ui| {
+        ui.label(tr("classroom.room"));
+        ui.add(widgets::TextField::singleline(&mut classroom.room));
+    });
+    let before = classroom.role;
+    ui.add(
+        widgets::ChoiceToggle::new(&mut classroom.role)
+            .choice(Role::Off, tr("classroom.off"))
+            .choice(Role::Teach, tr("classroom.teach"))
+            .choice(Role::Follow, tr("classroom.follow"))
+            .small(),
+    );
+    if classroom.role != before {
+        classroom.shown.clear();
+        classroom.last_sent = None;
+        classroom.teacher_chapter = None;
+        classroom.received.clear();
+        classroom.applied.clear();
+        classroom.last_heard = None;
+        match open(classroom.role) {
+            Ok(socket) => {
+                classroom.socket = socket;
+                classroom.error = None;
+            }
+            Err(error) => {
+                classroom.socket = None;
+                classroom.error = Some(error);
+            }
+        }
+    }
+
+    let status = match classroom.role {
+        Role::Off => None,
+        Role::Teach => Some(tr("classroom.teaching").replace("{room}", classroom.room.trim())),
+        Role::Follow => Some(match classroom.last_heard {
+            Some(heard) if heard.elapsed() < LOST_AFTER => tr("classroom.following").to_string(),
+            _ => tr("classroom.waiting").to_string(),
+        }),
+    };
+    if let Some(status) = status {
+        ui.label(egui::RichText::new(status).weak());
+    }
+    if let Some(error) = &classroom.error {
+        ui.label(error);
+    }
+}
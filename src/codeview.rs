@@ -0,0 +1,219 @@
+use std::ops::Range;
+
+use egui::text::LayoutJob;
+use egui::TextStyle;
+
+use crate::i18n::tr;
+use crate::palette::Palette;
+
+/// A framed block of monospace code.
+///
+/// `highlight_line` marks one whole line, `highlight_ranges` marks byte
+/// ranges counted over the lines joined with `\n`. `error_range` marks
+/// one such range in the error color instead.
+pub struct CodeBlock<'a> {
+    lines: Vec<&'a str>,
+    highlight_line: Option<usize>,
+    ranges: &'a [Range<usize>],
+    error: Option<Range<usize>>,
+    line_numbers: bool,
+    copyable: bool,
+}
+
+impl<'a> CodeBlock<'a> {
+    pub fn new<S: AsRef<str>>(lines: &'a [S]) -> Self {
+        Self {
+            lines: lines.iter().map(AsRef::as_ref).collect(),
+            highlight_line: None,
+            ranges: &[],
+            error: None,
+            line_numbers: false,
+            copyable: true,
+        }
+    }
+
+    pub fn line(line: &'a str) -> Self {
+        Self {
+            lines: vec![line],
+            highlight_line: None,
+            ranges: &[],
+            error: None,
+            line_numbers: false,
+            copyable: true,
+        }
+    }
+
+    pub fn highlight_line(mut self, line: Option<usize>) -> Self {
+        self.highlight_line = line;
+        self
+    }
+
+    pub fn highlight_ranges(mut self, ranges: &'a [Range<usize>]) -> Self {
+        self.ranges = ranges;
+        self
+    }
+
+    /// Underlines the bytes where something went wrong, e.g. where a parser
+    /// got stuck. Takes the place of the other highlights.
+    pub fn error_range(mut self, range: Range<usize>) -> Self {
+        self.error = Some(range);
+        self
+    }
+
+    pub fn line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers;
+        self
+    }
+
+    /// Whether hovering shows the copy button. Exercises that ask the
+    /// learner to retype the code turn it off.
+    pub fn copyable(mut self, copyable: bool) -> Self {
+        self.copyable = copyable;
+        self
+    }
+
+    pub fn job(&self, ui: &egui::Ui) -> LayoutJob {
+        let font = TextStyle::Monospace.resolve(ui.style());
+        let palette = Palette::from_ui(ui);
+        let normal = egui::TextFormat::simple(font.clone(), palette.text);
+        let highlight = egui::TextFormat::simple(font.clone(), palette.highlight);
+        let gutter = egui::TextFormat::simple(font, palette.weak_text);
+        let error_color = ui.visuals().error_fg_color;
+        let error = egui::TextFormat {
+            color: error_color,
+            underline: egui::Stroke::new(2.0, error_color),
+            ..normal.clone()
+        };
+        let gutter_width = self.lines.len().to_string().len();
+
+        let mut job = LayoutJob::default();
+        let mut offset = 0;
+        for (index, line) in self.lines.iter().enumerate() {
+            if self.line_numbers {
+                job.append(
+                    &format!("{:>gutter_width$} ", index + 1),
+                    0.0,
+                    gutter.clone(),
+                );
+            }
+            if let Some(range) = &self.error {
+                let ranges = std::slice::from_ref(range);
+                append_ranges(&mut job, line, offset, ranges, &normal, &error);
+            } else if self.highlight_line == Some(index) {
+                job.append(line, 0.0, highlight.clone());
+            } else {
+                append_ranges(&mut job, line, offset, self.ranges, &normal, &highlight);
+            }
+            if index + 1 < self.lines.len() {
+                job.append("\n", 0.0, normal.clone());
+            }
+            offset += line.len() + 1;
+        }
+        job
+    }
+}
+
+/// Appends `line`, which starts at byte `offset` of the block, with the parts
+/// covered by `ranges` in the highlight format.
+fn append_ranges(
+    job: &mut LayoutJob,
+    line: &str,
+    offset: usize,
+    ranges: &[Range<usize>],
+    normal: &egui::TextFormat,
+    highlight: &egui::TextFormat,
+) {
+    let mut cursor = 0;
+    for range in ranges {
+        let start = range.start.saturating_sub(offset).min(line.len());
+        let end = range.end.saturating_sub(offset).min(line.len());
+        if end <= cursor || end <= start {
+            continue;
+        }
+        let start = start.max(cursor);
+        if start > cursor {
+            job.append(&line[cursor..start], 0.0, normal.clone());
+        }
+        job.append(&line[start..end], 0.0, highlight.clone());
+        cursor = end;
+    }
+    if cursor < line.len() {
+        job.append(&line[cursor..], 0.0, normal.clone());
+    }
+}
+
+impl egui::Widget for CodeBlock<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        if crate::handout::capturing() {
+            crate::handout::code(self.lines.join("\n"));
+        }
+        let job = self.job(ui);
+        let palette = Palette::from_ui(ui);
+        let bg = palette.code_fill;
+        let stroke = palette.line;
+        let response = egui::Frame::group(ui.style())
+            .fill(bg)
+            .stroke(stroke)
+            .inner_margin(egui::Margin::same(8))
+            .corner_radius(10.0)
+            .show(ui, |ui| {
+                ui.label(job);
+            })
+            .response;
+        if self.copyable {
+            copy_button(ui, &response, &self.lines);
+        }
+        response
+    }
+}
+
+/// A small copy button in the top right corner of the frame, shown while
+/// the pointer is over it. It is painted over the code, so it never moves
+/// the layout around.
+fn copy_button(ui: &mut egui::Ui, frame: &egui::Response, lines: &[&str]) {
+    let id = frame.id.with("copy");
+    if !ui.rect_contains_pointer(frame.rect) {
+        ui.data_mut(|data| data.remove::<bool>(id));
+        return;
+    }
+    let copied = ui.data(|data| data.get_temp::<bool>(id)).unwrap_or(false);
+    let label = if copied {
+        tr("code.copied")
+    } else {
+        tr("code.copy")
+    };
+    let palette = Palette::from_ui(ui);
+    let font = TextStyle::Small.resolve(ui.style());
+    let galley = ui
+        .painter()
+        .layout_no_wrap(label.to_string(), font, palette.text);
+    let padding = egui::vec2(6.0, 2.0);
+    let size = galley.size() + padding * 2.0;
+    let rect = egui::Rect::from_min_size(
+        frame.rect.right_top() + egui::vec2(-size.x - 6.0, 6.0),
+        size,
+    );
+    let response = ui
+        .interact(rect, id, egui::Sense::click())
+        .on_hover_cursor(egui::CursorIcon::PointingHand)
+        .on_hover_text(tr("code.copy_hover"));
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, true, label));
+    if response.clicked() {
+        ui.ctx().copy_text(lines.join("\n"));
+        ui.data_mut(|data| data.insert_temp(id, true));
+    }
+    let stroke = if response.hovered() {
+        egui::Stroke::new(palette.line.width, palette.highlight)
+    } else {
+        palette.line
+    };
+    ui.painter().rect(
+        rect,
+        egui::CornerRadius::same(4),
+        palette.code_fill,
+        stroke,
+        egui::StrokeKind::Inside,
+    );
+    ui.painter()
+        .galley(rect.min + padding, galley, palette.text);
+}
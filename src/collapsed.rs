@@ -0,0 +1,78 @@
+use std::collections::BTreeSet;
+use std::sync::{OnceLock, RwLock};
+
+use crate::chapters::{self, Chapter};
+use crate::storage;
+
+const FILE: &str = "collapsed.txt";
+
+/// Folded cards, keyed by chapter id and card slug like the progress file.
+type Folded = BTreeSet<(String, String)>;
+
+static COLLAPSED: OnceLock<RwLock<Folded>> = OnceLock::new();
+
+fn collapsed_lock() -> &'static RwLock<Folded> {
+    COLLAPSED.get_or_init(|| RwLock::new(load()))
+}
+
+/// One `chapter<TAB>card` line per folded card.
+fn load() -> Folded {
+    let Some(text) = storage::load(FILE) else {
+        return Folded::new();
+    };
+    text.lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(chapter, card)| (chapter.to_string(), card.to_string()))
+        .collect()
+}
+
+fn save(folded: &Folded) -> Result<(), String> {
+    let mut text = String::new();
+    for (chapter, card) in folded {
+        text.push_str(&format!("{chapter}\t{card}\n"));
+    }
+    storage::save(FILE, &text)
+}
+
+fn entry(chapter: Chapter, title: &str) -> (String, String) {
+    (
+        chapters::def(chapter).id.to_string(),
+        chapters::card_slug(title),
+    )
+}
+
+/// Whether the learner folded the card with this heading.
+pub fn is_collapsed(chapter: Chapter, title: &str) -> bool {
+    collapsed_lock()
+        .read()
+        .expect("collapsed lock poisoned")
+        .contains(&entry(chapter, title))
+}
+
+pub fn set_collapsed(chapter: Chapter, title: &str, collapsed: bool) {
+    let mut folded = collapsed_lock().write().expect("collapsed lock poisoned");
+    let changed = if collapsed {
+        folded.insert(entry(chapter, title))
+    } else {
+        folded.remove(&entry(chapter, title))
+    };
+    if changed {
+        if let Err(error) = save(&folded) {
+            eprintln!("Could not save folded cards: {error}");
+        }
+    }
+}
+
+/// Unfolds every card in every chapter.
+pub fn clear() {
+    let mut folded = collapsed_lock().write().expect("collapsed lock poisoned");
+    folded.clear();
+    if let Err(error) = save(&folded) {
+        eprintln!("Could not save folded cards: {error}");
+    }
+}
+
+/// Picks up the folded cards from an imported backup.
+pub fn reload() {
+    *collapsed_lock().write().expect("collapsed lock poisoned") = load();
+}
@@ -0,0 +1,69 @@
+use egui::RichText;
+
+use GORBIE::prelude::*;
+
+use crate::i18n::tr;
+
+pub const PLAYERS: usize = 2;
+
+/// Turns and scores for two learners sharing one screen. Multiple-choice
+/// cards also keep each player's pick, see [`crate::exercise::MultipleChoice`].
+#[derive(Default)]
+pub struct Coop {
+    pub enabled: bool,
+    turn: usize,
+    scores: [u32; PLAYERS],
+}
+
+impl Coop {
+    /// Credits the current player for a correct answer and passes the turn.
+    /// Does nothing while co-op is switched off.
+    pub fn record(&mut self, correct: bool) {
+        if !self.enabled {
+            return;
+        }
+        if correct {
+            self.scores[self.turn] += 1;
+        }
+        self.turn = (self.turn + 1) % PLAYERS;
+    }
+
+    /// The player whose turn it is, counted from 0.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn reset(&mut self) {
+        self.turn = 0;
+        self.scores = [0; PLAYERS];
+    }
+
+    pub fn controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.add(widgets::ToggleButton::new(
+                &mut self.enabled,
+                tr("coop.toggle"),
+            ));
+            if self.enabled {
+                ui.add_space(12.0);
+                for (player, score) in self.scores.iter().enumerate() {
+                    let text = tr("coop.score")
+                        .replace("{player}", &(player + 1).to_string())
+                        .replace("{score}", &score.to_string());
+                    if player == self.turn {
+                        ui.label(RichText::new(text).strong());
+                    } else {
+                        ui.label(RichText::new(text).weak());
+                    }
+                    ui.add_space(8.0);
+                }
+                if ui.add(widgets::Button::new(tr("coop.reset"))).clicked() {
+                    self.reset();
+                }
+            }
+        });
+        if self.enabled {
+            ui.label(tr("coop.turn").replace("{player}", &(self.turn + 1).to_string()));
+        }
+    }
+}
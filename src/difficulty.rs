@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{OnceLock, RwLock};
+
+use crate::chapters::{self, Chapter};
+use crate::i18n::tr;
+use crate::storage;
+
+const FILE: &str = "difficulty.txt";
+const LOWEST: u8 = 1;
+const HIGHEST: u8 = 5;
+/// The level a new learner starts at. Its exercises match the ones the
+/// generators made before they became adaptive.
+const START: u8 = 2;
+/// Answers the level change looks back on.
+const WINDOW: usize = 6;
+/// Right answers in the window that raise the level.
+const RAISE_AT: usize = 6;
+/// Wrong answers in the window that lower it.
+const LOWER_AT: usize = 3;
+/// Seeds hold the level in their leading digit, so a seed rebuilds the same
+/// exercise at any level the learner is at now.
+const LEVEL_SPAN: u64 = 100_000;
+
+/// How hard generated exercises are, from 1 to 5.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Difficulty(u8);
+
+impl Difficulty {
+    /// A fixed level, for practice set to it instead of the adaptive one.
+    pub fn new(level: u8) -> Self {
+        Self(level.clamp(LOWEST, HIGHEST))
+    }
+
+    /// Every level, easiest first.
+    pub fn all() -> impl Iterator<Item = Self> {
+        (LOWEST..=HIGHEST).map(Self)
+    }
+
+    /// The level an exercise seed was drawn at.
+    pub fn of_seed(seed: u64) -> Self {
+        Self(((seed / LEVEL_SPAN) as u8).clamp(LOWEST, HIGHEST))
+    }
+
+    /// Turns random bits into a seed that carries this level.
+    pub fn seed(self, random: u64) -> u64 {
+        self.0 as u64 * LEVEL_SPAN + random % LEVEL_SPAN
+    }
+
+    /// Like [`Difficulty::seed`], with one switch of the generator kept in
+    /// the seed too: the upper half of the level's span means `flag` is on.
+    pub fn seed_with_flag(self, random: u64, flag: bool) -> u64 {
+        let half = LEVEL_SPAN / 2;
+        self.seed(random % half + if flag { half } else { 0 })
+    }
+
+    /// The switch [`Difficulty::seed_with_flag`] put into `seed`.
+    pub fn flag_of_seed(seed: u64) -> bool {
+        seed % LEVEL_SPAN >= LEVEL_SPAN / 2
+    }
+
+    pub fn level(self) -> u8 {
+        self.0
+    }
+
+    /// How deep generated expression trees may nest.
+    pub fn max_depth(self) -> u8 {
+        match self.0 {
+            1 => 2,
+            2 | 3 => 3,
+            _ => 4,
+        }
+    }
+
+    /// The largest number that appears in a generated exercise.
+    pub fn max_value(self) -> i32 {
+        match self.0 {
+            1 => 5,
+            2 => 9,
+            3 => 12,
+            4 => 15,
+            _ => 20,
+        }
+    }
+
+    /// Whether exercises mix in multiplication and negation, or stick to
+    /// adding and subtracting.
+    pub fn harder_ops(self) -> bool {
+        self.0 >= 2
+    }
+}
+
+/// A chapter's level and the answers since it last changed, newest last.
+#[derive(Clone)]
+struct Level {
+    level: u8,
+    recent: VecDeque<bool>,
+}
+
+impl Level {
+    fn new(level: u8) -> Self {
+        Self {
+            level,
+            recent: VecDeque::new(),
+        }
+    }
+}
+
+/// The level of each chapter practiced, by chapter id like the progress.
+/// Chapters not practiced yet start at `start`.
+struct Tracker {
+    start: u8,
+    chapters: BTreeMap<String, Level>,
+    /// Whether the warm-up quiz set the starting level already.
+    placed: bool,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self {
+            start: START,
+            chapters: BTreeMap::new(),
+            placed: false,
+        }
+    }
+}
+
+impl Tracker {
+    fn level(&self, chapter: Chapter) -> u8 {
+        self.chapters
+            .get(chapters::def(chapter).id)
+            .map_or(self.start, |level| level.level)
+    }
+}
+
+static TRACKER: OnceLock<RwLock<Tracker>> = OnceLock::new();
+
+fn tracker_lock() -> &'static RwLock<Tracker> {
+    TRACKER.get_or_init(|| RwLock::new(load()))
+}
+
+/// A `start<TAB>n` line, a `placed<TAB>1` line once the warm-up quiz was
+/// taken, and one `chapter<TAB>id<TAB>level<TAB>answers` line per chapter
+/// practiced, with the answers as `1` and `0`.
+fn load() -> Tracker {
+    let mut tracker = Tracker::default();
+    let Some(text) = storage::load(FILE) else {
+        return tracker;
+    };
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        match (fields.next(), fields.next()) {
+            // Files from before the levels were per chapter have one
+            // `level` line; it is where every chapter starts now.
+            (Some("start" | "level"), Some(value)) => {
+                if let Ok(level) = value.parse::<u8>() {
+                    tracker.start = level.clamp(LOWEST, HIGHEST);
+                }
+            }
+            (Some("placed"), Some(value)) => tracker.placed = value == "1",
+            (Some("chapter"), Some(id)) => {
+                let Some(Ok(level)) = fields.next().map(str::parse::<u8>) else {
+                    continue;
+                };
+                let mut entry = Level::new(level.clamp(LOWEST, HIGHEST));
+                entry.recent = fields
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .map(|answer| answer == '1')
+                    .collect();
+                tracker.chapters.insert(id.to_string(), entry);
+            }
+            _ => {}
+        }
+    }
+    tracker
+}
+
+fn save(tracker: &Tracker) -> Result<(), String> {
+    let mut text = format!(
+        "start\t{}\nplaced\t{}\n",
+        tracker.start,
+        if tracker.placed { 1 } else { 0 }
+    );
+    for (id, level) in &tracker.chapters {
+        let recent: String = level
+            .recent
+            .iter()
+            .map(|correct| if *correct { '1' } else { '0' })
+            .collect();
+        text.push_str(&format!("chapter\t{id}\t{}\t{recent}\n", level.level));
+    }
+    storage::save(FILE, &text)
+}
+
+fn update(change: impl FnOnce(&mut Tracker)) {
+    let mut tracker = tracker_lock().write().expect("difficulty lock poisoned");
+    change(&mut tracker);
+    if let Err(error) = save(&tracker) {
+        eprintln!("Could not save the difficulty: {error}");
+    }
+}
+
+/// The level new exercises in `chapter` are generated at.
+pub fn of_chapter(chapter: Chapter) -> Difficulty {
+    Difficulty(
+        tracker_lock()
+            .read()
+            .expect("difficulty lock poisoned")
+            .level(chapter),
+    )
+}
+
+/// The level new exercises in the open chapter are generated at.
+pub fn current() -> Difficulty {
+    of_chapter(chapters::current_chapter())
+}
+
+/// Counts one answer in `chapter`. A full window of right answers raises
+/// the chapter's level, too many wrong ones lower it; either way the window
+/// starts over. Called through [`crate::progress::record`], once per
+/// exercise answered.
+pub fn record(chapter: Chapter, correct: bool) {
+    update(|tracker| {
+        let start = tracker.start;
+        let entry = tracker
+            .chapters
+            .entry(chapters::def(chapter).id.to_string())
+            .or_insert_with(|| Level::new(start));
+        entry.recent.push_back(correct);
+        if entry.recent.len() > WINDOW {
+            entry.recent.pop_front();
+        }
+        let right = entry.recent.iter().filter(|correct| **correct).count();
+        let wrong = entry.recent.len() - right;
+        if right >= RAISE_AT && entry.level < HIGHEST {
+            entry.level += 1;
+            entry.recent.clear();
+        } else if wrong >= LOWER_AT && entry.level > LOWEST {
+            entry.level -= 1;
+            entry.recent.clear();
+        }
+    });
+}
+
+/// Starts practice in every chapter at `level`, as the warm-up quiz found
+/// it, with no answers counted yet.
+pub fn place(level: u8) {
+    update(|tracker| {
+        tracker.start = level.clamp(LOWEST, HIGHEST);
+        tracker.chapters.clear();
+        tracker.placed = true;
+    });
+}
+
+/// Whether the warm-up quiz was taken, so the overview can leave it out.
+pub fn placed() -> bool {
+    tracker_lock()
+        .read()
+        .expect("difficulty lock poisoned")
+        .placed
+}
+
+/// Starts over at the first level new learners get.
+pub fn clear() {
+    update(|tracker| *tracker = Tracker::default());
+}
+
+/// Takes the levels from the file again, after a backup was imported.
+pub fn reload() {
+    *tracker_lock().write().expect("difficulty lock poisoned") = load();
+}
+
+/// The level line for the chapter header, for the open chapter.
+pub fn summary() -> String {
+    tr("difficulty.summary")
+        .replace("{level}", &current().level().to_string())
+        .replace("{highest}", &HIGHEST.to_string())
+}
@@ -0,0 +1,665 @@
+use std::time::Duration;
+
+use web_time::Instant;
+use GORBIE::prelude::*;
+
+use crate::celebrate::{self, Reason};
+use crate::coop::{Coop, PLAYERS};
+use crate::difficulty::{self, Difficulty};
+use crate::i18n::tr;
+use crate::keyboard::AnswerToggle;
+use crate::progress::{self, ExerciseKey};
+use crate::review;
+use crate::rng::{self, SimpleRng};
+use crate::score;
+use crate::settings;
+use crate::supervised;
+use crate::worksheet;
+
+/// Questions in an end-of-chapter quiz.
+const QUIZ_LENGTH: usize = 5;
+/// Questions in a timed challenge.
+const CHALLENGE_LENGTH: usize = 10;
+/// Time for a whole challenge.
+const CHALLENGE_TIME: Duration = Duration::from_secs(120);
+
+/// A generated question with one right answer. Implementors only describe
+/// the question; [`MultipleChoice`] mixes the choices, grades the pick and
+/// hands out new exercises.
+pub trait Exercise: Sized {
+    type Answer: PartialEq;
+
+    fn generate(rng: &mut SimpleRng, difficulty: Difficulty) -> Self;
+    /// The exercise `seed` stands for, drawn from `rng` seeded with it.
+    /// Exercises that keep a switch in the seed, see
+    /// [`Difficulty::seed_with_flag`], read it here.
+    fn from_seed(rng: &mut SimpleRng, seed: u64) -> Self {
+        Self::generate(rng, Difficulty::of_seed(seed))
+    }
+    /// The question, shown above the exercise.
+    fn prompt(&self) -> String;
+    /// The code or values the question is about.
+    fn render(&self, ui: &mut egui::Ui);
+    /// What [`Exercise::render`] shows, as plain text for printed worksheets.
+    fn text(&self) -> String;
+    fn answer(&self) -> Self::Answer;
+    /// Wrong answers to offer next to the right one. Duplicates are dropped.
+    fn distractors(&self, rng: &mut SimpleRng) -> Vec<Self::Answer>;
+    fn choice_label(answer: &Self::Answer) -> String;
+
+    /// Every choice in the order shown: the answer and the distractors,
+    /// shuffled. Choices with an order of their own, like the lines of a
+    /// listing, keep it instead.
+    fn choices(&self, rng: &mut SimpleRng) -> Vec<Self::Answer> {
+        let mut choices = vec![self.answer()];
+        for distractor in self.distractors(rng) {
+            if !choices.contains(&distractor) {
+                choices.push(distractor);
+            }
+        }
+        rng.shuffle(&mut choices);
+        choices
+    }
+
+    /// Shown after picking `picked`, a wrong answer.
+    fn hint(&self, _picked: &Self::Answer) -> String {
+        "Try again.".to_string()
+    }
+
+    /// Shown under the feedback once a choice is picked, like why the
+    /// answer is right.
+    fn explain(&mut self, _ui: &mut egui::Ui, _correct: bool) {}
+}
+
+/// Card state for a multiple-choice exercise with a reproducible seed.
+pub struct MultipleChoice<E: Exercise> {
+    rng: SimpleRng,
+    seed: u64,
+    exercise: E,
+    choices: Vec<E::Answer>,
+    selection: Option<usize>,
+    /// The timed challenge, while one runs or its summary shows.
+    challenge: Option<Challenge>,
+    /// The level new exercises are drawn at, `None` for the chapter's.
+    level: Option<Difficulty>,
+    /// The switch new seeds carry, see [`Difficulty::seed_with_flag`].
+    flag: bool,
+    /// Turns and scores for learners sharing the screen.
+    coop: Coop,
+    /// Each player's pick while co-op is on; `selection` is unused then.
+    picks: [Option<usize>; PLAYERS],
+}
+
+/// A run of [`CHALLENGE_LENGTH`] exercises against the clock.
+struct Challenge {
+    started_at: Instant,
+    question_started_at: Instant,
+    /// Whether each answered question was right and how long it took.
+    answers: Vec<(bool, Duration)>,
+    /// Set once the last question is answered or the time is up.
+    finished: bool,
+}
+
+impl Challenge {
+    fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            started_at: now,
+            question_started_at: now,
+            answers: Vec::new(),
+            finished: false,
+        }
+    }
+}
+
+impl<E: Exercise> Default for MultipleChoice<E> {
+    fn default() -> Self {
+        Self::with_seed(rng::fresh_seed())
+    }
+}
+
+impl<E: Exercise> MultipleChoice<E> {
+    pub fn with_seed(seed: u64) -> Self {
+        let mut rng = SimpleRng::new(seed);
+        let exercise = E::from_seed(&mut rng, seed);
+        let choices = exercise.choices(&mut rng);
+        Self {
+            rng,
+            seed,
+            exercise,
+            choices,
+            selection: None,
+            challenge: None,
+            level: None,
+            flag: false,
+            coop: Coop::default(),
+            picks: [None; PLAYERS],
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn exercise(&self) -> &E {
+        &self.exercise
+    }
+
+    /// Where new exercises come from: `level`, or the chapter's level for
+    /// `None`, and `flag` kept in their seed.
+    pub fn draw(&mut self, level: Option<Difficulty>, flag: bool) {
+        self.level = level;
+        self.flag = flag;
+    }
+
+    /// The switch for two learners taking turns on this card, and their
+    /// scores while it is on.
+    pub fn coop_controls(&mut self, ui: &mut egui::Ui) {
+        self.coop.controls(ui);
+    }
+
+    /// A new exercise, drawn again a few times when it comes out the same
+    /// as the last one, which happens often with small question banks.
+    pub fn regenerate(&mut self) {
+        let previous = self.exercise.text();
+        for _ in 0..8 {
+            let difficulty = self.level.unwrap_or_else(difficulty::current);
+            let seed = difficulty.seed_with_flag(self.rng.next_u32() as u64, self.flag);
+            self.load_seed(seed);
+            if self.exercise.text() != previous {
+                break;
+            }
+        }
+    }
+
+    pub fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        self.exercise = E::from_seed(&mut self.rng, seed);
+        self.choices = self.exercise.choices(&mut self.rng);
+        self.selection = None;
+        self.picks = [None; PLAYERS];
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.selection
+            .is_some_and(|index| self.choices[index] == self.exercise.answer())
+    }
+
+    /// Prompt, "New exercise" and seed row, the exercise, the choices and
+    /// the feedback line. Answers are recorded under `key`. While a timed
+    /// challenge runs, the challenge takes the card's place. Returns whether
+    /// the first pick on the exercise was right, on the frame it is made.
+    pub fn show(&mut self, ui: &mut egui::Ui, key: ExerciseKey) -> Option<bool> {
+        if self.challenge.is_some() {
+            self.show_challenge(ui, key);
+            return None;
+        }
+        ui.label(self.exercise.prompt());
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            if ui.add(widgets::Button::new(tr("exercise.new"))).clicked() {
+                self.regenerate();
+            }
+            if let Some(seed) = rng::seed_field(ui, key, self.seed) {
+                self.load_seed(seed);
+            }
+            if settings::get().timed_challenges
+                && ui
+                    .add(widgets::Button::new(tr("challenge.start")))
+                    .on_hover_text(
+                        tr("challenge.hover")
+                            .replace("{total}", &CHALLENGE_LENGTH.to_string())
+                            .replace("{minutes}", &(CHALLENGE_TIME.as_secs() / 60).to_string()),
+                    )
+                    .clicked()
+            {
+                self.regenerate();
+                self.challenge = Some(Challenge::new());
+            }
+        });
+        if self.coop.enabled {
+            self.coop_question(ui, key)
+        } else {
+            self.question(ui, key)
+        }
+    }
+
+    /// A countdown, then one exercise after the other. Each pick moves on
+    /// to the next exercise right away; right or wrong shows in the summary
+    /// at the end.
+    fn show_challenge(&mut self, ui: &mut egui::Ui, key: ExerciseKey) {
+        let Some(challenge) = &mut self.challenge else {
+            return;
+        };
+        let remaining = CHALLENGE_TIME.saturating_sub(challenge.started_at.elapsed());
+        // Switching challenges off ends a running one, too.
+        if remaining.is_zero() || !settings::get().timed_challenges {
+            challenge.finished = true;
+        }
+        if challenge.finished {
+            let again = challenge_summary(ui, challenge);
+            match again {
+                Some(true) => {
+                    self.regenerate();
+                    self.challenge = Some(Challenge::new());
+                }
+                Some(false) => self.challenge = None,
+                None => {}
+            }
+            return;
+        }
+
+        ui.ctx().request_repaint_after(Duration::from_millis(200));
+        let seconds = remaining.as_secs();
+        ui.add(
+            widgets::ProgressBar::new(remaining.as_secs_f32() / CHALLENGE_TIME.as_secs_f32()).text(
+                tr("challenge.countdown")
+                    .replace("{time}", &format!("{}:{:02}", seconds / 60, seconds % 60))
+                    .replace("{number}", &(challenge.answers.len() + 1).to_string())
+                    .replace("{total}", &CHALLENGE_LENGTH.to_string()),
+            ),
+        );
+        ui.add_space(6.0);
+        ui.label(self.exercise.prompt());
+        ui.add_space(6.0);
+        self.exercise.render(ui);
+        ui.add_space(6.0);
+        // A plain toggle: feedback would only slow the learner down here.
+        let mut toggle = widgets::ChoiceToggle::new(&mut self.selection);
+        for (index, choice) in self.choices.iter().enumerate() {
+            toggle = toggle.choice(Some(index), E::choice_label(choice));
+        }
+        ui.add(toggle);
+        if self.selection.is_none() {
+            return;
+        }
+
+        let correct = self
+            .selection
+            .is_some_and(|index| self.choices[index] == self.exercise.answer());
+        progress::answer(key, correct);
+        if !correct {
+            review::add(key, self.seed);
+        }
+        challenge
+            .answers
+            .push((correct, challenge.question_started_at.elapsed()));
+        challenge.question_started_at = Instant::now();
+        challenge.finished = challenge.answers.len() == CHALLENGE_LENGTH;
+        if !challenge.finished {
+            self.regenerate();
+        }
+    }
+
+    /// The exercise, the choices and the feedback line. A wrong pick queues
+    /// the exercise for review. Returns whether the first pick was right, on
+    /// the frame it is made.
+    fn question(&mut self, ui: &mut egui::Ui, key: ExerciseKey) -> Option<bool> {
+        ui.add_space(6.0);
+        self.exercise.render(ui);
+        ui.add_space(6.0);
+
+        let before = self.selection;
+        let mut toggle = AnswerToggle::new(&mut self.selection);
+        for (index, choice) in self.choices.iter().enumerate() {
+            toggle = toggle.choice(Some(index), E::choice_label(choice));
+        }
+        ui.add(toggle);
+        if self.selection.is_some() && self.selection != before {
+            progress::answer(key, self.is_solved());
+            if !self.is_solved() {
+                review::add(key, self.seed);
+            }
+        }
+        ui.add_space(4.0);
+        match self.selection {
+            Some(_) if self.is_solved() => ui.label(tr("feedback.correct")),
+            Some(index) => ui.label(not_quite(&self.exercise.hint(&self.choices[index]))),
+            None => ui.label(tr("feedback.pick")),
+        };
+        if self.selection.is_some() {
+            self.exercise.explain(ui, self.is_solved());
+        }
+        (before.is_none() && self.selection.is_some()).then(|| self.is_solved())
+    }
+
+    /// The exercise and one pick per player, in turns, each into the
+    /// player's own slot. The picks stay hidden until everyone has picked,
+    /// so nobody copies. Returns whether a pick was right, on the frame it
+    /// is made.
+    fn coop_question(&mut self, ui: &mut egui::Ui, key: ExerciseKey) -> Option<bool> {
+        ui.add_space(6.0);
+        self.exercise.render(ui);
+        ui.add_space(6.0);
+
+        let answer = self.exercise.answer();
+        if let Some(picks) = self.picks.iter().copied().collect::<Option<Vec<_>>>() {
+            for (player, index) in picks.iter().enumerate() {
+                let choice = &self.choices[*index];
+                let verdict = if *choice == answer {
+                    tr("feedback.correct").to_string()
+                } else {
+                    not_quite(&self.exercise.hint(choice))
+                };
+                let picked = tr("coop.picked")
+                    .replace("{player}", &(player + 1).to_string())
+                    .replace("{answer}", &E::choice_label(choice));
+                ui.label(format!("{picked} {verdict}"));
+            }
+            ui.add_space(4.0);
+            ui.label(tr("coop.next"));
+            let everyone = picks.iter().all(|index| self.choices[*index] == answer);
+            self.exercise.explain(ui, everyone);
+            return None;
+        }
+
+        let mut pick = None;
+        let mut toggle = AnswerToggle::new(&mut pick);
+        for (index, choice) in self.choices.iter().enumerate() {
+            toggle = toggle.choice(Some(index), E::choice_label(choice));
+        }
+        ui.add(toggle);
+        let index = pick?;
+        let correct = self.choices[index] == answer;
+        self.picks[self.coop.turn()] = Some(index);
+        self.coop.record(correct);
+        progress::answer(key, correct);
+        if !correct {
+            review::add(key, self.seed);
+        }
+        Some(correct)
+    }
+}
+
+/// Card state for replaying the exercises answered wrongly on the card
+/// `key`, oldest first, until each one is answered right.
+pub struct Review<E: Exercise> {
+    current: Option<MultipleChoice<E>>,
+}
+
+impl<E: Exercise> Default for Review<E> {
+    fn default() -> Self {
+        Self { current: None }
+    }
+}
+
+impl<E: Exercise> Review<E> {
+    pub fn show(&mut self, ui: &mut egui::Ui, key: ExerciseKey) {
+        let seeds = review::seeds(key);
+        // Drop an exercise that left the queue some other way, e.g. when all
+        // progress was reset.
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|current| !current.is_solved() && !seeds.contains(&current.seed))
+        {
+            self.current = None;
+        }
+        let current = match &mut self.current {
+            Some(current) => current,
+            None => {
+                let Some(seed) = seeds.first() else {
+                    ui.label(tr("review.empty"));
+                    return;
+                };
+                self.current.insert(MultipleChoice::with_seed(*seed))
+            }
+        };
+
+        ui.label(tr("review.waiting").replace("{count}", &seeds.len().to_string()));
+        ui.add_space(6.0);
+        ui.label(current.exercise.prompt());
+        current.question(ui, key);
+        if !current.is_solved() {
+            return;
+        }
+        review::remove(key, current.seed);
+        ui.add_space(6.0);
+        if seeds.iter().all(|seed| *seed == current.seed) {
+            ui.label(tr("review.done"));
+        } else if ui.add(widgets::Button::new(tr("review.next"))).clicked() {
+            self.current = None;
+        }
+    }
+}
+
+struct QuizQuestion<E: Exercise> {
+    exercise: E,
+    choices: Vec<E::Answer>,
+    selection: Option<usize>,
+}
+
+impl<E: Exercise> QuizQuestion<E> {
+    fn is_correct(&self) -> bool {
+        self.selection
+            .is_some_and(|index| self.choices[index] == self.exercise.answer())
+    }
+}
+
+/// Card state for an end-of-chapter quiz: [`QUIZ_LENGTH`] exercises that
+/// stay put until the learner hands them in, then get graded together.
+pub struct Quiz<E: Exercise> {
+    rng: SimpleRng,
+    seed: u64,
+    questions: Vec<QuizQuestion<E>>,
+    /// Right answers, once the quiz has been handed in.
+    grade: Option<usize>,
+}
+
+impl<E: Exercise> Default for Quiz<E> {
+    fn default() -> Self {
+        let seed = rng::fresh_seed();
+        let mut quiz = Self {
+            rng: SimpleRng::new(seed),
+            seed,
+            questions: Vec::new(),
+            grade: None,
+        };
+        quiz.load_seed(seed);
+        quiz
+    }
+}
+
+impl<E: Exercise> Quiz<E> {
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
+    }
+
+    /// Builds every question from the one seed, so a quiz can be shared by
+    /// its seed like a single exercise.
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        self.questions = (0..QUIZ_LENGTH)
+            .map(|_| {
+                let exercise = E::from_seed(&mut self.rng, seed);
+                let choices = exercise.choices(&mut self.rng);
+                QuizQuestion {
+                    exercise,
+                    choices,
+                    selection: None,
+                }
+            })
+            .collect();
+        self.grade = None;
+    }
+
+    /// Grades every question, counts them towards the score and records the
+    /// quiz as solved under `key` when all answers are right.
+    fn hand_in(&mut self, key: ExerciseKey) {
+        let right = self
+            .questions
+            .iter()
+            .filter(|question| question.is_correct())
+            .count();
+        for question in &self.questions {
+            score::record(question.is_correct());
+        }
+        progress::record(key, right == self.questions.len());
+        if right == self.questions.len() {
+            celebrate::start(Reason::Quiz);
+        }
+        self.grade = Some(right);
+    }
+
+    /// The questions with their choices, then a hand-in button. After
+    /// grading, each question shows whether it was right and a new quiz
+    /// can be started.
+    pub fn show(&mut self, ui: &mut egui::Ui, key: ExerciseKey) {
+        let graded = self.grade.is_some();
+        ui.label(tr("quiz.intro"));
+        if let Some(seed) = rng::seed_field(ui, key, self.seed) {
+            self.load_seed(seed);
+        }
+        for (number, question) in self.questions.iter_mut().enumerate() {
+            ui.add_space(10.0);
+            ui.label(
+                egui::RichText::new(format!(
+                    "{} {}. {}",
+                    tr("quiz.question"),
+                    number + 1,
+                    question.exercise.prompt()
+                ))
+                .strong(),
+            );
+            ui.add_space(4.0);
+            question.exercise.render(ui);
+            ui.add_space(4.0);
+            // A plain toggle: the number keys would answer every question
+            // of the card at once.
+            let mut toggle = widgets::ChoiceToggle::new(&mut question.selection).small();
+            for (index, choice) in question.choices.iter().enumerate() {
+                toggle = toggle.choice(Some(index), E::choice_label(choice));
+            }
+            ui.add_enabled(!graded, toggle);
+            if graded {
+                if question.is_correct() {
+                    ui.label(tr("feedback.correct"));
+                } else {
+                    ui.label(
+                        tr("quiz.answer_was")
+                            .replace("{answer}", &E::choice_label(&question.exercise.answer())),
+                    );
+                }
+            }
+        }
+
+        ui.add_space(10.0);
+        match self.grade {
+            None => {
+                let complete = self
+                    .questions
+                    .iter()
+                    .all(|question| question.selection.is_some());
+                if ui
+                    .add_enabled(complete, widgets::Button::new(tr("quiz.hand_in")))
+                    .clicked()
+                {
+                    self.hand_in(key);
+                }
+            }
+            Some(right) => {
+                ui.label(
+                    tr("quiz.result")
+                        .replace("{right}", &right.to_string())
+                        .replace("{total}", &self.questions.len().to_string()),
+                );
+                if ui.add(widgets::Button::new(tr("quiz.new"))).clicked() {
+                    self.regenerate();
+                }
+            }
+        }
+    }
+}
+
+/// The result and the time each answer took. Returns `Some(true)` for
+/// another round, `Some(false)` to go back to plain practice.
+fn challenge_summary(ui: &mut egui::Ui, challenge: &Challenge) -> Option<bool> {
+    let right = challenge
+        .answers
+        .iter()
+        .filter(|(correct, _)| *correct)
+        .count();
+    let taken = challenge
+        .answers
+        .iter()
+        .map(|(_, time)| *time)
+        .sum::<Duration>()
+        .as_secs();
+    ui.label(
+        egui::RichText::new(
+            tr("challenge.result")
+                .replace("{right}", &right.to_string())
+                .replace("{total}", &CHALLENGE_LENGTH.to_string())
+                .replace("{time}", &format!("{}:{:02}", taken / 60, taken % 60)),
+        )
+        .strong(),
+    );
+    if challenge.answers.len() < CHALLENGE_LENGTH {
+        ui.label(tr("challenge.time_up"));
+    }
+    ui.add_space(6.0);
+    for (number, (correct, time)) in challenge.answers.iter().enumerate() {
+        ui.label(format!(
+            "{} {}: {:.1} s {}",
+            tr("quiz.question"),
+            number + 1,
+            time.as_secs_f32(),
+            if *correct { "\u{2713}" } else { "\u{2717}" }
+        ));
+    }
+    ui.add_space(6.0);
+    let mut again = None;
+    ui.horizontal(|ui| {
+        if ui
+            .add(widgets::Button::new(tr("challenge.again")))
+            .clicked()
+        {
+            again = Some(true);
+        }
+        if ui.add(widgets::Button::new(tr("challenge.done"))).clicked() {
+            again = Some(false);
+        }
+    });
+    again
+}
+
+/// A new exercise of type `E` with its answer, for a printed worksheet.
+pub fn worksheet_item<E: Exercise>(rng: &mut SimpleRng, difficulty: Difficulty) -> worksheet::Item {
+    let exercise = E::generate(rng, difficulty);
+    worksheet::Item {
+        question: format!("{}\n\n{}", exercise.prompt(), exercise.text()),
+        answer: E::choice_label(&exercise.answer()),
+    }
+}
+
+/// One round of `--self-check` for a multiple-choice exercise: it offers at
+/// least two choices, and no two of them read the same.
+pub fn self_check<E: Exercise>(rng: &mut SimpleRng, difficulty: Difficulty) -> Result<(), String> {
+    let exercise = E::generate(rng, difficulty);
+    let labels: Vec<String> = exercise.choices(rng).iter().map(E::choice_label).collect();
+    if labels.len() < 2 {
+        return Err(format!("only one choice for:\n{}", exercise.text()));
+    }
+    for (index, label) in labels.iter().enumerate() {
+        if labels[..index].contains(label) {
+            return Err(format!(
+                "the choice {label} shows up twice for:\n{}",
+                exercise.text()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Feedback for a wrong answer, with `hint` unless hints are switched off
+/// or locked away by supervised mode.
+pub fn not_quite(hint: &str) -> String {
+    if settings::get().hints && !supervised::active() {
+        format!("{} {hint}", tr("feedback.not_quite"))
+    } else {
+        tr("feedback.not_quite").to_string()
+    }
+}
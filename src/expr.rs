@@ -0,0 +1,1225 @@
+use std::ops::Range;
+
+use crate::codeview::CodeBlock;
+use crate::i18n::tr;
+use crate::palette::Palette;
+use crate::stepper::{stepper, Steps};
+use crate::supervised;
+use crate::tree_view::{draw_tree, TreeNode};
+
+/// The values and operators one family of expressions is built from.
+/// Chapters implement this for a marker type and get parsing helpers,
+/// step-by-step reduction, and rendering from this module.
+pub trait Language {
+    type Value: Clone;
+    type Unary: Copy;
+    type Binary: Copy;
+
+    fn value_text(value: &Self::Value) -> String;
+    /// Text written before and after the operand, e.g. `("(-", ")")`.
+    fn unary_text(op: Self::Unary) -> (&'static str, &'static str);
+    /// Short name shown on tree nodes, e.g. `"-"` or `"not"`.
+    fn unary_label(op: Self::Unary) -> &'static str;
+    /// Symbol written between the operands, e.g. `"+"` or `"and"`.
+    fn binary_label(op: Self::Binary) -> &'static str;
+    /// What working out the operator does, as the start of a sentence,
+    /// e.g. `"Multiply 2 by 3"`.
+    fn describe_unary(op: Self::Unary, value: &Self::Value) -> String;
+    fn describe_binary(op: Self::Binary, left: &Self::Value, right: &Self::Value) -> String;
+    /// The operation's name in a sentence, e.g. `"multiplication"`.
+    fn binary_name(op: Self::Binary) -> &'static str;
+    /// How tightly the operator holds on to its operands: the higher one
+    /// goes first when no parentheses decide.
+    fn precedence(op: Self::Binary) -> u8;
+    /// Stack machine instructions for the operators, e.g. `"neg"` or `"mul"`.
+    fn unary_instruction(op: Self::Unary) -> &'static str;
+    fn binary_instruction(op: Self::Binary) -> &'static str;
+    fn apply_unary(op: Self::Unary, value: &Self::Value) -> Result<Self::Value, String>;
+    fn apply_binary(
+        op: Self::Binary,
+        left: &Self::Value,
+        right: &Self::Value,
+    ) -> Result<Self::Value, String>;
+}
+
+pub enum Expr<L: Language> {
+    Value(L::Value),
+    /// A name that stands for a value given earlier, like `x` after
+    /// `x <- 4;`.
+    Var(String),
+    Unary(L::Unary, Box<Expr<L>>),
+    Binary(L::Binary, Box<Expr<L>>, Box<Expr<L>>),
+}
+
+impl<L: Language> Clone for Expr<L> {
+    fn clone(&self) -> Self {
+        match self {
+            Expr::Value(value) => Expr::Value(value.clone()),
+            Expr::Var(name) => Expr::Var(name.clone()),
+            Expr::Unary(op, inner) => Expr::Unary(*op, inner.clone()),
+            Expr::Binary(op, left, right) => Expr::Binary(*op, left.clone(), right.clone()),
+        }
+    }
+}
+
+impl<L: Language> Expr<L> {
+    pub fn value(value: L::Value) -> Self {
+        Expr::Value(value)
+    }
+
+    pub fn unary(op: L::Unary, inner: Expr<L>) -> Self {
+        Expr::Unary(op, Box::new(inner))
+    }
+
+    pub fn binary(op: L::Binary, left: Expr<L>, right: Expr<L>) -> Self {
+        Expr::Binary(op, Box::new(left), Box::new(right))
+    }
+
+    pub fn as_value(&self) -> Option<&L::Value> {
+        match self {
+            Expr::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn is_value(&self) -> bool {
+        matches!(self, Expr::Value(_))
+    }
+
+    /// The text of this node alone, as shown inside a tree box.
+    pub fn label(&self) -> String {
+        match self {
+            Expr::Value(value) => L::value_text(value),
+            Expr::Var(name) => name.clone(),
+            Expr::Unary(op, _) => L::unary_label(*op).to_string(),
+            Expr::Binary(op, _, _) => L::binary_label(*op).to_string(),
+        }
+    }
+
+    /// Direct children together with the step that leads to each.
+    pub fn children(&self) -> Vec<(PathStep, &Expr<L>)> {
+        match self {
+            Expr::Value(_) | Expr::Var(_) => Vec::new(),
+            Expr::Unary(_, inner) => vec![(PathStep::Unary, inner)],
+            Expr::Binary(_, left, right) => {
+                vec![(PathStep::Left, left), (PathStep::Right, right)]
+            }
+        }
+    }
+
+    /// The expression as a tree of labeled boxes, each tagged with its path.
+    pub fn to_tree(&self) -> TreeNode<Vec<PathStep>> {
+        fn build<L: Language>(expr: &Expr<L>, path: &mut Vec<PathStep>) -> TreeNode<Vec<PathStep>> {
+            let mut children = Vec::new();
+            for (step, child) in expr.children() {
+                path.push(step);
+                children.push(build(child, path));
+                path.pop();
+            }
+            TreeNode {
+                label: expr.label(),
+                path: path.clone(),
+                children,
+            }
+        }
+        build(self, &mut Vec::new())
+    }
+
+    pub fn count_ops(&self) -> usize {
+        match self {
+            Expr::Value(_) | Expr::Var(_) => 0,
+            Expr::Unary(_, inner) => 1 + inner.count_ops(),
+            Expr::Binary(_, left, right) => 1 + left.count_ops() + right.count_ops(),
+        }
+    }
+
+    /// Operators on the longest way from the root to a value.
+    pub fn depth(&self) -> usize {
+        match self {
+            Expr::Value(_) | Expr::Var(_) => 0,
+            Expr::Unary(_, inner) => 1 + inner.depth(),
+            Expr::Binary(_, left, right) => 1 + left.depth().max(right.depth()),
+        }
+    }
+
+    pub fn has_unary(&self) -> bool {
+        match self {
+            Expr::Value(_) | Expr::Var(_) => false,
+            Expr::Unary(..) => true,
+            Expr::Binary(_, left, right) => left.has_unary() || right.has_unary(),
+        }
+    }
+
+    pub fn eval(&self) -> Result<L::Value, String> {
+        self.eval_in(&[])
+    }
+
+    /// Like [`Expr::eval`], with names looked up in `env`.
+    pub fn eval_in(&self, env: &[(String, L::Value)]) -> Result<L::Value, String> {
+        match self {
+            Expr::Value(value) => Ok(value.clone()),
+            Expr::Var(name) => lookup(env, name).cloned(),
+            Expr::Unary(op, inner) => L::apply_unary(*op, &inner.eval_in(env)?),
+            Expr::Binary(op, left, right) => {
+                L::apply_binary(*op, &left.eval_in(env)?, &right.eval_in(env)?)
+            }
+        }
+    }
+}
+
+/// The value of `name`; the latest one when it was given several times.
+fn lookup<'a, V>(env: &'a [(String, V)], name: &str) -> Result<&'a V, String> {
+    env.iter()
+        .rev()
+        .find(|(bound, _)| bound == name)
+        .map(|(_, value)| value)
+        .ok_or_else(|| {
+            format!("`{name}` has no value yet. Give it one first, like `{name} <- 4;`.")
+        })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathStep {
+    Unary,
+    Left,
+    Right,
+}
+
+pub struct Step<L: Language> {
+    pub expr: Expr<L>,
+    pub highlight: Option<Vec<PathStep>>,
+    /// Why the highlighted part has no value, e.g. a division by zero. The
+    /// steps end here.
+    pub error: Option<String>,
+}
+
+/// An operator whose operands are all plain values, so it can be solved now,
+/// or a name to replace by its value.
+pub fn is_reducible<L: Language>(expr: &Expr<L>) -> bool {
+    match expr {
+        Expr::Value(_) => false,
+        Expr::Var(_) => true,
+        Expr::Unary(_, inner) => inner.is_value(),
+        Expr::Binary(_, left, right) => left.is_value() && right.is_value(),
+    }
+}
+
+/// Works out a reducible part. An operator that refuses its operands, like
+/// `7 / 0`, fails with the part's code in front of the reason, so the
+/// learner sees which box it was.
+fn eval_reducible<L: Language>(
+    expr: &Expr<L>,
+    env: &[(String, L::Value)],
+) -> Result<L::Value, String> {
+    let operand = |expr: &Expr<L>| {
+        expr.as_value()
+            .cloned()
+            .ok_or_else(|| "Expected a value".to_string())
+    };
+    let value = match expr {
+        Expr::Value(value) => return Ok(value.clone()),
+        Expr::Var(name) => return lookup(env, name).cloned(),
+        Expr::Unary(op, inner) => L::apply_unary(*op, &operand(inner)?),
+        Expr::Binary(op, left, right) => L::apply_binary(*op, &operand(left)?, &operand(right)?),
+    };
+    value.map_err(|error| format!("`{}` has no value. {error}", expr_to_string(expr)))
+}
+
+/// The path to the next part to solve: deepest first, then left to right.
+pub fn find_reducible<L: Language>(expr: &Expr<L>) -> Option<Vec<PathStep>> {
+    let child = expr.children().into_iter().find_map(|(step, child)| {
+        find_reducible(child).map(|mut path| {
+            path.insert(0, step);
+            path
+        })
+    });
+    child.or_else(|| is_reducible(expr).then(Vec::new))
+}
+
+pub fn reduce_at<L: Language>(expr: Expr<L>, path: &[PathStep]) -> Result<Expr<L>, String> {
+    reduce_in(expr, path, &[])
+}
+
+/// Like [`reduce_at`], with names looked up in `env`.
+pub fn reduce_in<L: Language>(
+    expr: Expr<L>,
+    path: &[PathStep],
+    env: &[(String, L::Value)],
+) -> Result<Expr<L>, String> {
+    let Some((head, tail)) = path.split_first() else {
+        return Ok(Expr::Value(eval_reducible(&expr, env)?));
+    };
+    match (head, expr) {
+        (PathStep::Unary, Expr::Unary(op, inner)) => {
+            Ok(Expr::unary(op, reduce_in(*inner, tail, env)?))
+        }
+        (PathStep::Left, Expr::Binary(op, left, right)) => Ok(Expr::Binary(
+            op,
+            Box::new(reduce_in(*left, tail, env)?),
+            right,
+        )),
+        (PathStep::Right, Expr::Binary(op, left, right)) => Ok(Expr::Binary(
+            op,
+            left,
+            Box::new(reduce_in(*right, tail, env)?),
+        )),
+        _ => Err("Invalid reduction path".to_string()),
+    }
+}
+
+/// `expr` with every name found in `env` replaced by its value, all at
+/// once. As in [`lookup`], the latest binding of a name wins; names missing
+/// from `env` stay.
+pub fn substitute<L: Language>(expr: Expr<L>, env: &[(String, L::Value)]) -> Expr<L> {
+    match expr {
+        Expr::Var(name) => match env.iter().rev().find(|(known, _)| *known == name) {
+            Some((_, value)) => Expr::Value(value.clone()),
+            None => Expr::Var(name),
+        },
+        Expr::Unary(op, inner) => Expr::unary(op, substitute(*inner, env)),
+        Expr::Binary(op, left, right) => {
+            Expr::binary(op, substitute(*left, env), substitute(*right, env))
+        }
+        value => value,
+    }
+}
+
+pub fn expr_at_path<'a, L: Language>(expr: &'a Expr<L>, path: &[PathStep]) -> Option<&'a Expr<L>> {
+    let Some((head, tail)) = path.split_first() else {
+        return Some(expr);
+    };
+    let (_, child) = expr.children().into_iter().find(|(step, _)| step == head)?;
+    expr_at_path(child, tail)
+}
+
+/// A sentence on the step at `path`: what it does, and why it comes now,
+/// told from the operator above it.
+pub fn explain<L: Language>(expr: &Expr<L>, path: &[PathStep]) -> Option<String> {
+    let node = expr_at_path(expr, path)?;
+    let what = match node {
+        Expr::Unary(op, inner) => L::describe_unary(*op, inner.as_value()?),
+        Expr::Binary(op, left, right) => {
+            L::describe_binary(*op, left.as_value()?, right.as_value()?)
+        }
+        _ => return None,
+    };
+    let Some((last, above)) = path.split_last() else {
+        return Some(format!("{what}. It is the last operation left."));
+    };
+    let why = match (expr_at_path(expr, above)?, node) {
+        (Expr::Binary(outer, ..), Expr::Binary(inner, ..))
+            if L::precedence(*inner) > L::precedence(*outer) =>
+        {
+            format!(
+                "{} binds tighter than {}",
+                L::binary_name(*inner),
+                L::binary_name(*outer)
+            )
+        }
+        (Expr::Unary(op, _), _) => {
+            format!("the `{}` above needs a single value", L::unary_label(*op))
+        }
+        (Expr::Binary(op, ..), _) if *last == PathStep::Left => format!(
+            "the `{}` above needs the value on its left first",
+            L::binary_label(*op)
+        ),
+        (Expr::Binary(op, ..), _) => format!(
+            "the `{}` above has its left side done and needs its right side next",
+            L::binary_label(*op)
+        ),
+        _ => return Some(format!("{what}.")),
+    };
+    Some(format!("{what} first, because {why}."))
+}
+
+pub fn path_in_subtree(path: &[PathStep], subtree: &[PathStep]) -> bool {
+    path.len() >= subtree.len() && path[..subtree.len()] == *subtree
+}
+
+/// Every step of the reduction of `expr`. A part that cannot be worked
+/// out ends the steps, with the reason on the last one.
+pub fn build_steps<L: Language>(expr: Expr<L>) -> Vec<Step<L>> {
+    build_steps_in(expr, &[])
+}
+
+/// Like [`build_steps`], with names replaced by their values from `env`,
+/// one name per step.
+pub fn build_steps_in<L: Language>(expr: Expr<L>, env: &[(String, L::Value)]) -> Vec<Step<L>> {
+    let mut steps: Vec<Step<L>> = Vec::new();
+    let mut current = expr;
+    loop {
+        let highlight = find_reducible(&current);
+        steps.push(Step {
+            expr: current.clone(),
+            highlight: highlight.clone(),
+            error: None,
+        });
+        let Some(path) = highlight else { break };
+        match reduce_in(current, &path, env) {
+            Ok(next) => current = next,
+            Err(error) => {
+                if let Some(last) = steps.last_mut() {
+                    last.error = Some(error);
+                }
+                break;
+            }
+        }
+    }
+    steps
+}
+
+/// Whether two expressions have the same operators and values in the same
+/// places.
+fn same_shape<L: Language>(a: &Expr<L>, b: &Expr<L>) -> bool {
+    first_difference(a, b).is_none()
+}
+
+/// The path to the first box where the trees of `a` and `b` part ways,
+/// reading top-down and left to right, or `None` for the same tree.
+pub fn first_difference<L: Language>(a: &Expr<L>, b: &Expr<L>) -> Option<Vec<PathStep>> {
+    let (a_children, b_children) = (a.children(), b.children());
+    if a.label() != b.label() || a_children.len() != b_children.len() {
+        return Some(Vec::new());
+    }
+    a_children
+        .iter()
+        .zip(&b_children)
+        .find_map(|((step, a), (_, b))| {
+            first_difference(a, b).map(|mut path| {
+                path.insert(0, *step);
+                path
+            })
+        })
+}
+
+/// What `--self-check` asks of a generated expression: its code parses back
+/// to the same tree, and stepping through it takes one step per operator and
+/// ends at the value [`Expr::eval`] gives. Returns that value.
+pub fn self_check<L: Language>(
+    expr: &Expr<L>,
+    parse: impl Fn(&str) -> Result<Expr<L>, String>,
+) -> Result<L::Value, String>
+where
+    L::Value: PartialEq,
+{
+    let text = expr_to_string(expr);
+    let parsed = parse(&text).map_err(|error| format!("`{text}` does not parse: {error}"))?;
+    if !same_shape(expr, &parsed) {
+        return Err(format!(
+            "`{text}` parses back as `{}`",
+            expr_to_string(&parsed)
+        ));
+    }
+    let value = expr
+        .eval()
+        .map_err(|error| format!("`{text}` does not evaluate: {error}"))?;
+    let steps = build_steps(expr.clone());
+    if let Some(error) = steps.last().and_then(|step| step.error.as_ref()) {
+        return Err(format!("`{text}` cannot be stepped through: {error}"));
+    }
+    if steps.len() != expr.count_ops() + 1 {
+        return Err(format!(
+            "`{text}` takes {} steps for {} operators",
+            steps.len() - 1,
+            expr.count_ops()
+        ));
+    }
+    match steps.last().and_then(|step| step.expr.as_value()) {
+        Some(last) if *last == value => Ok(value),
+        _ => Err(format!(
+            "stepping through `{text}` does not end at {}",
+            L::value_text(&value)
+        )),
+    }
+}
+
+/// One line of code with the next part highlighted, the tree below it.
+impl<L: Language> Steps for Vec<Step<L>> {
+    fn count(&self) -> usize {
+        self.len()
+    }
+
+    fn show(&self, ui: &mut egui::Ui, step: usize) {
+        let step = &self[step];
+        let highlight = step.highlight.as_deref();
+        let (expression, expression_ranges) = render_expr_with_highlight(&step.expr, highlight);
+        ui.add(CodeBlock::line(&expression).highlight_ranges(&expression_ranges));
+
+        ui.add_space(6.0);
+        ui.label(tr("steps.tree_view"));
+        ui.add_space(4.0);
+        draw_tree(ui, &step.expr.to_tree(), |path| {
+            highlight.is_some_and(|subtree| path_in_subtree(path, subtree))
+        });
+        ui.add_space(6.0);
+        let looked_up = highlight
+            .and_then(|path| expr_at_path(&step.expr, path))
+            .is_some_and(|node| matches!(node, Expr::Var(_)));
+        if let Some(error) = &step.error {
+            ui.label(egui::RichText::new(error).color(ui.visuals().error_fg_color));
+        } else if looked_up {
+            ui.label(tr("steps.looked_up"));
+        } else if let Some(path) = highlight {
+            ui.label(tr("steps.next"));
+            if let Some(sentence) = explain(&step.expr, path) {
+                ui.label(egui::RichText::new(sentence).italics());
+            }
+        } else {
+            ui.label(tr("steps.done"));
+        }
+    }
+}
+
+/// The expression in postfix order, the way a stack machine reads it, with
+/// the stack after each token. Operators with one operand are written in
+/// parentheses, like `(-)`, to tell them apart.
+pub struct Postfix<L: Language> {
+    tokens: Vec<String>,
+    /// The stack and what happened, before the first token and after each.
+    /// Ends early at a token that has no value.
+    frames: Vec<(Vec<L::Value>, String)>,
+}
+
+/// Every node below `expr`, children before their parent, left before right.
+/// Operators come in the order the stepper works them out.
+fn post_order<'a, L: Language>(expr: &'a Expr<L>, out: &mut Vec<&'a Expr<L>>) {
+    for (_, child) in expr.children() {
+        post_order(child, out);
+    }
+    out.push(expr);
+}
+
+impl<L: Language> Postfix<L> {
+    pub fn new(expr: &Expr<L>, env: &[(String, L::Value)]) -> Self {
+        let mut nodes = Vec::new();
+        post_order(expr, &mut nodes);
+        let tokens = nodes
+            .iter()
+            .map(|node| match node {
+                Expr::Unary(op, _) => format!("({})", L::unary_label(*op)),
+                _ => node.label(),
+            })
+            .collect();
+
+        let mut stack: Vec<L::Value> = Vec::new();
+        let mut frames = vec![(Vec::new(), tr("postfix.start").to_string())];
+        for node in nodes {
+            let text = |value: &L::Value| L::value_text(value);
+            let result = match node {
+                Expr::Value(value) => {
+                    stack.push(value.clone());
+                    Ok(tr("postfix.value").replace("{value}", &text(value)))
+                }
+                Expr::Var(name) => lookup(env, name).map(|value| {
+                    stack.push(value.clone());
+                    tr("postfix.load")
+                        .replace("{name}", name)
+                        .replace("{value}", &text(value))
+                }),
+                Expr::Unary(op, _) => match stack.pop() {
+                    Some(value) => L::apply_unary(*op, &value).map(|result| {
+                        stack.push(result.clone());
+                        tr("postfix.unary")
+                            .replace("{value}", &text(&value))
+                            .replace("{op}", L::unary_label(*op))
+                            .replace("{result}", &text(&result))
+                    }),
+                    None => Err(tr("stack.empty_error").to_string()),
+                },
+                Expr::Binary(op, _, _) => match (stack.pop(), stack.pop()) {
+                    (Some(right), Some(left)) => {
+                        L::apply_binary(*op, &left, &right).map(|result| {
+                            stack.push(result.clone());
+                            tr("postfix.binary")
+                                .replace("{right}", &text(&right))
+                                .replace("{left}", &text(&left))
+                                .replace("{op}", L::binary_label(*op))
+                                .replace("{result}", &text(&result))
+                        })
+                    }
+                    _ => Err(tr("stack.too_short").to_string()),
+                },
+            };
+            match result {
+                Ok(note) => frames.push((stack.clone(), note)),
+                Err(error) => {
+                    frames.push((stack.clone(), error));
+                    break;
+                }
+            }
+        }
+        Self { tokens, frames }
+    }
+}
+
+/// The stack with its top first, the top in the highlight color.
+fn show_stack<L: Language>(ui: &mut egui::Ui, stack: &[L::Value]) {
+    ui.label(tr("stack.title"));
+    let palette = Palette::from_ui(ui);
+    if stack.is_empty() {
+        ui.label(egui::RichText::new(tr("stack.empty")).color(palette.weak_text));
+    }
+    for (depth, value) in stack.iter().rev().enumerate() {
+        let color = if depth == 0 {
+            palette.highlight
+        } else {
+            palette.text
+        };
+        egui::Frame::new()
+            .fill(palette.code_fill)
+            .stroke(palette.line)
+            .inner_margin(egui::Margin::symmetric(12, 2))
+            .show(ui, |ui| {
+                ui.set_min_width(60.0);
+                ui.label(
+                    egui::RichText::new(L::value_text(value))
+                        .monospace()
+                        .color(color),
+                );
+            });
+    }
+}
+
+/// The expression compiled for a stack machine: `push` for a value, `load`
+/// for a name and one instruction per operator. Each step of the stepper
+/// runs up to the next `load` or operator, so the machine keeps pace with
+/// the tree.
+pub struct Program<L: Language> {
+    lines: Vec<String>,
+    /// For each step of the stepper: the line it runs and the stack just
+    /// before. Ends early at an instruction that has no value.
+    steps: Vec<(usize, Vec<L::Value>)>,
+    /// The stack once every instruction ran.
+    end: Vec<L::Value>,
+}
+
+impl<L: Language> Program<L> {
+    pub fn new(expr: &Expr<L>, env: &[(String, L::Value)]) -> Self {
+        let mut nodes = Vec::new();
+        post_order(expr, &mut nodes);
+        let lines = nodes
+            .iter()
+            .map(|node| match node {
+                Expr::Value(value) => format!("push {}", L::value_text(value)),
+                Expr::Var(name) => format!("load {name}"),
+                Expr::Unary(op, _) => L::unary_instruction(*op).to_string(),
+                Expr::Binary(op, _, _) => L::binary_instruction(*op).to_string(),
+            })
+            .collect();
+
+        let mut stack: Vec<L::Value> = Vec::new();
+        let mut steps = Vec::new();
+        for (line, node) in nodes.into_iter().enumerate() {
+            if !node.is_value() {
+                steps.push((line, stack.clone()));
+            }
+            let result = match node {
+                Expr::Value(value) => Ok(value.clone()),
+                Expr::Var(name) => lookup(env, name).cloned(),
+                Expr::Unary(op, _) => match stack.pop() {
+                    Some(value) => L::apply_unary(*op, &value),
+                    None => Err(tr("stack.empty_error").to_string()),
+                },
+                Expr::Binary(op, _, _) => match (stack.pop(), stack.pop()) {
+                    (Some(right), Some(left)) => L::apply_binary(*op, &left, &right),
+                    _ => Err(tr("stack.too_short").to_string()),
+                },
+            };
+            let Ok(value) = result else {
+                break;
+            };
+            stack.push(value);
+        }
+        Self {
+            lines,
+            steps,
+            end: stack,
+        }
+    }
+
+    /// The listing with the line that does step `step` of the stepper
+    /// highlighted, and the stack the line finds.
+    pub fn show(&self, ui: &mut egui::Ui, step: usize) {
+        let (line, stack) = match self.steps.get(step) {
+            Some((line, stack)) => (Some(*line), stack),
+            None => (None, &self.end),
+        };
+        ui.add(
+            CodeBlock::new(&self.lines)
+                .line_numbers(true)
+                .highlight_line(line),
+        );
+        ui.add_space(6.0);
+        show_stack::<L>(ui, stack);
+        ui.add_space(4.0);
+        match line {
+            Some(line) => ui.label(tr("program.line").replace("{line}", &(line + 1).to_string())),
+            None => ui.label(tr("program.done")),
+        };
+    }
+}
+
+/// The tokens with the one just read highlighted, the stack with its top
+/// first, and what the token did.
+impl<L: Language> Steps for Postfix<L> {
+    fn count(&self) -> usize {
+        self.frames.len()
+    }
+
+    fn show(&self, ui: &mut egui::Ui, step: usize) {
+        let mut line = String::new();
+        let mut ranges = Vec::new();
+        for (index, token) in self.tokens.iter().enumerate() {
+            if index > 0 {
+                line.push(' ');
+            }
+            let start = line.len();
+            line.push_str(token);
+            if index + 1 == step {
+                ranges.push(start..line.len());
+            }
+        }
+        ui.add(CodeBlock::line(&line).highlight_ranges(&ranges));
+        ui.add_space(6.0);
+
+        let (stack, note) = &self.frames[step];
+        show_stack::<L>(ui, stack);
+        ui.add_space(6.0);
+        ui.label(note);
+        if step + 1 == self.frames.len() && step == self.tokens.len() && stack.len() == 1 {
+            ui.label(tr("postfix.result"));
+        }
+    }
+}
+
+/// "Show the steps" under an answered question: folded away at first, then
+/// the whole reduction of `expr` to step through. Left out in supervised
+/// mode.
+pub fn worked_solution<L: Language>(ui: &mut egui::Ui, step: &mut usize, expr: &Expr<L>) {
+    if supervised::active() {
+        return;
+    }
+    egui::CollapsingHeader::new(tr("worked.show")).show(ui, |ui| {
+        stepper(ui, step, &build_steps(expr.clone()));
+    });
+}
+
+/// A parse error under an expression field: the message, the input once
+/// more with the character the parser stopped at underlined, and `tip`.
+/// Input that ended too early gets a marked blank at its end.
+pub fn parse_error(ui: &mut egui::Ui, input: &str, error: &str, at: usize, tip: &str) {
+    let at = at.min(input.len());
+    let marked = input[at..].chars().next().map_or(1, char::len_utf8);
+    let line = format!("{input} ");
+    ui.add_space(6.0);
+    ui.label(
+        egui::RichText::new(format!("Parse error: {error}")).color(ui.visuals().error_fg_color),
+    );
+    ui.add_space(2.0);
+    ui.add(
+        CodeBlock::line(&line)
+            .error_range(at..at + marked)
+            .copyable(false),
+    );
+    ui.add_space(2.0);
+    ui.label(egui::RichText::new(tip).color(ui.visuals().weak_text_color()));
+}
+
+/// The forms an expression went through so far, oldest first, each in a
+/// small frame with an arrow on to the next. Shows nothing before the
+/// first step.
+pub fn history(ui: &mut egui::Ui, forms: &[String]) {
+    if forms.is_empty() {
+        return;
+    }
+    let palette = Palette::from_ui(ui);
+    ui.horizontal_wrapped(|ui| {
+        for form in forms {
+            egui::Frame::new()
+                .fill(palette.code_fill)
+                .stroke(palette.line)
+                .inner_margin(egui::Margin::symmetric(6, 2))
+                .corner_radius(6.0)
+                .show(ui, |ui| {
+                    ui.label(
+                        egui::RichText::new(form)
+                            .monospace()
+                            .small()
+                            .color(palette.weak_text),
+                    );
+                });
+            ui.label(egui::RichText::new("\u{2192}").color(palette.weak_text));
+        }
+    });
+    ui.add_space(4.0);
+}
+
+pub fn expr_to_string<L: Language>(expr: &Expr<L>) -> String {
+    render_expr_with_highlight(expr, None).0
+}
+
+/// Renders `expr` as one line and reports the byte range of the highlighted
+/// subtree, if any.
+pub fn render_expr_with_highlight<L: Language>(
+    expr: &Expr<L>,
+    highlight: Option<&[PathStep]>,
+) -> (String, Vec<Range<usize>>) {
+    let mut text = String::new();
+    let mut highlight_range = None;
+    render_expr(expr, highlight, &mut text, &mut highlight_range);
+    let ranges = highlight_range.into_iter().collect();
+    (text, ranges)
+}
+
+fn render_expr<L: Language>(
+    expr: &Expr<L>,
+    highlight: Option<&[PathStep]>,
+    out: &mut String,
+    highlight_range: &mut Option<Range<usize>>,
+) {
+    let start = out.len();
+    let child_highlight = |step: PathStep| match highlight.and_then(|path| path.split_first()) {
+        Some((head, rest)) if *head == step => Some(rest),
+        _ => None,
+    };
+    match expr {
+        Expr::Value(value) => out.push_str(&L::value_text(value)),
+        Expr::Var(name) => out.push_str(name),
+        Expr::Unary(op, inner) => {
+            let (prefix, suffix) = L::unary_text(*op);
+            out.push_str(prefix);
+            render_expr(
+                inner,
+                child_highlight(PathStep::Unary),
+                out,
+                highlight_range,
+            );
+            out.push_str(suffix);
+        }
+        Expr::Binary(op, left, right) => {
+            out.push('(');
+            render_expr(left, child_highlight(PathStep::Left), out, highlight_range);
+            out.push(' ');
+            out.push_str(L::binary_label(*op));
+            out.push(' ');
+            render_expr(
+                right,
+                child_highlight(PathStep::Right),
+                out,
+                highlight_range,
+            );
+            out.push(')');
+        }
+    }
+    if highlight.is_some_and(|path| path.is_empty()) {
+        *highlight_range = Some(start..out.len());
+    }
+}
+
+/// Byte-level cursor shared by the hand-written expression parsers.
+#[derive(Clone)]
+pub struct Scanner<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    pub fn at_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    pub fn bump(&mut self) {
+        self.pos += 1;
+    }
+
+    pub fn skip_ws(&mut self) {
+        while let Some(byte) = self.peek() {
+            if !byte.is_ascii_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    pub fn consume_bytes(&mut self, bytes: &[u8]) -> bool {
+        if self.input.get(self.pos..self.pos + bytes.len()) == Some(bytes) {
+            self.pos += bytes.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Like `consume_bytes`, but only matches whole words: `not` does not
+    /// match the start of `nothing`.
+    pub fn consume_word(&mut self, word: &str) -> bool {
+        let bytes = word.as_bytes();
+        if self.input.get(self.pos..self.pos + bytes.len()) != Some(bytes) {
+            return false;
+        }
+        let next = self.input.get(self.pos + bytes.len()).copied();
+        if next.is_some_and(|byte| byte.is_ascii_alphanumeric() || byte == b'_') {
+            return false;
+        }
+        self.pos += bytes.len();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whole numbers that stop at an overflow, like the Expressions chapter.
+    struct Arithmetic;
+
+    #[derive(Clone, Copy)]
+    enum Sign {
+        Neg,
+    }
+
+    #[derive(Clone, Copy)]
+    enum Arith {
+        Add,
+        Mul,
+        Div,
+    }
+
+    impl Language for Arithmetic {
+        type Value = i64;
+        type Unary = Sign;
+        type Binary = Arith;
+
+        fn value_text(value: &i64) -> String {
+            value.to_string()
+        }
+
+        fn unary_text(_: Sign) -> (&'static str, &'static str) {
+            ("(-", ")")
+        }
+
+        fn unary_label(_: Sign) -> &'static str {
+            "-"
+        }
+
+        fn binary_label(op: Arith) -> &'static str {
+            match op {
+                Arith::Add => "+",
+                Arith::Mul => "*",
+                Arith::Div => "/",
+            }
+        }
+
+        fn describe_unary(_: Sign, value: &i64) -> String {
+            format!("Flip the sign of {value}")
+        }
+
+        fn describe_binary(op: Arith, left: &i64, right: &i64) -> String {
+            format!("Work out {left} {} {right}", Self::binary_label(op))
+        }
+
+        fn binary_name(op: Arith) -> &'static str {
+            Self::binary_label(op)
+        }
+
+        fn precedence(op: Arith) -> u8 {
+            match op {
+                Arith::Add => 1,
+                Arith::Mul | Arith::Div => 2,
+            }
+        }
+
+        fn unary_instruction(_: Sign) -> &'static str {
+            "neg"
+        }
+
+        fn binary_instruction(op: Arith) -> &'static str {
+            match op {
+                Arith::Add => "add",
+                Arith::Mul => "mul",
+                Arith::Div => "div",
+            }
+        }
+
+        fn apply_unary(_: Sign, value: &i64) -> Result<i64, String> {
+            value
+                .checked_neg()
+                .ok_or_else(|| "does not fit".to_string())
+        }
+
+        fn apply_binary(op: Arith, left: &i64, right: &i64) -> Result<i64, String> {
+            if matches!(op, Arith::Div) && *right == 0 {
+                return Err("dividing by zero has no answer".to_string());
+            }
+            match op {
+                Arith::Add => left.checked_add(*right),
+                Arith::Mul => left.checked_mul(*right),
+                Arith::Div => left.checked_div(*right),
+            }
+            .ok_or_else(|| "does not fit".to_string())
+        }
+    }
+
+    /// `true` and `false` with `not`, `and` and `or`, like the Booleans
+    /// chapter.
+    struct Logic;
+
+    #[derive(Clone, Copy)]
+    enum Not {
+        Not,
+    }
+
+    #[derive(Clone, Copy)]
+    enum Connective {
+        And,
+        Or,
+    }
+
+    impl Language for Logic {
+        type Value = bool;
+        type Unary = Not;
+        type Binary = Connective;
+
+        fn value_text(value: &bool) -> String {
+            value.to_string()
+        }
+
+        fn unary_text(_: Not) -> (&'static str, &'static str) {
+            ("not ", "")
+        }
+
+        fn unary_label(_: Not) -> &'static str {
+            "not"
+        }
+
+        fn binary_label(op: Connective) -> &'static str {
+            match op {
+                Connective::And => "and",
+                Connective::Or => "or",
+            }
+        }
+
+        fn describe_unary(_: Not, value: &bool) -> String {
+            format!("Flip {value} with `not`")
+        }
+
+        fn describe_binary(op: Connective, left: &bool, right: &bool) -> String {
+            format!("Work out {left} {} {right}", Self::binary_label(op))
+        }
+
+        fn binary_name(op: Connective) -> &'static str {
+            Self::binary_label(op)
+        }
+
+        fn precedence(op: Connective) -> u8 {
+            match op {
+                Connective::Or => 1,
+                Connective::And => 2,
+            }
+        }
+
+        fn unary_instruction(_: Not) -> &'static str {
+            "not"
+        }
+
+        fn binary_instruction(op: Connective) -> &'static str {
+            Self::binary_label(op)
+        }
+
+        fn apply_unary(_: Not, value: &bool) -> Result<bool, String> {
+            Ok(!value)
+        }
+
+        fn apply_binary(op: Connective, left: &bool, right: &bool) -> Result<bool, String> {
+            Ok(match op {
+                Connective::And => *left && *right,
+                Connective::Or => *left || *right,
+            })
+        }
+    }
+
+    fn num(value: i64) -> Expr<Arithmetic> {
+        Expr::value(value)
+    }
+
+    fn var<L: Language>(name: &str) -> Expr<L> {
+        Expr::Var(name.to_string())
+    }
+
+    /// `(1 + 2) * (3 + 4)`
+    fn product_of_sums() -> Expr<Arithmetic> {
+        Expr::binary(
+            Arith::Mul,
+            Expr::binary(Arith::Add, num(1), num(2)),
+            Expr::binary(Arith::Add, num(3), num(4)),
+        )
+    }
+
+    /// `not (true and false) or false`
+    fn negated_conjunction() -> Expr<Logic> {
+        Expr::binary(
+            Connective::Or,
+            Expr::unary(
+                Not::Not,
+                Expr::binary(Connective::And, Expr::value(true), Expr::value(false)),
+            ),
+            Expr::value(false),
+        )
+    }
+
+    #[test]
+    fn arithmetic_reduces_deepest_first_then_left_to_right() {
+        let expr = product_of_sums();
+        assert_eq!(find_reducible(&expr), Some(vec![PathStep::Left]));
+
+        let expr = reduce_in(expr, &[PathStep::Left], &[]).unwrap();
+        assert_eq!(expr_to_string(&expr), "(3 * (3 + 4))");
+        assert_eq!(find_reducible(&expr), Some(vec![PathStep::Right]));
+
+        let steps = build_steps_in(product_of_sums(), &[]);
+        let forms: Vec<String> = steps
+            .iter()
+            .map(|step| expr_to_string(&step.expr))
+            .collect();
+        assert_eq!(
+            forms,
+            ["((1 + 2) * (3 + 4))", "(3 * (3 + 4))", "(3 * 7)", "21"]
+        );
+        assert!(steps.iter().all(|step| step.error.is_none()));
+        assert_eq!(steps.last().unwrap().highlight, None);
+    }
+
+    #[test]
+    fn logic_reduces_inside_the_not_first() {
+        let expr = negated_conjunction();
+        assert_eq!(
+            find_reducible(&expr),
+            Some(vec![PathStep::Left, PathStep::Unary])
+        );
+
+        let steps = build_steps_in(expr, &[]);
+        let forms: Vec<String> = steps
+            .iter()
+            .map(|step| expr_to_string(&step.expr))
+            .collect();
+        assert_eq!(
+            forms,
+            [
+                "(not (true and false) or false)",
+                "(not false or false)",
+                "(true or false)",
+                "true"
+            ]
+        );
+        assert_eq!(steps.last().unwrap().expr.as_value(), Some(&true));
+    }
+
+    #[test]
+    fn reduce_in_refuses_a_path_into_a_value() {
+        let error = reduce_in(num(1), &[PathStep::Left], &[]).err();
+        assert_eq!(error.as_deref(), Some("Invalid reduction path"));
+    }
+
+    #[test]
+    fn overflow_ends_the_steps_with_the_reason() {
+        let expr = Expr::binary(Arith::Add, num(i64::MAX), num(1));
+        assert!(expr.eval().is_err());
+
+        let steps = build_steps_in(expr, &[]);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].highlight, Some(Vec::new()));
+        assert_eq!(
+            steps[0].error.as_deref(),
+            Some("`(9223372036854775807 + 1)` has no value. does not fit")
+        );
+
+        let negated = Expr::unary(Sign::Neg, num(i64::MIN));
+        assert!(reduce_in(negated, &[], &[]).is_err());
+    }
+
+    #[test]
+    fn division_by_zero_stops_at_the_division() {
+        let expr = Expr::binary(Arith::Add, num(1), Expr::binary(Arith::Div, num(7), num(0)));
+        let steps = build_steps_in(expr, &[]);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].highlight, Some(vec![PathStep::Right]));
+        let error = steps[0].error.as_deref().unwrap();
+        assert!(error.starts_with("`(7 / 0)` has no value."));
+        assert!(error.ends_with("dividing by zero has no answer"));
+    }
+
+    #[test]
+    fn postfix_ends_with_the_value_alone_on_the_stack() {
+        let postfix = Postfix::new(&product_of_sums(), &[]);
+        assert_eq!(postfix.tokens.join(" "), "1 2 + 3 4 + *");
+        assert_eq!(postfix.frames.len(), postfix.tokens.len() + 1);
+        assert_eq!(postfix.frames.last().unwrap().0, [21]);
+
+        let postfix = Postfix::new(&negated_conjunction(), &[]);
+        assert_eq!(postfix.tokens.join(" "), "true false and (not) false or");
+        assert_eq!(postfix.frames.len(), postfix.tokens.len() + 1);
+        assert_eq!(postfix.frames.last().unwrap().0, [true]);
+    }
+
+    #[test]
+    fn postfix_stops_at_a_token_without_a_value() {
+        let expr = Expr::binary(Arith::Add, Expr::binary(Arith::Div, num(7), num(0)), num(1));
+        let postfix = Postfix::new(&expr, &[]);
+        assert_eq!(postfix.tokens.join(" "), "7 0 / 1 +");
+        // The start, `7`, `0`, and the `/` that has no value.
+        assert_eq!(postfix.frames.len(), 4);
+        let (stack, note) = postfix.frames.last().unwrap();
+        assert!(stack.is_empty());
+        assert_eq!(note, "dividing by zero has no answer");
+    }
+
+    #[test]
+    fn substitute_and_lookup_agree_on_the_latest_binding() {
+        let env = vec![("x".to_string(), 1), ("x".to_string(), 5)];
+        let expr = Expr::<Arithmetic>::binary(Arith::Add, var("x"), var("y"));
+
+        assert_eq!(expr_to_string(&substitute(expr.clone(), &env)), "(5 + y)");
+        assert_eq!(var::<Arithmetic>("x").eval_in(&env), Ok(5));
+        let looked_up = reduce_in(expr.clone(), &[PathStep::Left], &env).unwrap();
+        assert_eq!(expr_to_string(&looked_up), "(5 + y)");
+
+        let error = expr.eval_in(&env).unwrap_err();
+        assert!(error.starts_with("`y` has no value yet."));
+    }
+
+    #[test]
+    fn names_are_looked_up_one_step_at_a_time() {
+        let env = vec![("a".to_string(), true), ("a".to_string(), false)];
+        let expr = Expr::<Logic>::binary(Connective::Or, var("a"), Expr::unary(Not::Not, var("a")));
+        let steps = build_steps_in(expr, &env);
+        let forms: Vec<String> = steps
+            .iter()
+            .map(|step| expr_to_string(&step.expr))
+            .collect();
+        assert_eq!(
+            forms,
+            [
+                "(a or not a)",
+                "(false or not a)",
+                "(false or not false)",
+                "(false or true)",
+                "true"
+            ]
+        );
+    }
+}
@@ -2,6 +2,10 @@ use egui::{Align2, Color32, FontId, Painter, Pos2, Rect, Stroke, TextStyle};
 
 use GORBIE::themes;
 
+use crate::i18n::tr;
+use crate::palette::Palette;
+use crate::svg::Svg;
+
 #[derive(Clone, Copy, Debug)]
 pub enum FlowchartNodeKind {
     Start,
@@ -31,6 +35,21 @@ impl FlowchartNode {
         self.active = active;
         self
     }
+
+    /// What a screen reader announces, e.g. "question: age >= 18 (true), on
+    /// the taken path".
+    fn describe(&self) -> String {
+        let label = self.label.replace('\n', " ");
+        let mut text = match self.kind {
+            FlowchartNodeKind::Start => tr("flowchart.start").to_string(),
+            FlowchartNodeKind::Decision => tr("flowchart.question").replace("{label}", &label),
+            FlowchartNodeKind::Action => tr("flowchart.step").replace("{label}", &label),
+        };
+        if self.active {
+            text.push_str(&format!(", {}", tr("flowchart.on_path")));
+        }
+        text
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -40,6 +59,8 @@ pub struct FlowchartEdge {
 }
 
 pub struct Flowchart {
+    /// Parent id of the per-node widgets that screen readers see.
+    pub id: egui::Id,
     pub rect: Rect,
     pub nodes: Vec<FlowchartNode>,
     pub edges: Vec<FlowchartEdge>,
@@ -56,16 +77,29 @@ pub struct FlowchartStyle {
     pub node_stroke: Stroke,
     pub active_edge_stroke: Stroke,
     pub inactive_edge_stroke: Stroke,
+    /// Draw edges that are not taken dashed, so the taken path stands out
+    /// without relying on color.
+    pub dashed_inactive_edges: bool,
 }
 
 impl FlowchartStyle {
     pub fn from_ui(ui: &egui::Ui) -> Self {
         let background = ui.visuals().window_fill;
         let outline = ui.visuals().widgets.noninteractive.bg_stroke.color;
-        let active = themes::ral(2009);
-        let inactive = themes::blend(background, outline, 0.55);
+        let palette = Palette::from_ui(ui);
+        let active = palette.highlight;
+        let inactive = if palette.high_contrast {
+            outline
+        } else {
+            themes::blend(background, outline, 0.55)
+        };
         let font_id = TextStyle::Monospace.resolve(ui.style());
-        let edge_width: f32 = 2.5;
+        let edge_width = palette.stroke_width(2.5);
+        let active_edge_width = if palette.shape_cues {
+            edge_width * 1.6
+        } else {
+            edge_width
+        };
         Self {
             font_id,
             text_color: ui.visuals().text_color(),
@@ -73,23 +107,92 @@ impl FlowchartStyle {
             edge_corner_radius: 9.0,
             start_radius: edge_width * 2.5,
             node_fill: background,
-            active_node_fill: themes::blend(background, active, 0.12),
-            node_stroke: Stroke::new(1.0, outline),
-            active_edge_stroke: Stroke::new(edge_width, active),
+            active_node_fill: if palette.high_contrast {
+                background
+            } else {
+                themes::blend(background, active, 0.12)
+            },
+            node_stroke: Stroke::new(palette.stroke_width(1.0), outline),
+            active_edge_stroke: Stroke::new(active_edge_width, active),
             inactive_edge_stroke: Stroke::new(edge_width, inactive),
+            dashed_inactive_edges: palette.shape_cues,
         }
     }
 }
 
+impl Flowchart {
+    fn node_at(&self, point: Pos2) -> Option<&FlowchartNode> {
+        self.nodes
+            .iter()
+            .find(|node| node.rect.expand(1.0).contains(point))
+    }
+
+    /// E.g. "arrow from start to question: age >= 18 (true), taken".
+    fn describe_edge(&self, edge: &FlowchartEdge) -> String {
+        let name = |point: Option<&Pos2>| {
+            point
+                .and_then(|point| self.node_at(*point))
+                .map(|node| node.describe())
+                .unwrap_or_else(|| tr("flowchart.nowhere").to_string())
+        };
+        let taken = if edge.active {
+            tr("flowchart.taken")
+        } else {
+            tr("flowchart.not_taken")
+        };
+        tr("flowchart.arrow")
+            .replace("{from}", &name(edge.points.first()))
+            .replace("{to}", &name(edge.points.last()))
+            .replace("{taken}", taken)
+    }
+}
+
+/// Gives every node and edge a hover-only widget so assistive technology
+/// can read the chart, which is otherwise only painted.
+fn expose_to_screen_readers(ui: &egui::Ui, chart: &Flowchart) {
+    let widgets = chart
+        .nodes
+        .iter()
+        .map(|node| (node.rect, node.describe()))
+        .chain(
+            chart
+                .edges
+                .iter()
+                .map(|edge| (Rect::from_points(&edge.points), chart.describe_edge(edge))),
+        );
+    for (index, (rect, text)) in widgets.enumerate() {
+        let response = ui.interact(rect, chart.id.with(index), egui::Sense::hover());
+        response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Label, true, &text));
+    }
+}
+
 pub fn paint_flowchart(ui: &egui::Ui, chart: &Flowchart, style: &FlowchartStyle) {
+    expose_to_screen_readers(ui, chart);
     let painter = ui.painter_at(chart.rect);
     for edge in &chart.edges {
-        let stroke = if edge.active {
-            style.active_edge_stroke
+        if edge.active {
+            paint_polyline(
+                &painter,
+                &edge.points,
+                style.active_edge_stroke,
+                style.edge_corner_radius,
+            );
+        } else if style.dashed_inactive_edges {
+            let stroke = style.inactive_edge_stroke;
+            painter.extend(egui::Shape::dashed_line(
+                &edge.points,
+                stroke,
+                stroke.width * 3.0,
+                stroke.width * 2.0,
+            ));
         } else {
-            style.inactive_edge_stroke
-        };
-        paint_polyline(&painter, &edge.points, stroke, style.edge_corner_radius);
+            paint_polyline(
+                &painter,
+                &edge.points,
+                style.inactive_edge_stroke,
+                style.edge_corner_radius,
+            );
+        }
     }
 
     for node in &chart.nodes {
@@ -141,6 +244,64 @@ pub fn paint_flowchart(ui: &egui::Ui, chart: &Flowchart, style: &FlowchartStyle)
     }
 }
 
+/// The chart as [`paint_flowchart`] draws it, for the "Export image" button.
+/// Edges keep sharp corners; the painter's rounding is only for the screen.
+pub fn flowchart_svg(chart: &Flowchart, style: &FlowchartStyle) -> Svg {
+    let mut svg = Svg::new(chart.rect, style.node_fill);
+    for edge in &chart.edges {
+        if edge.active {
+            svg.polyline(&edge.points, style.active_edge_stroke, false);
+        } else {
+            svg.polyline(
+                &edge.points,
+                style.inactive_edge_stroke,
+                style.dashed_inactive_edges,
+            );
+        }
+    }
+
+    for node in &chart.nodes {
+        match node.kind {
+            FlowchartNodeKind::Start => {
+                let fill = if node.active {
+                    style.active_edge_stroke.color
+                } else {
+                    style.inactive_edge_stroke.color
+                };
+                svg.circle(node.rect.center(), style.start_radius, fill);
+            }
+            FlowchartNodeKind::Decision | FlowchartNodeKind::Action => {
+                let fill = if node.active {
+                    style.active_node_fill
+                } else {
+                    style.node_fill
+                };
+                svg.rect(node.rect, style.node_corner_radius, fill, style.node_stroke);
+                if node.active {
+                    let inner_rect = node.rect.shrink(2.0);
+                    if inner_rect.is_positive() {
+                        svg.rect(
+                            inner_rect,
+                            style.node_corner_radius,
+                            Color32::TRANSPARENT,
+                            style.node_stroke,
+                        );
+                    }
+                }
+                if !node.label.is_empty() {
+                    svg.text(
+                        node.rect.center(),
+                        &node.label,
+                        style.font_id.size,
+                        style.text_color,
+                    );
+                }
+            }
+        }
+    }
+    svg
+}
+
 fn paint_polyline(
     painter: &Painter,
     points: &[Pos2],
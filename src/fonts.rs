@@ -0,0 +1,58 @@
+use std::sync::{Arc, OnceLock};
+
+use egui::{FontData, FontDefinitions, FontFamily};
+
+/// The OpenDyslexic files are read from here. They are released under the
+/// SIL Open Font License, see `assets/fonts/README.md`.
+const FONT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/assets/fonts");
+const BODY: (&str, &str) = ("OpenDyslexic", "OpenDyslexic-Regular.otf");
+const CODE: (&str, &str) = ("OpenDyslexicMono", "OpenDyslexicMono-Regular.otf");
+
+struct DyslexiaFonts {
+    body: Vec<u8>,
+    code: Vec<u8>,
+}
+
+fn dyslexia_fonts() -> Option<&'static DyslexiaFonts> {
+    static FONTS: OnceLock<Option<DyslexiaFonts>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let read = |file: &str| std::fs::read(format!("{FONT_DIR}/{file}")).ok();
+            Some(DyslexiaFonts {
+                body: read(BODY.1)?,
+                code: read(CODE.1)?,
+            })
+        })
+        .as_ref()
+}
+
+/// Whether the dyslexia-friendly font files are present.
+pub fn dyslexia_font_available() -> bool {
+    dyslexia_fonts().is_some()
+}
+
+/// Switches body text and code to OpenDyslexic, or back to the fonts egui
+/// started with. The tree and flowchart layouts measure glyphs every frame,
+/// so they adapt to the new metrics on their own.
+pub fn apply(ctx: &egui::Context, dyslexia_font: bool) {
+    static BASE: OnceLock<FontDefinitions> = OnceLock::new();
+    let base = BASE.get_or_init(|| ctx.fonts(|fonts| fonts.definitions().clone()));
+    let mut definitions = base.clone();
+    if let Some(fonts) = dyslexia_fonts().filter(|_| dyslexia_font) {
+        for ((name, _), data, family) in [
+            (BODY, &fonts.body, FontFamily::Proportional),
+            (CODE, &fonts.code, FontFamily::Monospace),
+        ] {
+            definitions.font_data.insert(
+                name.to_string(),
+                Arc::new(FontData::from_owned(data.clone())),
+            );
+            definitions
+                .families
+                .entry(family)
+                .or_default()
+                .insert(0, name.to_string());
+        }
+    }
+    ctx.set_fonts(definitions);
+}
@@ -0,0 +1,209 @@
+use GORBIE::cards::{with_padding, DEFAULT_CARD_PADDING};
+use GORBIE::prelude::*;
+
+use crate::chapters::{card_heading, Chapter};
+
+/// A word the course defines once and then reuses.
+pub struct Term {
+    pub word: &'static str,
+    pub definition: &'static str,
+    /// The chapter that introduces the word.
+    pub chapter: Chapter,
+}
+
+pub const TERMS: &[Term] = &[
+    Term {
+        word: "expression",
+        definition: "A piece of code that produces a value, like 2 + 3.",
+        chapter: Chapter::Expressions,
+    },
+    Term {
+        word: "operator",
+        definition: "A symbol that combines values, like + or *.",
+        chapter: Chapter::Expressions,
+    },
+    Term {
+        word: "precedence",
+        definition: "Which operator goes first when there are no parentheses. * comes before +.",
+        chapter: Chapter::Expressions,
+    },
+    Term {
+        word: "associativity",
+        definition:
+            "Which side goes first when the same operator repeats. 8 - 3 - 2 works left to right.",
+        chapter: Chapter::Expressions,
+    },
+    Term {
+        word: "boolean",
+        definition: "A value that is either true or false.",
+        chapter: Chapter::Booleans,
+    },
+    Term {
+        word: "variable",
+        definition: "A name for a place that holds a value.",
+        chapter: Chapter::State,
+    },
+    Term {
+        word: "state",
+        definition: "The values a program remembers right now. They can change over time.",
+        chapter: Chapter::State,
+    },
+    Term {
+        word: "condition",
+        definition: "A boolean question that decides which way the program goes.",
+        chapter: Chapter::IfElse,
+    },
+    Term {
+        word: "branch",
+        definition: "One of the paths an if/else can take.",
+        chapter: Chapter::IfElse,
+    },
+    Term {
+        word: "loop",
+        definition: "Steps that repeat until a condition becomes false.",
+        chapter: Chapter::Loops,
+    },
+    Term {
+        word: "function",
+        definition: "A named recipe: give it inputs, it gives back a result.",
+        chapter: Chapter::Functions,
+    },
+    Term {
+        word: "parameter",
+        definition: "The name a function uses for one of its inputs.",
+        chapter: Chapter::Functions,
+    },
+    Term {
+        word: "owner",
+        definition:
+            "The one variable responsible for a value. When the owner goes away, so does the value.",
+        chapter: Chapter::Ownership,
+    },
+    Term {
+        word: "move",
+        definition: "Handing a value to a new owner. The old name can no longer use it.",
+        chapter: Chapter::Ownership,
+    },
+    Term {
+        word: "borrow",
+        definition: "Using a value through a reference without taking ownership.",
+        chapter: Chapter::Borrowing,
+    },
+    Term {
+        word: "enum",
+        definition: "A type whose value is exactly one of a fixed list of variants.",
+        chapter: Chapter::StructsEnums,
+    },
+    Term {
+        word: "Result",
+        definition: "Either Ok with a value or Err with the reason something failed.",
+        chapter: Chapter::Results,
+    },
+    Term {
+        word: "trait",
+        definition: "A set of abilities a type can promise to have.",
+        chapter: Chapter::Traits,
+    },
+    Term {
+        word: "iterator",
+        definition: "Something that hands out items one at a time when asked.",
+        chapter: Chapter::Iterators,
+    },
+    Term {
+        word: "slice",
+        definition: "A view into part of a string or list, without copying it.",
+        chapter: Chapter::Strings,
+    },
+    Term {
+        word: "module",
+        definition: "A named box that groups related code.",
+        chapter: Chapter::Modules,
+    },
+    Term {
+        word: "thread",
+        definition: "A worker that runs its own steps at the same time as others.",
+        chapter: Chapter::Concurrency,
+    },
+    Term {
+        word: "channel",
+        definition: "A pipe that threads use to send messages to each other.",
+        chapter: Chapter::Concurrency,
+    },
+];
+
+pub fn lookup(word: &str) -> Option<&'static Term> {
+    TERMS
+        .iter()
+        .find(|term| term.word.eq_ignore_ascii_case(word))
+}
+
+/// Shows `shown` with a dotted underline and the definition on hover.
+pub fn term(ui: &mut egui::Ui, shown: &str, term: &Term) -> egui::Response {
+    let response = ui
+        .add(egui::Label::new(shown).sense(egui::Sense::hover()))
+        .on_hover_text(format!("{}: {}", term.word, term.definition));
+    let rect = response.rect;
+    let stroke = egui::Stroke::new(1.0, ui.visuals().weak_text_color());
+    ui.painter().extend(egui::Shape::dashed_line(
+        &[rect.left_bottom(), rect.right_bottom()],
+        stroke,
+        2.0,
+        2.0,
+    ));
+    response
+}
+
+/// Lays out a sentence in which `[[word]]` or `[[shown|word]]` marks a
+/// glossary term. Unknown terms are shown as plain text.
+pub fn text(ui: &mut egui::Ui, markup: &str) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        let mut rest = markup;
+        while let Some(start) = rest.find("[[") {
+            let Some(length) = rest[start..].find("]]") else {
+                break;
+            };
+            if start > 0 {
+                ui.label(&rest[..start]);
+            }
+            let inner = &rest[start + 2..start + length];
+            let (shown, word) = inner.split_once('|').unwrap_or((inner, inner));
+            match lookup(word) {
+                Some(found) => {
+                    term(ui, shown, found);
+                }
+                None => {
+                    ui.label(shown);
+                }
+            }
+            rest = &rest[start + length + 2..];
+        }
+        if !rest.is_empty() {
+            ui.label(rest);
+        }
+    });
+}
+
+/// A card listing the words a chapter introduces. Nothing is shown for
+/// chapters without terms.
+pub fn chapter_words(nb: &mut NotebookCtx, chapter: Chapter) {
+    if !TERMS.iter().any(|term| term.chapter == chapter) {
+        return;
+    }
+    nb.view(move |ui| {
+        with_padding(ui, DEFAULT_CARD_PADDING, |ui| {
+            if !card_heading(ui, "Words in this chapter") {
+                return;
+            }
+            ui.add_space(4.0);
+            ui.label("Hover a dotted word anywhere in the course to see what it means.");
+            ui.add_space(6.0);
+            for found in TERMS.iter().filter(|term| term.chapter == chapter) {
+                ui.horizontal_wrapped(|ui| {
+                    term(ui, found.word, found);
+                    ui.label(format!(" - {}", found.definition));
+                });
+            }
+        });
+    });
+}
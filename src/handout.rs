@@ -0,0 +1,280 @@
+use std::sync::{OnceLock, RwLock};
+
+use GORBIE::prelude::*;
+
+use crate::chapters::{self, Chapter};
+use crate::i18n::tr;
+use crate::search::read_literal;
+use crate::storage;
+use crate::svg::Svg;
+
+/// Where prose starts in a chapter source, in the order search looks too.
+const MARKERS: [&str; 4] = ["card_heading(ui, \"", "md!(", "note!(", "speech::lesson("];
+
+/// A piece of a chapter, in the order its source lists them.
+enum Block {
+    Card(String),
+    /// A lesson is a card of its own, so it ends the card before it.
+    Lesson(String),
+    Text(String),
+    Note(String),
+}
+
+/// What a card drew while the chapter was captured.
+enum Snapshot {
+    Diagram(Svg),
+    Code(String),
+}
+
+/// Snapshots and the heading of the card that drew each.
+type Snapshots = Vec<(Option<String>, Snapshot)>;
+
+/// Diagrams and code frames are only known once they are drawn, so exporting
+/// takes two frames: one to ask, and one in which every card draws open and
+/// hands what it shows over.
+enum Export {
+    Idle,
+    Requested(Chapter),
+    Capturing {
+        chapter: Chapter,
+        /// The heading of the card being drawn, which owns the snapshots
+        /// that follow it.
+        card: Option<String>,
+        snapshots: Snapshots,
+    },
+}
+
+struct Handout {
+    export: Export,
+    message: Option<String>,
+}
+
+static HANDOUT: OnceLock<RwLock<Handout>> = OnceLock::new();
+
+fn handout_lock() -> &'static RwLock<Handout> {
+    HANDOUT.get_or_init(|| {
+        RwLock::new(Handout {
+            export: Export::Idle,
+            message: None,
+        })
+    })
+}
+
+/// Prose whose text is filled in at run time, like `md!(ui, "{}", tr(key))`,
+/// has no place in a handout.
+fn is_template(text: &str) -> bool {
+    text.replace("{{", "").replace("}}", "").contains('{')
+}
+
+/// The card headings, markdown and notes in a chapter source.
+fn blocks(source: &str) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut rest = source;
+    while let Some((start, marker)) = MARKERS
+        .iter()
+        .filter_map(|marker| rest.find(marker).map(|start| (start, *marker)))
+        .min_by_key(|(start, _)| *start)
+    {
+        let literal = rest[start..]
+            .find('"')
+            .and_then(|quote| read_literal(&rest[start + quote..]));
+        rest = &rest[start + marker.len()..];
+        let Some(text) = literal else {
+            continue;
+        };
+        // Lessons are plain strings; the macros take format strings.
+        let formatted = matches!(marker, "md!(" | "note!(");
+        if formatted && is_template(&text) {
+            continue;
+        }
+        let text = if formatted {
+            text.replace("{{", "{").replace("}}", "}")
+        } else {
+            text
+        };
+        blocks.push(match marker {
+            "speech::lesson(" => Block::Lesson(text),
+            "md!(" => Block::Text(text),
+            "note!(" => Block::Note(text),
+            _ => Block::Card(text),
+        });
+    }
+    blocks
+}
+
+/// Appends a snapshot. Diagrams become numbered SVG files in `files`.
+fn place(document: &mut String, files: &mut Vec<Svg>, alt: &str, snapshot: Snapshot) {
+    match snapshot {
+        Snapshot::Diagram(svg) => {
+            files.push(svg);
+            document.push_str(&format!("![{alt}](diagram_{}.svg)\n\n", files.len()));
+        }
+        Snapshot::Code(code) => document.push_str(&format!("```\n{code}\n```\n\n")),
+    }
+}
+
+/// Ends a card: what it drew, or a pointer to the app if nothing of it
+/// made it onto paper.
+fn close_card(
+    document: &mut String,
+    card: &str,
+    has_text: bool,
+    snapshots: &mut Snapshots,
+    files: &mut Vec<Svg>,
+) {
+    let (own, others): (Snapshots, Snapshots) = std::mem::take(snapshots)
+        .into_iter()
+        .partition(|(owner, _)| owner.as_deref() == Some(card));
+    *snapshots = others;
+    if !has_text && own.is_empty() {
+        document.push_str("_This card is interactive. Try it in the app._\n\n");
+    }
+    for (_, snapshot) in own {
+        place(document, files, card, snapshot);
+    }
+}
+
+/// Writes the chapter to `gorbie_<id>_handout/handout.md` in the home
+/// directory, with each captured diagram next to it as an SVG file.
+fn write(chapter: Chapter, mut snapshots: Snapshots) -> Result<String, String> {
+    let def = chapters::def(chapter);
+    let mut document = format!("# {}. {}\n\n", def.label(), def.title);
+    let mut files = Vec::new();
+    let mut card: Option<String> = None;
+    let mut has_text = false;
+    for block in blocks(def.source) {
+        match block {
+            Block::Card(title) => {
+                if let Some(card) = card.take() {
+                    close_card(&mut document, &card, has_text, &mut snapshots, &mut files);
+                }
+                document.push_str(&format!("## {title}\n\n"));
+                card = Some(title);
+                has_text = false;
+            }
+            Block::Lesson(text) => {
+                if let Some(card) = card.take() {
+                    close_card(&mut document, &card, has_text, &mut snapshots, &mut files);
+                }
+                document.push_str(&format!("{}\n\n", text.trim()));
+            }
+            Block::Text(text) => {
+                document.push_str(&format!("{}\n\n", text.trim()));
+                has_text = true;
+            }
+            Block::Note(text) => {
+                for line in text.trim().lines() {
+                    document.push_str(&format!("> {line}\n"));
+                }
+                document.push('\n');
+                has_text = true;
+            }
+        }
+    }
+    if let Some(card) = &card {
+        close_card(&mut document, card, has_text, &mut snapshots, &mut files);
+    }
+    // Drawn outside a card the source names, e.g. under a heading built at
+    // run time.
+    for (_, snapshot) in snapshots {
+        place(&mut document, &mut files, "diagram", snapshot);
+    }
+
+    let folder = storage::home_file(&format!("gorbie_{}_handout", def.id));
+    let fail = |error: std::io::Error| format!("{}: {error}", folder.display());
+    std::fs::create_dir_all(&folder).map_err(fail)?;
+    for (index, svg) in files.iter().enumerate() {
+        std::fs::write(
+            folder.join(format!("diagram_{}.svg", index + 1)),
+            svg.finish(),
+        )
+        .map_err(fail)?;
+    }
+    let path = folder.join("handout.md");
+    std::fs::write(&path, document).map_err(fail)?;
+    Ok(path.display().to_string())
+}
+
+/// Whether the chapter is being captured right now. Cards draw open while
+/// it is, so collapsed ones still show everything.
+pub fn capturing() -> bool {
+    matches!(
+        handout_lock().read().expect("handout lock poisoned").export,
+        Export::Capturing { .. }
+    )
+}
+
+/// Notes that the card titled `title` starts drawing.
+pub fn card(title: &str) {
+    if let Export::Capturing { card, .. } = &mut handout_lock()
+        .write()
+        .expect("handout lock poisoned")
+        .export
+    {
+        *card = Some(title.to_string());
+    }
+}
+
+fn add(snapshot: Snapshot) {
+    if let Export::Capturing {
+        card, snapshots, ..
+    } = &mut handout_lock()
+        .write()
+        .expect("handout lock poisoned")
+        .export
+    {
+        snapshots.push((card.clone(), snapshot));
+    }
+}
+
+/// Adds a diagram as it is drawn now to the chapter being captured.
+pub fn diagram(svg: Svg) {
+    add(Snapshot::Diagram(svg));
+}
+
+/// Adds the text of a code frame to the chapter being captured.
+pub fn code(code: String) {
+    add(Snapshot::Code(code));
+}
+
+/// Moves an export along by one frame. Called before the cards draw.
+pub fn flush(ctx: &egui::Context) {
+    let mut handout = handout_lock().write().expect("handout lock poisoned");
+    match std::mem::replace(&mut handout.export, Export::Idle) {
+        Export::Idle => {}
+        Export::Requested(chapter) => {
+            handout.export = Export::Capturing {
+                chapter,
+                card: None,
+                snapshots: Vec::new(),
+            };
+            ctx.request_repaint();
+        }
+        Export::Capturing {
+            chapter, snapshots, ..
+        } => {
+            handout.message = Some(match write(chapter, snapshots) {
+                Ok(path) => tr("handout.saved").replace("{path}", &path),
+                Err(error) => error,
+            });
+            ctx.request_repaint();
+        }
+    }
+}
+
+/// The "Export chapter" button for the chapter header.
+pub fn button(ui: &mut egui::Ui, chapter: Chapter) {
+    let mut handout = handout_lock().write().expect("handout lock poisoned");
+    if ui
+        .add(widgets::Button::new(tr("handout.export")).small())
+        .on_hover_text(tr("handout.export_hover"))
+        .clicked()
+    {
+        handout.export = Export::Requested(chapter);
+        handout.message = None;
+        ui.ctx().request_repaint();
+    }
+    if let Some(message) = &handout.message {
+        ui.label(egui::RichText::new(message).weak());
+    }
+}
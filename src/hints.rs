@@ -0,0 +1,118 @@
+use GORBIE::prelude::*;
+
+use crate::i18n::tr;
+use crate::presentation;
+use crate::progress::{self, ExerciseKey};
+use crate::settings;
+use crate::supervised;
+
+/// How much of the next step the learner has asked to see. Each press of
+/// the hint button climbs one rung.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+enum Hint {
+    #[default]
+    None,
+    /// A sentence on what to look for.
+    Nudge,
+    /// The part of the exercise the step is in.
+    Region,
+    /// The step itself.
+    Reveal,
+}
+
+impl Hint {
+    fn next(self) -> Option<Self> {
+        match self {
+            Hint::None => Some(Hint::Nudge),
+            Hint::Nudge => Some(Hint::Region),
+            Hint::Region => Some(Hint::Reveal),
+            Hint::Reveal => None,
+        }
+    }
+
+    fn rung(self) -> usize {
+        self as usize
+    }
+}
+
+/// The hints taken on the current step of one exercise.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HintLadder {
+    hint: Hint,
+}
+
+impl HintLadder {
+    /// Every rung climbed, for showing the step without the learner asking.
+    pub fn revealed() -> Self {
+        Self { hint: Hint::Reveal }
+    }
+
+    /// Back to no hint, for a new exercise or once the step was taken.
+    pub fn reset(&mut self) {
+        self.hint = Hint::None;
+    }
+
+    /// The hint button, naming the rung it climbs to. Pressing it counts a
+    /// hint for `key`. Returns whether it was pressed; hidden while hints
+    /// are off or the secondary controls are.
+    pub fn button(&mut self, ui: &mut egui::Ui, key: ExerciseKey) -> bool {
+        if !presentation::show_secondary() || !settings::get().hints || supervised::active() {
+            return false;
+        }
+        let Some(next) = self.hint.next() else {
+            ui.add_enabled(false, widgets::Button::new(tr("hint.used_up")));
+            return false;
+        };
+        let label = match next {
+            Hint::Region => tr("hint.region"),
+            Hint::Reveal => tr("hint.reveal"),
+            _ => tr("hint.nudge"),
+        };
+        let label = format!("{label} ({}/{})", next.rung(), Hint::Reveal.rung());
+        if !ui.add(widgets::Button::new(label)).clicked() {
+            return false;
+        }
+        self.hint = next;
+        progress::record_hint(key);
+        true
+    }
+
+    /// The "Show me" button of a card that can play its solution; hidden in
+    /// supervised mode like the hints.
+    pub fn show_me(ui: &mut egui::Ui, enabled: bool) -> bool {
+        if supervised::active() {
+            return false;
+        }
+        ui.add_enabled(enabled, widgets::Button::new(tr("hint.show_me")))
+            .on_hover_text(tr("hint.show_me_hover"))
+            .clicked()
+    }
+
+    /// The path to highlight in an exercise whose next step is at `next`:
+    /// the node above it for [`Hint::Region`], then the step itself.
+    pub fn marked<'a, T>(&self, next: Option<&'a [T]>) -> Option<&'a [T]> {
+        let next = next?;
+        match self.hint {
+            Hint::Region => Some(&next[..next.len().saturating_sub(1)]),
+            Hint::Reveal => Some(next),
+            _ => None,
+        }
+    }
+
+    /// Whether the tree node at `path` lights up: everything under the
+    /// marked node while narrowing down, only the step once revealed.
+    pub fn marks<T: PartialEq>(&self, next: Option<&[T]>, path: &[T]) -> bool {
+        match (self.hint, self.marked(next)) {
+            (Hint::Region, Some(region)) => path.starts_with(region),
+            (_, Some(step)) => path == step,
+            _ => false,
+        }
+    }
+
+    /// The nudge sentence, from the first rung on.
+    pub fn nudge(&self, ui: &mut egui::Ui, text: &str) {
+        if self.hint >= Hint::Nudge {
+            ui.label(egui::RichText::new(format!("{} {text}", tr("hint.prefix"))).italics());
+        }
+    }
+}
@@ -0,0 +1,902 @@
+use crate::settings;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// Stable code used in the settings file.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|language| language.code() == code)
+    }
+
+    /// The language's own name, as shown in the switcher.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => EN,
+            Language::German => DE,
+        }
+    }
+}
+
+/// The text for `key` in the chosen language. Keys a language has not
+/// translated yet fall back to English, and unknown keys to the key itself
+/// so a typo shows up on screen instead of as a blank.
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(settings::get().language, key).unwrap_or(key)
+}
+
+/// The text for `key` in `language`, falling back to English like [`tr`].
+/// Search uses it to index every language, not just the chosen one.
+pub fn lookup(language: Language, key: &str) -> Option<&'static str> {
+    let find = |language: Language| {
+        language
+            .table()
+            .iter()
+            .find(|(known, _)| *known == key)
+            .map(|(_, text)| *text)
+    };
+    find(language).or_else(|| find(Language::English))
+}
+
+/// The language `text` is written in: the chosen language when its table
+/// holds the text, otherwise English, which every untranslated text, like
+/// the chapter lessons, is written in.
+pub fn language_of(text: &str) -> Language {
+    let chosen = settings::get().language;
+    let translated = chosen.table().iter().any(|(_, known)| *known == text);
+    if translated {
+        chosen
+    } else {
+        Language::English
+    }
+}
+
+const EN: &[(&str, &str)] = &[
+    ("feedback.correct", "Correct!"),
+    ("feedback.not_quite", "Not quite."),
+    ("feedback.pick", "Pick an answer."),
+    ("exercise.new", "New exercise"),
+    ("coop.toggle", "Two players"),
+    ("coop.score", "Player {player}: {score}"),
+    ("coop.reset", "Reset scores"),
+    ("coop.turn", "Player {player}, it is your turn."),
+    ("coop.picked", "Player {player} picked {answer}."),
+    ("coop.next", "Everyone has picked. Press New exercise for the next round."),
+    ("exercise.seed", "Seed"),
+    (
+        "dashboard.intro",
+        "# Dashboard\n\
+         How this learner is doing, for a tutor or a parent: answers and time per chapter, \
+         and the exercises that are still open. Chapters where fewer than six in ten \
+         answers are right are marked.",
+    ),
+    ("dashboard.chapter", "Chapter"),
+    ("dashboard.attempts", "Answers"),
+    ("dashboard.accuracy", "Right"),
+    ("dashboard.time", "Time"),
+    ("dashboard.solved", "Solved"),
+    ("dashboard.hints", "Hints"),
+    ("dashboard.card", "Card"),
+    (
+        "dashboard.no_card_time",
+        "No time recorded yet. Time counts for the card under the pointer.",
+    ),
+    ("mastery.title", "Mastery"),
+    ("mastery.level", "{mastered} of {tiers} tiers mastered"),
+    ("mastery.shallow", "Trees up to two operators deep"),
+    ("mastery.deep", "Trees three or more operators deep"),
+    ("mastery.negatives", "Trees with negation"),
+    ("mastery.not", "Trees with not"),
+    (
+        "progress.attempts",
+        "solved {solved}/{tries}, best streak {streak}, avg {seconds} s",
+    ),
+    (
+        "dashboard.struggling",
+        "Many wrong answers here. Going over this chapter together may help.",
+    ),
+    (
+        "dashboard.nothing_unfinished",
+        "Every exercise in the started chapters is solved.",
+    ),
+    ("exercise.copy_seed", "Copy"),
+    (
+        "exercise.copy_seed_hover",
+        "Copy the exercise code. Loading it under \"Exercise code\" brings back the same exercise.",
+    ),
+    (
+        "quiz.intro",
+        "Five questions. Answer all of them, then hand the quiz in to see your grade.",
+    ),
+    ("quiz.question", "Question"),
+    ("quiz.hand_in", "Hand in"),
+    ("quiz.result", "{right} of {total} right."),
+    ("quiz.answer_was", "Not quite. The answer was {answer}."),
+    ("quiz.new", "New quiz"),
+    ("challenge.start", "Timed challenge"),
+    (
+        "challenge.hover",
+        "Answer {total} exercises in {minutes} minutes. Switch challenges off in the settings.",
+    ),
+    ("challenge.countdown", "{time} left, exercise {number} of {total}"),
+    ("challenge.result", "{right} of {total} right in {time}"),
+    ("challenge.time_up", "Time is up."),
+    ("challenge.again", "Try again"),
+    ("challenge.done", "Back to practice"),
+    (
+        "review.empty",
+        "Nothing to review. Exercises you answer wrongly in practice show up here.",
+    ),
+    ("review.waiting", "Mistakes waiting for review: {count}"),
+    ("review.next", "Next mistake"),
+    ("review.done", "All mistakes reviewed. Well done!"),
+    ("stepper.prev", "Prev"),
+    ("stepper.next", "Next"),
+    ("stepper.reset", "Reset"),
+    ("stepper.step", "Step"),
+    ("stepper.play", "Play"),
+    ("stepper.pause", "Pause"),
+    ("stepper.seconds", "s per step"),
+    ("tree.zoom_in", "Zoom in"),
+    ("tree.zoom_out", "Zoom out"),
+    ("tree.zoom_reset", "Back to 100%"),
+    ("tree.pan_hint", "Drag to move around, pinch or Ctrl+scroll to zoom."),
+    ("selector.title", "Teaching notebooks"),
+    (
+        "selector.intro",
+        "Pick a chapter to open. A check mark means every exercise is solved.",
+    ),
+    (
+        "selector.keys",
+        "Keys: Page Up / Page Down change chapter, Tab moves between buttons, \
+         arrows step through a card, 1-9 pick an answer.",
+    ),
+    ("selector.search", "Search"),
+    ("selector.nothing_found", "Nothing found."),
+    (
+        "selector.solved",
+        "Exercises solved in this chapter: {percent}%",
+    ),
+    ("card.collapse", "Click to fold this card away."),
+    ("card.expand", "Click to open this card again."),
+    (
+        "score.summary",
+        "Score: {right} right, {wrong} wrong. Streak: {streak} (best {best})",
+    ),
+    ("difficulty.summary", "Practice level {level} of {highest}"),
+    ("selector.reset", "Reset this chapter"),
+    (
+        "selector.reset_hover",
+        "Puts every card in this chapter back to the start. Solved exercises stay solved.",
+    ),
+    ("selector.dashboard", "Dashboard"),
+    ("nav.previous", "\u{2190} Previous"),
+    ("nav.next", "Next \u{2192}"),
+    ("projector.toggle", "Presentation mode"),
+    (
+        "projector.on",
+        "Large print is on. Arrow Up and Arrow Down jump from card to card, \
+         Page Up and Page Down move one screen, Escape ends the presentation.",
+    ),
+    ("settings.title", "Settings"),
+    ("settings.language", "Language"),
+    ("settings.theme", "Theme"),
+    ("settings.light", "Light"),
+    ("settings.dark", "Dark"),
+    ("settings.text_size", "Text size"),
+    ("settings.animations", "Animations"),
+    ("settings.speech_rate", "Read-aloud speed"),
+    ("settings.slow", "Slow"),
+    ("settings.normal", "Normal"),
+    ("settings.fast", "Fast"),
+    ("settings.colorblind_safe", "Colorblind-safe highlights"),
+    ("settings.high_contrast", "High contrast"),
+    ("settings.dyslexia_font", "Dyslexia-friendly font"),
+    (
+        "settings.dyslexia_font_missing",
+        "The OpenDyslexic files are missing from assets/fonts.",
+    ),
+    ("settings.hints", "Hints after a wrong answer"),
+    ("hint.nudge", "Hint"),
+    ("hint.region", "Narrow it down"),
+    ("hint.reveal", "Show the step"),
+    ("hint.used_up", "No more hints"),
+    ("hint.prefix", "Hint:"),
+    ("worked.show", "Show the steps"),
+    (
+        "patterns.add_before_multiply",
+        "You often add or subtract before multiplying. The *, / or % inside has to be worked out first.",
+    ),
+    (
+        "patterns.or_before_and",
+        "You often pick or before the and inside it. Work out the inner box first.",
+    ),
+    (
+        "patterns.outer_first",
+        "You often pick an outer box before the boxes inside it. Work from the inside out.",
+    ),
+    (
+        "patterns.right_first",
+        "You often jump to the right. When two boxes are ready, take the left one first.",
+    ),
+    ("patterns.revisit", "Revisit \"{card}\""),
+    ("patterns.dismiss", "Got it"),
+    ("hint.show_me", "Show me"),
+    (
+        "hint.show_me_hover",
+        "Watch the whole tree being evaluated, one step at a time",
+    ),
+    ("supervised.title", "Supervised mode"),
+    (
+        "supervised.intro",
+        "Pick a PIN to hide hints, \"Show me\" and worked solutions, e.g. for a test.",
+    ),
+    (
+        "supervised.on",
+        "Supervised mode is on: hints and worked solutions are hidden.",
+    ),
+    ("supervised.pin", "PIN"),
+    ("supervised.lock", "Lock"),
+    ("supervised.unlock", "Unlock"),
+    ("supervised.wrong_pin", "That is not the PIN."),
+    ("supervised.short_pin", "Use at least {digits} digits."),
+    ("supervised.locked", "Locked by supervised mode"),
+    ("settings.timed_challenges", "Timed challenges"),
+    ("settings.reduced_motion", "Reduce motion"),
+    ("settings.language_scope", "Chapter lessons and exercises are still in English. The controls and the shared feedback are translated."),
+    ("keyboard.pick", "Keys 1-{count} pick an answer."),
+    ("steps.tree_view", "Tree view:"),
+    ("steps.looked_up", "The highlighted name is replaced by its value from the list above."),
+    ("steps.next", "The highlighted part is what you can evaluate next."),
+    ("steps.done", "Fully evaluated."),
+    ("stack.title", "Stack, top first:"),
+    ("stack.empty", "(empty)"),
+    ("stack.empty_error", "The stack is empty."),
+    ("stack.too_short", "The stack has fewer than two values."),
+    ("postfix.start", "The stack starts out empty. Read the tokens from left to right."),
+    ("postfix.value", "A value: push {value}."),
+    ("postfix.load", "Look up `{name}` and push its value, {value}."),
+    ("postfix.unary", "Pop {value}, apply `{op}` and push {result}."),
+    ("postfix.binary", "Pop {right} and {left}, work out {left} {op} {right} and push {result}."),
+    ("postfix.result", "One value is left on the stack: that is the value of the expression."),
+    ("program.line", "Every line before {line} already ran. Line {line} does the highlighted step."),
+    ("program.done", "Every line ran, and the value of the expression is left on the stack."),
+    ("tree_practice.new", "New tree"),
+    ("tree_practice.keys", "Or use the keys: arrows move the ring, Enter works out its box."),
+    ("tree_practice.oops", "Oops: {error}"),
+    ("tree_practice.not_yet", "Not yet. Work left-to-right; if there is no deeper expression, move up to the next level."),
+    ("tree_practice.watched", "That is how it goes: the tree ends at {value}."),
+    ("tree_practice.try_own", "Now press New tree and try one on your own."),
+    ("tree_practice.done", "All done! Value = {value}."),
+    ("tree_practice.nudge_numbers", "Find the deepest operator whose two sides are already numbers. If there are several, start with the leftmost."),
+    ("tree_practice.nudge_booleans", "Find the deepest operator whose sides are already true or false. If there are several, start with the leftmost."),
+    ("tree_practice.constant", "Constants already have a value."),
+    ("tree_practice.boolean", "Booleans already have a value."),
+    ("booleans.substitute", "First, every highlighted name is replaced by its value from the switches:"),
+    ("memory.owners", "owners"),
+    ("memory.heap", "heap values"),
+    ("memory.borrows", "borrows"),
+    ("memory.no_owners", "(no owners)"),
+    ("memory.moved", "{name} (moved)"),
+    ("tree.diagram", "Tree diagram with {count} boxes"),
+    ("tree.node", "{name} node"),
+    ("tree.child", "child {child}"),
+    ("tree.children", "children {rest} and {last}"),
+    ("tree.highlighted", "highlighted"),
+    ("tree.selected", "selected"),
+    ("tree.skipped", "skipped"),
+    ("tree.add", "add"),
+    ("tree.negate", "negate"),
+    ("tree.subtract", "subtract"),
+    ("tree.multiply", "multiply"),
+    ("tree.divide", "divide"),
+    ("tree.remainder", "remainder"),
+    ("tree.power", "power"),
+    ("flowchart.start", "start"),
+    ("flowchart.question", "question: {label}"),
+    ("flowchart.step", "step: {label}"),
+    ("flowchart.on_path", "on the taken path"),
+    ("flowchart.arrow", "arrow from {from} to {to}, {taken}"),
+    ("flowchart.nowhere", "nowhere"),
+    ("flowchart.taken", "taken"),
+    ("flowchart.not_taken", "not taken"),
+    ("celebrate.quiz", "Quiz complete, every answer right!"),
+    ("celebrate.streak", "{streak} right in a row!"),
+    ("settings.reset_all", "Reset all progress"),
+    (
+        "settings.reset_all_warning",
+        "This clears every exercise, answer and solved mark in all chapters. \
+         It cannot be undone.",
+    ),
+    ("settings.reset_all_confirm", "Erase everything"),
+    ("settings.cancel", "Cancel"),
+    ("backup.title", "Backup"),
+    ("classroom.title", "Classroom"),
+    (
+        "classroom.intro",
+        "One computer teaches, the others follow: they open the teacher's chapter and get the same exercises.",
+    ),
+    ("classroom.room", "Room"),
+    ("classroom.off", "Off"),
+    ("classroom.teach", "Teach"),
+    ("classroom.follow", "Follow"),
+    ("classroom.teaching", "Sending to everyone following room {room}"),
+    ("classroom.following", "Following the teacher"),
+    ("classroom.waiting", "Waiting for the teacher..."),
+    ("code.copy", "Copy"),
+    ("code.copied", "Copied"),
+    ("code.copy_hover", "Copy this code, e.g. to paste it into an answer or your notes"),
+    ("export.image", "Export image"),
+    (
+        "export.image_hover",
+        "Save this diagram as an SVG file in your home folder, e.g. for a handout",
+    ),
+    ("export.saved", "Saved to {path}"),
+    ("handout.export", "Export chapter"),
+    ("share.code", "Exercise code"),
+    (
+        "share.code_hover",
+        "Share this code: loading it shows someone else the exact same exercise",
+    ),
+    ("share.load", "Load exercise"),
+    ("share.load_hover", "Open the card of a code like EXPR-7FK2 with its exercise"),
+    ("share.malformed", "{code} is not an exercise code, e.g. EXPR-7FK2"),
+    ("share.unknown", "No exercise starts with the code {code}"),
+    (
+        "handout.export_hover",
+        "Write this chapter's text and diagrams to a printable Markdown handout in your home folder",
+    ),
+    ("handout.saved", "Handout saved to {path}"),
+    ("backup.file", "File"),
+    ("backup.export", "Export"),
+    (
+        "backup.export_hover",
+        "Saves all progress and settings to the file, to move them to another machine.",
+    ),
+    ("backup.import", "Import"),
+    (
+        "backup.import_hover",
+        "Replaces all progress and settings with the ones in the file.",
+    ),
+    ("backup.exported", "Progress exported."),
+    ("backup.imported", "Progress imported."),
+    ("speech.play", "\u{1F50A} Read aloud"),
+    ("speech.pause", "Pause"),
+    ("speech.resume", "Resume"),
+    ("speech.stop", "Stop"),
+    ("speech.hover", "Reads this card out loud."),
+    (
+        "speech.missing",
+        "No speech synthesizer found. Install espeak-ng to hear cards read aloud.",
+    ),
+    (
+        "schedule.intro",
+        "These exercises are due again. Answering them right pushes the next review further out.",
+    ),
+    (
+        "schedule.nothing_due",
+        "Nothing to review today. Solved exercises come back here after a day, then after longer and longer breaks.",
+    ),
+    ("calendar.streak", "Daily streak: {days} days"),
+    (
+        "calendar.total",
+        "Practiced on {practiced} of the last {days} days",
+    ),
+    (
+        "overview.badge",
+        "Badge earned: every exercise in this chapter is solved.",
+    ),
+    (
+        "overview.intro",
+        "# Teaching notebooks plan\n\
+         A practical learning path for absolute beginners.\n\n\
+         This series is designed for learners with little or no formal math background.\n\
+         Every concept is grounded in simple language, concrete examples, and visible feedback.",
+    ),
+    (
+        "overview.principles",
+        "## Principles\n\
+         - **Tiny steps**: one new idea per notebook.\n\
+         - **See the effect**: every concept has a visual or interactive demo.\n\
+         - **Practice > lecture**: short exercises after each demo.\n\
+         - **Build confidence**: celebrate correctness, then improve style.\n\
+         - **Vocabulary grows slowly**: define terms once and reuse them.",
+    ),
+    (
+        "overview.track_a",
+        "Track A - Programming foundations (10-12 notebooks)",
+    ),
+    (
+        "overview.track_b",
+        "## Track B - Theoretical CS (10-12 notebooks)\n\
+         1. Sets, relations, and graphs\n\
+         2. Finite state machines (DFA)\n\
+         3. Regular expressions as machines\n\
+         4. Context-free grammars\n\
+         5. Parse trees by hand\n\
+         6. Turing machines (tape + rules)\n\
+         7. Halting problem intuition\n\
+         8. Reductions and NP overview\n\
+         9. Why some problems stay hard\n\
+         10. Mini project: build a tiny parser",
+    ),
+    ("overview.track_c", "Track C - Rust (12-15 notebooks)"),
+    (
+        "overview.widgets",
+        "## Shared visual widgets\n\
+         - Stack and call-frame viewer\n\
+         - Memory map (owned vs borrowed)\n\
+         - Tape simulator (Turing machines)\n\
+         - Parse tree explorer\n\
+         - Stepper for algorithms\n\
+         - Tiny code runner with logs",
+    ),
+    (
+        "overview.template",
+        "## Lesson template (every notebook)\n\
+         1. Short story or real-life analogy\n\
+         2. Minimal code demo\n\
+         3. Interactive widget\n\
+         4. Exercise (3-5 minutes)\n\
+         5. Recap in one paragraph",
+    ),
+    (
+        "overview.milestones",
+        "## Milestones\n\
+         - **Week 1**: basic variables, conditions, and loops\n\
+         - **Week 2**: functions + lists + small projects\n\
+         - **Week 3**: automata and parsing intuition\n\
+         - **Week 4**: Rust ownership and references\n\
+         - **Week 5**: build a mini project together",
+    ),
+    (
+        "overview.pilot",
+        "Start with five pilot notebooks:\n\
+         - **Hello, expressions** (programming)\n\
+         - **To Bool or Not to Bool** (programming)\n\
+         - **Hello, state** (programming)\n\
+         - **DFA basics** (theory)\n\
+         - **Ownership 101** (Rust)\n\n\
+         We will test them, refine the language, and then expand.",
+    ),
+];
+
+const DE: &[(&str, &str)] = &[
+    ("feedback.correct", "Richtig!"),
+    ("feedback.not_quite", "Nicht ganz."),
+    ("feedback.pick", "Wähle eine Antwort."),
+    ("exercise.new", "Neue Aufgabe"),
+    ("coop.toggle", "Zwei Spieler"),
+    ("coop.score", "Spieler {player}: {score}"),
+    ("coop.reset", "Punkte zurücksetzen"),
+    ("coop.turn", "Spieler {player}, du bist dran."),
+    ("coop.picked", "Spieler {player} hat {answer} gewählt."),
+    ("coop.next", "Alle haben gewählt. Drücke Neue Aufgabe für die nächste Runde."),
+    ("exercise.seed", "Startwert"),
+    (
+        "dashboard.intro",
+        "# Übersicht für Lehrende\n\
+         Wie es bei diesem Lernenden läuft, für Tutorinnen oder Eltern: Antworten und Zeit \
+         je Kapitel und die Aufgaben, die noch offen sind. Kapitel, in denen weniger als \
+         sechs von zehn Antworten stimmen, sind markiert.",
+    ),
+    ("dashboard.chapter", "Kapitel"),
+    ("dashboard.attempts", "Antworten"),
+    ("dashboard.accuracy", "Richtig"),
+    ("dashboard.time", "Zeit"),
+    ("dashboard.solved", "Gelöst"),
+    ("dashboard.hints", "Hinweise"),
+    ("dashboard.card", "Karte"),
+    (
+        "dashboard.no_card_time",
+        "Noch keine Zeit erfasst. Die Zeit zählt für die Karte unter dem Mauszeiger.",
+    ),
+    ("mastery.title", "Beherrschung"),
+    ("mastery.level", "{mastered} von {tiers} Stufen gemeistert"),
+    ("mastery.shallow", "Bäume bis zu zwei Operatoren tief"),
+    ("mastery.deep", "Bäume ab drei Operatoren Tiefe"),
+    ("mastery.negatives", "Bäume mit Minuszeichen"),
+    ("mastery.not", "Bäume mit not"),
+    (
+        "progress.attempts",
+        "gelöst {solved}/{tries}, beste Serie {streak}, Ø {seconds} s",
+    ),
+    (
+        "dashboard.struggling",
+        "Hier gab es viele falsche Antworten. Das Kapitel gemeinsam durchzugehen kann helfen.",
+    ),
+    (
+        "dashboard.nothing_unfinished",
+        "Alle Aufgaben in den begonnenen Kapiteln sind gelöst.",
+    ),
+    ("exercise.copy_seed", "Kopieren"),
+    (
+        "exercise.copy_seed_hover",
+        "Aufgabencode kopieren. Unter \"Aufgabencode\" geladen bringt er dieselbe Aufgabe zurück.",
+    ),
+    (
+        "quiz.intro",
+        "Fünf Fragen. Beantworte alle und gib das Quiz dann ab, um deine Note zu sehen.",
+    ),
+    ("quiz.question", "Frage"),
+    ("quiz.hand_in", "Abgeben"),
+    ("quiz.result", "{right} von {total} richtig."),
+    ("quiz.answer_was", "Nicht ganz. Die Antwort war {answer}."),
+    ("quiz.new", "Neues Quiz"),
+    ("challenge.start", "Auf Zeit"),
+    (
+        "challenge.hover",
+        "Löse {total} Aufgaben in {minutes} Minuten. In den Einstellungen lässt sich das abschalten.",
+    ),
+    ("challenge.countdown", "Noch {time}, Aufgabe {number} von {total}"),
+    ("challenge.result", "{right} von {total} richtig in {time}"),
+    ("challenge.time_up", "Die Zeit ist um."),
+    ("challenge.again", "Noch einmal"),
+    ("challenge.done", "Zurück zum Üben"),
+    (
+        "review.empty",
+        "Nichts zu wiederholen. Aufgaben, die du beim Üben falsch beantwortest, landen hier.",
+    ),
+    ("review.waiting", "Fehler zum Wiederholen: {count}"),
+    ("review.next", "Nächster Fehler"),
+    ("review.done", "Alle Fehler wiederholt. Gut gemacht!"),
+    ("stepper.prev", "Zurück"),
+    ("stepper.next", "Weiter"),
+    ("stepper.reset", "Neustart"),
+    ("stepper.step", "Schritt"),
+    ("stepper.play", "Abspielen"),
+    ("stepper.pause", "Pause"),
+    ("stepper.seconds", "s pro Schritt"),
+    ("tree.zoom_in", "Vergrößern"),
+    ("tree.zoom_out", "Verkleinern"),
+    ("tree.zoom_reset", "Zurück auf 100 %"),
+    ("tree.pan_hint", "Ziehen zum Verschieben, Zwei-Finger-Geste oder Strg+Mausrad zum Zoomen."),
+    ("selector.title", "Lern-Notebooks"),
+    (
+        "selector.intro",
+        "Wähle ein Kapitel. Ein Haken heißt: alle Aufgaben sind gelöst.",
+    ),
+    (
+        "selector.keys",
+        "Tasten: Bild auf / Bild ab wechseln das Kapitel, Tab springt zwischen Knöpfen, \
+         Pfeile gehen durch eine Karte, 1-9 wählen eine Antwort.",
+    ),
+    ("selector.search", "Suche"),
+    ("selector.nothing_found", "Nichts gefunden."),
+    (
+        "selector.solved",
+        "Gelöste Aufgaben in diesem Kapitel: {percent}%",
+    ),
+    ("card.collapse", "Klicken, um diese Karte einzuklappen."),
+    ("card.expand", "Klicken, um diese Karte wieder zu öffnen."),
+    (
+        "score.summary",
+        "Punkte: {right} richtig, {wrong} falsch. Serie: {streak} (beste {best})",
+    ),
+    ("difficulty.summary", "Übungsstufe {level} von {highest}"),
+    ("selector.reset", "Kapitel zurücksetzen"),
+    (
+        "selector.reset_hover",
+        "Setzt jede Karte dieses Kapitels auf den Anfang. Gelöste Aufgaben bleiben gelöst.",
+    ),
+    ("selector.dashboard", "Übersichtstafel"),
+    ("nav.previous", "\u{2190} Zurück"),
+    ("nav.next", "Weiter \u{2192}"),
+    ("projector.toggle", "Präsentationsmodus"),
+    (
+        "projector.on",
+        "Großdruck ist an. Pfeil hoch und Pfeil runter springen von Karte zu Karte, \
+         Bild auf und Bild ab blättern eine Bildschirmseite, Escape beendet die Präsentation.",
+    ),
+    ("settings.title", "Einstellungen"),
+    ("settings.language", "Sprache"),
+    ("settings.theme", "Farbschema"),
+    ("settings.light", "Hell"),
+    ("settings.dark", "Dunkel"),
+    ("settings.text_size", "Schriftgröße"),
+    ("settings.animations", "Animationen"),
+    ("settings.speech_rate", "Vorlesetempo"),
+    ("settings.slow", "Langsam"),
+    ("settings.normal", "Normal"),
+    ("settings.fast", "Schnell"),
+    (
+        "settings.colorblind_safe",
+        "Hervorhebungen für Farbenblinde",
+    ),
+    ("settings.high_contrast", "Hoher Kontrast"),
+    ("settings.dyslexia_font", "Schrift für Legastheniker"),
+    (
+        "settings.dyslexia_font_missing",
+        "Die OpenDyslexic-Dateien fehlen in assets/fonts.",
+    ),
+    ("settings.hints", "Hinweise nach falschen Antworten"),
+    ("hint.nudge", "Hinweis"),
+    ("hint.region", "Eingrenzen"),
+    ("hint.reveal", "Schritt zeigen"),
+    ("hint.used_up", "Keine Hinweise mehr"),
+    ("hint.prefix", "Hinweis:"),
+    ("worked.show", "Lösungsweg zeigen"),
+    (
+        "patterns.add_before_multiply",
+        "Du addierst oder subtrahierst oft vor dem Multiplizieren. Das *, / oder % darin kommt zuerst dran.",
+    ),
+    (
+        "patterns.or_before_and",
+        "Du nimmst oft or vor dem and darin. Rechne zuerst den inneren Kasten aus.",
+    ),
+    (
+        "patterns.outer_first",
+        "Du nimmst oft einen äußeren Kasten vor den Kästen darin. Arbeite von innen nach außen.",
+    ),
+    (
+        "patterns.right_first",
+        "Du springst oft nach rechts. Wenn zwei Kästen bereit sind, nimm zuerst den linken.",
+    ),
+    ("patterns.revisit", "Nochmal ansehen: \"{card}\""),
+    ("patterns.dismiss", "Verstanden"),
+    ("hint.show_me", "Zeig es mir"),
+    (
+        "hint.show_me_hover",
+        "Zusehen, wie der ganze Baum Schritt für Schritt ausgewertet wird",
+    ),
+    ("supervised.title", "Aufsichtsmodus"),
+    (
+        "supervised.intro",
+        "Wähle eine PIN, um Hinweise, \"Zeig es mir\" und Lösungswege auszublenden, z. B. für einen Test.",
+    ),
+    (
+        "supervised.on",
+        "Der Aufsichtsmodus ist an: Hinweise und Lösungswege sind ausgeblendet.",
+    ),
+    ("supervised.pin", "PIN"),
+    ("supervised.lock", "Sperren"),
+    ("supervised.unlock", "Entsperren"),
+    ("supervised.wrong_pin", "Das ist nicht die PIN."),
+    ("supervised.short_pin", "Nimm mindestens {digits} Ziffern."),
+    ("supervised.locked", "Im Aufsichtsmodus gesperrt"),
+    ("settings.timed_challenges", "Aufgaben auf Zeit"),
+    ("settings.reduced_motion", "Weniger Bewegung"),
+    ("settings.language_scope", "Die Kapiteltexte und Aufgaben sind noch auf Englisch. Übersetzt sind die Bedienelemente und die gemeinsamen Rückmeldungen."),
+    ("keyboard.pick", "Die Tasten 1-{count} wählen eine Antwort."),
+    ("steps.tree_view", "Baumansicht:"),
+    ("steps.looked_up", "Der markierte Name wird durch seinen Wert aus der Liste oben ersetzt."),
+    ("steps.next", "Den markierten Teil kannst du als Nächstes ausrechnen."),
+    ("steps.done", "Fertig ausgerechnet."),
+    ("stack.title", "Stapel, oberster Wert zuerst:"),
+    ("stack.empty", "(leer)"),
+    ("stack.empty_error", "Der Stapel ist leer."),
+    ("stack.too_short", "Auf dem Stapel liegen weniger als zwei Werte."),
+    ("postfix.start", "Der Stapel ist anfangs leer. Lies die Zeichen von links nach rechts."),
+    ("postfix.value", "Ein Wert: Lege {value} auf den Stapel."),
+    ("postfix.load", "Schlage `{name}` nach und lege seinen Wert {value} auf den Stapel."),
+    ("postfix.unary", "Nimm {value} herunter, wende `{op}` an und lege {result} auf den Stapel."),
+    ("postfix.binary", "Nimm {right} und {left} herunter, rechne {left} {op} {right} aus und lege {result} auf den Stapel."),
+    ("postfix.result", "Ein Wert bleibt auf dem Stapel: Das ist der Wert des Ausdrucks."),
+    ("program.line", "Alle Zeilen vor {line} sind schon gelaufen. Zeile {line} macht den markierten Schritt."),
+    ("program.done", "Alle Zeilen sind gelaufen, und der Wert des Ausdrucks liegt auf dem Stapel."),
+    ("tree_practice.new", "Neuer Baum"),
+    ("tree_practice.keys", "Oder nimm die Tasten: Die Pfeile bewegen den Ring, Enter rechnet seinen Kasten aus."),
+    ("tree_practice.oops", "Hoppla: {error}"),
+    ("tree_practice.not_yet", "Noch nicht. Arbeite von links nach rechts; gibt es keinen tieferen Ausdruck, geh eine Ebene höher."),
+    ("tree_practice.watched", "So geht es: Der Baum endet bei {value}."),
+    ("tree_practice.try_own", "Drücke jetzt Neuer Baum und versuch einen selbst."),
+    ("tree_practice.done", "Fertig! Wert = {value}."),
+    ("tree_practice.nudge_numbers", "Finde den tiefsten Operator, dessen beide Seiten schon Zahlen sind. Gibt es mehrere, fang mit dem linken an."),
+    ("tree_practice.nudge_booleans", "Finde den tiefsten Operator, dessen Seiten schon true oder false sind. Gibt es mehrere, fang mit dem linken an."),
+    ("tree_practice.constant", "Konstanten haben schon einen Wert."),
+    ("tree_practice.boolean", "Wahrheitswerte haben schon einen Wert."),
+    ("booleans.substitute", "Zuerst wird jeder markierte Name durch seinen Wert von den Schaltern ersetzt:"),
+    ("memory.owners", "Besitzer"),
+    ("memory.heap", "Werte auf dem Heap"),
+    ("memory.borrows", "Ausleihen"),
+    ("memory.no_owners", "(keine Besitzer)"),
+    ("memory.moved", "{name} (verschoben)"),
+    ("tree.diagram", "Baumdiagramm mit {count} Kästen"),
+    ("tree.node", "Knoten {name}"),
+    ("tree.child", "Kind {child}"),
+    ("tree.children", "Kinder {rest} und {last}"),
+    ("tree.highlighted", "hervorgehoben"),
+    ("tree.selected", "ausgewählt"),
+    ("tree.skipped", "übersprungen"),
+    ("tree.add", "addieren"),
+    ("tree.negate", "negieren"),
+    ("tree.subtract", "subtrahieren"),
+    ("tree.multiply", "multiplizieren"),
+    ("tree.divide", "dividieren"),
+    ("tree.remainder", "Rest"),
+    ("tree.power", "potenzieren"),
+    ("flowchart.start", "Start"),
+    ("flowchart.question", "Frage: {label}"),
+    ("flowchart.step", "Schritt: {label}"),
+    ("flowchart.on_path", "auf dem genommenen Weg"),
+    ("flowchart.arrow", "Pfeil von {from} nach {to}, {taken}"),
+    ("flowchart.nowhere", "nirgendwo"),
+    ("flowchart.taken", "genommen"),
+    ("flowchart.not_taken", "nicht genommen"),
+    ("celebrate.quiz", "Test geschafft, alles richtig!"),
+    ("celebrate.streak", "{streak} richtig in Folge!"),
+    ("settings.reset_all", "Gesamten Fortschritt löschen"),
+    (
+        "settings.reset_all_warning",
+        "Das löscht alle Aufgaben, Antworten und Haken in allen Kapiteln. \
+         Es lässt sich nicht rückgängig machen.",
+    ),
+    ("settings.reset_all_confirm", "Alles löschen"),
+    ("settings.cancel", "Abbrechen"),
+    ("backup.title", "Sicherung"),
+    ("classroom.title", "Klassenraum"),
+    (
+        "classroom.intro",
+        "Ein Computer unterrichtet, die anderen folgen: Sie öffnen das Kapitel der Lehrkraft und bekommen dieselben Aufgaben.",
+    ),
+    ("classroom.room", "Raum"),
+    ("classroom.off", "Aus"),
+    ("classroom.teach", "Unterrichten"),
+    ("classroom.follow", "Folgen"),
+    ("classroom.teaching", "Sende an alle, die Raum {room} folgen"),
+    ("classroom.following", "Folge der Lehrkraft"),
+    ("classroom.waiting", "Warte auf die Lehrkraft..."),
+    ("code.copy", "Kopieren"),
+    ("code.copied", "Kopiert"),
+    (
+        "code.copy_hover",
+        "Diesen Code kopieren, z. B. für eine Antwort oder deine Notizen",
+    ),
+    ("export.image", "Bild exportieren"),
+    (
+        "export.image_hover",
+        "Dieses Diagramm als SVG-Datei im Home-Ordner speichern, z. B. für ein Arbeitsblatt",
+    ),
+    ("export.saved", "Gespeichert unter {path}"),
+    ("handout.export", "Kapitel exportieren"),
+    ("share.code", "Aufgabencode"),
+    (
+        "share.code_hover",
+        "Teile diesen Code: Wer ihn lädt, bekommt genau dieselbe Aufgabe",
+    ),
+    ("share.load", "Aufgabe laden"),
+    (
+        "share.load_hover",
+        "Die Karte zu einem Code wie EXPR-7FK2 mit seiner Aufgabe öffnen",
+    ),
+    ("share.malformed", "{code} ist kein Aufgabencode, z. B. EXPR-7FK2"),
+    ("share.unknown", "Keine Aufgabe beginnt mit dem Code {code}"),
+    (
+        "handout.export_hover",
+        "Text und Diagramme dieses Kapitels als druckbares Markdown-Handout im Home-Ordner speichern",
+    ),
+    ("handout.saved", "Handout gespeichert unter {path}"),
+    ("backup.file", "Datei"),
+    ("backup.export", "Exportieren"),
+    (
+        "backup.export_hover",
+        "Speichert Fortschritt und Einstellungen in der Datei, etwa für einen anderen Rechner.",
+    ),
+    ("backup.import", "Importieren"),
+    (
+        "backup.import_hover",
+        "Ersetzt Fortschritt und Einstellungen durch die aus der Datei.",
+    ),
+    ("backup.exported", "Fortschritt exportiert."),
+    ("backup.imported", "Fortschritt importiert."),
+    ("speech.play", "\u{1F50A} Vorlesen"),
+    ("speech.pause", "Pause"),
+    ("speech.resume", "Weiter"),
+    ("speech.stop", "Stopp"),
+    ("speech.hover", "Liest diese Karte laut vor."),
+    (
+        "speech.missing",
+        "Keine Sprachausgabe gefunden. Mit espeak-ng werden Karten vorgelesen.",
+    ),
+    (
+        "schedule.intro",
+        "Diese Aufgaben sind wieder dran. Richtige Antworten schieben die nächste Wiederholung weiter hinaus.",
+    ),
+    (
+        "schedule.nothing_due",
+        "Heute gibt es nichts zu wiederholen. Gelöste Aufgaben kommen nach einem Tag wieder, dann nach immer längeren Pausen.",
+    ),
+    ("calendar.streak", "Tage in Folge: {days}"),
+    (
+        "calendar.total",
+        "An {practiced} der letzten {days} Tage geübt",
+    ),
+    (
+        "overview.badge",
+        "Abzeichen verdient: jede Aufgabe in diesem Kapitel ist gelöst.",
+    ),
+    (
+        "overview.intro",
+        "# Plan der Lern-Notebooks\n\
+         Ein praktischer Lernpfad für absolute Anfänger.\n\n\
+         Die Reihe ist für Lernende mit wenig oder keinem Mathe-Hintergrund gedacht.\n\
+         Jedes Konzept steht auf einfacher Sprache, konkreten Beispielen und sichtbarer Rückmeldung.",
+    ),
+    (
+        "overview.principles",
+        "## Grundsätze\n\
+         - **Kleine Schritte**: eine neue Idee pro Notebook.\n\
+         - **Die Wirkung sehen**: jedes Konzept hat eine Grafik oder eine interaktive Demo.\n\
+         - **Üben > Vortrag**: kurze Aufgaben nach jeder Demo.\n\
+         - **Selbstvertrauen aufbauen**: erst Richtigkeit feiern, dann den Stil verbessern.\n\
+         - **Wortschatz wächst langsam**: Begriffe einmal erklären und dann wiederverwenden.",
+    ),
+    (
+        "overview.track_a",
+        "Pfad A - Grundlagen des Programmierens (10-12 Notebooks)",
+    ),
+    (
+        "overview.track_b",
+        "## Pfad B - Theoretische Informatik (10-12 Notebooks)\n\
+         1. Mengen, Relationen und Graphen\n\
+         2. Endliche Automaten (DEA)\n\
+         3. Reguläre Ausdrücke als Maschinen\n\
+         4. Kontextfreie Grammatiken\n\
+         5. Syntaxbäume von Hand\n\
+         6. Turingmaschinen (Band + Regeln)\n\
+         7. Das Halteproblem verstehen\n\
+         8. Reduktionen und NP im Überblick\n\
+         9. Warum manche Probleme schwer bleiben\n\
+         10. Mini-Projekt: ein kleiner Parser",
+    ),
+    ("overview.track_c", "Pfad C - Rust (12-15 Notebooks)"),
+    (
+        "overview.widgets",
+        "## Gemeinsame Werkzeuge\n\
+         - Anzeige für Stack und Aufrufe\n\
+         - Speicherkarte (besessen oder geliehen)\n\
+         - Band-Simulator (Turingmaschinen)\n\
+         - Syntaxbaum-Explorer\n\
+         - Schrittweise Algorithmen\n\
+         - Kleiner Code-Runner mit Protokoll",
+    ),
+    (
+        "overview.template",
+        "## Aufbau jeder Lektion\n\
+         1. Kurze Geschichte oder Vergleich aus dem Alltag\n\
+         2. Kleinste Code-Demo\n\
+         3. Interaktives Werkzeug\n\
+         4. Aufgabe (3-5 Minuten)\n\
+         5. Zusammenfassung in einem Absatz",
+    ),
+    (
+        "overview.milestones",
+        "## Meilensteine\n\
+         - **Woche 1**: Variablen, Bedingungen und Schleifen\n\
+         - **Woche 2**: Funktionen, Listen und kleine Projekte\n\
+         - **Woche 3**: Automaten und Parsen verstehen\n\
+         - **Woche 4**: Besitz und Referenzen in Rust\n\
+         - **Woche 5**: gemeinsam ein Mini-Projekt bauen",
+    ),
+    (
+        "overview.pilot",
+        "Wir starten mit fünf Pilot-Notebooks:\n\
+         - **Hallo, Ausdrücke** (Programmieren)\n\
+         - **To Bool or Not to Bool** (Programmieren)\n\
+         - **Hallo, Zustand** (Programmieren)\n\
+         - **DEA-Grundlagen** (Theorie)\n\
+         - **Besitz 101** (Rust)\n\n\
+         Wir testen sie, schärfen die Sprache und bauen dann aus.",
+    ),
+];
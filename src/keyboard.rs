@@ -0,0 +1,142 @@
+use GORBIE::prelude::*;
+
+use crate::expr::{expr_at_path, Expr, Language, PathStep};
+use crate::i18n::tr;
+
+const NUMBER_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+/// Whether shortcuts belong to the card `ui` is drawing: the pointer is over
+/// it or the focused widget sits inside it, and no text field is taking the
+/// keys. Tab moves the focus from card to card.
+pub fn card_active(ui: &egui::Ui) -> bool {
+    if ui.ctx().wants_keyboard_input() {
+        return false;
+    }
+    if ui.response().contains_pointer() {
+        return true;
+    }
+    let rect = ui.max_rect();
+    ui.memory(|memory| memory.focused())
+        .and_then(|id| ui.ctx().read_response(id))
+        .is_some_and(|response| rect.contains_rect(response.rect))
+}
+
+/// Index of the number key 1-9 pressed this frame, counted from zero.
+fn number_pressed(ui: &egui::Ui) -> Option<usize> {
+    ui.input(|input| NUMBER_KEYS.iter().position(|key| input.key_pressed(*key)))
+}
+
+/// A box in an expression tree picked with the arrow keys, so a tree can be
+/// worked out without the mouse.
+#[derive(Default)]
+pub struct TreeCursor {
+    selected: Option<Vec<PathStep>>,
+}
+
+impl TreeCursor {
+    pub fn selected(&self) -> Option<&Vec<PathStep>> {
+        self.selected.as_ref()
+    }
+
+    pub fn reset(&mut self) {
+        self.selected = None;
+    }
+
+    /// Moves the cursor while the card is active: up to the parent, down to
+    /// the first child, left and right between siblings. The first press
+    /// lands on the root. Returns the selected path when Enter is pressed.
+    pub fn keys<L: Language>(&mut self, ui: &egui::Ui, expr: &Expr<L>) -> Option<Vec<PathStep>> {
+        if !card_active(ui) {
+            return None;
+        }
+        let (up, down, left, right, enter) = ui.input(|input| {
+            (
+                input.key_pressed(egui::Key::ArrowUp),
+                input.key_pressed(egui::Key::ArrowDown),
+                input.key_pressed(egui::Key::ArrowLeft),
+                input.key_pressed(egui::Key::ArrowRight),
+                input.key_pressed(egui::Key::Enter),
+            )
+        });
+        // A reduced tree can lose the box the cursor was on.
+        if self
+            .selected
+            .as_ref()
+            .is_some_and(|path| expr_at_path(expr, path).is_none())
+        {
+            self.selected = Some(Vec::new());
+        }
+        let Some(path) = &mut self.selected else {
+            if up || down || left || right {
+                self.selected = Some(Vec::new());
+            }
+            return None;
+        };
+        if up {
+            path.pop();
+        }
+        if down {
+            if let Some((step, _)) =
+                expr_at_path(expr, path).and_then(|node| node.children().into_iter().next())
+            {
+                path.push(step);
+            }
+        }
+        if left && path.last() == Some(&PathStep::Right) {
+            *path.last_mut().expect("path has a last step") = PathStep::Left;
+        }
+        if right && path.last() == Some(&PathStep::Left) {
+            *path.last_mut().expect("path has a last step") = PathStep::Right;
+        }
+        enter.then(|| path.clone())
+    }
+}
+
+/// A small [`widgets::ChoiceToggle`] for answers that can also be picked
+/// with the number keys while its card is active.
+pub struct AnswerToggle<'a, T> {
+    selection: &'a mut T,
+    choices: Vec<(T, egui::WidgetText)>,
+}
+
+impl<'a, T: PartialEq + Clone> AnswerToggle<'a, T> {
+    pub fn new(selection: &'a mut T) -> Self {
+        Self {
+            selection,
+            choices: Vec::new(),
+        }
+    }
+
+    pub fn choice(mut self, value: T, label: impl Into<egui::WidgetText>) -> Self {
+        self.choices.push((value, label.into()));
+        self
+    }
+}
+
+impl<T: PartialEq + Clone> egui::Widget for AnswerToggle<'_, T> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let Self { selection, choices } = self;
+        if ui.is_enabled() && card_active(ui) {
+            if let Some((value, _)) = number_pressed(ui).and_then(|index| choices.get(index)) {
+                *selection = value.clone();
+            }
+        }
+        let count = choices.len().min(NUMBER_KEYS.len());
+        let mut toggle = widgets::ChoiceToggle::new(selection).small();
+        for (value, label) in choices {
+            toggle = toggle.choice(value, label);
+        }
+        ui.add(toggle)
+            .on_hover_text(tr("keyboard.pick").replace("{count}", &count.to_string()))
+    }
+}
@@ -1,20 +1,120 @@
+use std::sync::Once;
+
 use GORBIE::prelude::*;
 
+mod backup;
+mod celebrate;
 mod chapters;
+mod classroom;
+mod codeview;
+mod collapsed;
+mod coop;
+mod difficulty;
+mod exercise;
+mod expr;
 mod flowchart;
+mod fonts;
+mod glossary;
+mod handout;
+mod hints;
+mod i18n;
+mod keyboard;
+mod mastery;
+mod memory_map;
+mod palette;
+mod patterns;
+mod presentation;
+mod progress;
+mod review;
+mod rng;
+mod schedule;
+mod score;
+mod search;
+mod self_check;
+mod session;
+mod settings;
+mod share;
+mod speech;
+mod stats;
+mod stepper;
+mod storage;
+mod supervised;
+mod svg;
+mod tree_practice;
+mod tree_view;
+mod worksheet;
+
+/// The value of `--chapter <id>` or `--chapter=<id>`, e.g. `loops` or
+/// `expressions#tree_practice`.
+fn start_target() -> Option<String> {
+    let mut args = std::env::args();
+    while let Some(arg) = args.next() {
+        if arg == "--chapter" {
+            return args.next();
+        }
+        if let Some(target) = arg.strip_prefix("--chapter=") {
+            return Some(target.to_string());
+        }
+    }
+    None
+}
+
+/// The chapter id and exercise count after `--print-worksheet`. Missing
+/// values come back empty and are reported when the worksheet is printed.
+fn worksheet_args() -> Option<(String, String)> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--print-worksheet")?;
+    let value = |offset: usize| args.get(index + offset).cloned().unwrap_or_default();
+    Some((value(1), value(2)))
+}
+
+/// The number of rounds after `--self-check`, empty for the default.
+fn self_check_args() -> Option<String> {
+    let args: Vec<String> = std::env::args().collect();
+    let index = args.iter().position(|arg| arg == "--self-check")?;
+    Some(
+        args.get(index + 1)
+            .filter(|rounds| !rounds.starts_with("--"))
+            .cloned()
+            .unwrap_or_default(),
+    )
+}
+
+/// Leaves with exit code 0 or 1, printing the error of a failed run.
+fn exit_with(result: Result<(), String>) -> ! {
+    let code = match result {
+        Ok(()) => 0,
+        Err(error) => {
+            eprintln!("{error}");
+            1
+        }
+    };
+    std::process::exit(code);
+}
 
 #[notebook]
 fn main(nb: &mut NotebookCtx) {
+    static START: Once = Once::new();
+    START.call_once(|| {
+        // Both print to the terminal and leave before the first frame is
+        // drawn.
+        if let Some((chapter, count)) = worksheet_args() {
+            exit_with(worksheet::print(&chapter, &count));
+        }
+        if let Some(rounds) = self_check_args() {
+            exit_with(self_check::run(&rounds));
+        }
+        if let Some(target) = start_target() {
+            if let Err(error) = chapters::open_target(&target) {
+                eprintln!("{error}");
+            }
+        }
+    });
+
     let selection = chapters::current_chapter();
     chapters::chapter_selector(nb);
 
-    match selection {
-        chapters::Chapter::Overview => chapters::overview(nb),
-        chapters::Chapter::Expressions => chapters::expressions(nb),
-        chapters::Chapter::Booleans => chapters::booleans(nb),
-        chapters::Chapter::State => chapters::state(nb),
-        chapters::Chapter::IfElse => chapters::if_else(nb),
-        chapters::Chapter::Loops => chapters::loops(nb),
-        chapters::Chapter::Functions => chapters::functions(nb),
-    }
+    (chapters::def(selection).render)(nb);
+    glossary::chapter_words(nb, selection);
+    chapters::chapter_nav(nb);
 }
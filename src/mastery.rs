@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use GORBIE::prelude::*;
+
+use crate::chapters::{self, Chapter};
+use crate::expr::{Expr, Language};
+use crate::i18n::tr;
+use crate::storage;
+
+const FILE: &str = "mastery.txt";
+/// Exercises solved at a tier before the meter counts it as mastered.
+const NEEDED: u32 = 5;
+
+/// The tiers of the concept a chapter practices, easiest first, as
+/// translation keys for their names.
+const CONCEPTS: &[(Chapter, &[&str])] = &[
+    (
+        Chapter::Expressions,
+        &["mastery.shallow", "mastery.deep", "mastery.negatives"],
+    ),
+    (
+        Chapter::Booleans,
+        &["mastery.shallow", "mastery.deep", "mastery.not"],
+    ),
+];
+
+/// Solved exercises per chapter id and tier.
+type Counts = BTreeMap<(String, usize), u32>;
+
+static MASTERY: OnceLock<RwLock<Counts>> = OnceLock::new();
+
+fn mastery_lock() -> &'static RwLock<Counts> {
+    MASTERY.get_or_init(|| RwLock::new(load()))
+}
+
+/// One `chapter<TAB>tier<TAB>solved` line per tier practiced.
+fn load() -> Counts {
+    let mut counts = Counts::new();
+    let Some(text) = storage::load(FILE) else {
+        return counts;
+    };
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        let (Some(chapter), Some(Ok(tier)), Some(Ok(solved))) = (
+            fields.next(),
+            fields.next().map(str::parse),
+            fields.next().map(str::parse),
+        ) else {
+            continue;
+        };
+        counts.insert((chapter.to_string(), tier), solved);
+    }
+    counts
+}
+
+fn save(counts: &Counts) -> Result<(), String> {
+    let mut text = String::new();
+    for ((chapter, tier), solved) in counts {
+        text.push_str(&format!("{chapter}\t{tier}\t{solved}\n"));
+    }
+    storage::save(FILE, &text)
+}
+
+fn tiers(chapter: Chapter) -> Option<&'static [&'static str]> {
+    CONCEPTS
+        .iter()
+        .find(|(concept, _)| *concept == chapter)
+        .map(|(_, tiers)| *tiers)
+}
+
+/// The tier an expression tree trains: trees up to two operators deep,
+/// deeper ones, and any with a unary operator, like `-` or `not`.
+pub fn tree_tier<L: Language>(expr: &Expr<L>) -> usize {
+    if expr.has_unary() {
+        2
+    } else if expr.depth() >= 3 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Counts one exercise solved at `tier` of the chapter's concept. Called
+/// once per exercise, when it is solved.
+pub fn solved(chapter: Chapter, tier: usize) {
+    if tiers(chapter).is_none_or(|tiers| tier >= tiers.len()) {
+        return;
+    }
+    let mut counts = mastery_lock().write().expect("mastery lock poisoned");
+    *counts
+        .entry((chapters::def(chapter).id.to_string(), tier))
+        .or_default() += 1;
+    if let Err(error) = save(&counts) {
+        eprintln!("Could not save mastery: {error}");
+    }
+}
+
+/// Forgets every solved exercise, together with the progress.
+pub fn clear() {
+    let mut counts = mastery_lock().write().expect("mastery lock poisoned");
+    counts.clear();
+    if let Err(error) = save(&counts) {
+        eprintln!("Could not save mastery: {error}");
+    }
+}
+
+/// Replaces the counts with what is saved, after a backup was imported.
+pub fn reload() {
+    *mastery_lock().write().expect("mastery lock poisoned") = load();
+}
+
+/// The mastery meter of the chapter's concept: one bar filling up over
+/// all tiers, and how far each tier is. Shows nothing for chapters without
+/// tiers.
+pub fn meter(ui: &mut egui::Ui, chapter: Chapter) {
+    let Some(tiers) = tiers(chapter) else {
+        return;
+    };
+    let counts = mastery_lock().read().expect("mastery lock poisoned");
+    let id = chapters::def(chapter).id;
+    let solved: Vec<u32> = (0..tiers.len())
+        .map(|tier| {
+            counts
+                .get(&(id.to_string(), tier))
+                .copied()
+                .unwrap_or(0)
+                .min(NEEDED)
+        })
+        .collect();
+    let mastered = solved
+        .iter()
+        .take_while(|solved| **solved >= NEEDED)
+        .count();
+    let filled = solved.iter().sum::<u32>() as f32 / (NEEDED as usize * tiers.len()) as f32;
+
+    ui.label(egui::RichText::new(tr("mastery.title")).strong());
+    ui.add(
+        widgets::ProgressBar::new(filled).text(
+            tr("mastery.level")
+                .replace("{mastered}", &mastered.to_string())
+                .replace("{tiers}", &tiers.len().to_string()),
+        ),
+    );
+    for (name, solved) in tiers.iter().zip(&solved) {
+        let mark = if *solved >= NEEDED {
+            "\u{2714}"
+        } else {
+            "\u{25CB}"
+        };
+        ui.label(
+            egui::RichText::new(format!("{mark} {} {solved}/{NEEDED}", tr(name)))
+                .small()
+                .weak(),
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use egui::TextStyle;
+
+use crate::i18n::tr;
+use crate::palette::Palette;
+
+/// Where an owner keeps its value.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Slot {
+    /// Right in the owner's box, like a number.
+    Inline(&'static str),
+    /// On the heap, drawn in its own box with an arrow from the owner.
+    Owns(&'static str),
+    Moved,
+}
+
+pub struct Owner {
+    pub name: &'static str,
+    pub slot: Slot,
+}
+
+pub const fn inline(name: &'static str, value: &'static str) -> Owner {
+    Owner {
+        name,
+        slot: Slot::Inline(value),
+    }
+}
+
+pub const fn owns(name: &'static str, value: &'static str) -> Owner {
+    Owner {
+        name,
+        slot: Slot::Owns(value),
+    }
+}
+
+pub const fn moved(name: &'static str) -> Owner {
+    Owner {
+        name,
+        slot: Slot::Moved,
+    }
+}
+
+/// A reference `name` to the owner `target`.
+pub struct Borrow {
+    pub name: &'static str,
+    pub target: &'static str,
+    pub exclusive: bool,
+}
+
+pub const fn shared(name: &'static str, target: &'static str) -> Borrow {
+    Borrow {
+        name,
+        target,
+        exclusive: false,
+    }
+}
+
+pub const fn exclusive(name: &'static str, target: &'static str) -> Borrow {
+    Borrow {
+        name,
+        target,
+        exclusive: true,
+    }
+}
+
+/// Draws the owners on the left. The right column holds the heap values
+/// they own, or, when there are `borrows`, the borrows with arrows back to
+/// their owners; maps with borrows keep their values inline. Owners that
+/// changed since `previous` are outlined in the accent color.
+pub fn paint(ui: &mut egui::Ui, owners: &[Owner], previous: Option<&[Owner]>, borrows: &[Borrow]) {
+    let font_id = TextStyle::Monospace.resolve(ui.style());
+    let row_height = ui.fonts_mut(|fonts| fonts.row_height(&font_id)).max(1.0);
+    let box_h = row_height + 12.0;
+    let row_gap = 10.0;
+    let header_h = row_height + 8.0;
+    let width = ui.available_width().max(280.0);
+    let rows = owners.len().max(borrows.len()).max(1);
+    let height = header_h + rows as f32 * (box_h + row_gap);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+
+    let palette = Palette::from_ui(ui);
+    let highlight_color = palette.highlight;
+    let text_color = palette.text;
+    let weak_color = palette.weak_text;
+    let line_stroke = palette.line;
+    let fill = palette.code_fill;
+    let column_w = (width * 0.36).min(220.0);
+    let owner_x = rect.left();
+    let right_x = rect.right() - column_w;
+
+    painter.text(
+        egui::pos2(owner_x, rect.top()),
+        egui::Align2::LEFT_TOP,
+        tr("memory.owners"),
+        font_id.clone(),
+        weak_color,
+    );
+    painter.text(
+        egui::pos2(right_x, rect.top()),
+        egui::Align2::LEFT_TOP,
+        if borrows.is_empty() {
+            tr("memory.heap")
+        } else {
+            tr("memory.borrows")
+        },
+        font_id.clone(),
+        weak_color,
+    );
+
+    if owners.is_empty() {
+        painter.text(
+            egui::pos2(owner_x, rect.top() + header_h),
+            egui::Align2::LEFT_TOP,
+            tr("memory.no_owners"),
+            font_id,
+            weak_color,
+        );
+        return;
+    }
+
+    let row_rect = |x: f32, index: usize| {
+        let top = rect.top() + header_h + index as f32 * (box_h + row_gap);
+        egui::Rect::from_min_size(egui::pos2(x, top), egui::vec2(column_w, box_h))
+    };
+
+    for (index, owner) in owners.iter().enumerate() {
+        let owner_rect = row_rect(owner_x, index);
+        let changed = previous.is_some_and(|previous| {
+            previous
+                .iter()
+                .find(|before| before.name == owner.name)
+                .is_none_or(|before| before.slot != owner.slot)
+        });
+        let stroke = if changed {
+            egui::Stroke::new(line_stroke.width.max(1.0) * 2.0, highlight_color)
+        } else {
+            line_stroke
+        };
+        painter.rect(
+            owner_rect,
+            egui::CornerRadius::same(4),
+            fill,
+            stroke,
+            egui::StrokeKind::Inside,
+        );
+
+        let (label, color) = match owner.slot {
+            Slot::Inline(value) => (format!("{} = {value}", owner.name), text_color),
+            Slot::Owns(_) => (owner.name.to_string(), text_color),
+            Slot::Moved => (tr("memory.moved").replace("{name}", owner.name), weak_color),
+        };
+        painter.text(
+            owner_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            label,
+            font_id.clone(),
+            color,
+        );
+
+        if let Slot::Owns(value) = owner.slot {
+            let value_rect = row_rect(right_x, index);
+            painter.rect(
+                value_rect,
+                egui::CornerRadius::same(4),
+                fill,
+                line_stroke,
+                egui::StrokeKind::Inside,
+            );
+            painter.text(
+                value_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                value,
+                font_id.clone(),
+                text_color,
+            );
+            let arrow_color = if changed {
+                highlight_color
+            } else {
+                line_stroke.color
+            };
+            let start = owner_rect.right_center() + egui::vec2(4.0, 0.0);
+            let end = value_rect.left_center() - egui::vec2(4.0, 0.0);
+            painter.arrow(start, end - start, egui::Stroke::new(2.0, arrow_color));
+        }
+    }
+
+    for (index, borrow) in borrows.iter().enumerate() {
+        let borrow_rect = row_rect(right_x, index);
+        let (label, stroke) = if borrow.exclusive {
+            (
+                format!("{}: &mut {}", borrow.name, borrow.target),
+                egui::Stroke::new(3.0, highlight_color),
+            )
+        } else {
+            (
+                format!("{}: &{}", borrow.name, borrow.target),
+                egui::Stroke::new(1.5, line_stroke.color),
+            )
+        };
+        painter.rect(
+            borrow_rect,
+            egui::CornerRadius::same(4),
+            fill,
+            stroke,
+            egui::StrokeKind::Inside,
+        );
+        painter.text(
+            borrow_rect.center(),
+            egui::Align2::CENTER_CENTER,
+            label,
+            font_id.clone(),
+            text_color,
+        );
+
+        let Some(target) = owners.iter().position(|owner| owner.name == borrow.target) else {
+            continue;
+        };
+        let target_rect = row_rect(owner_x, target);
+        let start = borrow_rect.left_center() - egui::vec2(4.0, 0.0);
+        let end = target_rect.right_center() + egui::vec2(4.0, 0.0);
+        painter.arrow(start, end - start, stroke);
+    }
+}
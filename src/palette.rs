@@ -0,0 +1,75 @@
+use egui::{Color32, Stroke};
+use GORBIE::themes;
+
+use crate::presentation;
+use crate::settings;
+
+/// Colors for code frames and painted diagrams. Everything is read from the
+/// current egui visuals, so diagrams follow the light or dark theme.
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    /// Marks the active step, the selected node or the changed value.
+    pub highlight: Color32,
+    pub text: Color32,
+    pub weak_text: Color32,
+    /// Outline of boxes and nodes.
+    pub line: Stroke,
+    /// Background of code frames and diagram boxes.
+    pub code_fill: Color32,
+    /// Diagrams should mark highlights with line width and dashes as well
+    /// as color.
+    pub shape_cues: bool,
+    /// Fills stay solid instead of being tinted, and lines get thicker.
+    pub high_contrast: bool,
+}
+
+impl Palette {
+    pub fn from_ui(ui: &egui::Ui) -> Self {
+        let visuals = ui.visuals();
+        let settings = settings::get();
+        let colorblind_safe = settings.colorblind_safe;
+        // Traffic orange is too dark against dark backgrounds, so it is
+        // lightened there. The colorblind-safe blue and sky blue come from
+        // the Okabe-Ito palette.
+        let highlight = match (colorblind_safe, visuals.dark_mode) {
+            (false, false) => themes::ral(2009),
+            (false, true) => themes::blend(themes::ral(2009), Color32::WHITE, 0.3),
+            (true, false) => Color32::from_rgb(0, 114, 178),
+            (true, true) => Color32::from_rgb(86, 180, 233),
+        };
+        Self {
+            highlight,
+            text: visuals.text_color(),
+            weak_text: if settings.high_contrast {
+                visuals.text_color()
+            } else {
+                visuals.weak_text_color()
+            },
+            line: visuals.widgets.inactive.bg_stroke,
+            code_fill: visuals.code_bg_color,
+            shape_cues: colorblind_safe,
+            high_contrast: settings.high_contrast,
+        }
+    }
+
+    /// The code background tinted towards the highlight, for selected boxes.
+    /// High contrast keeps the plain background; the highlight outline
+    /// marks the box instead.
+    pub fn tint(&self, amount: f32) -> Color32 {
+        if self.high_contrast {
+            self.code_fill
+        } else {
+            themes::blend(self.code_fill, self.highlight, amount)
+        }
+    }
+
+    /// Width for diagram lines, thicker in projector and high-contrast mode.
+    pub fn stroke_width(&self, width: f32) -> f32 {
+        let width = presentation::stroke_width(width);
+        if self.high_contrast {
+            width * 2.0
+        } else {
+            width
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use GORBIE::prelude::*;
+
+use crate::chapters::{self, Chapter};
+use crate::expr::{expr_at_path, is_reducible, Expr, Language, PathStep};
+use crate::i18n::tr;
+use crate::storage;
+
+const FILE: &str = "patterns.txt";
+/// Wrong clicks with the same pattern before the tip shows. One slip is
+/// not a habit.
+const TIP_AFTER: u32 = 3;
+
+/// A habit that wrong clicks in tree practice give away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Pattern {
+    /// `+` or `-` picked while a `*`, `/` or `%` below it was not worked
+    /// out yet.
+    AddBeforeMultiply,
+    /// `or` picked while an `and` below it was not worked out yet.
+    OrBeforeAnd,
+    /// Any other operator picked before the boxes inside it.
+    OuterFirst,
+    /// A box that was ready, but to the right of the one to take first.
+    RightFirst,
+}
+
+impl Pattern {
+    const ALL: [Pattern; 4] = [
+        Pattern::AddBeforeMultiply,
+        Pattern::OrBeforeAnd,
+        Pattern::OuterFirst,
+        Pattern::RightFirst,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Pattern::AddBeforeMultiply => "add_before_multiply",
+            Pattern::OrBeforeAnd => "or_before_and",
+            Pattern::OuterFirst => "outer_first",
+            Pattern::RightFirst => "right_first",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|pattern| pattern.name() == text)
+    }
+
+    fn tip(self) -> &'static str {
+        match self {
+            Pattern::AddBeforeMultiply => tr("patterns.add_before_multiply"),
+            Pattern::OrBeforeAnd => tr("patterns.or_before_and"),
+            Pattern::OuterFirst => tr("patterns.outer_first"),
+            Pattern::RightFirst => tr("patterns.right_first"),
+        }
+    }
+}
+
+/// Wrong clicks per chapter id and pattern.
+type Counts = BTreeMap<(String, Pattern), u32>;
+
+static PATTERNS: OnceLock<RwLock<Counts>> = OnceLock::new();
+
+fn patterns_lock() -> &'static RwLock<Counts> {
+    PATTERNS.get_or_init(|| RwLock::new(load()))
+}
+
+/// One `chapter<TAB>pattern<TAB>count` line per habit seen.
+fn load() -> Counts {
+    let mut counts = Counts::new();
+    let Some(text) = storage::load(FILE) else {
+        return counts;
+    };
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        let (Some(chapter), Some(Some(pattern)), Some(Ok(count))) = (
+            fields.next(),
+            fields.next().map(Pattern::parse),
+            fields.next().map(str::parse),
+        ) else {
+            continue;
+        };
+        counts.insert((chapter.to_string(), pattern), count);
+    }
+    counts
+}
+
+fn update(change: impl FnOnce(&mut Counts)) {
+    let mut counts = patterns_lock().write().expect("patterns lock poisoned");
+    change(&mut counts);
+    let mut text = String::new();
+    for ((chapter, pattern), count) in counts.iter() {
+        text.push_str(&format!("{chapter}\t{}\t{count}\n", pattern.name()));
+    }
+    if let Err(error) = storage::save(FILE, &text) {
+        eprintln!("Could not save mistake patterns: {error}");
+    }
+}
+
+/// Whether an operator labelled `label` sits somewhere below `expr`.
+fn has_below<L: Language>(expr: &Expr<L>, label: &str) -> bool {
+    expr.children()
+        .into_iter()
+        .any(|(_, child)| !child.is_value() && (child.label() == label || has_below(child, label)))
+}
+
+/// What a click on the box at `clicked` says, when the box to take was at
+/// `next`. Clicks on values or on the right box say nothing.
+pub fn classify<L: Language>(
+    expr: &Expr<L>,
+    clicked: &[PathStep],
+    next: &[PathStep],
+) -> Option<Pattern> {
+    let node = expr_at_path(expr, clicked)?;
+    if node.is_value() || clicked == next {
+        return None;
+    }
+    if is_reducible(node) {
+        return Some(Pattern::RightFirst);
+    }
+    let binary = matches!(node, Expr::Binary(..));
+    let label = node.label();
+    Some(match label.as_str() {
+        "+" | "-" if binary && ["*", "/", "%"].iter().any(|op| has_below(node, op)) => {
+            Pattern::AddBeforeMultiply
+        }
+        "or" if has_below(node, "and") => Pattern::OrBeforeAnd,
+        _ => Pattern::OuterFirst,
+    })
+}
+
+/// Counts a wrong click with `pattern` in the chapter.
+pub fn record(chapter: Chapter, pattern: Pattern) {
+    let id = chapters::def(chapter).id.to_string();
+    update(|counts| *counts.entry((id, pattern)).or_default() += 1);
+}
+
+pub fn clear() {
+    update(Counts::clear);
+}
+
+/// Reads the counts from an imported backup.
+pub fn reload() {
+    *patterns_lock().write().expect("patterns lock poisoned") = load();
+}
+
+/// The habit seen most often in the chapter, once it was seen enough.
+fn strongest(chapter: Chapter) -> Option<Pattern> {
+    let id = chapters::def(chapter).id;
+    let counts = patterns_lock().read().expect("patterns lock poisoned");
+    counts
+        .iter()
+        .filter(|((chapter, _), count)| chapter == id && **count >= TIP_AFTER)
+        .max_by_key(|(_, count)| **count)
+        .map(|((_, pattern), _)| *pattern)
+}
+
+/// A framed tip for the chapter's strongest habit, with a link back to the
+/// card `lesson` that explains the rule and a button to put it away.
+pub fn tip(ui: &mut egui::Ui, chapter: Chapter, lesson: &str) {
+    let Some(pattern) = strongest(chapter) else {
+        return;
+    };
+    ui.add_space(6.0);
+    ui.group(|ui| {
+        ui.label(egui::RichText::new(pattern.tip()).strong());
+        ui.horizontal(|ui| {
+            if ui
+                .link(tr("patterns.revisit").replace("{card}", lesson))
+                .clicked()
+            {
+                let target = format!(
+                    "{}#{}",
+                    chapters::def(chapter).id,
+                    chapters::card_slug(lesson)
+                );
+                if let Err(error) = chapters::open_target(&target) {
+                    eprintln!("{error}");
+                }
+            }
+            if ui
+                .add(widgets::Button::new(tr("patterns.dismiss")).small())
+                .clicked()
+            {
+                let id = chapters::def(chapter).id.to_string();
+                update(|counts| {
+                    counts.remove(&(id, pattern));
+                });
+            }
+        });
+    });
+}
@@ -0,0 +1,114 @@
+use std::sync::{OnceLock, RwLock};
+
+use GORBIE::prelude::*;
+
+use crate::i18n::tr;
+
+const ZOOM: f32 = 1.6;
+const STROKE_SCALE: f32 = 2.5;
+/// Room left above a card when jumping to it, so its frame stays visible.
+const CARD_MARGIN: f32 = 12.0;
+
+static PRESENTATION: OnceLock<RwLock<bool>> = OnceLock::new();
+/// Distance from the top of the screen to each card seen in the last frame,
+/// in page order.
+static CARDS: OnceLock<RwLock<Vec<f32>>> = OnceLock::new();
+
+fn presentation_lock() -> &'static RwLock<bool> {
+    PRESENTATION.get_or_init(|| RwLock::new(false))
+}
+
+fn cards_lock() -> &'static RwLock<Vec<f32>> {
+    CARDS.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Whether the large-print projector mode is switched on.
+pub fn enabled() -> bool {
+    *presentation_lock().read().expect("presentation lock poisoned")
+}
+
+fn set_enabled(ctx: &egui::Context, enabled: bool) {
+    *presentation_lock().write().expect("presentation lock poisoned") = enabled;
+    ctx.set_zoom_factor(if enabled { ZOOM } else { 1.0 });
+    ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(enabled));
+}
+
+/// Marks the top of a card as a stop for the arrow keys. Called from card
+/// headings and lesson cards while they are drawn.
+pub fn card_stop(ui: &egui::Ui) {
+    if enabled() {
+        let offset = ui.min_rect().top() - ui.clip_rect().top();
+        cards_lock()
+            .write()
+            .expect("cards lock poisoned")
+            .push(offset);
+    }
+}
+
+/// How far to scroll so the card after (or before) the one at the top of
+/// the screen lines up with the top.
+fn card_jump(cards: &[f32], forward: bool) -> Option<f32> {
+    let mut stops = cards.iter().map(|offset| offset - CARD_MARGIN);
+    if forward {
+        stops.find(|offset| *offset > 1.0)
+    } else {
+        stops.rev().find(|offset| *offset < -1.0)
+    }
+}
+
+/// Scales a stroke width so lines stay readable from the back of a room.
+pub fn stroke_width(width: f32) -> f32 {
+    if enabled() {
+        width * STROKE_SCALE
+    } else {
+        width
+    }
+}
+
+/// Hints and other helpers are hidden while projecting to a class.
+pub fn show_secondary() -> bool {
+    !enabled()
+}
+
+/// Toggle plus keyboard paging: the arrow keys jump from card to card,
+/// Page Up / Page Down move one screen at a time and Escape leaves the mode.
+/// Runs in the first card, so the card stops are the ones from last frame.
+pub fn controls(ui: &mut egui::Ui) {
+    let mut on = enabled();
+    ui.add(widgets::ToggleButton::new(&mut on, tr("projector.toggle")));
+    if on && ui.input(|input| input.key_pressed(egui::Key::Escape)) {
+        on = false;
+    }
+    if on != enabled() {
+        set_enabled(ui.ctx(), on);
+    }
+    let cards = std::mem::take(&mut *cards_lock().write().expect("cards lock poisoned"));
+    if !on {
+        return;
+    }
+    ui.label(tr("projector.on"));
+    if ui.ctx().wants_keyboard_input() {
+        return;
+    }
+
+    let page = ui.clip_rect().height();
+    let (down, up, next, previous) = ui.input(|input| {
+        (
+            input.key_pressed(egui::Key::PageDown),
+            input.key_pressed(egui::Key::PageUp),
+            input.key_pressed(egui::Key::ArrowDown),
+            input.key_pressed(egui::Key::ArrowUp),
+        )
+    });
+    if down {
+        ui.scroll_with_delta(egui::vec2(0.0, -page));
+    }
+    if up {
+        ui.scroll_with_delta(egui::vec2(0.0, page));
+    }
+    if next || previous {
+        if let Some(offset) = card_jump(&cards, next) {
+            ui.scroll_with_delta(egui::vec2(0.0, -offset));
+        }
+    }
+}
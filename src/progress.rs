@@ -0,0 +1,395 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::{OnceLock, RwLock};
+
+use web_time::Instant;
+
+use crate::celebrate::{self, Reason};
+use crate::chapters::{self, Chapter};
+use crate::difficulty;
+use crate::i18n::tr;
+use crate::schedule;
+use crate::score;
+use crate::storage;
+
+const FILE: &str = "progress.txt";
+/// Every this many instances of a card right at once in a row earn a
+/// celebration.
+const CELEBRATED_STREAK: u32 = 5;
+/// One line per day with an answer, as days since 1970-01-01.
+const DAYS_FILE: &str = "practice_days.txt";
+
+/// A card key as passed to `nb.state`, e.g. `(Chapter::Loops, "loop_practice_state")`.
+pub type ExerciseKey = (Chapter, &'static str);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Status {
+    Attempted,
+    Solved,
+}
+
+impl Status {
+    fn name(self) -> &'static str {
+        match self {
+            Status::Attempted => "attempted",
+            Status::Solved => "solved",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "attempted" => Some(Status::Attempted),
+            "solved" => Some(Status::Solved),
+            _ => None,
+        }
+    }
+}
+
+/// What is known about one exercise: how far it got, how many hints were
+/// taken on the way, and how the generated instances of it went.
+#[derive(Clone, Copy, Debug, Default)]
+struct Record {
+    status: Option<Status>,
+    hints: u32,
+    /// Instances answered, and how many of those were right at once.
+    tries: u32,
+    solved: u32,
+    streak: u32,
+    best_streak: u32,
+    /// Time from an instance showing up to its first answer, summed.
+    seconds: f64,
+}
+
+/// The instance a card shows now, and whether its first answer was
+/// counted yet.
+struct Shown {
+    seed: u64,
+    since: Instant,
+    counted: bool,
+}
+
+/// One record per exercise, keyed by chapter id and card key so the file
+/// stays readable and survives chapters being reordered.
+type Records = BTreeMap<(String, String), Record>;
+
+static PROGRESS: OnceLock<RwLock<Records>> = OnceLock::new();
+
+fn progress_lock() -> &'static RwLock<Records> {
+    PROGRESS.get_or_init(|| RwLock::new(load()))
+}
+
+static DAYS: OnceLock<RwLock<BTreeSet<u64>>> = OnceLock::new();
+
+fn days_lock() -> &'static RwLock<BTreeSet<u64>> {
+    DAYS.get_or_init(|| RwLock::new(load_days()))
+}
+
+fn load_days() -> BTreeSet<u64> {
+    storage::load(DAYS_FILE)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect()
+}
+
+fn save_days(days: &BTreeSet<u64>) {
+    let text: String = days.iter().map(|day| format!("{day}\n")).collect();
+    if let Err(error) = storage::save(DAYS_FILE, &text) {
+        eprintln!("Could not save the practice days: {error}");
+    }
+}
+
+/// Marks today as a practice day, once.
+fn practiced_today() {
+    let today = schedule::today();
+    if days_lock()
+        .read()
+        .expect("days lock poisoned")
+        .contains(&today)
+    {
+        return;
+    }
+    let mut days = days_lock().write().expect("days lock poisoned");
+    days.insert(today);
+    save_days(&days);
+}
+
+static SHOWN: OnceLock<RwLock<HashMap<ExerciseKey, Shown>>> = OnceLock::new();
+
+fn shown_lock() -> &'static RwLock<HashMap<ExerciseKey, Shown>> {
+    SHOWN.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// One `chapter<TAB>card<TAB>status<TAB>hints<TAB>tries<TAB>solved<TAB>
+/// streak<TAB>best streak<TAB>seconds` line per exercise, with `open` as
+/// the status of an exercise not answered yet. Older files stop after the
+/// status or the hints; missing numbers count as zero. Lines that do not
+/// parse are skipped rather than failing the whole file.
+fn load() -> Records {
+    let mut records = Records::new();
+    let Some(text) = storage::load(FILE) else {
+        return records;
+    };
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        let (Some(chapter), Some(card), Some(status)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let status = match status {
+            "open" => None,
+            status => match Status::parse(status) {
+                Some(status) => Some(status),
+                None => continue,
+            },
+        };
+        let mut number = || {
+            fields
+                .next()
+                .and_then(|number| number.parse().ok())
+                .unwrap_or(0)
+        };
+        let record = Record {
+            status,
+            hints: number(),
+            tries: number(),
+            solved: number(),
+            streak: number(),
+            best_streak: number(),
+            seconds: fields
+                .next()
+                .and_then(|seconds| seconds.parse().ok())
+                .unwrap_or(0.0),
+        };
+        records.insert((chapter.to_string(), card.to_string()), record);
+    }
+    records
+}
+
+fn save(records: &Records) -> Result<(), String> {
+    let mut text = String::new();
+    for ((chapter, card), record) in records {
+        let status = record.status.map_or("open", Status::name);
+        text.push_str(&format!(
+            "{chapter}\t{card}\t{status}\t{}\t{}\t{}\t{}\t{}\t{:.1}\n",
+            record.hints,
+            record.tries,
+            record.solved,
+            record.streak,
+            record.best_streak,
+            record.seconds
+        ));
+    }
+    storage::save(FILE, &text)
+}
+
+fn entry(key: ExerciseKey) -> (String, String) {
+    (chapters::def(key.0).id.to_string(), key.1.to_string())
+}
+
+/// Notes that the card `key` shows the instance with `seed`. Called every
+/// frame by the seed field; a new seed starts the clock for its answer.
+pub fn shown(key: ExerciseKey, seed: u64) {
+    let mut shown = shown_lock().write().expect("shown lock poisoned");
+    if shown.get(&key).is_some_and(|shown| shown.seed == seed) {
+        return;
+    }
+    shown.insert(
+        key,
+        Shown {
+            seed,
+            since: Instant::now(),
+            counted: false,
+        },
+    );
+}
+
+/// Counts the first answer to the instance the card shows into `record`.
+/// Returns whether there was one to count.
+fn count_try(key: ExerciseKey, correct: bool, record: &mut Record) -> bool {
+    let mut shown = shown_lock().write().expect("shown lock poisoned");
+    let Some(shown) = shown.get_mut(&key).filter(|shown| !shown.counted) else {
+        return false;
+    };
+    shown.counted = true;
+    record.tries += 1;
+    record.seconds += shown.since.elapsed().as_secs_f64();
+    if correct {
+        record.solved += 1;
+        record.streak += 1;
+        record.best_streak = record.best_streak.max(record.streak);
+        if record.streak.is_multiple_of(CELEBRATED_STREAK) {
+            celebrate::start(Reason::Streak(record.streak));
+        }
+    } else {
+        record.streak = 0;
+    }
+    true
+}
+
+/// Notes an answer to an exercise. A solved exercise stays solved. The
+/// first answer to each instance also moves the chapter's practice level.
+/// Cards call this every frame an answer is showing, so the file is only
+/// written when the status improves or a new instance was answered.
+pub fn record(key: ExerciseKey, correct: bool) {
+    schedule::answered(key, correct);
+    practiced_today();
+    let status = if correct {
+        Status::Solved
+    } else {
+        Status::Attempted
+    };
+    let mut records = progress_lock().write().expect("progress lock poisoned");
+    let record = records.entry(entry(key)).or_default();
+    let counted = count_try(key, correct, record);
+    if counted {
+        difficulty::record(key.0, correct);
+    }
+    let improved = record.status.is_none_or(|known| known < status);
+    if improved {
+        record.status = Some(status);
+    }
+    if !counted && !improved {
+        return;
+    }
+    if let Err(error) = save(&records) {
+        eprintln!("Could not save progress: {error}");
+    }
+}
+
+/// Notes an answer given just now, on the frame it is picked or checked:
+/// it counts towards the score and streak and towards the card `key`.
+pub fn answer(key: ExerciseKey, correct: bool) {
+    score::record(correct);
+    record(key, correct);
+}
+
+/// Forgets every answer, for handing the course to a new learner.
+pub fn clear() {
+    let mut records = progress_lock().write().expect("progress lock poisoned");
+    records.clear();
+    if let Err(error) = save(&records) {
+        eprintln!("Could not save progress: {error}");
+    }
+    let mut days = days_lock().write().expect("days lock poisoned");
+    days.clear();
+    save_days(&days);
+}
+
+/// Replaces the records with what is saved, after a backup was imported.
+pub fn reload() {
+    *progress_lock().write().expect("progress lock poisoned") = load();
+    *days_lock().write().expect("days lock poisoned") = load_days();
+}
+
+/// Whether anything was answered on `day`, counted like [`schedule::today`].
+pub fn practiced_on(day: u64) -> bool {
+    days_lock()
+        .read()
+        .expect("days lock poisoned")
+        .contains(&day)
+}
+
+/// Days in a row with practice, up to today. A streak that ran until
+/// yesterday still counts while today has no answer yet.
+pub fn daily_streak() -> u32 {
+    let days = days_lock().read().expect("days lock poisoned");
+    let today = schedule::today();
+    let mut day = if days.contains(&today) {
+        today
+    } else {
+        today.saturating_sub(1)
+    };
+    let mut streak = 0;
+    while days.contains(&day) {
+        streak += 1;
+        let Some(before) = day.checked_sub(1) else {
+            break;
+        };
+        day = before;
+    }
+    streak
+}
+
+/// Counts a hint taken on an exercise. Every rung of the ladder counts.
+pub fn record_hint(key: ExerciseKey) {
+    let mut records = progress_lock().write().expect("progress lock poisoned");
+    records.entry(entry(key)).or_default().hints += 1;
+    if let Err(error) = save(&records) {
+        eprintln!("Could not save progress: {error}");
+    }
+}
+
+fn record_of(key: ExerciseKey) -> Record {
+    let records = progress_lock().read().expect("progress lock poisoned");
+    records.get(&entry(key)).copied().unwrap_or_default()
+}
+
+fn status(key: ExerciseKey) -> Option<Status> {
+    record_of(key).status
+}
+
+/// `"solved 4/6, best streak 3, avg 12 s"` for the card `key`, once one of
+/// its instances was answered.
+pub fn attempt_summary(key: ExerciseKey) -> Option<String> {
+    let record = record_of(key);
+    if record.tries == 0 {
+        return None;
+    }
+    let average = (record.seconds / f64::from(record.tries)).round();
+    Some(
+        tr("progress.attempts")
+            .replace("{solved}", &record.solved.to_string())
+            .replace("{tries}", &record.tries.to_string())
+            .replace("{streak}", &record.best_streak.to_string())
+            .replace("{seconds}", &average.to_string()),
+    )
+}
+
+/// Hints taken across the chapter's exercises.
+pub fn hints(chapter: Chapter) -> u32 {
+    chapters::def(chapter)
+        .exercises
+        .iter()
+        .map(|card| record_of((chapter, card)).hints)
+        .sum()
+}
+
+fn is_solved(key: ExerciseKey) -> bool {
+    status(key) == Some(Status::Solved)
+}
+
+/// Solved and total exercises in a chapter, or `None` if it has none.
+pub fn completion(chapter: Chapter) -> Option<(usize, usize)> {
+    let cards = chapters::def(chapter).exercises;
+    if cards.is_empty() {
+        return None;
+    }
+    let solved = cards
+        .iter()
+        .filter(|card| is_solved((chapter, **card)))
+        .count();
+    Some((solved, cards.len()))
+}
+
+/// The chapter's exercises that are not solved yet, tried or not.
+pub fn unfinished(chapter: Chapter) -> Vec<&'static str> {
+    chapters::def(chapter)
+        .exercises
+        .iter()
+        .copied()
+        .filter(|card| !is_solved((chapter, card)))
+        .collect()
+}
+
+/// Whether the chapter's badge is earned: it has exercises and every one
+/// of them was solved at least once.
+pub fn badge_earned(chapter: Chapter) -> bool {
+    completion(chapter).is_some_and(|(solved, total)| solved == total)
+}
+
+/// Completion as a whole percentage, for compact labels.
+pub fn percent(chapter: Chapter) -> Option<u32> {
+    let (solved, total) = completion(chapter)?;
+    Some((solved * 100 / total) as u32)
+}
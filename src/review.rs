@@ -0,0 +1,103 @@
+use std::sync::{OnceLock, RwLock};
+
+use crate::chapters;
+use crate::progress::ExerciseKey;
+use crate::storage;
+
+const FILE: &str = "mistakes.txt";
+
+/// A wrongly answered exercise: the chapter id and card key of the card it
+/// came from, which names the exercise type, and the seed that rebuilds it.
+type Mistake = (String, String, u64);
+
+static MISTAKES: OnceLock<RwLock<Vec<Mistake>>> = OnceLock::new();
+
+fn mistakes_lock() -> &'static RwLock<Vec<Mistake>> {
+    MISTAKES.get_or_init(|| RwLock::new(load()))
+}
+
+/// One `chapter<TAB>card<TAB>seed` line per mistake, oldest first.
+fn load() -> Vec<Mistake> {
+    let Some(text) = storage::load(FILE) else {
+        return Vec::new();
+    };
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let (Some(chapter), Some(card), Some(seed)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return None;
+            };
+            Some((chapter.to_string(), card.to_string(), seed.parse().ok()?))
+        })
+        .collect()
+}
+
+fn save(mistakes: &[Mistake]) -> Result<(), String> {
+    let mut text = String::new();
+    for (chapter, card, seed) in mistakes {
+        text.push_str(&format!("{chapter}\t{card}\t{seed}\n"));
+    }
+    storage::save(FILE, &text)
+}
+
+fn update(change: impl FnOnce(&mut Vec<Mistake>) -> bool) {
+    let mut mistakes = mistakes_lock().write().expect("mistakes lock poisoned");
+    if change(&mut mistakes) {
+        if let Err(error) = save(&mistakes) {
+            eprintln!("Could not save mistakes: {error}");
+        }
+    }
+}
+
+fn mistake(key: ExerciseKey, seed: u64) -> Mistake {
+    (chapters::def(key.0).id.to_string(), key.1.to_string(), seed)
+}
+
+/// Queues the exercise built from `seed` on the card `key` for review.
+pub fn add(key: ExerciseKey, seed: u64) {
+    let mistake = mistake(key, seed);
+    update(|mistakes| {
+        let new = !mistakes.contains(&mistake);
+        if new {
+            mistakes.push(mistake);
+        }
+        new
+    });
+}
+
+/// Takes an exercise off the queue once it has been answered correctly.
+pub fn remove(key: ExerciseKey, seed: u64) {
+    let mistake = mistake(key, seed);
+    update(|mistakes| {
+        let before = mistakes.len();
+        mistakes.retain(|known| *known != mistake);
+        mistakes.len() != before
+    });
+}
+
+/// Seeds still waiting for review on the card `key`, oldest first.
+pub fn seeds(key: ExerciseKey) -> Vec<u64> {
+    let (chapter, card, _) = mistake(key, 0);
+    mistakes_lock()
+        .read()
+        .expect("mistakes lock poisoned")
+        .iter()
+        .filter(|(known_chapter, known_card, _)| *known_chapter == chapter && *known_card == card)
+        .map(|(_, _, seed)| *seed)
+        .collect()
+}
+
+/// Forgets every queued mistake.
+pub fn clear() {
+    update(|mistakes| {
+        mistakes.clear();
+        true
+    });
+}
+
+/// Reads the queue from the file again, after a backup was imported.
+pub fn reload() {
+    *mistakes_lock().write().expect("mistakes lock poisoned") = load();
+}
@@ -0,0 +1,108 @@
+use web_time::{SystemTime, UNIX_EPOCH};
+use GORBIE::prelude::*;
+
+use crate::classroom;
+use crate::difficulty;
+use crate::i18n::tr;
+use crate::progress::{self, ExerciseKey};
+use crate::share;
+
+/// Exercise seeds stay short so they are easy to read out and type back in.
+pub const SEED_RANGE: u64 = 1_000_000;
+
+pub struct SimpleRng {
+    state: u64,
+}
+
+impl SimpleRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        (x >> 32) as u32
+    }
+
+    pub fn gen_range_i32(&mut self, min: i32, max: i32) -> i32 {
+        let span = (max - min + 1) as u32;
+        let value = self.next_u32() % span;
+        min + value as i32
+    }
+
+    pub fn gen_range_i64(&mut self, min: i64, max: i64) -> i64 {
+        let span = (max - min + 1) as u64;
+        let value = self.next_u32() as u64 % span;
+        min + value as i64
+    }
+
+    pub fn shuffle<T>(&mut self, values: &mut [T]) {
+        if values.len() <= 1 {
+            return;
+        }
+        for i in (1..values.len()).rev() {
+            let j = self.gen_range_i64(0, i as i64) as usize;
+            values.swap(i, j);
+        }
+    }
+
+    /// Draws the seed for the next exercise, at the current practice level.
+    pub fn next_seed(&mut self) -> u64 {
+        difficulty::current().seed(self.next_u32() as u64)
+    }
+}
+
+/// `web_time` is `std::time` on native targets and reads the browser clock
+/// in the web build, where `std::time::SystemTime::now` panics.
+pub fn seed_from_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_nanos() as u64)
+        .unwrap_or(1)
+}
+
+/// A short seed for the first exercise of a card, at the current practice
+/// level.
+pub fn fresh_seed() -> u64 {
+    difficulty::current().seed(seed_from_time())
+}
+
+/// Shows the seed behind the current exercise, the code to share it and
+/// how the learner did on the card `key` so far. Another seed can be typed
+/// in.
+///
+/// Returns the new seed when it changed: typed here, loaded from a shared
+/// code, or sent by the teacher of a classroom.
+pub fn seed_field(ui: &mut egui::Ui, key: ExerciseKey, seed: u64) -> Option<u64> {
+    let mut value = seed;
+    let code = share::code(key, seed);
+    ui.horizontal(|ui| {
+        ui.label(tr("exercise.seed"));
+        ui.add(widgets::NumberField::new(&mut value).speed(1.0));
+        if let Some(code) = &code {
+            ui.label(egui::RichText::new(code).monospace())
+                .on_hover_text(tr("share.code_hover"));
+        }
+        if ui
+            .add(widgets::Button::new(tr("exercise.copy_seed")).small())
+            .on_hover_text(tr("exercise.copy_seed_hover"))
+            .clicked()
+        {
+            ui.ctx()
+                .copy_text(code.clone().unwrap_or_else(|| seed.to_string()));
+        }
+    });
+    classroom::observe(key, seed);
+    progress::shown(key, seed);
+    if let Some(summary) = progress::attempt_summary(key) {
+        ui.label(egui::RichText::new(summary).small().weak());
+    }
+    let value = value.min(SEED_RANGE - 1);
+    share::take(key)
+        .or_else(|| classroom::take(key))
+        .or((value != seed).then_some(value))
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::chapters::{self, CHAPTERS};
+use crate::progress::ExerciseKey;
+use crate::storage;
+
+const FILE: &str = "schedule.txt";
+/// Days until the next review after 1, 2, 3, ... right answers in a row.
+/// Past the end the last interval repeats.
+const INTERVALS: [u64; 6] = [1, 3, 7, 14, 30, 60];
+
+/// When a solved exercise comes up for review again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Slot {
+    /// Right answers in a row at review time.
+    level: usize,
+    /// Day number, counted from the Unix epoch.
+    due: u64,
+}
+
+/// Keyed by chapter id and card key, like the progress file.
+type Slots = BTreeMap<(String, String), Slot>;
+
+static SCHEDULE: OnceLock<RwLock<Slots>> = OnceLock::new();
+
+fn schedule_lock() -> &'static RwLock<Slots> {
+    SCHEDULE.get_or_init(|| RwLock::new(load()))
+}
+
+/// One `chapter<TAB>card<TAB>level<TAB>due` line per exercise.
+fn load() -> Slots {
+    let mut slots = Slots::new();
+    let Some(text) = storage::load(FILE) else {
+        return slots;
+    };
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        let (Some(chapter), Some(card), Some(level), Some(due)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if let (Ok(level), Ok(due)) = (level.parse(), due.parse()) {
+            slots.insert((chapter.to_string(), card.to_string()), Slot { level, due });
+        }
+    }
+    slots
+}
+
+fn save(slots: &Slots) -> Result<(), String> {
+    let mut text = String::new();
+    for ((chapter, card), slot) in slots {
+        text.push_str(&format!(
+            "{chapter}\t{card}\t{}\t{}\n",
+            slot.level, slot.due
+        ));
+    }
+    storage::save(FILE, &text)
+}
+
+/// Days since 1970-01-01, in UTC.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / 86_400)
+        .unwrap_or(0)
+}
+
+fn interval(level: usize) -> u64 {
+    INTERVALS[level.min(INTERVALS.len() - 1)]
+}
+
+/// Moves an exercise along its schedule. The first right answer puts it on
+/// the schedule; a right answer on a due exercise waits longer next time,
+/// a wrong one starts it over at a day. Called every frame an answer shows,
+/// so answers on exercises that are not due change nothing.
+pub fn answered(key: ExerciseKey, correct: bool) {
+    let entry = (chapters::def(key.0).id.to_string(), key.1.to_string());
+    let today = today();
+    let mut slots = schedule_lock().write().expect("schedule lock poisoned");
+    let slot = match slots.get(&entry) {
+        None if correct => Slot {
+            level: 1,
+            due: today + interval(0),
+        },
+        Some(slot) if slot.due <= today && correct => Slot {
+            level: slot.level + 1,
+            due: today + interval(slot.level),
+        },
+        Some(slot) if slot.due <= today => Slot {
+            level: 0,
+            due: today + interval(0),
+        },
+        _ => return,
+    };
+    slots.insert(entry, slot);
+    if let Err(error) = save(&slots) {
+        eprintln!("Could not save the review schedule: {error}");
+    }
+}
+
+/// Exercises due for review today, the longest overdue first.
+pub fn due_today() -> Vec<ExerciseKey> {
+    let today = today();
+    let slots = schedule_lock().read().expect("schedule lock poisoned");
+    let mut due: Vec<(u64, ExerciseKey)> = CHAPTERS
+        .iter()
+        .flat_map(|def| def.exercises.iter().map(|card| (def.chapter, *card)))
+        .filter_map(|key| {
+            let slot = slots.get(&(chapters::def(key.0).id.to_string(), key.1.to_string()))?;
+            (slot.due <= today).then_some((slot.due, key))
+        })
+        .collect();
+    due.sort_by_key(|(day, _)| *day);
+    due.into_iter().map(|(_, key)| key).collect()
+}
+
+/// Forgets the whole schedule.
+pub fn clear() {
+    let mut slots = schedule_lock().write().expect("schedule lock poisoned");
+    slots.clear();
+    if let Err(error) = save(&slots) {
+        eprintln!("Could not save the review schedule: {error}");
+    }
+}
+
+/// Rereads the schedule after a backup was imported.
+pub fn reload() {
+    *schedule_lock().write().expect("schedule lock poisoned") = load();
+}
@@ -0,0 +1,104 @@
+use std::sync::{OnceLock, RwLock};
+
+use crate::i18n::tr;
+use crate::stats;
+use crate::storage;
+
+const FILE: &str = "score.txt";
+
+/// Answers picked on practice cards across the whole course.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Score {
+    right: u32,
+    wrong: u32,
+    /// Right answers in a row since the last wrong one.
+    streak: u32,
+    best_streak: u32,
+}
+
+static SCORE: OnceLock<RwLock<Score>> = OnceLock::new();
+
+fn score_lock() -> &'static RwLock<Score> {
+    SCORE.get_or_init(|| RwLock::new(load()))
+}
+
+/// One `name<TAB>count` line per counter. Missing counters start at zero.
+fn load() -> Score {
+    let mut score = Score::default();
+    let Some(text) = storage::load(FILE) else {
+        return score;
+    };
+    for line in text.lines() {
+        let Some((name, value)) = line.split_once('\t') else {
+            continue;
+        };
+        let Ok(value) = value.parse() else {
+            continue;
+        };
+        match name {
+            "right" => score.right = value,
+            "wrong" => score.wrong = value,
+            "streak" => score.streak = value,
+            "best_streak" => score.best_streak = value,
+            _ => {}
+        }
+    }
+    score
+}
+
+fn save(score: &Score) -> Result<(), String> {
+    let text = format!(
+        "right\t{}\nwrong\t{}\nstreak\t{}\nbest_streak\t{}\n",
+        score.right, score.wrong, score.streak, score.best_streak,
+    );
+    storage::save(FILE, &text)
+}
+
+fn update(change: impl FnOnce(&mut Score)) {
+    let mut score = score_lock().write().expect("score lock poisoned");
+    change(&mut score);
+    if let Err(error) = save(&score) {
+        eprintln!("Could not save the score: {error}");
+    }
+}
+
+/// Counts one picked answer. A wrong answer ends the streak. The answer
+/// also counts towards the open chapter's statistics.
+pub fn record(correct: bool) {
+    stats::record(correct);
+    update(|score| {
+        if correct {
+            score.right += 1;
+            score.streak += 1;
+            score.best_streak = score.best_streak.max(score.streak);
+        } else {
+            score.wrong += 1;
+            score.streak = 0;
+        }
+    });
+}
+
+/// Starts the score over at zero.
+pub fn clear() {
+    update(|score| *score = Score::default());
+}
+
+/// Takes the score from the file, e.g. one just imported from a backup.
+pub fn reload() {
+    *score_lock().write().expect("score lock poisoned") = load();
+}
+
+/// The score line for the chapter header, or `None` before the first answer.
+pub fn summary() -> Option<String> {
+    let score = *score_lock().read().expect("score lock poisoned");
+    if score.right + score.wrong == 0 {
+        return None;
+    }
+    Some(
+        tr("score.summary")
+            .replace("{right}", &score.right.to_string())
+            .replace("{wrong}", &score.wrong.to_string())
+            .replace("{streak}", &score.streak.to_string())
+            .replace("{best}", &score.best_streak.to_string()),
+    )
+}
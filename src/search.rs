@@ -0,0 +1,178 @@
+use std::sync::OnceLock;
+
+use crate::chapters::{self, Chapter, CHAPTERS};
+use crate::i18n::{self, Language};
+use crate::settings;
+
+const MAX_HITS: usize = 8;
+
+/// One searchable piece of a chapter: its title, a card heading, or a line
+/// of markdown, either written in the source or looked up with `tr`.
+pub struct Entry {
+    pub chapter: Chapter,
+    /// Heading of the card to scroll to, if the entry belongs to one.
+    pub card: Option<String>,
+    pub text: String,
+    lower: String,
+    /// Set for text from a translation table; only entries in the chosen
+    /// language are searched.
+    language: Option<Language>,
+    /// Titles sort before card headings, and those before markdown.
+    rank: u8,
+}
+
+static INDEX: OnceLock<Vec<Entry>> = OnceLock::new();
+
+fn index() -> &'static [Entry] {
+    INDEX.get_or_init(build_index)
+}
+
+fn entry(chapter: Chapter, card: Option<String>, text: String, rank: u8) -> Entry {
+    let lower = text.to_lowercase();
+    Entry {
+        chapter,
+        card,
+        text,
+        lower,
+        language: None,
+        rank,
+    }
+}
+
+/// Pushes one entry per prose line of `text`, skipping code fences and
+/// placeholders like `md!(ui, "{}", tr(key))`.
+fn push_lines(entries: &mut Vec<Entry>, chapter: Chapter, text: &str, language: Option<Language>) {
+    for line in text.lines() {
+        let line = line
+            .trim_start_matches(['#', '-', '*', ' '])
+            .trim_end_matches('\\')
+            .trim();
+        if !line.chars().any(char::is_alphanumeric) || line.starts_with("```") {
+            continue;
+        }
+        entries.push(Entry {
+            language,
+            ..entry(chapter, None, line.to_string(), 2)
+        });
+    }
+}
+
+/// Chapter titles, then every card heading and markdown line found in the
+/// chapter sources, then the text of every `tr("key")` they call in each
+/// language.
+fn build_index() -> Vec<Entry> {
+    let mut entries = Vec::new();
+    for def in CHAPTERS.iter().filter(|def| def.is_lesson()) {
+        entries.push(entry(def.chapter, None, def.title.to_string(), 0));
+        for (start, _) in def.source.match_indices("card_heading(ui, \"") {
+            let literal = start + "card_heading(ui, ".len();
+            if let Some(title) = read_literal(&def.source[literal..]) {
+                entries.push(entry(def.chapter, Some(title.clone()), title, 1));
+            }
+        }
+        for macro_name in ["md!(", "note!(", "speech::lesson(", "speech::markdown("] {
+            for (start, _) in def.source.match_indices(macro_name) {
+                let rest = &def.source[start..];
+                let Some(quote) = rest.find('"') else {
+                    continue;
+                };
+                let Some(text) = read_literal(&rest[quote..]) else {
+                    continue;
+                };
+                push_lines(&mut entries, def.chapter, &text, None);
+            }
+        }
+        for (start, _) in def.source.match_indices("tr(\"") {
+            let before = def.source[..start].chars().next_back();
+            if before.is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                continue;
+            }
+            let Some(key) = read_literal(&def.source[start + "tr(".len()..]) else {
+                continue;
+            };
+            for language in Language::ALL {
+                if let Some(text) = i18n::lookup(language, &key) {
+                    push_lines(&mut entries, def.chapter, text, Some(language));
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// The heading of the card that keeps its state under `key`, read from the
+/// first `card_heading` after `state_key("<key>")` in the chapter source.
+pub fn card_title(chapter: Chapter, key: &str) -> Option<String> {
+    let source = chapters::def(chapter).source;
+    let start = source.find(&format!("state_key(\"{key}\")"))?;
+    let rest = &source[start..];
+    let heading = rest.find("card_heading(ui, \"")? + "card_heading(ui, ".len();
+    read_literal(&rest[heading..])
+}
+
+/// Reads the Rust string literal `source` starts with. Line continuations
+/// and the common escapes are resolved; anything else is kept as written.
+pub fn read_literal(source: &str) -> Option<String> {
+    let mut chars = source.strip_prefix('"')?.chars().peekable();
+    let mut text = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(text),
+            '\\' => match chars.next()? {
+                'n' => text.push('\n'),
+                '\n' => {
+                    while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                        chars.next();
+                    }
+                }
+                'u' => {
+                    let code: String = chars
+                        .by_ref()
+                        .skip_while(|c| *c == '{')
+                        .take_while(|c| *c != '}')
+                        .collect();
+                    if let Some(c) = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                        text.push(c);
+                    }
+                }
+                other => text.push(other),
+            },
+            other => text.push(other),
+        }
+    }
+    None
+}
+
+/// Entries containing every word of `query`, chapter titles and card
+/// headings first.
+pub fn search(query: &str) -> Vec<&'static Entry> {
+    let words: Vec<String> = query
+        .split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+    let language = settings::get().language;
+    let mut hits: Vec<&Entry> = index()
+        .iter()
+        .filter(|entry| entry.language.is_none_or(|known| known == language))
+        .filter(|entry| words.iter().all(|word| entry.lower.contains(word)))
+        .collect();
+    hits.sort_by_key(|entry| entry.rank);
+    hits.dedup_by(|a, b| a.chapter == b.chapter && a.text == b.text);
+    hits.truncate(MAX_HITS);
+    hits
+}
+
+/// Opens the chapter of `entry` and scrolls to its card, if it has one.
+pub fn open(entry: &Entry) {
+    let def = chapters::def(entry.chapter);
+    let target = match &entry.card {
+        Some(card) => format!("{}#{}", def.id, chapters::card_slug(card)),
+        None => def.id.to_string(),
+    };
+    if let Err(error) = chapters::open_target(&target) {
+        eprintln!("{error}");
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::chapters::{self, CHAPTERS};
+use crate::difficulty::Difficulty;
+use crate::rng::SimpleRng;
+
+/// Rounds per level when `--self-check` gets no count.
+const DEFAULT_ROUNDS: usize = 2000;
+/// Failures printed per chapter; the rest are only counted.
+const SHOWN_FAILURES: usize = 5;
+/// A chapter whose checks run longer than this has a generator stuck in a
+/// loop, which a learner would see as a frozen card.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How one chapter's checks went: rounds run, and the seed and message of
+/// each failure.
+struct Report {
+    rounds: usize,
+    failures: Vec<(u64, String)>,
+}
+
+fn check_chapter(chapter: chapters::Chapter, rounds: usize) -> Report {
+    let mut report = Report {
+        rounds: 0,
+        failures: Vec::new(),
+    };
+    for difficulty in Difficulty::all() {
+        for round in 0..rounds {
+            let seed = difficulty.seed(round as u64);
+            let mut rng = SimpleRng::new(seed);
+            match chapters::self_check(chapter, &mut rng, difficulty) {
+                None => return report,
+                Some(Ok(())) => {}
+                Some(Err(error)) => report.failures.push((seed, error)),
+            }
+            report.rounds += 1;
+        }
+    }
+    report
+}
+
+/// Runs every exercise generator `rounds` times at each level and prints
+/// what went wrong, with the seed to reproduce it. Fails if anything did.
+pub fn run(rounds: &str) -> Result<(), String> {
+    let rounds = if rounds.is_empty() {
+        DEFAULT_ROUNDS
+    } else {
+        rounds
+            .parse()
+            .ok()
+            .filter(|rounds| *rounds > 0)
+            .ok_or_else(|| format!("'{rounds}' is not a number of rounds"))?
+    };
+
+    let mut failed = 0;
+    for def in CHAPTERS {
+        let chapter = def.chapter;
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = sender.send(check_chapter(chapter, rounds));
+        });
+        let Ok(report) = receiver.recv_timeout(TIMEOUT) else {
+            println!("{}: did not finish within {} s", def.id, TIMEOUT.as_secs());
+            failed += 1;
+            continue;
+        };
+        if report.rounds == 0 {
+            continue;
+        }
+        println!(
+            "{}: {} rounds, {} failed",
+            def.id,
+            report.rounds,
+            report.failures.len()
+        );
+        for (seed, error) in report.failures.iter().take(SHOWN_FAILURES) {
+            println!("  seed {seed}: {error}");
+        }
+        if report.failures.len() > SHOWN_FAILURES {
+            println!("  ...");
+        }
+        failed += report.failures.len();
+    }
+
+    if failed > 0 {
+        return Err(format!("Self-check found {failed} problems"));
+    }
+    println!("All generators passed");
+    Ok(())
+}
@@ -0,0 +1,103 @@
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use crate::chapters::{self, Chapter, CHAPTERS};
+use crate::storage;
+
+const FILE: &str = "session.txt";
+/// Scrolling writes the file at most this often.
+const SAVE_INTERVAL: f64 = 1.0;
+/// Give up restoring the scroll position if the page is still too short
+/// after this many frames.
+const RESTORE_FRAMES: u32 = 10;
+
+#[derive(Default)]
+struct Session {
+    /// Scroll offset still to be restored, with the frames tried so far.
+    restore: Option<(f32, u32)>,
+    /// What the file currently says.
+    saved: Option<(Chapter, f32)>,
+    last_save: f64,
+}
+
+static SESSION: OnceLock<RwLock<Session>> = OnceLock::new();
+
+fn session_lock() -> &'static RwLock<Session> {
+    SESSION.get_or_init(|| RwLock::new(Session::default()))
+}
+
+/// The chapter that was open when the app last closed. Also queues its
+/// scroll position to be restored by [`track_scroll`].
+pub fn saved_chapter() -> Option<Chapter> {
+    let text = storage::load(FILE)?;
+    let mut chapter = None;
+    let mut scroll = 0.0;
+    for line in text.lines() {
+        match line.split_once('\t') {
+            Some(("chapter", id)) => {
+                chapter = CHAPTERS
+                    .iter()
+                    .find(|def| def.id == id)
+                    .map(|def| def.chapter);
+            }
+            Some(("scroll", value)) => scroll = value.parse().unwrap_or(0.0),
+            _ => {}
+        }
+    }
+    let chapter = chapter?;
+    let mut session = session_lock().write().expect("session lock poisoned");
+    session.restore = Some((scroll, 0));
+    session.saved = Some((chapter, scroll));
+    Some(chapter)
+}
+
+/// Drops a queued scroll restore, e.g. when the command line asked for a
+/// specific chapter.
+pub fn forget_scroll() {
+    session_lock()
+        .write()
+        .expect("session lock poisoned")
+        .restore = None;
+}
+
+/// Called from the first card every frame. Restores the saved scroll
+/// position once and saves the current chapter and position when they change.
+pub fn track_scroll(ui: &mut egui::Ui) {
+    let offset = (ui.clip_rect().top() - ui.max_rect().top()).max(0.0);
+    let now = ui.input(|input| input.time);
+    let chapter = chapters::current_chapter();
+    let mut session = session_lock().write().expect("session lock poisoned");
+
+    if let Some((target, frames)) = session.restore {
+        if offset + 1.0 >= target || frames >= RESTORE_FRAMES {
+            session.restore = None;
+        } else {
+            ui.scroll_with_delta(egui::vec2(0.0, offset - target));
+            session.restore = Some((target, frames + 1));
+            ui.ctx().request_repaint();
+            return;
+        }
+    }
+
+    let changed = match session.saved {
+        Some((saved_chapter, saved_offset)) => {
+            saved_chapter != chapter || (saved_offset - offset).abs() >= 1.0
+        }
+        None => true,
+    };
+    if !changed {
+        return;
+    }
+    let chapter_changed = session.saved.map(|(saved, _)| saved) != Some(chapter);
+    if !chapter_changed && now - session.last_save < SAVE_INTERVAL {
+        ui.ctx()
+            .request_repaint_after(Duration::from_secs_f64(SAVE_INTERVAL));
+        return;
+    }
+    let text = format!("chapter\t{}\nscroll\t{offset}\n", chapters::def(chapter).id);
+    if let Err(error) = storage::save(FILE, &text) {
+        eprintln!("Could not save the open chapter: {error}");
+    }
+    session.saved = Some((chapter, offset));
+    session.last_save = now;
+}
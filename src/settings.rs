@@ -0,0 +1,409 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once, OnceLock, RwLock};
+use std::time::Duration;
+
+use GORBIE::prelude::*;
+
+use crate::backup;
+use crate::chapters;
+use crate::fonts;
+use crate::i18n::{tr, Language};
+use crate::storage;
+use crate::supervised;
+
+const FILE: &str = "settings.txt";
+const TEXT_SCALES: [u32; 4] = [100, 125, 150, 175];
+const ANIMATION_SPEEDS: [(f32, &str); 3] = [
+    (0.5, "settings.slow"),
+    (1.0, "settings.normal"),
+    (2.0, "settings.fast"),
+];
+const SPEECH_RATES: [(f32, &str); 3] = [
+    (0.75, "settings.slow"),
+    (1.0, "settings.normal"),
+    (1.25, "settings.fast"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    fn name(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "light" => Some(Theme::Light),
+            "dark" => Some(Theme::Dark),
+            _ => None,
+        }
+    }
+}
+
+/// Choices the learner makes once for the whole course.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    pub theme: Theme,
+    /// Text size in percent.
+    pub text_scale: u32,
+    /// Whether wrong answers come with a hint.
+    pub hints: bool,
+    /// Multiplier for autoplaying animations; 2.0 plays twice as fast.
+    pub animation_speed: f32,
+    /// Blue instead of orange highlights, plus thicker and dashed lines in
+    /// diagrams so the highlight does not rely on color alone.
+    pub colorblind_safe: bool,
+    /// Black and white backgrounds, solid outlines and thicker lines.
+    pub high_contrast: bool,
+    /// OpenDyslexic for body text and code, if the font files are present.
+    pub dyslexia_font: bool,
+    pub language: Language,
+    /// Multiplier for the read-aloud speed.
+    pub speech_rate: f32,
+    /// Whether practice cards offer a challenge against the clock. Off for
+    /// learners who find timers stressful.
+    pub timed_challenges: bool,
+    /// Celebrations without confetti, for learners bothered by movement.
+    pub reduced_motion: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::Light,
+            text_scale: 100,
+            hints: true,
+            animation_speed: 1.0,
+            colorblind_safe: false,
+            high_contrast: false,
+            dyslexia_font: false,
+            language: Language::English,
+            speech_rate: 1.0,
+            timed_challenges: true,
+            reduced_motion: false,
+        }
+    }
+}
+
+static SETTINGS: OnceLock<RwLock<Settings>> = OnceLock::new();
+/// Whether the "reset all progress" confirmation is open.
+static CONFIRM_RESET: AtomicBool = AtomicBool::new(false);
+
+fn settings_lock() -> &'static RwLock<Settings> {
+    SETTINGS.get_or_init(|| RwLock::new(load()))
+}
+
+/// One `name<TAB>value` line per setting. Unknown names and values that do
+/// not parse keep their default.
+fn load() -> Settings {
+    let mut settings = Settings::default();
+    let Some(text) = storage::load(FILE) else {
+        return settings;
+    };
+    for line in text.lines() {
+        let Some((name, value)) = line.split_once('\t') else {
+            continue;
+        };
+        match name {
+            "theme" => settings.theme = Theme::parse(value).unwrap_or(settings.theme),
+            "text_scale" => {
+                settings.text_scale = value
+                    .parse()
+                    .ok()
+                    .filter(|scale| TEXT_SCALES.contains(scale))
+                    .unwrap_or(settings.text_scale);
+            }
+            "hints" => settings.hints = value.parse().unwrap_or(settings.hints),
+            "animation_speed" => {
+                settings.animation_speed = value
+                    .parse()
+                    .ok()
+                    .filter(|speed: &f32| *speed > 0.0)
+                    .unwrap_or(settings.animation_speed);
+            }
+            "colorblind_safe" => {
+                settings.colorblind_safe = value.parse().unwrap_or(settings.colorblind_safe);
+            }
+            "high_contrast" => {
+                settings.high_contrast = value.parse().unwrap_or(settings.high_contrast);
+            }
+            "dyslexia_font" => {
+                settings.dyslexia_font = value.parse().unwrap_or(settings.dyslexia_font);
+            }
+            "language" => settings.language = Language::parse(value).unwrap_or(settings.language),
+            "speech_rate" => {
+                settings.speech_rate = value
+                    .parse()
+                    .ok()
+                    .filter(|rate: &f32| *rate > 0.0)
+                    .unwrap_or(settings.speech_rate);
+            }
+            "timed_challenges" => {
+                settings.timed_challenges = value.parse().unwrap_or(settings.timed_challenges);
+            }
+            "reduced_motion" => {
+                settings.reduced_motion = value.parse().unwrap_or(settings.reduced_motion);
+            }
+            _ => {}
+        }
+    }
+    settings
+}
+
+fn save(settings: &Settings) -> Result<(), String> {
+    let text = format!(
+        "theme\t{}\ntext_scale\t{}\nhints\t{}\nanimation_speed\t{}\n\
+         colorblind_safe\t{}\nhigh_contrast\t{}\ndyslexia_font\t{}\n\
+         language\t{}\nspeech_rate\t{}\ntimed_challenges\t{}\nreduced_motion\t{}\n",
+        settings.theme.name(),
+        settings.text_scale,
+        settings.hints,
+        settings.animation_speed,
+        settings.colorblind_safe,
+        settings.high_contrast,
+        settings.dyslexia_font,
+        settings.language.code(),
+        settings.speech_rate,
+        settings.timed_challenges,
+        settings.reduced_motion,
+    );
+    storage::save(FILE, &text)
+}
+
+/// The current settings.
+pub fn get() -> Settings {
+    *settings_lock().read().expect("settings lock poisoned")
+}
+
+fn set(ctx: &egui::Context, settings: Settings) {
+    let previous = std::mem::replace(
+        &mut *settings_lock().write().expect("settings lock poisoned"),
+        settings,
+    );
+    apply(ctx, &previous);
+    if let Err(error) = save(&settings) {
+        eprintln!("Could not save settings: {error}");
+    }
+}
+
+/// Pushes the theme, text size, contrast and font into egui. Fonts are only
+/// reloaded when the font choice differs from `previous`.
+fn apply(ctx: &egui::Context, previous: &Settings) {
+    let settings = get();
+    ctx.set_theme(match settings.theme {
+        Theme::Light => egui::Theme::Light,
+        Theme::Dark => egui::Theme::Dark,
+    });
+    apply_style(ctx, &settings);
+    if settings.dyslexia_font != previous.dyslexia_font {
+        fonts::apply(ctx, settings.dyslexia_font);
+    }
+}
+
+/// Rebuilds the light and dark styles from the ones egui started with, so
+/// switching back and forth does not compound. Layout code that resolves a
+/// `TextStyle` picks the new size up on the next frame.
+fn apply_style(ctx: &egui::Context, settings: &Settings) {
+    static BASE: OnceLock<Vec<(egui::Theme, Arc<egui::Style>)>> = OnceLock::new();
+    let base = BASE.get_or_init(|| {
+        [egui::Theme::Light, egui::Theme::Dark]
+            .into_iter()
+            .map(|theme| (theme, ctx.style_of(theme)))
+            .collect()
+    });
+    let factor = settings.text_scale as f32 / 100.0;
+    for (theme, style) in base {
+        let mut style = (**style).clone();
+        for font_id in style.text_styles.values_mut() {
+            font_id.size *= factor;
+        }
+        if settings.high_contrast {
+            high_contrast(&mut style.visuals);
+        }
+        ctx.set_style_of(*theme, style);
+    }
+}
+
+/// Pure black on white, or white on black, with every outline in the text
+/// color and twice as thick.
+fn high_contrast(visuals: &mut egui::Visuals) {
+    let (background, foreground) = if visuals.dark_mode {
+        (egui::Color32::BLACK, egui::Color32::WHITE)
+    } else {
+        (egui::Color32::WHITE, egui::Color32::BLACK)
+    };
+    visuals.override_text_color = Some(foreground);
+    visuals.panel_fill = background;
+    visuals.window_fill = background;
+    visuals.extreme_bg_color = background;
+    visuals.faint_bg_color = background;
+    visuals.code_bg_color = background;
+    let widgets = &mut visuals.widgets;
+    for widget in [
+        &mut widgets.noninteractive,
+        &mut widgets.inactive,
+        &mut widgets.hovered,
+        &mut widgets.active,
+        &mut widgets.open,
+    ] {
+        widget.bg_stroke = egui::Stroke::new(widget.bg_stroke.width.max(1.0) * 2.0, foreground);
+        widget.fg_stroke.color = foreground;
+    }
+}
+
+/// Loads and applies the settings file again, after a backup was imported.
+pub fn reload(ctx: &egui::Context) {
+    let previous = std::mem::replace(
+        &mut *settings_lock().write().expect("settings lock poisoned"),
+        load(),
+    );
+    apply(ctx, &previous);
+}
+
+/// Applies the saved settings on the first frame.
+pub fn restore(ctx: &egui::Context) {
+    static RESTORE: Once = Once::new();
+    RESTORE.call_once(|| apply(ctx, &Settings::default()));
+}
+
+/// How long an animation frame of `interval` lasts at the chosen speed.
+pub fn animation_interval(interval: Duration) -> Duration {
+    interval.div_f32(get().animation_speed)
+}
+
+/// The settings section of the chapter selector.
+pub fn controls(ui: &mut egui::Ui) {
+    let mut settings = get();
+
+    ui.horizontal(|ui| {
+        ui.label(tr("settings.language"));
+        let mut toggle = widgets::ChoiceToggle::new(&mut settings.language).small();
+        for language in Language::ALL {
+            toggle = toggle.choice(language, language.name());
+        }
+        ui.add(toggle);
+    });
+    if settings.language != Language::English {
+        ui.label(
+            egui::RichText::new(tr("settings.language_scope"))
+                .small()
+                .weak(),
+        );
+    }
+    ui.horizontal(|ui| {
+        ui.label(tr("settings.theme"));
+        ui.add(
+            widgets::ChoiceToggle::new(&mut settings.theme)
+                .choice(Theme::Light, tr("settings.light"))
+                .choice(Theme::Dark, tr("settings.dark"))
+                .small(),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("settings.text_size"));
+        let mut toggle = widgets::ChoiceToggle::new(&mut settings.text_scale).small();
+        for scale in TEXT_SCALES {
+            toggle = toggle.choice(scale, format!("{scale}%"));
+        }
+        ui.add(toggle);
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("settings.animations"));
+        let mut toggle = widgets::ChoiceToggle::new(&mut settings.animation_speed).small();
+        for (speed, label) in ANIMATION_SPEEDS {
+            toggle = toggle.choice(speed, tr(label));
+        }
+        ui.add(toggle);
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("settings.speech_rate"));
+        let mut toggle = widgets::ChoiceToggle::new(&mut settings.speech_rate).small();
+        for (rate, label) in SPEECH_RATES {
+            toggle = toggle.choice(rate, tr(label));
+        }
+        ui.add(toggle);
+    });
+    ui.add(widgets::ToggleButton::new(
+        &mut settings.colorblind_safe,
+        tr("settings.colorblind_safe"),
+    ));
+    ui.add(widgets::ToggleButton::new(
+        &mut settings.high_contrast,
+        tr("settings.high_contrast"),
+    ));
+    let available = fonts::dyslexia_font_available();
+    let response = ui.add_enabled(
+        available,
+        widgets::ToggleButton::new(&mut settings.dyslexia_font, tr("settings.dyslexia_font")),
+    );
+    if !available {
+        response.on_disabled_hover_text(tr("settings.dyslexia_font_missing"));
+    }
+    ui.add_enabled(
+        !supervised::active(),
+        widgets::ToggleButton::new(&mut settings.hints, tr("settings.hints")),
+    )
+    .on_disabled_hover_text(tr("supervised.locked"));
+    ui.add(widgets::ToggleButton::new(
+        &mut settings.timed_challenges,
+        tr("settings.timed_challenges"),
+    ));
+    ui.add(widgets::ToggleButton::new(
+        &mut settings.reduced_motion,
+        tr("settings.reduced_motion"),
+    ));
+
+    if settings != get() {
+        set(ui.ctx(), settings);
+    }
+
+    ui.add_space(6.0);
+    if ui
+        .add(widgets::Button::new(tr("settings.reset_all")))
+        .clicked()
+    {
+        CONFIRM_RESET.store(true, Ordering::Relaxed);
+    }
+    if CONFIRM_RESET.load(Ordering::Relaxed) {
+        confirm_reset(ui.ctx());
+    }
+    ui.add_space(6.0);
+    supervised::controls(ui);
+    ui.add_space(6.0);
+    backup::controls(ui);
+}
+
+/// Asks before wiping every chapter and the saved progress. Escape or a
+/// click outside the dialog cancels.
+fn confirm_reset(ctx: &egui::Context) {
+    let modal = egui::Modal::new(egui::Id::new("confirm_reset")).show(ctx, |ui| {
+        ui.label(egui::RichText::new(tr("settings.reset_all")).heading());
+        ui.label(tr("settings.reset_all_warning"));
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            if ui
+                .add(widgets::Button::new(tr("settings.reset_all_confirm")))
+                .clicked()
+            {
+                chapters::reset_all();
+                CONFIRM_RESET.store(false, Ordering::Relaxed);
+            }
+            if ui
+                .add(widgets::Button::new(tr("settings.cancel")))
+                .clicked()
+            {
+                CONFIRM_RESET.store(false, Ordering::Relaxed);
+            }
+        });
+    });
+    if modal.should_close() {
+        CONFIRM_RESET.store(false, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::{OnceLock, RwLock};
+
+use GORBIE::prelude::*;
+
+use crate::chapters::{self, Chapter};
+use crate::i18n::tr;
+use crate::progress::ExerciseKey;
+use crate::rng::SEED_RANGE;
+
+/// Crockford's base32: no I, L, O or U, so a code read out loud or copied
+/// off a board cannot be misread.
+const DIGITS: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+/// Enough base32 digits for every seed below [`SEED_RANGE`].
+const SEED_DIGITS: u32 = 4;
+
+/// The short name in front of each code and the card it opens. Cards with
+/// a seed field and no entry here show no code.
+const CARDS: &[(&str, ExerciseKey)] = &[
+    ("EXPT", (Chapter::Expressions, "tree_exercise_state")),
+    ("EXPR", (Chapter::Expressions, "random_exercise_state")),
+    ("DICT", (Chapter::Expressions, "dictation_state")),
+    ("CODE", (Chapter::Expressions, "tree_to_code_state")),
+    ("CMPR", (Chapter::Expressions, "compare_state")),
+    ("BILD", (Chapter::Expressions, "builder_state")),
+    ("TRGT", (Chapter::Expressions, "target_state")),
+    ("PARN", (Chapter::Expressions, "paren_state")),
+    ("BOOT", (Chapter::Booleans, "tree_exercise_state")),
+    ("BOOL", (Chapter::Booleans, "random_exercise_state")),
+    ("SIMP", (Chapter::Booleans, "simplify_state")),
+    ("MAKE", (Chapter::Booleans, "assignment_state")),
+    ("TABL", (Chapter::Booleans, "reconstruct_state")),
+    ("STAT", (Chapter::State, "practice_state")),
+    ("STQZ", (Chapter::State, "quiz_state")),
+    ("IFEL", (Chapter::IfElse, "random_practice_state")),
+    ("TERM", (Chapter::Loops, "loop_termination_state")),
+    ("LOOP", (Chapter::Loops, "loop_practice_state")),
+    ("LPQZ", (Chapter::Loops, "quiz_state")),
+    ("FUNC", (Chapter::Functions, "function_practice_state")),
+    ("FNQZ", (Chapter::Functions, "quiz_state")),
+    ("MOVE", (Chapter::Ownership, "compile_quiz_state")),
+    ("BORR", (Chapter::Borrowing, "borrow_quiz_state")),
+    ("ARMS", (Chapter::StructsEnums, "arm_quiz_state")),
+    ("RSLT", (Chapter::Results, "outcome_quiz_state")),
+    ("BOND", (Chapter::Traits, "bound_quiz_state")),
+    ("ITER", (Chapter::Iterators, "output_quiz_state")),
+    ("SLCE", (Chapter::Strings, "slice_quiz_state")),
+    ("PATH", (Chapter::Modules, "path_quiz_state")),
+    ("ORDR", (Chapter::Concurrency, "order_quiz_state")),
+];
+
+/// What is typed into the load box, the last error, and a loaded exercise
+/// its card has not picked up yet.
+struct Share {
+    input: String,
+    error: Option<String>,
+    pending: Option<(ExerciseKey, u64)>,
+}
+
+static SHARE: OnceLock<RwLock<Share>> = OnceLock::new();
+
+fn share_lock() -> &'static RwLock<Share> {
+    SHARE.get_or_init(|| {
+        RwLock::new(Share {
+            input: String::new(),
+            error: None,
+            pending: None,
+        })
+    })
+}
+
+/// The code for the exercise with `seed` on the card `key`, e.g.
+/// `"EXPR-7FK2"`.
+pub fn code(key: ExerciseKey, seed: u64) -> Option<String> {
+    let (prefix, _) = CARDS.iter().find(|(_, card)| *card == key)?;
+    let digits: String = (0..SEED_DIGITS)
+        .rev()
+        .map(|place| DIGITS[(seed >> (5 * place)) as usize % 32] as char)
+        .collect();
+    Some(format!("{prefix}-{digits}"))
+}
+
+/// The card and seed behind a code. Case, spaces and the letters Crockford
+/// reads as digits are forgiven.
+pub fn parse(code: &str) -> Result<(ExerciseKey, u64), String> {
+    let code = code.trim().to_uppercase();
+    let (prefix, digits) = code
+        .split_once('-')
+        .ok_or_else(|| tr("share.malformed").replace("{code}", &code))?;
+    let key = CARDS
+        .iter()
+        .find(|(name, _)| *name == prefix.trim())
+        .map(|(_, key)| *key)
+        .ok_or_else(|| tr("share.unknown").replace("{code}", &code))?;
+    let digits = digits.trim();
+    if digits.chars().count() != SEED_DIGITS as usize {
+        return Err(tr("share.malformed").replace("{code}", &code));
+    }
+    let mut seed = 0;
+    for digit in digits.chars() {
+        let digit = match digit {
+            'O' => '0',
+            'I' | 'L' => '1',
+            other => other,
+        };
+        let value = DIGITS
+            .iter()
+            .position(|known| *known as char == digit)
+            .ok_or_else(|| tr("share.malformed").replace("{code}", &code))?;
+        seed = seed * 32 + value as u64;
+    }
+    if seed >= SEED_RANGE {
+        return Err(tr("share.malformed").replace("{code}", &code));
+    }
+    Ok((key, seed))
+}
+
+/// The seed loaded for the card `key`, once. The card calls this every
+/// frame and switches to the exercise when it gets one.
+pub fn take(key: ExerciseKey) -> Option<u64> {
+    let mut share = share_lock().write().expect("share lock poisoned");
+    match share.pending {
+        Some((pending, seed)) if pending == key => {
+            share.pending = None;
+            Some(seed)
+        }
+        _ => None,
+    }
+}
+
+/// The "Load exercise" box: a code typed in here opens its card with the
+/// same exercise.
+pub fn load_box(ui: &mut egui::Ui) {
+    let mut share = share_lock().write().expect("share lock poisoned");
+    let mut load = None;
+    ui.horizontal(|ui| {
+        ui.label(tr("share.code"));
+        let response = ui.add(widgets::TextField::singleline(&mut share.input));
+        let submitted =
+            response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+        if ui
+            .add(widgets::Button::new(tr("share.load")).small())
+            .on_hover_text(tr("share.load_hover"))
+            .clicked()
+            || submitted
+        {
+            load = Some(parse(&share.input));
+        }
+    });
+    match load {
+        Some(Ok((key, seed))) => {
+            share.pending = Some((key, seed));
+            share.input.clear();
+            share.error = None;
+            chapters::open_exercise(key);
+        }
+        Some(Err(error)) => share.error = Some(error),
+        None => {}
+    }
+    if let Some(error) = &share.error {
+        ui.label(egui::RichText::new(error).weak());
+    }
+}
@@ -0,0 +1,224 @@
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use GORBIE::prelude::*;
+
+use crate::i18n::{self, tr, Language};
+use crate::presentation;
+use crate::settings;
+
+/// Words per minute at the normal read-aloud speed.
+const WORDS_PER_MINUTE: f32 = 160.0;
+
+/// The card being read aloud. Only one card speaks at a time.
+struct Reading {
+    child: Child,
+    /// The markdown the card was built from, to tell the cards apart.
+    text: String,
+    paused: bool,
+}
+
+static READING: OnceLock<RwLock<Option<Reading>>> = OnceLock::new();
+/// Set once starting the synthesizer failed, so the buttons stop trying.
+static UNAVAILABLE: AtomicBool = AtomicBool::new(false);
+
+fn reading_lock() -> &'static RwLock<Option<Reading>> {
+    READING.get_or_init(|| RwLock::new(None))
+}
+
+/// A markdown card with a speaker button that reads it aloud, for learners
+/// who are still getting used to reading.
+pub fn lesson(nb: &mut NotebookCtx, text: &'static str) {
+    nb.view(move |ui| markdown(ui, text));
+}
+
+/// Markdown with the speaker button, for prose inside a card or text that
+/// is put together when the card is drawn.
+pub fn markdown(ui: &mut egui::Ui, text: &str) {
+    presentation::card_stop(ui);
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+        button(ui, text);
+    });
+    md!(ui, "{text}");
+}
+
+/// The speaker button on its own, with pause and stop while `text` is
+/// being read. Card headings use it to read their title.
+pub fn button(ui: &mut egui::Ui, text: &str) {
+    if UNAVAILABLE.load(Ordering::Relaxed) {
+        ui.add_enabled(false, widgets::Button::new("\u{1F50A}").small())
+            .on_disabled_hover_text(tr("speech.missing"));
+        return;
+    }
+    let mut reading = reading_lock().write().expect("reading lock poisoned");
+    // Forget a reading that has finished on its own.
+    if reading
+        .as_mut()
+        .is_some_and(|current| !matches!(current.child.try_wait(), Ok(None)))
+    {
+        *reading = None;
+    }
+    match reading.as_mut().filter(|current| current.text == text) {
+        Some(current) => {
+            // Repaint now and then so the buttons reset when the reading ends.
+            ui.ctx().request_repaint_after(Duration::from_millis(250));
+            if ui
+                .add(widgets::Button::new(tr("speech.stop")).small())
+                .clicked()
+            {
+                stop(reading.take());
+                return;
+            }
+            if cfg!(unix) {
+                let label = if current.paused {
+                    tr("speech.resume")
+                } else {
+                    tr("speech.pause")
+                };
+                if ui.add(widgets::Button::new(label).small()).clicked() {
+                    current.paused = !current.paused;
+                    signal(
+                        &current.child,
+                        if current.paused { "-STOP" } else { "-CONT" },
+                    );
+                }
+            }
+        }
+        None => {
+            if ui
+                .add(widgets::Button::new(tr("speech.play")).small())
+                .on_hover_text(tr("speech.hover"))
+                .clicked()
+            {
+                stop(reading.take());
+                match speak(&plain_text(text), i18n::language_of(text)) {
+                    Ok(child) => {
+                        *reading = Some(Reading {
+                            child,
+                            text: text.to_string(),
+                            paused: false,
+                        })
+                    }
+                    Err(error) => {
+                        eprintln!("Could not read aloud: {error}");
+                        UNAVAILABLE.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn stop(reading: Option<Reading>) {
+    if let Some(mut reading) = reading {
+        // A paused process has to be woken up before it can exit.
+        if reading.paused {
+            signal(&reading.child, "-CONT");
+        }
+        let _ = reading.child.kill();
+        let _ = reading.child.wait();
+    }
+}
+
+fn signal(child: &Child, signal: &str) {
+    let _ = Command::new("kill")
+        .arg(signal)
+        .arg(child.id().to_string())
+        .status();
+}
+
+/// Starts the system speech synthesizer on `text`: espeak-ng or espeak on
+/// Linux, with a voice for `language`, and the default voice of `say` on
+/// macOS and of the .NET synthesizer on Windows. The text goes in through
+/// stdin so it needs no quoting.
+fn speak(text: &str, language: Language) -> Result<Child, String> {
+    let settings = settings::get();
+    let words_per_minute = (WORDS_PER_MINUTE * settings.speech_rate).round() as u32;
+    let commands: Vec<Command> = if cfg!(target_os = "macos") {
+        let mut say = Command::new("say");
+        say.arg("-r").arg(words_per_minute.to_string());
+        vec![say]
+    } else if cfg!(windows) {
+        // The .NET rate runs from -10 to 10 with 0 as the normal speed.
+        let rate = ((settings.speech_rate - 1.0) * 10.0)
+            .round()
+            .clamp(-10.0, 10.0);
+        let mut powershell = Command::new("powershell");
+        powershell.arg("-NoProfile").arg("-Command").arg(format!(
+            "Add-Type -AssemblyName System.Speech; \
+             $voice = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             $voice.Rate = {rate}; \
+             $voice.Speak([Console]::In.ReadToEnd())"
+        ));
+        vec![powershell]
+    } else {
+        ["espeak-ng", "espeak"]
+            .into_iter()
+            .map(|program| {
+                let mut espeak = Command::new(program);
+                espeak
+                    .arg("--stdin")
+                    .arg("-s")
+                    .arg(words_per_minute.to_string())
+                    .arg("-v")
+                    .arg(language.code());
+                espeak
+            })
+            .collect()
+    };
+    let mut last_error = String::from("no speech synthesizer for this system");
+    for mut command in commands {
+        let spawned = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(mut child) => {
+                let written = match child.stdin.take() {
+                    Some(mut stdin) => stdin.write_all(text.as_bytes()),
+                    None => Ok(()),
+                };
+                if let Err(error) = written {
+                    // The synthesizer would wait for the rest of the text
+                    // forever, so it is stopped and reaped here.
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(error.to_string());
+                }
+                return Ok(child);
+            }
+            Err(error) => last_error = format!("{command:?}: {error}"),
+        }
+    }
+    Err(last_error)
+}
+
+/// The card text without markdown markup and without code blocks, which
+/// read aloud as a string of symbols.
+fn plain_text(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut in_code = false;
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+        let line = line.trim_start_matches(['#', '-', '>', ' ']);
+        let line: String = line
+            .chars()
+            .filter(|c| !matches!(c, '*' | '`' | '_'))
+            .collect();
+        if !line.is_empty() {
+            text.push_str(&line);
+            text.push('\n');
+        }
+    }
+    text
+}
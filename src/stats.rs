@@ -0,0 +1,240 @@
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use web_time::Instant;
+
+use crate::chapters::{self, Chapter};
+use crate::storage;
+
+const FILE: &str = "stats.txt";
+/// Time per card, kept apart so the chapter file keeps its format.
+const CARD_FILE: &str = "card_time.txt";
+/// A gap between frames longer than this means the learner was away, and
+/// does not count as time in the chapter.
+const IDLE: Duration = Duration::from_secs(300);
+/// Time is saved in steps of this many seconds, not every frame.
+const SAVE_EVERY: u64 = 10;
+
+/// Answers and time in one chapter.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ChapterStats {
+    pub right: u32,
+    pub wrong: u32,
+    pub seconds: f64,
+}
+
+impl ChapterStats {
+    pub fn attempts(&self) -> u32 {
+        self.right + self.wrong
+    }
+
+    /// Right answers as a whole percentage, or `None` before the first one.
+    pub fn accuracy(&self) -> Option<u32> {
+        let attempts = self.attempts();
+        (attempts > 0).then(|| self.right * 100 / attempts)
+    }
+
+    /// Whether the learner has opened the chapter or answered anything in it.
+    pub fn started(&self) -> bool {
+        self.attempts() > 0 || self.seconds > 0.0
+    }
+}
+
+/// Keyed by chapter id, like the progress file.
+type Stats = BTreeMap<String, ChapterStats>;
+
+/// Seconds per chapter id and card heading.
+type CardTimes = BTreeMap<(String, String), f64>;
+
+static STATS: OnceLock<RwLock<Stats>> = OnceLock::new();
+static LAST_FRAME: OnceLock<RwLock<Option<Instant>>> = OnceLock::new();
+static CARD_TIMES: OnceLock<RwLock<CardTimes>> = OnceLock::new();
+/// The heading of the card under the pointer in the frame drawn last.
+static POINTED_CARD: OnceLock<RwLock<Option<String>>> = OnceLock::new();
+
+fn stats_lock() -> &'static RwLock<Stats> {
+    STATS.get_or_init(|| RwLock::new(load()))
+}
+
+fn last_frame_lock() -> &'static RwLock<Option<Instant>> {
+    LAST_FRAME.get_or_init(|| RwLock::new(None))
+}
+
+fn card_times_lock() -> &'static RwLock<CardTimes> {
+    CARD_TIMES.get_or_init(|| RwLock::new(load_card_times()))
+}
+
+fn pointed_card_lock() -> &'static RwLock<Option<String>> {
+    POINTED_CARD.get_or_init(|| RwLock::new(None))
+}
+
+/// One `chapter<TAB>card<TAB>seconds` line per card.
+fn load_card_times() -> CardTimes {
+    let mut times = CardTimes::new();
+    let Some(text) = storage::load(CARD_FILE) else {
+        return times;
+    };
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        let (Some(chapter), Some(card), Some(Ok(seconds))) =
+            (fields.next(), fields.next(), fields.next().map(str::parse))
+        else {
+            continue;
+        };
+        times.insert((chapter.to_string(), card.to_string()), seconds);
+    }
+    times
+}
+
+fn write_card_times(times: &CardTimes) {
+    let mut text = String::new();
+    for ((chapter, card), seconds) in times {
+        text.push_str(&format!("{chapter}\t{card}\t{seconds:.0}\n"));
+    }
+    if let Err(error) = storage::save(CARD_FILE, &text) {
+        eprintln!("Could not save the time per card: {error}");
+    }
+}
+
+/// One `chapter<TAB>right<TAB>wrong<TAB>seconds` line per chapter.
+fn load() -> Stats {
+    let mut stats = Stats::new();
+    let Some(text) = storage::load(FILE) else {
+        return stats;
+    };
+    for line in text.lines() {
+        let mut fields = line.split('\t');
+        let (Some(chapter), Some(right), Some(wrong), Some(seconds)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        if let (Ok(right), Ok(wrong), Ok(seconds)) = (right.parse(), wrong.parse(), seconds.parse())
+        {
+            stats.insert(
+                chapter.to_string(),
+                ChapterStats {
+                    right,
+                    wrong,
+                    seconds,
+                },
+            );
+        }
+    }
+    stats
+}
+
+fn save(stats: &Stats) -> Result<(), String> {
+    let mut text = String::new();
+    for (chapter, entry) in stats {
+        text.push_str(&format!(
+            "{chapter}\t{}\t{}\t{:.0}\n",
+            entry.right, entry.wrong, entry.seconds
+        ));
+    }
+    storage::save(FILE, &text)
+}
+
+fn write(stats: &Stats) {
+    if let Err(error) = save(stats) {
+        eprintln!("Could not save the learning statistics: {error}");
+    }
+}
+
+/// Counts one answer towards the open chapter.
+pub fn record(correct: bool) {
+    let id = chapters::def(chapters::current_chapter()).id.to_string();
+    let mut stats = stats_lock().write().expect("stats lock poisoned");
+    let entry = stats.entry(id).or_default();
+    if correct {
+        entry.right += 1;
+    } else {
+        entry.wrong += 1;
+    }
+    write(&stats);
+}
+
+/// Adds the time since the last frame to the open chapter. Called once per
+/// frame.
+pub fn tick() {
+    let now = Instant::now();
+    let Some(last) = last_frame_lock()
+        .write()
+        .expect("last frame lock poisoned")
+        .replace(now)
+    else {
+        return;
+    };
+    let elapsed = now.duration_since(last);
+    let def = chapters::def(chapters::current_chapter());
+    if elapsed > IDLE || !def.is_lesson() {
+        return;
+    }
+    let id = def.id.to_string();
+    let pointed = pointed_card_lock()
+        .write()
+        .expect("pointed card lock poisoned")
+        .take();
+    let mut times = card_times_lock().write().expect("card times lock poisoned");
+    if let Some(card) = pointed {
+        *times.entry((id.clone(), card)).or_default() += elapsed.as_secs_f64();
+    }
+    let mut stats = stats_lock().write().expect("stats lock poisoned");
+    let entry = stats.entry(id).or_default();
+    let before = entry.seconds as u64 / SAVE_EVERY;
+    entry.seconds += elapsed.as_secs_f64();
+    if entry.seconds as u64 / SAVE_EVERY != before {
+        write(&stats);
+        write_card_times(&times);
+    }
+}
+
+/// Notes that the card titled `title` is drawn. Time goes to the card
+/// under the pointer, the closest the app gets to where the learner looks.
+pub fn card_drawn(ui: &egui::Ui, title: &str) {
+    if ui.response().contains_pointer() {
+        *pointed_card_lock()
+            .write()
+            .expect("pointed card lock poisoned") = Some(title.to_string());
+    }
+}
+
+/// The cards the most time went to, longest first, as chapter id, card
+/// heading and seconds.
+pub fn top_cards(count: usize) -> Vec<(String, String, f64)> {
+    let times = card_times_lock().read().expect("card times lock poisoned");
+    let mut cards: Vec<(String, String, f64)> = times
+        .iter()
+        .map(|((chapter, card), seconds)| (chapter.clone(), card.clone(), *seconds))
+        .collect();
+    cards.sort_by(|a, b| b.2.total_cmp(&a.2));
+    cards.truncate(count);
+    cards
+}
+
+/// The numbers for `chapter`, all zero if it was never opened.
+pub fn chapter(chapter: Chapter) -> ChapterStats {
+    stats_lock()
+        .read()
+        .expect("stats lock poisoned")
+        .get(chapters::def(chapter).id)
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Forgets every answer and all the time spent.
+pub fn clear() {
+    let mut stats = stats_lock().write().expect("stats lock poisoned");
+    stats.clear();
+    write(&stats);
+    let mut times = card_times_lock().write().expect("card times lock poisoned");
+    times.clear();
+    write_card_times(&times);
+}
+
+/// Reads the statistics from an imported backup.
+pub fn reload() {
+    *stats_lock().write().expect("stats lock poisoned") = load();
+    *card_times_lock().write().expect("card times lock poisoned") = load_card_times();
+}
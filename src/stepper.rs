@@ -0,0 +1,157 @@
+use std::time::Duration;
+
+use web_time::Instant;
+use GORBIE::prelude::*;
+
+use crate::i18n::tr;
+use crate::keyboard;
+use crate::settings;
+
+/// A sequence the learner walks through one step at a time.
+pub trait Steps {
+    fn count(&self) -> usize;
+    fn show(&self, ui: &mut egui::Ui, step: usize);
+}
+
+/// Prev/Next/Reset buttons and a step counter. The arrow keys move too while
+/// the card is active, see [`keyboard::card_active`].
+pub struct Controls<'a> {
+    step: &'a mut usize,
+    last: usize,
+    next_label: &'a str,
+    counter_label: &'a str,
+}
+
+impl<'a> Controls<'a> {
+    /// `last` is the index of the final step; `step` is clamped to it.
+    pub fn new(step: &'a mut usize, last: usize) -> Self {
+        Self {
+            step,
+            last,
+            next_label: tr("stepper.next"),
+            counter_label: tr("stepper.step"),
+        }
+    }
+
+    pub fn next_label(mut self, label: &'a str) -> Self {
+        self.next_label = label;
+        self
+    }
+
+    pub fn counter_label(mut self, label: &'a str) -> Self {
+        self.counter_label = label;
+        self
+    }
+
+    pub fn show(self, ui: &mut egui::Ui) {
+        let Self {
+            step,
+            last,
+            next_label,
+            counter_label,
+        } = self;
+        if *step > last {
+            *step = last;
+        }
+
+        if keyboard::card_active(ui) {
+            let (back, forward) = ui.input(|input| {
+                (
+                    input.key_pressed(egui::Key::ArrowLeft),
+                    input.key_pressed(egui::Key::ArrowRight),
+                )
+            });
+            if back {
+                *step = step.saturating_sub(1);
+            }
+            if forward {
+                *step = (*step + 1).min(last);
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(*step > 0, widgets::Button::new(tr("stepper.prev")))
+                .clicked()
+            {
+                *step = step.saturating_sub(1);
+            }
+            if ui
+                .add_enabled(*step < last, widgets::Button::new(next_label))
+                .clicked()
+            {
+                *step = (*step + 1).min(last);
+            }
+            if ui.add(widgets::Button::new(tr("stepper.reset"))).clicked() {
+                *step = 0;
+            }
+            ui.add_space(6.0);
+            ui.label(format!("{counter_label} {}/{last}", *step));
+        });
+    }
+}
+
+/// Plays a stepper like an animation: a Play/Pause button and a slider for
+/// the seconds each step stays up. Cards keep one in their state.
+pub struct Autoplay {
+    /// While playing: when the current step appeared.
+    since: Option<Instant>,
+    seconds: f32,
+}
+
+impl Default for Autoplay {
+    fn default() -> Self {
+        Self {
+            since: None,
+            seconds: 1.0,
+        }
+    }
+}
+
+impl Autoplay {
+    pub fn stop(&mut self) {
+        self.since = None;
+    }
+
+    /// Moves `step` on once its time is up, stopping at `last`, and shows
+    /// the controls. Playing from the last step starts over.
+    pub fn show(&mut self, ui: &mut egui::Ui, step: &mut usize, last: usize) {
+        let interval = settings::animation_interval(Duration::from_secs_f32(self.seconds));
+        if let Some(since) = self.since {
+            if since.elapsed() >= interval {
+                *step = (*step + 1).min(last);
+                self.since = (*step < last).then(Instant::now);
+            }
+        }
+        ui.horizontal(|ui| {
+            let label = if self.since.is_some() {
+                tr("stepper.pause")
+            } else {
+                tr("stepper.play")
+            };
+            // A click while playing pauses: `take` clears the start time.
+            if ui.add(widgets::Button::new(label)).clicked() && self.since.take().is_none() {
+                if *step >= last {
+                    *step = 0;
+                }
+                self.since = Some(Instant::now());
+            }
+            ui.add(widgets::Slider::new(&mut self.seconds, 0.25..=3.0).text(tr("stepper.seconds")));
+        });
+        if self.since.is_some() {
+            ui.ctx().request_repaint_after(interval);
+        }
+    }
+}
+
+/// Controls followed by the current step, for cards whose whole body is the
+/// step view.
+pub fn stepper(ui: &mut egui::Ui, step: &mut usize, steps: &impl Steps) {
+    let count = steps.count();
+    if count == 0 {
+        return;
+    }
+    Controls::new(step, count - 1).show(ui);
+    ui.add_space(8.0);
+    steps.show(ui, *step);
+}
@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+
+/// Headless runs render screenshots and should neither see nor change what
+/// the learner saved. The web build has no file system to save to.
+fn persistent() -> bool {
+    cfg!(not(target_arch = "wasm32")) && !std::env::args().any(|arg| arg == "--headless")
+}
+
+fn data_path(name: &str) -> Option<PathBuf> {
+    let data = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(data.join("gorbie_teaches_cs").join(name))
+}
+
+/// `name` in the home directory, for files the learner picks up themselves,
+/// like backups and exported images.
+pub fn home_file(name: &str) -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(name)
+}
+
+/// The contents of a saved file, or `None` if there is none yet.
+pub fn load(name: &str) -> Option<String> {
+    if !persistent() {
+        return None;
+    }
+    std::fs::read_to_string(data_path(name)?).ok()
+}
+
+pub fn save(name: &str, text: &str) -> Result<(), String> {
+    if !persistent() {
+        return Ok(());
+    }
+    let path = data_path(name).ok_or_else(|| format!("No place to store {name}"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+    }
+    std::fs::write(path, text).map_err(|error| error.to_string())
+}
@@ -0,0 +1,106 @@
+use std::sync::{OnceLock, RwLock};
+
+use GORBIE::prelude::*;
+
+use crate::i18n::tr;
+use crate::storage;
+
+/// Holds the PIN while supervised mode is on. Kept out of backups, so
+/// importing one cannot switch the lock off.
+const FILE: &str = "supervised.txt";
+const MIN_PIN_DIGITS: usize = 4;
+
+/// The PIN is stored as typed: it keeps a learner from peeking at
+/// answers during a test, it is not meant to stop anyone with the files.
+struct Supervision {
+    pin: Option<String>,
+    input: String,
+    error: Option<String>,
+}
+
+static SUPERVISION: OnceLock<RwLock<Supervision>> = OnceLock::new();
+
+fn supervision_lock() -> &'static RwLock<Supervision> {
+    SUPERVISION.get_or_init(|| {
+        let pin = storage::load(FILE)
+            .map(|text| text.trim().to_string())
+            .filter(|pin| !pin.is_empty());
+        RwLock::new(Supervision {
+            pin,
+            input: String::new(),
+            error: None,
+        })
+    })
+}
+
+fn save(pin: Option<&str>) {
+    if let Err(error) = storage::save(FILE, pin.unwrap_or_default()) {
+        eprintln!("Could not save supervised mode: {error}");
+    }
+}
+
+/// Whether hints, "Show me" and worked solutions are locked away.
+pub fn active() -> bool {
+    supervision_lock()
+        .read()
+        .expect("supervision lock poisoned")
+        .pin
+        .is_some()
+}
+
+/// The supervised mode section of the settings: a PIN field that locks,
+/// or unlocks again with the same PIN.
+pub fn controls(ui: &mut egui::Ui) {
+    let mut supervision = supervision_lock()
+        .write()
+        .expect("supervision lock poisoned");
+    ui.label(egui::RichText::new(tr("supervised.title")).strong());
+    ui.label(tr(if supervision.pin.is_some() {
+        "supervised.on"
+    } else {
+        "supervised.intro"
+    }));
+    let mut submit = false;
+    ui.horizontal(|ui| {
+        ui.label(tr("supervised.pin"));
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut supervision.input)
+                .password(true)
+                .desired_width(80.0),
+        );
+        submit = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+        let label = if supervision.pin.is_some() {
+            tr("supervised.unlock")
+        } else {
+            tr("supervised.lock")
+        };
+        submit |= ui.add(widgets::Button::new(label).small()).clicked();
+    });
+    if submit {
+        let input = std::mem::take(&mut supervision.input);
+        let input = input.trim();
+        match &supervision.pin {
+            Some(pin) if pin == input => {
+                supervision.pin = None;
+                supervision.error = None;
+                save(None);
+            }
+            Some(_) => supervision.error = Some(tr("supervised.wrong_pin").to_string()),
+            None if input.chars().count() < MIN_PIN_DIGITS
+                || !input.chars().all(|digit| digit.is_ascii_digit()) =>
+            {
+                supervision.error = Some(
+                    tr("supervised.short_pin").replace("{digits}", &MIN_PIN_DIGITS.to_string()),
+                );
+            }
+            None => {
+                supervision.pin = Some(input.to_string());
+                supervision.error = None;
+                save(Some(input));
+            }
+        }
+    }
+    if let Some(error) = &supervision.error {
+        ui.label(egui::RichText::new(error).weak());
+    }
+}
@@ -0,0 +1,164 @@
+use egui::{Color32, Pos2, Rect, Stroke};
+use web_time::{SystemTime, UNIX_EPOCH};
+use GORBIE::prelude::*;
+
+use crate::handout;
+use crate::i18n::tr;
+use crate::storage;
+
+/// A diagram as SVG markup, drawn from the same boxes, lines and labels the
+/// painter gets, so it can go into printed handouts.
+pub struct Svg {
+    /// The diagram's area on screen. Its top left corner becomes `(0, 0)`.
+    area: Rect,
+    body: String,
+}
+
+/// `#rrggbb` and an opacity from 0 to 1.
+fn color(color: Color32) -> (String, f32) {
+    let [r, g, b, a] = color.to_srgba_unmultiplied();
+    (format!("#{r:02x}{g:02x}{b:02x}"), a as f32 / 255.0)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Svg {
+    pub fn new(area: Rect, background: Color32) -> Self {
+        let mut svg = Self {
+            area,
+            body: String::new(),
+        };
+        svg.rect(
+            Rect::from_min_size(area.min, area.size()),
+            0.0,
+            background,
+            Stroke::NONE,
+        );
+        svg
+    }
+
+    fn point(&self, point: Pos2) -> Pos2 {
+        point - self.area.min.to_vec2()
+    }
+
+    pub fn rect(&mut self, rect: Rect, radius: f32, fill: Color32, stroke: Stroke) {
+        let min = self.point(rect.min);
+        let (fill, fill_opacity) = color(fill);
+        let (line, line_opacity) = color(stroke.color);
+        self.body.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{radius:.1}\" \
+             fill=\"{fill}\" fill-opacity=\"{fill_opacity:.2}\" stroke=\"{line}\" \
+             stroke-opacity=\"{line_opacity:.2}\" stroke-width=\"{:.1}\"/>\n",
+            min.x,
+            min.y,
+            rect.width(),
+            rect.height(),
+            stroke.width,
+        ));
+    }
+
+    pub fn polyline(&mut self, points: &[Pos2], stroke: Stroke, dashed: bool) {
+        let points: Vec<String> = points
+            .iter()
+            .map(|point| {
+                let point = self.point(*point);
+                format!("{:.1},{:.1}", point.x, point.y)
+            })
+            .collect();
+        let (line, opacity) = color(stroke.color);
+        let dashes = if dashed {
+            format!(
+                " stroke-dasharray=\"{:.1} {:.1}\"",
+                stroke.width * 3.0,
+                stroke.width * 2.0
+            )
+        } else {
+            String::new()
+        };
+        self.body.push_str(&format!(
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{line}\" stroke-opacity=\"{opacity:.2}\" \
+             stroke-width=\"{:.1}\" stroke-linejoin=\"round\"{dashes}/>\n",
+            points.join(" "),
+            stroke.width,
+        ));
+    }
+
+    pub fn circle(&mut self, center: Pos2, radius: f32, fill: Color32) {
+        let center = self.point(center);
+        let (fill, opacity) = color(fill);
+        self.body.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{radius:.1}\" fill=\"{fill}\" \
+             fill-opacity=\"{opacity:.2}\"/>\n",
+            center.x, center.y,
+        ));
+    }
+
+    /// Monospace text centered on `center`, one row per line.
+    pub fn text(&mut self, center: Pos2, text: &str, size: f32, fill: Color32) {
+        let center = self.point(center);
+        let (fill, opacity) = color(fill);
+        let lines: Vec<&str> = text.lines().collect();
+        let first = -(lines.len() as f32 - 1.0) / 2.0;
+        for (index, line) in lines.iter().enumerate() {
+            let y = center.y + (first + index as f32) * size * 1.2;
+            self.body.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{y:.1}\" font-family=\"monospace\" font-size=\"{size:.1}\" \
+                 text-anchor=\"middle\" dominant-baseline=\"central\" fill=\"{fill}\" \
+                 fill-opacity=\"{opacity:.2}\">{}</text>\n",
+                center.x,
+                escape(line),
+            ));
+        }
+    }
+
+    pub fn finish(&self) -> String {
+        let size = self.area.size();
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" \
+             viewBox=\"0 0 {w:.1} {h:.1}\">\n{}</svg>\n",
+            self.body,
+            w = size.x,
+            h = size.y,
+        )
+    }
+}
+
+/// Writes the diagram to `gorbie_<name>_<time>.svg` in the home directory.
+fn save(name: &str, svg: &Svg) -> Result<String, String> {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let path = storage::home_file(&format!("gorbie_{name}_{seconds}.svg"));
+    std::fs::write(&path, svg.finish()).map_err(|error| format!("{}: {error}", path.display()))?;
+    Ok(path.display().to_string())
+}
+
+/// An "Export image" button under a diagram. `build` draws the diagram as
+/// it is on screen right now; where the file went shows next to the button.
+pub fn export_button(ui: &mut egui::Ui, name: &str, build: impl Fn() -> Svg) {
+    if handout::capturing() {
+        handout::diagram(build());
+    }
+    let id = ui.id().with(("svg_export", name));
+    ui.horizontal(|ui| {
+        if ui
+            .add(widgets::Button::new(tr("export.image")).small())
+            .on_hover_text(tr("export.image_hover"))
+            .clicked()
+        {
+            let message = match save(name, &build()) {
+                Ok(path) => tr("export.saved").replace("{path}", &path),
+                Err(error) => error,
+            };
+            ui.data_mut(|data| data.insert_temp(id, message));
+        }
+        if let Some(message) = ui.data(|data| data.get_temp::<String>(id)) {
+            ui.label(egui::RichText::new(message).weak());
+        }
+    });
+}
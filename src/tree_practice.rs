@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use egui::RichText;
+use web_time::Instant;
+use GORBIE::prelude::*;
+
+use crate::codeview::CodeBlock;
+use crate::coop::Coop;
+use crate::difficulty::Difficulty;
+use crate::expr::{
+    self, expr_at_path, expr_to_string, find_reducible, reduce_at, render_expr_with_highlight,
+    Expr, Language,
+};
+use crate::hints::HintLadder;
+use crate::i18n::tr;
+use crate::keyboard::TreeCursor;
+use crate::mastery;
+use crate::patterns;
+use crate::progress::{self, ExerciseKey};
+use crate::rng::{self, SimpleRng};
+use crate::settings;
+use crate::tree_view::draw_tree_interactive;
+
+/// How long each step of "Show me" stays up before the next one.
+const DEMO_STEP: Duration = Duration::from_millis(1200);
+
+/// What a tree practice card says about its own kind of values.
+pub struct Wording {
+    /// Keeps the clicks on this tree apart from other trees on the page.
+    pub id: &'static str,
+    /// The translation key of the first hint: how to find the next box.
+    pub nudge: &'static str,
+    /// The translation key of the feedback for a click on a box that is a
+    /// value already.
+    pub already_value: &'static str,
+    /// The lesson [`patterns::tip`] points back to.
+    pub lesson: &'static str,
+}
+
+/// A random tree to work out box by box, with hints, co-op turns and a
+/// "Show me" that plays the steps at a readable pace.
+pub struct TreePractice<L: Language> {
+    key: ExerciseKey,
+    generate: fn(&mut SimpleRng, Difficulty) -> Expr<L>,
+    wording: Wording,
+    rng: SimpleRng,
+    seed: u64,
+    expr: Expr<L>,
+    /// The mastery tier of the tree as generated.
+    tier: usize,
+    feedback: Option<String>,
+    coop: Coop,
+    hints: HintLadder,
+    /// While "Show me" plays: when the highlighted step appeared.
+    demo: Option<Instant>,
+    /// The tree was solved by "Show me", so it does not count as solved.
+    watched: bool,
+    /// The code of the tree before each step taken, oldest first.
+    history: Vec<String>,
+    cursor: TreeCursor,
+}
+
+impl<L: Language> TreePractice<L> {
+    /// Practice for the exercise `key`, on trees from `generate`.
+    pub fn new(
+        key: ExerciseKey,
+        generate: fn(&mut SimpleRng, Difficulty) -> Expr<L>,
+        wording: Wording,
+    ) -> Self {
+        let seed = rng::fresh_seed();
+        let mut rng = SimpleRng::new(seed);
+        let expr = generate(&mut rng, Difficulty::of_seed(seed));
+        let tier = mastery::tree_tier(&expr);
+        Self {
+            key,
+            generate,
+            wording,
+            rng,
+            seed,
+            expr,
+            tier,
+            feedback: None,
+            coop: Coop::default(),
+            hints: HintLadder::default(),
+            demo: None,
+            watched: false,
+            history: Vec::new(),
+            cursor: TreeCursor::default(),
+        }
+    }
+
+    fn regenerate(&mut self) {
+        let seed = self.rng.next_seed();
+        self.load_seed(seed);
+    }
+
+    fn load_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = SimpleRng::new(seed);
+        self.expr = (self.generate)(&mut self.rng, Difficulty::of_seed(seed));
+        self.tier = mastery::tree_tier(&self.expr);
+        self.feedback = None;
+        self.hints.reset();
+        self.demo = None;
+        self.watched = false;
+        self.history.clear();
+        self.cursor.reset();
+    }
+
+    /// Evaluates the highlighted step of the demo. Returns when the next
+    /// step appeared, or `None` once the tree is a single value.
+    fn demo_step(&mut self) -> Option<Instant> {
+        let path = find_reducible(&self.expr)?;
+        let expr = reduce_at(self.expr.clone(), &path).ok()?;
+        self.history.push(expr_to_string(&self.expr));
+        self.expr = expr;
+        find_reducible(&self.expr).map(|_| Instant::now())
+    }
+
+    /// The card below its heading and introduction: the controls, the code
+    /// and the tree to click, and the feedback.
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        let (chapter, _) = self.key;
+        self.coop.controls(ui);
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            if ui
+                .add(widgets::Button::new(tr("tree_practice.new")))
+                .clicked()
+            {
+                self.regenerate();
+            }
+            if let Some(seed) = rng::seed_field(ui, self.key, self.seed) {
+                self.load_seed(seed);
+            }
+            if self.hints.button(ui, self.key) {
+                self.feedback = None;
+            }
+            let solvable = self.demo.is_none() && find_reducible(&self.expr).is_some();
+            if HintLadder::show_me(ui, solvable) {
+                self.demo = Some(Instant::now());
+                self.watched = true;
+                self.feedback = None;
+                self.hints.reset();
+            }
+        });
+        ui.add_space(6.0);
+
+        if let Some(shown) = self.demo {
+            let interval = settings::animation_interval(DEMO_STEP);
+            if shown.elapsed() >= interval {
+                self.demo = self.demo_step();
+            }
+            ui.ctx().request_repaint_after(interval);
+        }
+        let next_path = find_reducible(&self.expr);
+        let hints = if self.demo.is_some() {
+            HintLadder::revealed()
+        } else {
+            self.hints
+        };
+        let highlight_path = hints.marked(next_path.as_deref());
+        let done = next_path.is_none();
+
+        expr::history(ui, &self.history);
+        let (expression, expression_ranges) =
+            render_expr_with_highlight(&self.expr, highlight_path);
+        ui.add(CodeBlock::line(&expression).highlight_ranges(&expression_ranges));
+        ui.add_space(6.0);
+        hints.nudge(ui, tr(self.wording.nudge));
+
+        let pressed = self.cursor.keys(ui, &self.expr);
+        let tree = self.expr.to_tree();
+        let clicked = draw_tree_interactive(
+            ui,
+            self.wording.id,
+            &tree,
+            |path| hints.marks(next_path.as_deref(), path),
+            self.cursor.selected(),
+        );
+        ui.label(RichText::new(tr("tree_practice.keys")).small().weak());
+        if !done && self.demo.is_none() {
+            if let Some(path) = clicked.or(pressed) {
+                if next_path.as_ref() == Some(&path) {
+                    match reduce_at(self.expr.clone(), &path) {
+                        Ok(expr) => {
+                            self.history.push(expr_to_string(&self.expr));
+                            self.expr = expr;
+                            self.feedback = None;
+                            self.hints.reset();
+                            if self.expr.is_value() {
+                                mastery::solved(chapter, self.tier);
+                                if !self.watched {
+                                    progress::answer(self.key, true);
+                                }
+                            }
+                            self.coop.record(true);
+                        }
+                        Err(error) => {
+                            self.feedback =
+                                Some(tr("tree_practice.oops").replace("{error}", &error));
+                        }
+                    }
+                } else {
+                    let value = expr_at_path(&self.expr, &path).is_some_and(Expr::is_value);
+                    self.feedback = Some(if value {
+                        tr(self.wording.already_value).to_string()
+                    } else {
+                        tr("tree_practice.not_yet").to_string()
+                    });
+                    self.coop.record(false);
+                    if let Some(pattern) = next_path
+                        .as_deref()
+                        .and_then(|next| patterns::classify(&self.expr, &path, next))
+                    {
+                        patterns::record(chapter, pattern);
+                    }
+                    progress::answer(self.key, false);
+                }
+            }
+        }
+
+        ui.add_space(6.0);
+        if let Some(value) = self.expr.as_value() {
+            let value = L::value_text(value);
+            if self.watched {
+                ui.label(tr("tree_practice.watched").replace("{value}", &value));
+                ui.label(tr("tree_practice.try_own"));
+            } else {
+                ui.label(tr("tree_practice.done").replace("{value}", &value));
+            }
+        }
+        if let Some(feedback) = &self.feedback {
+            ui.label(feedback);
+        }
+        patterns::tip(ui, chapter, self.wording.lesson);
+    }
+}
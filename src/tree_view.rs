@@ -0,0 +1,480 @@
+use std::hash::Hash;
+
+use egui::TextStyle;
+use GORBIE::prelude::*;
+
+use crate::i18n::tr;
+use crate::palette::Palette;
+use crate::presentation;
+use crate::svg::{self, Svg};
+
+const MIN_ZOOM: f32 = 0.4;
+const MAX_ZOOM: f32 = 2.5;
+/// How much one press of a zoom button zooms.
+const ZOOM_STEP: f32 = 1.25;
+/// Zoomed or wide trees scroll once they are taller than this.
+const VIEWPORT_HEIGHT: f32 = 360.0;
+
+/// One box in a drawn tree. `path` identifies the node when highlighting
+/// and is handed back when the box is clicked.
+pub struct TreeNode<P> {
+    pub label: String,
+    pub path: P,
+    pub children: Vec<TreeNode<P>>,
+}
+
+struct NodeDraw<'a, P> {
+    label: &'a str,
+    path: &'a P,
+    depth: usize,
+    x: i32,
+    children: Vec<usize>,
+}
+
+struct NodeLayout<'a, P> {
+    rect: egui::Rect,
+    label: &'a str,
+    path: &'a P,
+    highlight: bool,
+    /// Where the keyboard cursor is.
+    selected: bool,
+    /// Left out of the evaluation, drawn faded.
+    skipped: bool,
+    children: Vec<usize>,
+}
+
+/// Leaves get consecutive columns; a parent sits centered above its
+/// first and last child.
+fn build_nodes<'a, P>(
+    node: &'a TreeNode<P>,
+    depth: usize,
+    nodes: &mut Vec<NodeDraw<'a, P>>,
+    next_leaf_x: &mut i32,
+) -> usize {
+    let children: Vec<usize> = node
+        .children
+        .iter()
+        .map(|child| build_nodes(child, depth + 1, nodes, next_leaf_x))
+        .collect();
+    let x = match (children.first(), children.last()) {
+        (Some(first), Some(last)) => (nodes[*first].x + nodes[*last].x) / 2,
+        _ => {
+            let x = *next_leaf_x;
+            *next_leaf_x += 1;
+            x
+        }
+    };
+
+    let index = nodes.len();
+    nodes.push(NodeDraw {
+        label: &node.label,
+        path: &node.path,
+        depth,
+        x,
+        children,
+    });
+    index
+}
+
+fn build_tree_layout<'a, P: PartialEq>(
+    ui: &egui::Ui,
+    root: &'a TreeNode<P>,
+    highlight: &impl Fn(&P) -> bool,
+    skipped: &impl Fn(&P) -> bool,
+    selected: Option<&P>,
+    zoom: f32,
+) -> (Vec<NodeLayout<'a, P>>, egui::Vec2, egui::FontId) {
+    let mut nodes = Vec::new();
+    let mut next_leaf_x = 0;
+    build_nodes(root, 0, &mut nodes, &mut next_leaf_x);
+
+    let max_label_len = nodes
+        .iter()
+        .map(|node| node.label.chars().count())
+        .max()
+        .unwrap_or(1);
+    let min_x = nodes.iter().map(|node| node.x).min().unwrap_or(0);
+    let max_x = nodes.iter().map(|node| node.x).max().unwrap_or(0);
+    let max_depth = nodes.iter().map(|node| node.depth).max().unwrap_or(0);
+
+    let mut font_id = TextStyle::Monospace.resolve(ui.style());
+    font_id.size *= zoom;
+    let (char_width, row_height) = ui.fonts_mut(|fonts| {
+        let width = fonts.glyph_width(&font_id, '0');
+        let height = fonts.row_height(&font_id);
+        (width.max(1.0), height.max(1.0))
+    });
+    let node_padding = egui::vec2((char_width * 0.6).max(4.0), (row_height * 0.2).max(2.0));
+    let node_width = max_label_len as f32 * char_width + node_padding.x * 2.0;
+    let node_height = row_height + node_padding.y * 2.0;
+    let col_gap = (char_width * 2.0).max(8.0);
+    let row_gap = (row_height * 0.8).max(8.0);
+    let col_spacing = node_width + col_gap;
+    let row_spacing = node_height + row_gap;
+
+    let layout_width = node_width + (max_x - min_x) as f32 * col_spacing;
+    let layout_height = node_height + max_depth as f32 * row_spacing;
+
+    let mut layouts = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let x_center = node_width / 2.0 + (node.x - min_x) as f32 * col_spacing;
+        let y_center = node_height / 2.0 + node.depth as f32 * row_spacing;
+        let rect = egui::Rect::from_center_size(
+            egui::pos2(x_center, y_center),
+            egui::vec2(node_width, node_height),
+        );
+        layouts.push(NodeLayout {
+            rect,
+            label: node.label,
+            path: node.path,
+            highlight: highlight(node.path),
+            selected: selected == Some(node.path),
+            skipped: skipped(node.path),
+            children: node.children,
+        });
+    }
+
+    (layouts, egui::vec2(layout_width, layout_height), font_id)
+}
+
+/// The stroke of a box or an edge. Without color cues the highlighted path
+/// is drawn twice as thick.
+fn line_stroke(palette: &Palette, highlight: bool) -> egui::Stroke {
+    let width = presentation::stroke_width(palette.line.width.max(1.0));
+    match (highlight, palette.shape_cues) {
+        (false, _) => egui::Stroke::new(width, palette.line.color),
+        (true, true) => egui::Stroke::new(width * 2.0, palette.highlight),
+        (true, false) => egui::Stroke::new(width, palette.highlight),
+    }
+}
+
+/// Paints laid out boxes and the edges between them, centered in the space
+/// left. Returns the clicked path and the space the tree took.
+fn paint_layout<P: Clone + Hash>(
+    ui: &mut egui::Ui,
+    layouts: &mut [NodeLayout<'_, P>],
+    desired: egui::Vec2,
+    font_id: &egui::FontId,
+    click_id: Option<&str>,
+) -> (Option<P>, egui::Rect) {
+    let (rect, response) = ui.allocate_at_least(desired, egui::Sense::hover());
+    let mut origin = rect.min;
+    if rect.width() > desired.x {
+        origin.x += (rect.width() - desired.x) / 2.0;
+    }
+    if rect.height() > desired.y {
+        origin.y += (rect.height() - desired.y) / 2.0;
+    }
+
+    for layout in layouts.iter_mut() {
+        layout.rect = layout.rect.translate(origin.to_vec2());
+    }
+
+    let palette = Palette::from_ui(ui);
+    response.widget_info(|| {
+        egui::WidgetInfo::labeled(
+            egui::WidgetType::Other,
+            true,
+            tr("tree.diagram").replace("{count}", &layouts.len().to_string()),
+        )
+    });
+    let painter = ui.painter();
+    let mut clicked = None;
+
+    for (index, layout) in layouts.iter().enumerate() {
+        for child_idx in &layout.children {
+            let child = &layouts[*child_idx];
+            let stroke = line_stroke(&palette, layout.highlight && child.highlight);
+            let start = layout.rect.center_bottom() + egui::vec2(0.0, stroke.width / 2.0);
+            let end = child.rect.center_top() - egui::vec2(0.0, stroke.width / 2.0);
+            let mid_y = (start.y + end.y) / 2.0;
+            let points = vec![
+                start,
+                egui::pos2(start.x, mid_y),
+                egui::pos2(end.x, mid_y),
+                end,
+            ];
+            if child.skipped {
+                let stroke = egui::Stroke::new(stroke.width, palette.weak_text);
+                painter.extend(egui::Shape::dashed_line(&points, stroke, 4.0, 3.0));
+            } else {
+                painter.add(egui::Shape::line(points, stroke));
+            }
+        }
+
+        // Every box gets its own widget so screen readers can walk the tree.
+        let node_response = match click_id {
+            Some(click_id) => {
+                let id = ui.make_persistent_id((click_id, layout.path));
+                ui.interact(layout.rect, id, egui::Sense::click())
+            }
+            None => ui.interact(layout.rect, response.id.with(index), egui::Sense::hover()),
+        };
+        if node_response.clicked() {
+            clicked = Some(layout.path.clone());
+        }
+        let widget_type = if click_id.is_some() {
+            egui::WidgetType::Button
+        } else {
+            egui::WidgetType::Label
+        };
+        node_response.widget_info(|| {
+            egui::WidgetInfo::labeled(widget_type, true, describe_node(layout, layouts))
+        });
+
+        let mut stroke = line_stroke(&palette, layout.highlight);
+        if layout.skipped {
+            stroke.color = palette.weak_text;
+        }
+        painter.rect(
+            layout.rect,
+            egui::CornerRadius::same(4),
+            palette.code_fill,
+            stroke,
+            egui::StrokeKind::Inside,
+        );
+        if layout.selected {
+            painter.rect_stroke(
+                layout.rect.expand(3.0),
+                egui::CornerRadius::same(6),
+                ui.visuals().selection.stroke,
+                egui::StrokeKind::Outside,
+            );
+        }
+        let color = if layout.skipped {
+            palette.weak_text
+        } else if layout.highlight {
+            palette.highlight
+        } else {
+            palette.text
+        };
+        let galley = ui.fonts_mut(|fonts| {
+            fonts.layout_no_wrap(layout.label.to_string(), font_id.clone(), color)
+        });
+        let text_pos = layout.rect.center() - galley.size() / 2.0;
+        painter.galley(text_pos, galley, palette.text);
+    }
+
+    (clicked, rect)
+}
+
+/// The drawn tree as an image, for the export button and handouts.
+fn tree_svg<P>(
+    rect: egui::Rect,
+    layouts: &[NodeLayout<'_, P>],
+    palette: &Palette,
+    font_size: f32,
+) -> Svg {
+    let mut svg = Svg::new(rect, palette.code_fill);
+    for layout in layouts {
+        for child_idx in &layout.children {
+            let child = &layouts[*child_idx];
+            let start = layout.rect.center_bottom();
+            let end = child.rect.center_top();
+            let mid_y = (start.y + end.y) / 2.0;
+            svg.polyline(
+                &[
+                    start,
+                    egui::pos2(start.x, mid_y),
+                    egui::pos2(end.x, mid_y),
+                    end,
+                ],
+                line_stroke(palette, layout.highlight && child.highlight),
+                false,
+            );
+        }
+    }
+    for layout in layouts {
+        let color = if layout.skipped {
+            palette.weak_text
+        } else if layout.highlight {
+            palette.highlight
+        } else {
+            palette.text
+        };
+        svg.rect(
+            layout.rect,
+            4.0,
+            palette.code_fill,
+            line_stroke(palette, layout.highlight),
+        );
+        svg.text(layout.rect.center(), layout.label, font_size, color);
+    }
+    svg
+}
+
+fn set_zoom(ui: &egui::Ui, id: egui::Id, zoom: f32) {
+    ui.data_mut(|data| data.insert_temp(id, zoom.clamp(MIN_ZOOM, MAX_ZOOM)));
+    ui.ctx().request_repaint();
+}
+
+/// Buttons to zoom out, back to 100% and in, for trees in a viewport.
+fn zoom_controls(ui: &mut egui::Ui, id: egui::Id, zoom: f32) {
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(zoom > MIN_ZOOM, widgets::Button::new("\u{2212}").small())
+            .on_hover_text(tr("tree.zoom_out"))
+            .clicked()
+        {
+            set_zoom(ui, id, zoom / ZOOM_STEP);
+        }
+        let percent = format!("{:.0}%", zoom * 100.0);
+        if ui
+            .add(widgets::Button::new(percent).small())
+            .on_hover_text(tr("tree.zoom_reset"))
+            .clicked()
+        {
+            set_zoom(ui, id, 1.0);
+        }
+        if ui
+            .add_enabled(zoom < MAX_ZOOM, widgets::Button::new("+").small())
+            .on_hover_text(tr("tree.zoom_in"))
+            .clicked()
+        {
+            set_zoom(ui, id, zoom * ZOOM_STEP);
+        }
+        ui.label(egui::RichText::new(tr("tree.pan_hint")).small().weak());
+    });
+}
+
+/// Draws the tree, in a viewport to pan and zoom once it is wider than the
+/// card or zoomed. Pinching or Ctrl+scrolling over any tree zooms it.
+fn paint_tree<P: Clone + Hash + PartialEq>(
+    ui: &mut egui::Ui,
+    root: &TreeNode<P>,
+    highlight: impl Fn(&P) -> bool,
+    skipped: impl Fn(&P) -> bool,
+    selected: Option<&P>,
+    click_id: Option<&str>,
+) -> Option<P> {
+    let id = ui.id().with(("tree_view", click_id));
+    let zoom = ui.data(|data| data.get_temp::<f32>(id)).unwrap_or(1.0);
+    let (mut layouts, desired, font_id) =
+        build_tree_layout(ui, root, &highlight, &skipped, selected, zoom);
+    let (clicked, rect, hovered) = if zoom == 1.0 && desired.x <= ui.available_width() {
+        let (clicked, rect) = paint_layout(ui, &mut layouts, desired, &font_id, click_id);
+        (clicked, rect, ui.rect_contains_pointer(rect))
+    } else {
+        zoom_controls(ui, id, zoom);
+        let output = egui::ScrollArea::both()
+            .id_salt(id)
+            .max_height(VIEWPORT_HEIGHT)
+            .show(ui, |ui| {
+                paint_layout(ui, &mut layouts, desired, &font_id, click_id)
+            });
+        let (clicked, rect) = output.inner;
+        (clicked, rect, ui.rect_contains_pointer(output.inner_rect))
+    };
+    if hovered {
+        let delta = ui.input(|input| input.zoom_delta());
+        if delta != 1.0 {
+            set_zoom(ui, id, zoom * delta);
+        }
+    }
+
+    let palette = Palette::from_ui(ui);
+    svg::export_button(ui, "tree", || {
+        tree_svg(rect, &layouts, &palette, font_id.size)
+    });
+
+    clicked
+}
+
+/// Operator symbols as words, e.g. `*` is read as "multiply". A `-` with one
+/// child is a negation.
+fn spoken(label: &str, children: usize) -> &str {
+    match (label, children) {
+        ("+", _) => tr("tree.add"),
+        ("-", 1) => tr("tree.negate"),
+        ("-", _) => tr("tree.subtract"),
+        ("*", _) => tr("tree.multiply"),
+        ("/", _) => tr("tree.divide"),
+        ("%", _) => tr("tree.remainder"),
+        ("^", _) => tr("tree.power"),
+        _ => label,
+    }
+}
+
+/// What a screen reader announces for a box, e.g. "multiply node, children
+/// 3 and 2, highlighted". A child is marked highlighted when the edge to it
+/// is.
+fn describe_node<P>(layout: &NodeLayout<'_, P>, layouts: &[NodeLayout<'_, P>]) -> String {
+    let name = spoken(layout.label, layout.children.len());
+    if layout.children.is_empty() {
+        return format!("{name}{}", states(layout));
+    }
+    let children: Vec<String> = layout
+        .children
+        .iter()
+        .map(|index| {
+            let child = &layouts[*index];
+            let name = spoken(child.label, child.children.len());
+            if layout.highlight && child.highlight {
+                format!("{name} ({})", tr("tree.highlighted"))
+            } else {
+                name.to_string()
+            }
+        })
+        .collect();
+    let mut text = tr("tree.node").replace("{name}", name);
+    text.push_str(", ");
+    match children.as_slice() {
+        [only] => text.push_str(&tr("tree.child").replace("{child}", only)),
+        [rest @ .., last] => text.push_str(
+            &tr("tree.children")
+                .replace("{rest}", &rest.join(", "))
+                .replace("{last}", last),
+        ),
+        [] => {}
+    }
+    text.push_str(&states(layout));
+    text
+}
+
+/// The states of a box as read after its description, e.g. ", highlighted".
+fn states<P>(layout: &NodeLayout<'_, P>) -> String {
+    [
+        (layout.highlight, "tree.highlighted"),
+        (layout.selected, "tree.selected"),
+        (layout.skipped, "tree.skipped"),
+    ]
+    .into_iter()
+    .filter(|(on, _)| *on)
+    .map(|(_, key)| format!(", {}", tr(key)))
+    .collect()
+}
+
+/// Draws the tree centered in the available width, or in a viewport to pan
+/// and zoom when it does not fit. Nodes for which `highlight` returns true,
+/// and the edges between them, use the accent color.
+pub fn draw_tree<P: Clone + Hash + PartialEq>(
+    ui: &mut egui::Ui,
+    root: &TreeNode<P>,
+    highlight: impl Fn(&P) -> bool,
+) {
+    paint_tree(ui, root, highlight, |_| false, None, None);
+}
+
+/// Like [`draw_tree`], with the nodes for which `skipped` returns true
+/// faded out, for parts an evaluation never looks at.
+pub fn draw_tree_skipping<P: Clone + Hash + PartialEq>(
+    ui: &mut egui::Ui,
+    root: &TreeNode<P>,
+    highlight: impl Fn(&P) -> bool,
+    skipped: impl Fn(&P) -> bool,
+) {
+    paint_tree(ui, root, highlight, skipped, None, None);
+}
+
+/// Like [`draw_tree`], but every box is clickable. Returns the path of the
+/// clicked node. `id` keeps click state apart when a page shows several trees.
+/// The box at `selected` gets a ring, for a cursor moved with the keys.
+pub fn draw_tree_interactive<P: Clone + Hash + PartialEq>(
+    ui: &mut egui::Ui,
+    id: &str,
+    root: &TreeNode<P>,
+    highlight: impl Fn(&P) -> bool,
+    selected: Option<&P>,
+) -> Option<P> {
+    paint_tree(ui, root, highlight, |_| false, selected, Some(id))
+}
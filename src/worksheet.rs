@@ -0,0 +1,61 @@
+use crate::chapters::{self, CHAPTERS};
+use crate::difficulty::Difficulty;
+use crate::rng::{self, SimpleRng};
+
+/// One question of a printed worksheet, and its line in the answer key.
+pub struct Item {
+    /// Markdown: the prompt and the code or values it is about.
+    pub question: String,
+    pub answer: String,
+}
+
+/// Writes `count` fresh exercises from the chapter with the id `chapter`
+/// to `<chapter>_worksheet.md` in the working directory, and their answers
+/// to `<chapter>_answers.md`. Exercises come at the saved practice level.
+pub fn print(chapter: &str, count: &str) -> Result<(), String> {
+    let printable: Vec<&str> = CHAPTERS
+        .iter()
+        .filter(|def| {
+            let mut rng = SimpleRng::new(1);
+            chapters::worksheet_item(def.chapter, &mut rng, Difficulty::of_seed(0)).is_some()
+        })
+        .map(|def| def.id)
+        .collect();
+    let def = CHAPTERS
+        .iter()
+        .find(|def| def.id == chapter && printable.contains(&def.id))
+        .ok_or_else(|| {
+            format!(
+                "No worksheets for '{chapter}'. Try one of: {}",
+                printable.join(", ")
+            )
+        })?;
+    let count: usize = count
+        .parse()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| format!("'{count}' is not a number of exercises"))?;
+
+    let seed = rng::fresh_seed();
+    let mut rng = SimpleRng::new(seed);
+    let difficulty = Difficulty::of_seed(seed);
+    let mut sheet = format!("# {}\n\nName: ____________________\n\n", def.title);
+    let mut key = format!("# {}: answers\n\nSeed {seed}\n\n", def.title);
+    for number in 1..=count {
+        let Some(item) = chapters::worksheet_item(def.chapter, &mut rng, difficulty) else {
+            break;
+        };
+        sheet.push_str(&format!(
+            "## {number}.\n\n{}\n\nAnswer: __________\n\n",
+            item.question
+        ));
+        key.push_str(&format!("{number}. {}\n", item.answer));
+    }
+
+    let sheet_path = format!("{}_worksheet.md", def.id);
+    let key_path = format!("{}_answers.md", def.id);
+    std::fs::write(&sheet_path, sheet).map_err(|error| format!("{sheet_path}: {error}"))?;
+    std::fs::write(&key_path, key).map_err(|error| format!("{key_path}: {error}"))?;
+    println!("Wrote {sheet_path} and {key_path}");
+    Ok(())
+}